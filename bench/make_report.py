@@ -24,6 +24,17 @@ def load_rows(csv_path):
     return rows
 
 
+# Major `schema_version` this report understands; rows without one count as 1.0.
+ROW_SCHEMA_MAJOR = 1
+
+
+def check_schema(rows, path):
+    for i, r in enumerate(rows):
+        v = r.get('schema_version') or '1.0'
+        if str(v).split('.', 1)[0] != str(ROW_SCHEMA_MAJOR):
+            sys.exit(f"error: {path}: row {i + 1} has schema_version {v}, this report reads {ROW_SCHEMA_MAJOR}.x")
+
+
 def best_rows_by_impl(rows):
     # Choose the row with the largest 'popped' per (impl, lang, graph), tie-breaker: smallest time_ns
     best = {}
@@ -61,6 +72,7 @@ def main():
     outdir = pathlib.Path(args.out)
     outdir.mkdir(parents=True, exist_ok=True)
     rows = load_rows(args.csv)
+    check_schema(rows, args.csv)
     summary = best_rows_by_impl(rows)
     md = ["# BMSSP 1000x Report",""]
     if args.meta and yaml is not None:
@@ -12,6 +12,17 @@ except Exception:
 from datetime import datetime, timezone
 
 ROOT = pathlib.Path(__file__).resolve().parents[1]
+# Major `schema_version` of the rows this script reads (see bench/schema.json).
+ROW_SCHEMA_MAJOR = 1
+
+
+def row_schema_major(r):
+    """Major version of a row's `schema_version`; rows without one count as 1.0."""
+    v = r.get('schema_version') or '1.0'
+    try:
+        return int(str(v).split('.', 1)[0])
+    except ValueError:
+        return None
 
 
 def cfg_key_blob(graph_cfg, k, seed, maxw):
@@ -514,6 +525,10 @@ def main():
         for r in rows:
             if 'threads' not in r:
                 r['threads'] = 1
+            if row_schema_major(r) != ROW_SCHEMA_MAJOR:
+                invalid_rows += 1
+                print(f"[warn] row skipped: schema_version {r.get('schema_version')!r} is not {ROW_SCHEMA_MAJOR}.x", file=sys.stderr)
+                continue
             if validator is not None:
                 try:
                     validator.validate(r)
@@ -597,7 +612,7 @@ def main():
             for r in all_rows:
                 f.write(json.dumps(r) + '\n')
         csv_path = out_dir / f'agg-{stamp_part}.csv'
-        keys = ['impl', 'lang', 'graph', 'n', 'm', 'k', 'B', 'seed', 'threads', 'time_ns', 'popped', 'edges_scanned', 'heap_pushes', 'B_prime', 'mem_bytes']
+        keys = ['schema_version', 'impl', 'lang', 'graph', 'n', 'm', 'k', 'B', 'seed', 'threads', 'time_ns', 'popped', 'edges_scanned', 'heap_pushes', 'B_prime', 'mem_bytes']
         with open(csv_path, 'w', newline='') as f:
             w = csv.writer(f)
             w.writerow(keys)
@@ -613,7 +628,7 @@ def main():
             f.write(json.dumps(r) + '\n')
 
     csv_path = out_dir / f'agg-{stamp}.csv'
-    keys = ['impl', 'lang', 'graph', 'n', 'm', 'k', 'B', 'seed', 'threads', 'time_ns', 'popped', 'edges_scanned', 'heap_pushes', 'B_prime', 'mem_bytes']
+    keys = ['schema_version', 'impl', 'lang', 'graph', 'n', 'm', 'k', 'B', 'seed', 'threads', 'time_ns', 'popped', 'edges_scanned', 'heap_pushes', 'B_prime', 'mem_bytes']
     with open(csv_path, 'w', newline='') as f:
         w = csv.writer(f)
         w.writerow(keys)
@@ -1,5 +1,6 @@
 use bmssp::*;
-use criterion::{criterion_group, criterion_main, Criterion, black_box};
+use bmssp::generators::{make_ba, make_er, make_grid, pick_sources};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput, black_box};
 use rand::{rngs::StdRng, Rng, SeedableRng};
 
 fn random_graph(n: usize, m: usize, seed: u64) -> Graph {
@@ -15,6 +16,35 @@ fn random_graph(n: usize, m: usize, seed: u64) -> Graph {
     g
 }
 
+/// Sequential search swept over graph family x size x k x B, so crossover points show up in one
+/// report. Throughput is edges scanned per second, measured from one untimed run of each cell.
+fn bench_sweep(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sweep");
+    group.sample_size(20);
+    for n in [10_000, 100_000] {
+        let side = (n as f64).sqrt() as usize;
+        let families = [
+            ("grid", make_grid(side, side, 100, 42)),
+            ("er", make_er(n, 4.0 / n as f64, 100, 42)),
+            ("ba", make_ba(n, 5, 2, 100, 42)),
+        ];
+        for (family, g) in &families {
+            for k in [1, 16] {
+                let sources = pick_sources(g.len(), k, 7);
+                for bound in [100u64, 500, 2000] {
+                    let edges = bounded_multi_source_shortest_paths(g, &sources, bound).edges_scanned;
+                    group.throughput(Throughput::Elements(edges.max(1) as u64));
+                    let id = BenchmarkId::new(format!("{family}/n={n}/k={k}"), format!("B={bound}"));
+                    group.bench_with_input(id, &bound, |b, &bound| {
+                        b.iter(|| black_box(bounded_multi_source_shortest_paths(g, black_box(&sources), bound).explored.len()))
+                    });
+                }
+            }
+        }
+    }
+    group.finish();
+}
+
 fn bench_bmssp(c: &mut Criterion) {
     let n = 50_000;
     let m = 200_000;
@@ -22,13 +52,139 @@ fn bench_bmssp(c: &mut Criterion) {
     let sources: Vec<(usize, u64)> = (0..32).map(|i| (i * (n/32), 0)).collect();
     let bound: u64 = 300;
 
-    c.bench_function("bmssp_50k_200k_bound300", |b| {
+    // Same instance in the u32 CSR layout, to compare cache behavior against Vec<Vec<(usize, u64)>>.
+    let cg = CompactGraph::from_graph(&g).expect("fits in u32");
+    c.bench_function("bmssp_compact_50k_200k_bound300", |b| {
         b.iter(|| {
-            let res = bounded_multi_source_shortest_paths(&g, black_box(&sources), black_box(bound));
+            let res = bounded_multi_source_shortest_paths_compact(&cg, black_box(&sources), black_box(bound));
             black_box(res.explored.len());
         })
     });
+
+    // The generic semiring loop instantiated at min-plus should track the hand-written search.
+    c.bench_function("bmssp_semiring_minplus_50k_200k_bound300", |b| {
+        b.iter(|| black_box(bmssp_semiring::<MinPlus>(&g, black_box(&sources), black_box(bound)).explored.len()))
+    });
+
+    // Scalar vs vectorized relaxation on the same CSR arrays; a denser graph gives the 8-lane
+    // kernel full chunks to work on.
+    let dense = CompactGraph::from_graph(&random_graph(20_000, 640_000, 7)).expect("fits in u32");
+    let dense_sources: Vec<(usize, u64)> = (0..32).map(|i| (i * (20_000/32), 0)).collect();
+    let mut group = c.benchmark_group("relax_kernel_20k_640k_bound40");
+    group.bench_function("scalar", |b| {
+        b.iter(|| black_box(bounded_multi_source_shortest_paths_compact(&dense, black_box(&dense_sources), 40).explored.len()))
+    });
+    group.bench_function("simd", |b| {
+        b.iter(|| black_box(bounded_multi_source_shortest_paths_simd(&dense, black_box(&dense_sources), 40).explored.len()))
+    });
+    group.finish();
+}
+
+/// `bmssp_sharded` scaling at 1..16 shards, with sources spread over the grid vs clustered in
+/// one neighborhood (where shards redo each other's work). The merge is timed on its own from
+/// precomputed shard results, split the same round-robin way `bmssp_sharded` does.
+fn bench_sharded(c: &mut Criterion) {
+    let g = make_grid(300, 300, 100, 42);
+    let (k, bound) = (64, 1500);
+    let layouts = [
+        ("spread", pick_sources(g.len(), k, 7)),
+        ("clustered", clustered_queries(&g, 1, k, bound, 7).remove(0).sources),
+    ];
+    for (layout, sources) in &layouts {
+        let mut group = c.benchmark_group(format!("sharded_grid300_k64_bound1500/{layout}"));
+        group.sample_size(20);
+        for threads in [1, 2, 4, 8, 16] {
+            group.bench_with_input(BenchmarkId::new("total", threads), &threads, |b, &t| {
+                b.iter(|| black_box(bmssp_sharded(&g, black_box(sources), bound, t).explored.len()))
+            });
+            if threads == 1 { continue; }
+            let parts: Vec<BmsspResult> = (0..threads).map(|s| {
+                let shard: Vec<(Node, Weight)> = sources.iter().copied().skip(s).step_by(threads).collect();
+                bounded_multi_source_shortest_paths(&g, &shard, bound)
+            }).collect();
+            let opts = BmsspOptions::default();
+            group.bench_with_input(BenchmarkId::new("merge", threads), &parts, |b, parts| {
+                b.iter_batched(|| parts.clone(), |p| black_box(merge_shards(&g, bound, &opts, p).explored.len()), BatchSize::LargeInput)
+            });
+        }
+        group.finish();
+    }
+}
+
+/// Each `QueueKind` on the same searches. Results are identical, so only the queue differs:
+/// lazy binary-heap duplicates vs pairing-heap decrease-key vs multi-level buckets.
+fn bench_queues(c: &mut Criterion) {
+    let n = 100_000;
+    let side = (n as f64).sqrt() as usize;
+    let families = [
+        ("grid", make_grid(side, side, 100, 42)),
+        ("er", make_er(n, 4.0 / n as f64, 100, 42)),
+        ("ba", make_ba(n, 5, 2, 100, 42)),
+    ];
+    let mut group = c.benchmark_group("queue");
+    group.sample_size(20);
+    for (family, g) in &families {
+        let sources = pick_sources(g.len(), 16, 7);
+        for bound in [500u64, 2000] {
+            for &q in QueueKind::ALL {
+                let id = BenchmarkId::new(format!("{family}/n={n}/B={bound}"), q.as_str());
+                group.bench_with_input(id, &q, |b, &q| {
+                    b.iter(|| black_box(bmssp_with_queue_kind(g, black_box(&sources), bound, q).explored.len()))
+                });
+            }
+        }
+    }
+    group.finish();
+}
+
+/// Default vs batched loop, from a sparse grid to a dense random graph where each settle
+/// relaxes ~64 edges and batching should matter most.
+fn bench_batched(c: &mut Criterion) {
+    let instances = [
+        ("grid300", make_grid(300, 300, 100, 42), 2000u64),
+        ("random_20k_320k", random_graph(20_000, 320_000, 7), 60),
+        ("random_20k_1280k", random_graph(20_000, 1_280_000, 7), 20),
+    ];
+    let mut group = c.benchmark_group("batched");
+    group.sample_size(20);
+    for (name, g, bound) in &instances {
+        let sources = pick_sources(g.len(), 16, 7);
+        group.bench_with_input(BenchmarkId::new(*name, "default"), bound, |b, &bound| {
+            b.iter(|| black_box(bounded_multi_source_shortest_paths(g, black_box(&sources), bound).explored.len()))
+        });
+        group.bench_with_input(BenchmarkId::new(*name, "batched"), bound, |b, &bound| {
+            b.iter(|| black_box(bmssp_batched(g, black_box(&sources), bound).explored.len()))
+        });
+    }
+    group.finish();
+}
+
+/// Bound-sensitivity sweep: one bounded run per bound vs one `run_multi_bound` pass.
+fn bench_multi_bound(c: &mut Criterion) {
+    let g = make_grid(300, 300, 100, 42);
+    let sources = pick_sources(g.len(), 16, 7);
+    let bounds: Vec<Weight> = (1..=16).map(|i| i * 250).collect();
+    let mut group = c.benchmark_group("multi_bound_grid300_16_bounds");
+    group.sample_size(20);
+    group.bench_function("separate", |b| {
+        b.iter(|| black_box(bounds.iter().map(|&bound| bounded_multi_source_shortest_paths(&g, black_box(&sources), bound).b_prime).max()))
+    });
+    group.bench_function("one_pass", |b| {
+        b.iter(|| black_box(run_multi_bound(&g, black_box(&sources), &bounds, false).len()))
+    });
+    group.finish();
+}
+
+// 10k x 10k grid (1e8 nodes) generated on the fly; the hashed-label search only touches the
+// explored diamond, so nothing proportional to n is allocated.
+fn bench_implicit(c: &mut Criterion) {
+    let side = 10_000;
+    let grid = ImplicitGraph::grid(side, side, 1);
+    let center = (side / 2) * side + side / 2;
+    c.bench_function("implicit_grid_10k_x_10k_bound200", |b| {
+        b.iter(|| black_box(bmssp_sparse(&grid, black_box(&[(center, 0)]), 200).explored.len()))
+    });
 }
 
-criterion_group!(benches, bench_bmssp);
+criterion_group!(benches, bench_sweep, bench_sharded, bench_bmssp, bench_queues, bench_batched, bench_multi_bound, bench_implicit);
 criterion_main!(benches);
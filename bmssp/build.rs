@@ -0,0 +1,18 @@
+//! Regenerates the C header for the `ffi` feature; a no-op otherwise.
+fn main() {
+    // Without any rerun-if-changed line Cargo reruns this on every change in the package.
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "ffi")]
+    {
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml")).expect("cbindgen.toml");
+        cbindgen::Builder::new()
+            .with_src(format!("{crate_dir}/src/ffi.rs"))
+            .with_config(config)
+            .generate()
+            .expect("generate bmssp.h")
+            .write_to_file(format!("{crate_dir}/include/bmssp.h"));
+    }
+}
@@ -0,0 +1,55 @@
+//! Byte decoding and the naive oracle shared by the fuzz targets.
+use bmssp::{Graph, Node, Weight};
+
+/// Small graph and query decoded from fuzzer input.
+#[derive(Debug)]
+pub struct Case {
+    pub graph: Graph,
+    pub edges: Vec<(Node, Node, Weight)>,
+    pub sources: Vec<(Node, Weight)>,
+    pub bound: Weight,
+    /// Extra byte for targets that need a knob (thread count, budget).
+    pub knob: u8,
+}
+
+/// Header `n, bound, k, knob`, then `k` `(source, offset)` pairs, then `(u, v, w)` triples until
+/// the input runs out. Everything is reduced modulo small ranges so most inputs decode.
+pub fn decode(data: &[u8]) -> Option<Case> {
+    let (head, rest) = data.split_at_checked(4)?;
+    let n = 1 + head[0] as usize % 32;
+    let bound = head[1] as Weight;
+    let k = 1 + head[2] as usize % 4;
+    let (src_bytes, edge_bytes) = rest.split_at_checked(2 * k)?;
+    let sources = src_bytes.chunks_exact(2).map(|c| (c[0] as usize % n, (c[1] % 64) as Weight)).collect();
+    let edges: Vec<_> = edge_bytes.chunks_exact(3)
+        .map(|c| (c[0] as usize % n, c[1] as usize % n, (c[2] % 16) as Weight))
+        .collect();
+    let mut graph = Graph::new(n);
+    for &(u, v, w) in &edges { graph.add_edge(u, v, w); }
+    Some(Case{ graph, edges, sources, bound, knob: head[3] })
+}
+
+/// O(n²) Dijkstra without a heap or a bound. Sources with `d0 >= bound` are dropped, as the
+/// library does. Returns exact distances; the expected B' is the smallest of these at or above
+/// the bound.
+pub fn naive(case: &Case) -> Vec<Weight> {
+    let n = case.graph.len();
+    let mut dist = vec![Weight::MAX; n];
+    let mut done = vec![false; n];
+    for &(s, d0) in &case.sources {
+        if d0 < case.bound { dist[s] = dist[s].min(d0); }
+    }
+    loop {
+        let Some(u) = (0..n).filter(|&v| !done[v] && dist[v] < Weight::MAX).min_by_key(|&v| dist[v]) else { break };
+        done[u] = true;
+        for &(a, v, w) in &case.edges {
+            if a == u { dist[v] = dist[v].min(dist[u] + w); }
+        }
+    }
+    dist
+}
+
+/// Expected B' given exact distances.
+pub fn excluded_min(dist: &[Weight], bound: Weight) -> Weight {
+    dist.iter().copied().filter(|&d| d >= bound).min().unwrap_or(Weight::MAX)
+}
@@ -0,0 +1,29 @@
+//! Sequential and sharded BMSSP against the naive oracle: settled set, distances, settle order
+//! (sharded merges report `(dist, node)` order) and B'. There is no recursive (Duan et al.) implementation in this crate yet; add it here
+//! when there is.
+#![no_main]
+use bmssp::*;
+use libfuzzer_sys::fuzz_target;
+
+mod common;
+
+fuzz_target!(|data: &[u8]| {
+    let Some(case) = common::decode(data) else { return };
+    let exact = common::naive(&case);
+    let bound = case.bound;
+    let expect: Vec<Weight> = exact.iter().map(|&d| if d < bound { d } else { Weight::MAX }).collect();
+
+    let seq = bounded_multi_source_shortest_paths(&case.graph, &case.sources, bound);
+    assert_eq!(seq.dist, expect, "{case:?}");
+    assert!(seq.explored.windows(2).all(|w| seq.dist[w[0]] <= seq.dist[w[1]]));
+    assert_eq!(seq.explored.len(), expect.iter().filter(|&&d| d < bound).count());
+    assert_eq!(seq.b_prime, common::excluded_min(&exact, bound), "{case:?}");
+
+    let threads = 1 + case.knob as usize % 4;
+    let par = bmssp_sharded(&case.graph, &case.sources, bound, threads);
+    assert_eq!(par.dist, expect, "{case:?}");
+    let mut canonical = seq.explored.clone();
+    canonical.sort_by_key(|&v| (seq.dist[v], v));
+    if case.sources.len() > 1 && threads > 1 { assert_eq!(par.explored, canonical, "{case:?}"); }
+    assert_eq!(par.b_prime, seq.b_prime, "{case:?}");
+});
@@ -0,0 +1,31 @@
+//! B' at the break condition: a search cut short by a settle or edge budget must return a
+//! prefix of the full settle order, and B' must be the label of the first node it left unsettled.
+#![no_main]
+use bmssp::*;
+use libfuzzer_sys::fuzz_target;
+
+mod common;
+
+fuzz_target!(|data: &[u8]| {
+    let Some(case) = common::decode(data) else { return };
+    let bound = case.bound;
+    let full = bounded_multi_source_shortest_paths(&case.graph, &case.sources, bound);
+    assert_eq!(full.b_prime, common::excluded_min(&common::naive(&case), bound), "{case:?}");
+
+    let budget = (case.knob >> 1) as usize % 40;
+    let opts = if case.knob & 1 == 0 {
+        BmsspOptions{ max_settled: Some(budget), ..Default::default() }
+    } else {
+        BmsspOptions{ max_edges_scanned: Some(budget), ..Default::default() }
+    };
+    let r = bmssp_with_options(&case.graph, &case.sources, bound, &opts);
+    assert_eq!(r.explored[..], full.explored[..r.explored.len()], "{case:?}");
+    for &v in &r.explored { assert_eq!(r.dist[v], full.dist[v]); }
+    if r.termination == Termination::Bound {
+        assert_eq!(r.explored.len(), full.explored.len());
+        assert_eq!(r.b_prime, full.b_prime, "{case:?}");
+    } else {
+        let next = full.explored[r.explored.len()];
+        assert_eq!(r.b_prime, full.dist[next], "{case:?}");
+    }
+});
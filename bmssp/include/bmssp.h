@@ -0,0 +1,141 @@
+#ifndef BMSSP_H
+#define BMSSP_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef struct BmsspResult BmsspResult;
+
+/**
+ * Distance reported for nodes not settled below the bound.
+ */
+#define BMSSP_UNREACHED UINT64_MAX
+
+/**
+ * Opaque graph handle.
+ */
+typedef struct BmsspGraph BmsspGraph;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * New graph with `n` nodes and no edges.
+ */
+BmsspGraph *bmssp_graph_new(size_t n);
+
+/**
+ * # Safety
+ * `g` must be NULL or a handle from `bmssp_graph_new` not yet freed.
+ */
+void bmssp_graph_free(BmsspGraph *g);
+
+/**
+ * Node count, 0 for NULL.
+ *
+ * # Safety
+ * `g` must be NULL or a live graph handle.
+ */
+size_t bmssp_graph_num_nodes(const BmsspGraph *g);
+
+/**
+ * Adds the directed edge `u -> v`. Returns 0 on success, -1 for NULL or out-of-range nodes.
+ *
+ * # Safety
+ * `g` must be NULL or a live graph handle.
+ */
+int32_t bmssp_add_edge(BmsspGraph *g, size_t u, size_t v, uint64_t w);
+
+/**
+ * Runs `bounded_multi_source_shortest_paths` from `k` sources. `source_dists` may be NULL,
+ * meaning every source starts at 0. Returns NULL for a NULL graph or an out-of-range source.
+ *
+ * # Safety
+ * `g` must be NULL or a live graph handle; `sources` (and `source_dists` unless NULL) must
+ * point to `k` readable elements unless `k` is 0.
+ */
+BmsspResult *bmssp_run(const BmsspGraph *g,
+                       const size_t *sources,
+                       const uint64_t *source_dists,
+                       size_t k,
+                       uint64_t bound);
+
+/**
+ * # Safety
+ * `r` must be NULL or a handle from `bmssp_run` not yet freed.
+ */
+void bmssp_result_free(BmsspResult *r);
+
+/**
+ * Length of the distance array (the graph's node count), 0 for NULL.
+ *
+ * # Safety
+ * `r` must be NULL or a live result handle.
+ */
+size_t bmssp_result_num_nodes(const BmsspResult *r);
+
+/**
+ * Distance to `v`, or `BMSSP_UNREACHED` if `v` was not settled (or is out of range).
+ *
+ * # Safety
+ * `r` must be NULL or a live result handle.
+ */
+uint64_t bmssp_result_dist(const BmsspResult *r, size_t v);
+
+/**
+ * Borrowed view of all distances (`bmssp_result_num_nodes` entries), valid until the result
+ * is freed.
+ *
+ * # Safety
+ * `r` must be NULL or a live result handle.
+ */
+const uint64_t *bmssp_result_dists(const BmsspResult *r);
+
+/**
+ * Number of settled nodes.
+ *
+ * # Safety
+ * `r` must be NULL or a live result handle.
+ */
+size_t bmssp_result_explored_len(const BmsspResult *r);
+
+/**
+ * Settled nodes in (distance, node) order, `bmssp_result_explored_len` entries, valid until
+ * the result is freed.
+ *
+ * # Safety
+ * `r` must be NULL or a live result handle.
+ */
+const size_t *bmssp_result_explored(const BmsspResult *r);
+
+/**
+ * Tight boundary B'; `BMSSP_UNREACHED` when nothing was cut off by the bound.
+ *
+ * # Safety
+ * `r` must be NULL or a live result handle.
+ */
+uint64_t bmssp_result_b_prime(const BmsspResult *r);
+
+/**
+ * # Safety
+ * `r` must be NULL or a live result handle.
+ */
+size_t bmssp_result_edges_scanned(const BmsspResult *r);
+
+/**
+ * # Safety
+ * `r` must be NULL or a live result handle.
+ */
+size_t bmssp_result_heap_pushes(const BmsspResult *r);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* BMSSP_H */
@@ -0,0 +1,210 @@
+//! Registry of the bounded search variants behind one `prepare` / `run` signature, so the CLI
+//! (`--algo NAME`), scenario sweeps and the harness can iterate over every algorithm instead of
+//! growing a flag per variant. Register a new variant by adding it to `Algorithm` and `ALL`.
+//! There is no recursive (Duan et al.) variant in the crate yet.
+use crate::{
+    bmssp_batched, bmssp_bfs, bmssp_parallel, bmssp_small_weights, bmssp_partitioned, bmssp_sharded, bounded_multi_source_shortest_paths,
+    bounded_multi_source_shortest_paths_compact, bounded_multi_source_shortest_paths_simd, bmssp_with_queue_kind, hl,
+    uniform_weight, BmsspResult, CompactGraph, ContractionHierarchy, Graph, HubLabels, Node, QueueKind, SmallWeightKernel, Weight,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// Sequential binary-heap search (`bounded_multi_source_shortest_paths`).
+    Dijkstra,
+    /// Sources split over `threads` independent searches, merged (`bmssp_sharded`).
+    Sharded,
+    /// Delta-stepping with atomic labels (`bmssp_parallel`).
+    Atomic,
+    /// BFS-partitioned graph, one owner thread per part (`bmssp_partitioned`).
+    Partitioned,
+    /// Sequential search on a pairing heap with decrease-key (`QueueKind::Pairing`).
+    Pairing,
+    /// Sequential search on multi-level buckets (`QueueKind::Mlb`).
+    Mlb,
+    /// Sequential search with batched pushes and equal-distance chunks (`bmssp_batched`).
+    Batched,
+    /// FIFO BFS (`bmssp_bfs`) when every edge has the same weight, else `Dijkstra`. `prepare`
+    /// checks the weights.
+    Bfs,
+    /// 0-1 BFS or circular buckets (`bmssp_small_weights`) when `GraphStats::small_weight_kernel`
+    /// finds a tiny weight range, else `Dijkstra`.
+    Deque,
+    /// Sequential search over the u32 CSR layout.
+    Compact,
+    /// `Compact` with the 8-lane relaxation kernel.
+    Simd,
+    /// PHAST sweep over a contraction hierarchy built in `prepare`.
+    Phast,
+    /// Hub-label scan; labels are built in `prepare`.
+    HubLabels,
+}
+
+impl Algorithm {
+    pub const ALL: &'static [Algorithm] = &[
+        Algorithm::Dijkstra,
+        Algorithm::Sharded,
+        Algorithm::Atomic,
+        Algorithm::Partitioned,
+        Algorithm::Pairing,
+        Algorithm::Mlb,
+        Algorithm::Batched,
+        Algorithm::Bfs,
+        Algorithm::Deque,
+        Algorithm::Compact,
+        Algorithm::Simd,
+        Algorithm::Phast,
+        Algorithm::HubLabels,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Dijkstra => "dijkstra",
+            Algorithm::Sharded => "sharded",
+            Algorithm::Atomic => "atomic",
+            Algorithm::Partitioned => "partitioned",
+            Algorithm::Pairing => "pairing",
+            Algorithm::Mlb => "mlb",
+            Algorithm::Batched => "batched",
+            Algorithm::Bfs => "bfs",
+            Algorithm::Deque => "deque",
+            Algorithm::Compact => "compact",
+            Algorithm::Simd => "simd",
+            Algorithm::Phast => "phast",
+            Algorithm::HubLabels => "hub-labels",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> { Self::ALL.iter().copied().find(|a| a.name() == s) }
+
+    /// Comma-separated registry names, for error messages.
+    pub fn names() -> String { Self::ALL.iter().map(|a| a.name()).collect::<Vec<_>>().join(", ") }
+
+    /// Whether `threads` changes what `run` does.
+    pub fn uses_threads(self) -> bool { matches!(self, Algorithm::Sharded | Algorithm::Atomic | Algorithm::Partitioned) }
+
+    /// Frontier queue of the sequential search; `Binary` for everything but the queue variants.
+    pub fn queue(self) -> QueueKind {
+        match self {
+            Algorithm::Pairing => QueueKind::Pairing,
+            Algorithm::Mlb => QueueKind::Mlb,
+            _ => QueueKind::Binary,
+        }
+    }
+
+    /// Whether `prepare` does per-graph work worth timing separately (`preprocess_ns`).
+    pub fn has_index(self) -> bool { matches!(self, Algorithm::Bfs | Algorithm::Deque | Algorithm::Compact | Algorithm::Simd | Algorithm::Phast | Algorithm::HubLabels) }
+
+    /// Build whatever per-graph index the algorithm needs. `None` if the graph doesn't fit its
+    /// layout (the CSR variants need u32 node ids and weights).
+    pub fn prepare(self, g: &Graph) -> Option<Prepared<'_>> {
+        let index = match self {
+            Algorithm::Compact | Algorithm::Simd => Index::Compact(CompactGraph::from_graph(g)?),
+            Algorithm::Phast => Index::Ch(ContractionHierarchy::build(g)),
+            Algorithm::HubLabels => Index::Hl(hl::build(g)),
+            Algorithm::Bfs => uniform_weight(g).map_or(Index::None, Index::Uniform),
+            Algorithm::Deque => g.stats().small_weight_kernel().map_or(Index::None, Index::SmallWeights),
+            _ => Index::None,
+        };
+        Some(Prepared{ algo: self, g, index })
+    }
+
+    /// `prepare` then `run`; preprocessing is repeated on every call.
+    pub fn run(self, g: &Graph, sources: &[(Node, Weight)], bound: Weight, threads: usize) -> Option<BmsspResult> {
+        Some(self.prepare(g)?.run(sources, bound, threads))
+    }
+}
+
+enum Index {
+    None,
+    Compact(CompactGraph),
+    Ch(ContractionHierarchy),
+    Hl(HubLabels),
+    /// Weight of every edge, for `Bfs`.
+    Uniform(Weight),
+    SmallWeights(SmallWeightKernel),
+}
+
+/// An algorithm bound to a graph and its prebuilt index, ready for repeated queries.
+pub struct Prepared<'g> {
+    algo: Algorithm,
+    g: &'g Graph,
+    index: Index,
+}
+
+impl Prepared<'_> {
+    pub fn algorithm(&self) -> Algorithm { self.algo }
+
+    pub fn run(&self, sources: &[(Node, Weight)], bound: Weight, threads: usize) -> BmsspResult {
+        let g = self.g;
+        match (&self.index, self.algo) {
+            (Index::Compact(cg), Algorithm::Simd) => bounded_multi_source_shortest_paths_simd(cg, sources, bound),
+            (Index::Compact(cg), _) => bounded_multi_source_shortest_paths_compact(cg, sources, bound),
+            (Index::Ch(ch), _) => ch.phast(g, sources, bound),
+            (Index::Hl(hl), _) => hl.bmssp(g, sources, bound),
+            (Index::Uniform(w), _) => bmssp_bfs(g, sources, bound, *w),
+            (Index::SmallWeights(k), _) => bmssp_small_weights(g, sources, bound, *k),
+            (Index::None, Algorithm::Sharded) => bmssp_sharded(g, sources, bound, threads),
+            (Index::None, Algorithm::Atomic) => bmssp_parallel(g, sources, bound, threads),
+            (Index::None, Algorithm::Partitioned) => bmssp_partitioned(g, sources, bound, threads),
+            (Index::None, Algorithm::Batched) => bmssp_batched(g, sources, bound),
+            (Index::None, a) if a.queue() != QueueKind::Binary => bmssp_with_queue_kind(g, sources, bound, a.queue()),
+            (Index::None, _) => bounded_multi_source_shortest_paths(g, sources, bound),
+        }
+    }
+
+    /// Graph plus index bytes, using each structure's own estimate.
+    pub fn memory_estimate_bytes(&self) -> usize {
+        match &self.index {
+            Index::None | Index::Uniform(_) | Index::SmallWeights(_) => self.g.memory_estimate_bytes(),
+            Index::Compact(cg) => cg.memory_estimate_bytes(),
+            Index::Ch(ch) => self.g.memory_estimate_bytes() + ch.memory_estimate_bytes(),
+            Index::Hl(hl) => self.g.memory_estimate_bytes() + hl.memory_estimate_bytes(),
+        }
+    }
+
+    /// `memory_estimate_bytes` from allocated capacities (`Graph::memory_actual_bytes`).
+    pub fn memory_actual_bytes(&self) -> usize {
+        match &self.index {
+            Index::None | Index::Uniform(_) | Index::SmallWeights(_) => self.g.memory_actual_bytes(),
+            Index::Compact(cg) => cg.memory_actual_bytes(),
+            Index::Ch(ch) => self.g.memory_actual_bytes() + ch.memory_actual_bytes(),
+            Index::Hl(hl) => self.g.memory_actual_bytes() + hl.memory_actual_bytes(),
+        }
+    }
+
+    /// The shared edge weight when `Bfs` runs the BFS kernel; `None` if it fell back.
+    pub fn uniform_weight(&self) -> Option<Weight> {
+        if let Index::Uniform(w) = self.index { Some(w) } else { None }
+    }
+
+    /// The kernel `Deque` runs; `None` if it fell back.
+    pub fn small_weight_kernel(&self) -> Option<SmallWeightKernel> {
+        if let Index::SmallWeights(k) = self.index { Some(k) } else { None }
+    }
+
+    pub fn hub_labels(&self) -> Option<&HubLabels> {
+        if let Index::Hl(hl) = &self.index { Some(hl) } else { None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{make_er, pick_sources};
+
+    #[test]
+    fn every_registered_algorithm_matches_dijkstra() {
+        let g = make_er(300, 0.02, 9, 5);
+        let sources = pick_sources(300, 6, 11);
+        let expect = bounded_multi_source_shortest_paths(&g, &sources, 30);
+        for &a in Algorithm::ALL {
+            assert_eq!(Algorithm::parse(a.name()), Some(a));
+            let r = a.run(&g, &sources, 30, 3).unwrap();
+            assert_eq!(r.dist, expect.dist, "{}", a.name());
+            assert_eq!(r.explored, expect.explored, "{}", a.name());
+            assert_eq!(r.b_prime, expect.b_prime, "{}", a.name());
+        }
+        assert_eq!(Algorithm::parse("recursive"), None);
+    }
+}
@@ -0,0 +1,265 @@
+//! Graph analytics built on bounded searches.
+use crate::options::BmsspOptions;
+use crate::solver::Solver;
+use crate::{bmssp_with_options, Graph, Node, Weight};
+
+/// Brandes betweenness restricted to pairs `(s, t)` with `s` in `sample_sources` and
+/// `dist(s, t) < bound`, scaled by `n / |sample_sources|`. Sampling every node with an
+/// unbounded `bound` gives exact (ordered-pair) betweenness. Requires positive weights.
+pub fn approx_betweenness(g: &Graph, sample_sources: &[Node], bound: Weight) -> Vec<f64> {
+    let n = g.len();
+    let mut bc = vec![0.0f64; n];
+    if sample_sources.is_empty() { return bc; }
+    let opts = BmsspOptions{ count_paths: true, ..Default::default() };
+    let mut delta = vec![0.0f64; n];
+    for &s in sample_sources {
+        let r = bmssp_with_options(g, &[(s, 0)], bound, &opts);
+        // Settle order is non-decreasing in distance, so every DAG successor of `v` is
+        // finished when `v` is reached in reverse.
+        for &v in r.explored.iter().rev() {
+            let (dv, sv) = (r.dist[v], r.path_counts[v] as f64);
+            let mut acc = 0.0;
+            for &(w, wt) in &g.adj[v] {
+                let dw = r.dist[w];
+                if dw != Weight::MAX && dv.saturating_add(wt) == dw {
+                    acc += sv / r.path_counts[w] as f64 * (1.0 + delta[w]);
+                }
+            }
+            delta[v] = acc;
+            if v != s { bc[v] += acc; }
+        }
+        for &v in &r.explored { delta[v] = 0.0; }
+    }
+    let scale = n as f64 / sample_sources.len() as f64;
+    for b in &mut bc { *b *= scale; }
+    bc
+}
+
+/// Truncated harmonic closeness: for each `v` in `nodes`, the sum of `1 / dist(v, u)` over
+/// nodes `u != v` settled within `bound`. One `Solver` is reused across all queries.
+pub fn bounded_closeness(g: &Graph, nodes: &[Node], bound: Weight) -> Vec<f64> {
+    let mut solver = Solver::new(g.len());
+    nodes.iter().map(|&v| {
+        solver.run(g, &[(v, 0)], bound);
+        solver.settled().iter().map(|&u| solver.dist(u)).filter(|&d| d > 0).map(|d| 1.0 / d as f64).sum()
+    }).collect()
+}
+
+/// Greedy k-center (Gonzalez farthest-point): start at the first live node, then repeatedly add
+/// the node farthest from every chosen center, a 2-approximation of the smallest covering
+/// radius. Each new center runs one bounded search from itself, capped by `bound_hint` and by
+/// the current radius, since only nodes closer than that can get nearer; nodes no center
+/// reaches within `bound_hint` count as farthest (lowest id first). Returns the centers in
+/// pick order and the covering radius, `Weight::MAX` if some live node is still unreached.
+/// Stops early once every node is a center.
+pub fn k_center_greedy(g: &Graph, k: usize, bound_hint: Weight) -> (Vec<Node>, Weight) {
+    let mut nearest = vec![Weight::MAX; g.len()];
+    let mut solver = Solver::new(g.len());
+    let mut centers = Vec::new();
+    let (mut next, mut radius) = ((0..g.len()).find(|&v| !g.is_removed(v)), Weight::MAX);
+    while let Some(c) = next.filter(|_| centers.len() < k) {
+        centers.push(c);
+        solver.run(g, &[(c, 0)], bound_hint.min(radius));
+        for &v in solver.settled() { nearest[v] = nearest[v].min(solver.dist(v)); }
+        let far = (0..g.len()).filter(|&v| !g.is_removed(v)).max_by_key(|&v| (nearest[v], std::cmp::Reverse(v)));
+        radius = far.map_or(0, |v| nearest[v]);
+        next = far.filter(|_| radius > 0);
+    }
+    (centers, radius)
+}
+
+/// Weakly connected component of every node (edge direction ignored), numbered `0..` in order
+/// of each component's smallest node. Returns the labels and the component count.
+pub fn weak_components(g: &Graph) -> (Vec<usize>, usize) {
+    let n = g.len();
+    let mut parent: Vec<usize> = (0..n).collect();
+    fn find(parent: &mut [usize], mut v: usize) -> usize {
+        while parent[v] != v { parent[v] = parent[parent[v]]; v = parent[v]; }
+        v
+    }
+    for (u, edges) in g.adj.iter().enumerate() {
+        for &(v, _) in edges {
+            let (a, b) = (find(&mut parent, u), find(&mut parent, v));
+            // Smaller root wins, so each root is its component's smallest node.
+            if a != b { parent[a.max(b)] = a.min(b); }
+        }
+    }
+    let mut label = vec![usize::MAX; n];
+    let mut count = 0;
+    for v in 0..n {
+        let r = find(&mut parent, v);
+        if label[r] == usize::MAX { label[r] = count; count += 1; }
+        label[v] = label[r];
+    }
+    (label, count)
+}
+
+/// Strongly connected components by iterative Tarjan: `(label, count)` with labels in
+/// topological order of the condensation, so every edge `u -> v` has `label[u] <= label[v]`.
+pub fn scc(g: &Graph) -> (Vec<usize>, usize) {
+    const UNSEEN: usize = usize::MAX;
+    let n = g.len();
+    let (mut index, mut low, mut label) = (vec![UNSEEN; n], vec![0usize; n], vec![UNSEEN; n]);
+    let (mut stack, mut call): (Vec<Node>, Vec<(Node, usize)>) = (Vec::new(), Vec::new());
+    let (mut next, mut count) = (0usize, 0usize);
+    for root in 0..n {
+        if index[root] != UNSEEN { continue; }
+        index[root] = next; low[root] = next; next += 1;
+        stack.push(root); call.push((root, 0));
+        while let Some(&mut (v, ref mut i)) = call.last_mut() {
+            if let Some(&(w, _)) = g.adj[v].get(*i) {
+                *i += 1;
+                if index[w] == UNSEEN {
+                    index[w] = next; low[w] = next; next += 1;
+                    stack.push(w); call.push((w, 0));
+                } else if label[w] == UNSEEN {
+                    // Still on the stack: same component as some ancestor.
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+            call.pop();
+            if let Some(&(p, _)) = call.last() { low[p] = low[p].min(low[v]); }
+            if low[v] == index[v] {
+                loop {
+                    let w = stack.pop().unwrap();
+                    label[w] = count;
+                    if w == v { break; }
+                }
+                count += 1;
+            }
+        }
+    }
+    // Tarjan finishes sinks first; flip to sources first.
+    for l in &mut label { *l = count - 1 - *l; }
+    (label, count)
+}
+
+/// Condensation of `g`: one node per strongly connected component (numbered as in `scc`), with
+/// the lightest edge between each pair of components and no self-loops. Returns it with the
+/// component label of every original node. The result is a DAG.
+pub fn condensation(g: &Graph) -> (Graph, Vec<usize>) {
+    let (label, count) = scc(g);
+    let mut h = Graph::new(count);
+    for (u, edges) in g.adj.iter().enumerate() {
+        for &(v, w) in edges {
+            if label[u] != label[v] { h.adj[label[u]].push((label[v], w)); }
+        }
+    }
+    for edges in &mut h.adj {
+        edges.sort_unstable();
+        edges.dedup_by_key(|e| e.0);
+    }
+    (h, label)
+}
+
+/// Sources whose weak component has fewer than `min_size` nodes, with that component's size.
+/// A bounded search from such a source can only settle that many nodes, whatever the bound.
+pub fn sources_in_small_components(g: &Graph, sources: &[(Node, Weight)], min_size: usize) -> Vec<(Node, usize)> {
+    let (label, count) = weak_components(g);
+    let mut size = vec![0usize; count];
+    for &c in &label { size[c] += 1; }
+    sources.iter().map(|&(s, _)| (s, size[label[s]])).filter(|&(_, n)| n < min_size).collect()
+}
+
+/// Subgraph induced by the weak components that contain any of `seeds`, with nodes renumbered
+/// densely in original order. Returns it with `kept`, where `kept[new] = old`. Searches from
+/// the seeds settle the same nodes at the same distances, on a smaller `n`.
+pub fn component_subgraph(g: &Graph, seeds: &[Node]) -> (Graph, Vec<Node>) {
+    let (label, count) = weak_components(g);
+    let mut wanted = vec![false; count];
+    for &s in seeds { wanted[label[s]] = true; }
+    let kept: Vec<Node> = (0..g.len()).filter(|&v| wanted[label[v]]).collect();
+    let mut new_id = vec![usize::MAX; g.len()];
+    for (i, &v) in kept.iter().enumerate() { new_id[v] = i; }
+    let mut h = Graph::new(kept.len());
+    for (i, &v) in kept.iter().enumerate() {
+        // Same component, so every target is kept too.
+        h.adj[i] = g.adj[v].iter().map(|&(t, w)| (new_id[t], w)).collect();
+    }
+    (h, kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_on_path_with_all_pivots() {
+        let mut g = Graph::new(5);
+        for i in 0..4 { g.add_undirected_edge(i, i + 1, 1); }
+        let all: Vec<Node> = (0..5).collect();
+        let bc = approx_betweenness(&g, &all, Weight::MAX);
+        assert_eq!(bc, vec![0.0, 6.0, 8.0, 6.0, 0.0]);
+        // Truncation: with bound 3 only pairs at distance <= 2 count.
+        let bc = approx_betweenness(&g, &all, 3);
+        assert_eq!(bc, vec![0.0, 2.0, 2.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn splits_dependency_across_equal_paths() {
+        // Diamond 0 -> {1, 2} -> 3: each middle node carries half of the 0 -> 3 pair.
+        let mut g = Graph::new(4);
+        g.add_edge(0, 1, 1); g.add_edge(0, 2, 1); g.add_edge(1, 3, 1); g.add_edge(2, 3, 1);
+        let bc = approx_betweenness(&g, &[0], Weight::MAX);
+        assert_eq!(bc, vec![0.0, 2.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn weak_components_ignore_direction() {
+        let mut g = Graph::new(6);
+        g.add_edge(1, 0, 1); g.add_edge(2, 1, 1); g.add_edge(5, 3, 1);
+        assert_eq!(weak_components(&g), (vec![0, 0, 0, 1, 2, 1], 3));
+    }
+
+    #[test]
+    fn scc_labels_follow_topological_order() {
+        // 0 <-> 1 -> 2 <-> 3 <-> 4, 5 -> 0, 6 alone.
+        let mut g = Graph::new(7);
+        for (u, v, w) in [(0, 1, 1), (1, 0, 1), (1, 2, 7), (1, 2, 3), (2, 3, 1), (3, 4, 1), (4, 2, 1), (5, 0, 2)] { g.add_edge(u, v, w); }
+        let (label, count) = scc(&g);
+        assert_eq!(count, 4);
+        assert_eq!(label[0], label[1]);
+        assert!(label[2] == label[3] && label[3] == label[4]);
+        for u in 0..7 { for &(v, _) in &g.adj[u] { assert!(label[u] <= label[v]); } }
+        let (h, hl) = condensation(&g);
+        assert_eq!(hl, label);
+        assert_eq!(h.num_edges(), 2);
+        assert_eq!(h.adj[label[1]], [(label[2], 3)]);
+        assert_eq!(h.adj[label[5]], [(label[0], 2)]);
+    }
+
+    #[test]
+    fn restricts_to_source_components() {
+        let mut g = Graph::new(7);
+        g.add_edge(0, 1, 1); g.add_edge(1, 2, 1); g.add_edge(4, 3, 2); g.add_edge(5, 6, 1);
+        assert_eq!(sources_in_small_components(&g, &[(0, 0), (3, 0), (5, 0)], 3), [(3, 2), (5, 2)]);
+        let (h, kept) = component_subgraph(&g, &[3, 1]);
+        assert_eq!(kept, [0, 1, 2, 3, 4]);
+        assert_eq!(h.adj, vec![vec![(1, 1)], vec![(2, 1)], vec![], vec![], vec![(3, 2)]]);
+        let full = crate::bounded_multi_source_shortest_paths(&g, &[(4, 0)], 10);
+        let sub = crate::bounded_multi_source_shortest_paths(&h, &[(4, 0)], 10);
+        assert_eq!(sub.settled_pairs(), full.settled_pairs());
+    }
+
+    #[test]
+    fn k_center_spreads_along_a_path() {
+        let mut g = Graph::new(9);
+        for i in 0..8 { g.add_undirected_edge(i, i + 1, 1); }
+        assert_eq!(k_center_greedy(&g, 3, Weight::MAX), (vec![0, 8, 4], 2));
+        // A short hint still finds the far end: unreached nodes count as farthest.
+        assert_eq!(k_center_greedy(&g, 3, 3), (vec![0, 3, 6], 2));
+        assert_eq!(k_center_greedy(&g, 1, 3), (vec![0], Weight::MAX));
+        assert_eq!(k_center_greedy(&g, 20, Weight::MAX).0.len(), 9);
+        assert_eq!(k_center_greedy(&Graph::new(0), 2, 5), (vec![], Weight::MAX));
+    }
+
+    #[test]
+    fn closeness_truncates_at_bound() {
+        let mut g = Graph::new(5);
+        for i in 0..4 { g.add_undirected_edge(i, i + 1, 1); }
+        let c = bounded_closeness(&g, &[0, 2], 3);
+        assert_eq!(c, vec![1.0 + 0.5, 2.0 * (1.0 + 0.5)]);
+        assert_eq!(bounded_closeness(&g, &[4], 1), vec![0.0]);
+    }
+}
@@ -0,0 +1,93 @@
+//! Unit-weight fast path. When every edge has the same weight `w`, labels are `w` times hop
+//! counts plus source offsets, and a FIFO queue already pops in non-decreasing label order, so
+//! the bounded search needs no heap (`--algo bfs`).
+use std::collections::VecDeque;
+
+use crate::{BmsspResult, GraphLike, Node, Weight};
+
+/// The weight shared by every edge, or `None` if two edges differ or there are no edges.
+pub fn uniform_weight<G: GraphLike>(g: &G) -> Option<Weight> {
+    let mut first = None;
+    for u in 0..g.num_nodes() {
+        for (_, w) in g.neighbors(u) {
+            match first {
+                None => first = Some(w),
+                Some(x) if x != w => return None,
+                _ => {}
+            }
+        }
+    }
+    first
+}
+
+/// Bounded BFS for a graph whose edges all weigh `w` (see `uniform_weight`); the edges' own
+/// weights are not read. Distances, B' and the settled set match
+/// `bounded_multi_source_shortest_paths`, and sources may carry any offsets. `explored` is in
+/// FIFO order, so it is sorted by distance but ties are not in node order.
+pub fn bmssp_bfs<G: GraphLike>(g: &G, sources: &[(Node, Weight)], bound: Weight, w: Weight) -> BmsspResult {
+    let n = g.num_nodes();
+    let mut dist = vec![Weight::MAX; n];
+    let mut seeds: Vec<(Weight, Node)> = sources.iter().filter(|&&(s, d0)| s < n && d0 < bound && !g.is_removed(s)).map(|&(s, d0)| (d0, s)).collect();
+    seeds.sort_unstable();
+    let mut next_seed = 0;
+    let mut queue: VecDeque<(Weight, Node)> = VecDeque::new();
+    let (mut explored, mut b_prime) = (Vec::new(), Weight::MAX);
+    let (mut edges_scanned, mut heap_pushes) = (0, 0);
+    loop {
+        // Seeds at or below the head's label go in front, keeping the queue sorted.
+        while let Some(&(d0, s)) = seeds.get(next_seed) {
+            if queue.front().is_some_and(|&(d, _)| d < d0) { break; }
+            next_seed += 1;
+            if d0 < dist[s] {
+                dist[s] = d0;
+                queue.push_front((d0, s));
+            }
+        }
+        let Some((d, v)) = queue.pop_front() else { break };
+        if d != dist[v] { continue; }
+        explored.push(v);
+        let nd = d.saturating_add(w);
+        for (to, _) in g.neighbors(v) {
+            edges_scanned += 1;
+            if nd < dist[to] && nd < bound {
+                dist[to] = nd;
+                queue.push_back((nd, to));
+                heap_pushes += 1;
+            } else if nd >= bound && nd < b_prime {
+                b_prime = nd;
+            }
+        }
+    }
+    BmsspResult{ dist, explored, b_prime, edges_scanned, heap_pushes, ..Default::default() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use crate::generators::{make_ba, make_grid};
+    use crate::Graph;
+
+    #[test]
+    fn bfs_matches_dijkstra_on_uniform_weights() {
+        let reweight = |g: Graph, w| {
+            let mut h = Graph::new(g.len());
+            for u in 0..g.len() { for &(v, _) in &g.adj[u] { h.add_edge(u, v, w); } }
+            h
+        };
+        for (g, w) in [(reweight(make_grid(40, 40, 1, 1), 1), 1), (reweight(make_ba(800, 4, 3, 9, 2), 3), 3)] {
+            assert_eq!(uniform_weight(&g), Some(w));
+            let sources = [(5, 0), (700, 4), (301, 2), (5, 1), (640, 11)];
+            for bound in [0, 1, 5, 13, 40, Weight::MAX] {
+                let (a, b) = (bounded_multi_source_shortest_paths(&g, &sources, bound), bmssp_bfs(&g, &sources, bound, w));
+                assert_eq!((&a.dist, a.b_prime), (&b.dist, b.b_prime), "B={bound}");
+                assert!(b.explored.windows(2).all(|p| b.dist[p[0]] <= b.dist[p[1]]));
+                let mut settled = b.explored.clone();
+                settled.sort_unstable_by_key(|&v| (b.dist[v], v));
+                assert_eq!(settled, a.explored);
+            }
+        }
+        assert_eq!(uniform_weight(&make_grid(5, 5, 9, 1)), None);
+        assert_eq!(uniform_weight(&Graph::new(3)), None);
+    }
+}
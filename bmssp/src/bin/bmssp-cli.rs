@@ -1,16 +1,33 @@
 use bmssp::*;
-use rand::{rngs::StdRng, Rng, SeedableRng};
-use serde::Serialize;
+use bmssp::generators::{make_ba, make_er, make_grid, pick_sources, pick_sources_with, with_random_offsets, SourceStrategy};
+use serde::{Deserialize, Serialize};
 use std::time::Instant;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufReader;
 
 #[derive(Debug, Clone, Copy)]
 enum GraphType { Grid, ER, BA }
 
+/// How `--threads > 1` runs are parallelized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ParMode { Sharded, Atomic, Partitioned }
+impl ParMode {
+    fn name(self) -> &'static str {
+        match self { ParMode::Sharded => "sharded", ParMode::Atomic => "atomic", ParMode::Partitioned => "partitioned" }
+    }
+}
+
+/// Where `--source-offsets` takes the sources' `d0` from.
+#[derive(Debug, Clone)]
+enum SourceOffsets { Random(Weight), File(PathBuf) }
+
+/// One `--json` line. Fields are documented in `bench/schema.json`; renaming, removing or
+/// changing the meaning of one needs a major bump of `ROW_SCHEMA_VERSION`, adding an optional
+/// one a minor bump.
 #[derive(Serialize)]
 struct OutputRow {
+    schema_version: &'static str,
     #[serde(rename = "impl")] impl_: &'static str,
     lang: &'static str,
     graph: &'static str,
@@ -26,209 +43,1289 @@ struct OutputRow {
     heap_pushes: usize,
     #[serde(rename = "B_prime")] b_prime: u64,
     mem_bytes: usize,
+    /// `mem_bytes` recomputed from allocated `Vec` capacities.
+    mem_actual_bytes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")] parallel: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")] pool: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")] reorder: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")] reorder_ns: Option<u128>,
+    /// Time to read and parse `--graph-file`, excluded from `time_ns`.
+    #[serde(skip_serializing_if = "Option::is_none")] parse_ns: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")] preprocess_ns: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")] numa: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")] termination: Option<&'static str>,
+    /// With a budget: the distance every settled row is complete up to (see `BmsspResult::effective_bound`).
+    #[serde(skip_serializing_if = "Option::is_none")] effective_bound: Option<Weight>,
+    #[serde(skip_serializing_if = "Option::is_none")] overflows: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")] cost: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")] queue: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")] batched: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")] query: Option<usize>,
+    /// `rank` subcommand: the target's Dijkstra rank from the query's source, and the target.
+    #[serde(skip_serializing_if = "Option::is_none")] rank: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")] target: Option<Node>,
+    /// `--source-strategy` when not uniform.
+    #[serde(skip_serializing_if = "Option::is_none")] sources: Option<&'static str>,
+    /// `--source-offsets`: `random:MAX` or `file`.
+    #[serde(skip_serializing_if = "Option::is_none")] offsets: Option<String>,
+    /// `--mode` when not `single`.
+    #[serde(skip_serializing_if = "Option::is_none")] mode: Option<&'static str>,
+    /// `--mode phased`: phase index and the phase's lower bound (the previous phase's B').
+    #[serde(skip_serializing_if = "Option::is_none")] phase: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")] lower_bound: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")] speedup: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")] efficiency: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")] scenario: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")] algo: Option<&'static str>,
+}
+
+impl OutputRow {
+    /// Row for `inst` and `args` with zeroed counters and no optional columns but the ones
+    /// every mode reports; callers fill the rest with struct update syntax.
+    fn base(inst: &Instance, args: &Args) -> Self {
+        OutputRow{
+            schema_version: ROW_SCHEMA_VERSION,
+            impl_: "rust-bmssp",
+            lang: "Rust",
+            graph: inst.gname,
+            n: inst.g.len(),
+            m: inst.g.num_edges(),
+            k: inst.sources.len(),
+            b: args.b,
+            seed: args.seed,
+            threads: 1,
+            time_ns: 0,
+            popped: 0,
+            edges_scanned: 0,
+            heap_pushes: 0,
+            b_prime: 0,
+            mem_bytes: inst.mem,
+            mem_actual_bytes: inst.mem_actual,
+            parallel: None,
+            pool: None,
+            reorder: args.reorder,
+            reorder_ns: inst.reorder_ns,
+            parse_ns: inst.parse_ns,
+            preprocess_ns: None,
+            numa: None,
+            termination: None,
+            effective_bound: None,
+            overflows: None,
+            cost: None,
+            queue: None,
+            batched: None,
+            query: None,
+            rank: None,
+            target: None,
+            sources: args.source_tag(),
+            offsets: args.offsets_tag(),
+            mode: None,
+            phase: None,
+            lower_bound: None,
+            speedup: None,
+            efficiency: None,
+            scenario: args.scenario.clone(),
+            algo: args.algo,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Args {
+    graph: GraphType,
+    n: usize,
+    grid_rc: Option<(usize,usize)>,
+    p: f64,
+    m0: usize,
+    m_ba: usize,
+    maxw: u32,
+    k: usize,
+    b: u64,
+    seed: u64,
+    trials: usize,
+    threads: usize,
+    json: bool,
+    graph_file: Option<PathBuf>,
+    /// Threads for parsing `--graph-file`; 1 streams it line by line.
+    parse_threads: usize,
+    /// `--graph-format`; detected from the file when unset.
+    graph_format: Option<formats::GraphFormat>,
+    sources_file: Option<PathBuf>,
+    source_strategy: SourceStrategy,
+    source_offsets: Option<SourceOffsets>,
+    validate_graph: bool,
+    dedup: bool,
+    reorder: Option<&'static str>,
+    parallel: ParMode,
+    pool_reuse: bool,
+    threads_sweep: Option<Vec<usize>>,
+    numa: bool,
+    gpu: bool,
+    /// Algorithm that builds an index (or CSR copy) before the timed queries.
+    prepared: Option<Algorithm>,
+    cost: Option<&'static str>,
+    queue: QueueKind,
+    batched: bool,
+    max_edges: Option<usize>,
+    max_settled: Option<usize>,
+    deadline_ms: Option<u64>,
+    avoid: Vec<Node>,
+    max_hops: Option<u32>,
+    tie_break: TieBreak,
+    overflow: OverflowPolicy,
+    save_csr: Option<PathBuf>,
+    queries: Option<PathBuf>,
+    /// `workload` subcommand: generator kind, number of queries and output file.
+    workload: Option<&'static str>,
+    count: usize,
+    out: Option<PathBuf>,
+    /// `stats` subcommand: print `Graph::stats` for the instance instead of timing it.
+    stats: bool,
+    /// `rank` subcommand: time `--count` sources' Dijkstra-rank pairs instead of `--k` sources.
+    rank_eval: bool,
+    /// `--mode phased`: chain searches of width `--B`, each seeded with the previous boundary.
+    phased: bool,
+    /// `--mode reach`: only the set of nodes within `--B` (`reachable_within`), no distances.
+    reach_only: bool,
+    /// Stop `--mode phased` after this many phases.
+    max_phases: Option<usize>,
+    /// Sources in weak components below this size are reported; default 1% of `n`.
+    min_component: Option<usize>,
+    strict_components: bool,
+    restrict_components: bool,
+    /// Call `Graph::shrink_to_fit_all` before timing.
+    shrink: bool,
+    config: Option<PathBuf>,
+    dump_dist: Option<PathBuf>,
+    /// Binary snapshot of the oracle result, for repeated `bmssp-cli diff`.
+    save_result: Option<PathBuf>,
+    /// Results of one tracked search, in original ids: distances (binary for `.bin`), nearest
+    /// sources, and paths.
+    out_distances: Option<PathBuf>,
+    out_labels: Option<PathBuf>,
+    out_paths: Option<PathBuf>,
+    trace: Option<PathBuf>,
+    histogram: Option<Weight>,
+    /// Row tags set for cells of a `--config` matrix.
+    scenario: Option<String>,
+    algo: Option<&'static str>,
 }
 
-fn parse_args() -> (GraphType, usize, Option<(usize,usize)>, f64, usize, usize, u32, usize, u64, u64, usize, usize, bool, Option<PathBuf>, Option<PathBuf>) {
+impl Args {
+    /// Row tag for a non-default `--source-strategy`.
+    fn source_tag(&self) -> Option<&'static str> {
+        (self.source_strategy != SourceStrategy::Uniform).then(|| self.source_strategy.as_str())
+    }
+
+    /// Row tag for `--source-offsets`.
+    fn offsets_tag(&self) -> Option<String> {
+        self.source_offsets.as_ref().map(|o| match o { SourceOffsets::Random(max) => format!("random:{max}"), SourceOffsets::File(_) => "file".into() })
+    }
+}
+
+fn parse_args() -> Args {
     // Minimal, no external clap to keep deps small.
-    let mut graph = GraphType::ER;
-    let mut n: usize = 10_000;
-    let mut grid_rc: Option<(usize,usize)> = None;
+    let mut a = Args {
+        graph: GraphType::ER,
+        n: 10_000,
+        grid_rc: None,
+        p: 0.0005,
+        m0: 5,
+        m_ba: 5,
+        maxw: 100,
+        k: 16,
+        b: 500,
+        seed: 42,
+        trials: 5,
+        threads: 1,
+        json: true,
+        graph_file: None,
+        parse_threads: 1,
+        graph_format: None,
+        sources_file: None,
+        source_strategy: SourceStrategy::Uniform,
+        source_offsets: None,
+        validate_graph: false,
+        dedup: false,
+        reorder: None,
+        parallel: ParMode::Sharded,
+        pool_reuse: false,
+        threads_sweep: None,
+        numa: false,
+        gpu: false,
+        prepared: None,
+        cost: None,
+        queue: QueueKind::Binary,
+        batched: false,
+        max_edges: None,
+        max_settled: None,
+        deadline_ms: None,
+        avoid: Vec::new(),
+        max_hops: None,
+        tie_break: TieBreak::NodeId,
+        overflow: OverflowPolicy::Saturate,
+        save_csr: None,
+        queries: None,
+        workload: None,
+        count: 100,
+        out: None,
+        config: None,
+        dump_dist: None,
+        save_result: None,
+        out_distances: None,
+        out_labels: None,
+        out_paths: None,
+        trace: None,
+        histogram: None,
+        stats: false,
+        rank_eval: false,
+        phased: false,
+        reach_only: false,
+        max_phases: None,
+        min_component: None,
+        strict_components: false,
+        restrict_components: false,
+        shrink: false,
+        scenario: None,
+        algo: None,
+    };
     let mut rows_opt: Option<usize> = None;
     let mut cols_opt: Option<usize> = None;
-    let mut p: f64 = 0.0005;
-    let mut m0: usize = 5;
-    let mut m_ba: usize = 5;
-    let mut maxw: u32 = 100;
-    let mut k: usize = 16;
-    let mut b: u64 = 500;
-    let mut seed: u64 = 42;
-    let mut trials: usize = 5;
-    let mut threads: usize = 1;
-    let mut json: bool = true;
-    let mut graph_file: Option<PathBuf> = None;
-    let mut sources_file: Option<PathBuf> = None;
-
-    let mut it = std::env::args().skip(1);
-    while let Some(a) = it.next() {
-        match a.as_str() {
+
+    let mut it = std::env::args().skip(1).peekable();
+    // `run` is the default subcommand and may be omitted.
+    match it.peek().map(String::as_str) {
+        Some("run") => { it.next(); }
+        Some("workload") => { it.next(); a.workload = Some("uniform"); }
+        Some("stats") => { it.next(); a.stats = true; }
+        Some("rank") => { it.next(); a.rank_eval = true; }
+        _ => {}
+    }
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
             "--graph" => {
                 let v = it.next().expect("--graph value");
-                graph = match v.as_str() { "grid" => GraphType::Grid, "er" => GraphType::ER, "ba" => GraphType::BA, _ => panic!("bad graph") };
+                a.graph = match v.as_str() { "grid" => GraphType::Grid, "er" => GraphType::ER, "ba" => GraphType::BA, _ => panic!("bad graph") };
             }
-            "--n" => n = it.next().unwrap().parse().unwrap(),
+            "--n" => a.n = it.next().unwrap().parse().unwrap(),
             "--rows" => { rows_opt = Some(it.next().unwrap().parse().unwrap()); }
             "--cols" => { cols_opt = Some(it.next().unwrap().parse().unwrap()); }
-            "--p" => p = it.next().unwrap().parse().unwrap(),
-            "--m0" => m0 = it.next().unwrap().parse().unwrap(),
-            "--m" => m_ba = it.next().unwrap().parse().unwrap(),
-            "--maxw" => maxw = it.next().unwrap().parse().unwrap(),
-            "--k" => k = it.next().unwrap().parse().unwrap(),
-            "--B" => b = it.next().unwrap().parse().unwrap(),
-            "--seed" => seed = it.next().unwrap().parse().unwrap(),
-            "--trials" => trials = it.next().unwrap().parse().unwrap(),
-            "--threads" => threads = it.next().unwrap().parse().unwrap(),
-            "--json" => json = true,
-        "--graph-file" => { let v = it.next().expect("--graph-file value"); graph_file = Some(PathBuf::from(v)); }
-        "--sources-file" => { let v = it.next().expect("--sources-file value"); sources_file = Some(PathBuf::from(v)); }
+            "--p" => a.p = it.next().unwrap().parse().unwrap(),
+            "--m0" => a.m0 = it.next().unwrap().parse().unwrap(),
+            "--m" => a.m_ba = it.next().unwrap().parse().unwrap(),
+            "--maxw" => a.maxw = it.next().unwrap().parse().unwrap(),
+            "--k" => a.k = it.next().unwrap().parse().unwrap(),
+            "--B" => a.b = it.next().unwrap().parse().unwrap(),
+            "--seed" => a.seed = it.next().unwrap().parse().unwrap(),
+            "--trials" => a.trials = it.next().unwrap().parse().unwrap(),
+            "--threads" => a.threads = it.next().unwrap().parse().unwrap(),
+            "--json" => a.json = true,
+            "--graph-file" => { let v = it.next().expect("--graph-file value"); a.graph_file = Some(PathBuf::from(v)); }
+            "--sources-file" => { let v = it.next().expect("--sources-file value"); a.sources_file = Some(PathBuf::from(v)); }
+            "--source-strategy" => {
+                let v = it.next().expect("--source-strategy value");
+                let names: Vec<_> = SourceStrategy::ALL.iter().map(|s| s.as_str()).collect();
+                a.source_strategy = SourceStrategy::parse(&v).unwrap_or_else(|| panic!("bad source strategy (expected one of {})", names.join(", ")));
+            }
+            "--source-offsets" => {
+                let v = it.next().expect("--source-offsets value");
+                a.source_offsets = Some(match v.strip_prefix("random:") {
+                    Some(max) => SourceOffsets::Random(max.parse().unwrap_or_else(|_| panic!("bad --source-offsets random:MAX"))),
+                    None => SourceOffsets::File(PathBuf::from(v)),
+                });
+            }
+            "--mode" => {
+                let v = it.next().expect("--mode value");
+                (a.phased, a.reach_only) = match v.as_str() { "single" => (false, false), "phased" => (true, false), "reach" => (false, true), _ => panic!("bad mode (single|phased|reach)") };
+            }
+            "--max-phases" => a.max_phases = Some(it.next().unwrap().parse().unwrap()),
+            "--validate-graph" => a.validate_graph = true,
+            "--dedup" => a.dedup = true,
+            "--kind" => {
+                let v = it.next().expect("--kind value");
+                a.workload = Some(match v.as_str() { "uniform" => "uniform", "clustered" => "clustered", "rank" => "rank", _ => panic!("--kind must be uniform|clustered|rank") });
+            }
+            "--graph-format" => {
+                let v = it.next().expect("--graph-format value");
+                a.graph_format = Some(formats::GraphFormat::parse(&v).expect("bad graph format (edges|dimacs|metis|mtx|graphml|osm)"));
+            }
+            "--parse-threads" => a.parse_threads = it.next().unwrap().parse().unwrap(),
+            "--count" => a.count = it.next().unwrap().parse().unwrap(),
+            "--out" => { let v = it.next().expect("--out value"); a.out = Some(PathBuf::from(v)); }
+            "--trace" => { let v = it.next().expect("--trace value"); a.trace = Some(PathBuf::from(v)); }
+            "--histogram" => {
+                let w: Weight = it.next().and_then(|v| v.parse().ok()).unwrap_or(0);
+                if w == 0 { eprintln!("--histogram takes a positive bucket width"); std::process::exit(2); }
+                a.histogram = Some(w);
+            }
+            "--dump-dist" => { let v = it.next().expect("--dump-dist value"); a.dump_dist = Some(PathBuf::from(v)); }
+            "--save-result" => { let v = it.next().expect("--save-result value"); a.save_result = Some(PathBuf::from(v)); }
+            "--out-distances" => { let v = it.next().expect("--out-distances value"); a.out_distances = Some(PathBuf::from(v)); }
+            "--out-labels" => { let v = it.next().expect("--out-labels value"); a.out_labels = Some(PathBuf::from(v)); }
+            "--out-paths" => { let v = it.next().expect("--out-paths value"); a.out_paths = Some(PathBuf::from(v)); }
+            "--config" => { let v = it.next().expect("--config value"); a.config = Some(PathBuf::from(v)); }
+            "--queries" => { let v = it.next().expect("--queries value"); a.queries = Some(PathBuf::from(v)); }
+            "--save-csr" => { let v = it.next().expect("--save-csr value"); a.save_csr = Some(PathBuf::from(v)); }
+            "--parallel" => {
+                let v = it.next().expect("--parallel value");
+                a.parallel = match v.as_str() { "sharded" => ParMode::Sharded, "atomic" => ParMode::Atomic, "partitioned" => ParMode::Partitioned, _ => panic!("bad parallel mode") };
+            }
+            "--threads-sweep" => {
+                let v = it.next().expect("--threads-sweep value");
+                a.threads_sweep = Some(v.split(',').map(|t| t.trim().parse().expect("bad thread count")).collect());
+            }
+            "--numa" => a.numa = true,
+            "--gpu" => a.gpu = true,
+            "--phast" => a.prepared = Some(Algorithm::Phast),
+            "--hub-labels" => a.prepared = Some(Algorithm::HubLabels),
+            "--algo" => {
+                let v = it.next().expect("--algo value");
+                let algo = Algorithm::parse(&v).unwrap_or_else(|| panic!("bad algo (expected one of {})", Algorithm::names()));
+                apply_algo(&mut a, algo);
+            }
+            "--batched" => a.batched = true,
+            "--queue" => {
+                let v = it.next().expect("--queue value");
+                let names: Vec<_> = QueueKind::ALL.iter().map(|q| q.as_str()).collect();
+                a.queue = QueueKind::parse(&v).unwrap_or_else(|| panic!("bad queue (expected one of {})", names.join(", ")));
+            }
+            "--cost" => {
+                let v = it.next().expect("--cost value");
+                a.cost = match v.as_str() { "sum" => None, "bottleneck" => Some("bottleneck"), "hops" => Some("hops"), _ => panic!("bad cost model") };
+            }
+            "--max-edges" => a.max_edges = Some(it.next().unwrap().parse().unwrap()),
+            "--max-settled" => a.max_settled = Some(it.next().unwrap().parse().unwrap()),
+            "--deadline-ms" => a.deadline_ms = Some(it.next().unwrap().parse().unwrap()),
+            "--max-hops" => a.max_hops = Some(it.next().unwrap().parse().unwrap()),
+            "--tie-break" => {
+                let v = it.next().expect("--tie-break value");
+                a.tie_break = TieBreak::parse(&v).expect("bad tie-break policy (id|insertion|any)");
+            }
+            "--overflow" => {
+                let v = it.next().expect("--overflow value");
+                a.overflow = match v.as_str() {
+                    "saturate" => OverflowPolicy::Saturate,
+                    "error" => OverflowPolicy::Error,
+                    "wrap" => OverflowPolicy::WrapDebugPanic,
+                    _ => panic!("bad overflow policy (saturate|error|wrap)"),
+                };
+            }
+            "--avoid" => {
+                let v = it.next().expect("--avoid value");
+                a.avoid = v.split(',').map(|t| t.trim().parse().expect("bad node id")).collect();
+            }
+            "--pool" => {
+                let v = it.next().expect("--pool value");
+                a.pool_reuse = match v.as_str() { "spawn" => false, "reuse" => true, _ => panic!("bad pool mode") };
+            }
+            "--min-component" => a.min_component = Some(it.next().and_then(|v| v.parse().ok()).expect("--min-component value")),
+            "--strict-components" => a.strict_components = true,
+            "--restrict-components" => a.restrict_components = true,
+            "--shrink" => a.shrink = true,
+            "--reorder" => {
+                let v = it.next().expect("--reorder value");
+                a.reorder = match v.as_str() { "none" => None, "rcm" => Some("rcm"), "bfs" => Some("bfs"), _ => panic!("bad reorder") };
+            }
             _ => {}
         }
     }
-    if rows_opt.is_some() || cols_opt.is_some() { grid_rc = Some((rows_opt.unwrap_or(1), cols_opt.unwrap_or(1))); }
-    (graph, n, grid_rc, p, m0, m_ba, maxw, k, b, seed, trials, threads, json, graph_file, sources_file)
+    if rows_opt.is_some() || cols_opt.is_some() { a.grid_rc = Some((rows_opt.unwrap_or(1), cols_opt.unwrap_or(1))); }
+    a
 }
 
-fn make_grid(rows: usize, cols: usize, maxw: u32, seed: u64) -> Graph {
-    let mut rng = StdRng::seed_from_u64(seed);
-    let mut g = Graph::new(rows * cols);
-    let idx = |r: usize, c: usize| -> usize { r * cols + c };
-    for r in 0..rows {
-        for c in 0..cols {
-            let u = idx(r,c);
-            if r + 1 < rows {
-                let w = rng.gen_range(1..=maxw) as u64;
-                g.add_undirected_edge(u, idx(r+1,c), w);
-            }
-            if c + 1 < cols {
-                let w = rng.gen_range(1..=maxw) as u64;
-                g.add_undirected_edge(u, idx(r,c+1), w);
-            }
-        }
+/// Sweep declared in a `--config` TOML file. Every axis is a list and the CLI runs the full
+/// cross product; axes left out keep the value from the command line.
+///
+/// ```toml
+/// name = "grid-vs-er"
+/// graph = ["grid", "er"]
+/// n = [10000, 100000]
+/// k = [4, 16]
+/// B = [100, 500]
+/// threads = [1, 4]
+/// algo = ["dijkstra", "sharded", "atomic"]
+/// trials = 3
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Scenario {
+    name: Option<String>,
+    #[serde(default)] graph: Vec<String>,
+    #[serde(default)] n: Vec<usize>,
+    #[serde(default)] k: Vec<usize>,
+    #[serde(default, rename = "B")] b: Vec<u64>,
+    #[serde(default)] threads: Vec<usize>,
+    #[serde(default)] algo: Vec<String>,
+    seed: Option<u64>,
+    trials: Option<usize>,
+    maxw: Option<u32>,
+    p: Option<f64>,
+}
+
+/// Point `a` at `algo`'s code path and tag its rows. `dijkstra` is the default CPU path
+/// (sharded when `threads > 1`); the queue variants, `batched` and the prepared algorithms are
+/// single-threaded.
+fn apply_algo(a: &mut Args, algo: Algorithm) {
+    match algo {
+        Algorithm::Dijkstra => {}
+        Algorithm::Sharded => a.parallel = ParMode::Sharded,
+        Algorithm::Atomic => a.parallel = ParMode::Atomic,
+        Algorithm::Partitioned => a.parallel = ParMode::Partitioned,
+        Algorithm::Pairing | Algorithm::Mlb => a.queue = algo.queue(),
+        Algorithm::Batched => a.batched = true,
+        Algorithm::Bfs | Algorithm::Deque | Algorithm::Compact | Algorithm::Simd | Algorithm::Phast | Algorithm::HubLabels => a.prepared = Some(algo),
     }
-    g
+    a.algo = Some(algo.name());
 }
 
-fn make_er(n: usize, p: f64, maxw: u32, seed: u64) -> Graph {
-    let mut rng = StdRng::seed_from_u64(seed);
-    let mut g = Graph::new(n);
-    for u in 0..n {
-        for v in 0..n {
-            if u == v { continue; }
-            if rng.gen::<f64>() < p {
-                let w = rng.gen_range(1..=maxw) as u64;
-                g.add_edge(u, v, w);
-            }
+fn run_config(path: &PathBuf, base: &Args) {
+    let text = std::fs::read_to_string(path).unwrap_or_else(|e| { eprintln!("error: --config {}: {e}", path.display()); std::process::exit(2) });
+    let sc: Scenario = toml::from_str(&text).unwrap_or_else(|e| { eprintln!("error: --config {}: {e}", path.display()); std::process::exit(2) });
+    let or = |v: &Vec<usize>, d: usize| if v.is_empty() { vec![d] } else { v.clone() };
+    let graphs: Vec<GraphType> = if sc.graph.is_empty() { vec![base.graph] } else {
+        sc.graph.iter().map(|g| match g.as_str() {
+            "grid" => GraphType::Grid, "er" => GraphType::ER, "ba" => GraphType::BA,
+            other => { eprintln!("error: --config: unknown graph {other:?}"); std::process::exit(2) }
+        }).collect()
+    };
+    let algos: Vec<Algorithm> = if sc.algo.is_empty() { vec![Algorithm::Dijkstra] } else if sc.algo == ["all"] { Algorithm::ALL.to_vec() } else {
+        sc.algo.iter().map(|a| Algorithm::parse(a).unwrap_or_else(|| {
+            eprintln!("error: --config: unknown algo {a:?} (expected \"all\" or one of {})", Algorithm::names());
+            std::process::exit(2)
+        })).collect()
+    };
+    let (ns, ks, threads) = (or(&sc.n, base.n), or(&sc.k, base.k), or(&sc.threads, base.threads));
+    let bs = if sc.b.is_empty() { vec![base.b] } else { sc.b.clone() };
+    let name = sc.name.clone().unwrap_or_else(|| path.file_stem().map_or("config".into(), |s| s.to_string_lossy().into_owned()));
+    let mut cells = 0;
+    for &graph in &graphs { for &n in &ns { for &k in &ks { for &b in &bs { for (ti, &t) in threads.iter().enumerate() { for &algo in &algos {
+        let single = algo.has_index() || algo.queue() != QueueKind::Binary || algo == Algorithm::Batched;
+        if single && ti > 0 { continue; }
+        let mut a = base.clone();
+        a.config = None;
+        (a.graph, a.n, a.k, a.b) = (graph, n, k, b);
+        if !sc.n.is_empty() { a.grid_rc = None; }
+        a.threads = if single { 1 } else { t };
+        a.seed = sc.seed.unwrap_or(a.seed);
+        a.trials = sc.trials.unwrap_or(a.trials);
+        a.maxw = sc.maxw.unwrap_or(a.maxw);
+        a.p = sc.p.unwrap_or(a.p);
+        apply_algo(&mut a, algo);
+        a.scenario = Some(name.clone());
+        run(a);
+        cells += 1;
+    }}}}}}
+    eprintln!("scenario {name}: ran {cells} cells");
+}
+
+/// `bmssp-cli diff EXPECTED.dist ACTUAL.dist [--k N]`: exit status 1 when they differ. Either
+/// file may be a `--save-result` snapshot instead of a `.dist` file.
+fn run_diff(argv: &[String]) {
+    let mut files = Vec::new();
+    let mut k = 20;
+    let mut it = argv.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--k" => k = it.next().and_then(|v| v.parse().ok()).unwrap_or_else(|| { eprintln!("--k takes a number"); std::process::exit(2) }),
+            _ => files.push(PathBuf::from(a)),
         }
     }
-    g
+    let [expected, actual] = &files[..] else { eprintln!("usage: bmssp-cli diff EXPECTED.dist ACTUAL.dist [--k N]"); std::process::exit(2) };
+    let load = |p: &PathBuf| File::open(p).and_then(|f| {
+        let mut r = BufReader::new(f);
+        if std::io::BufRead::fill_buf(&mut r)?.starts_with(snapshot::SNAPSHOT_MAGIC) {
+            BmsspResult::read_snapshot(r).map(|res| res.settled_pairs())
+        } else { io::read_dists(r) }
+    })
+        .unwrap_or_else(|e| { eprintln!("error: {}: {e}", p.display()); std::process::exit(2) });
+    let d = compare_results(&load(expected), &load(actual), k);
+    print!("{d}");
+    if d.is_match() { println!(); } else { std::process::exit(1); }
 }
 
-fn make_ba(n: usize, m0: usize, m: usize, maxw: u32, seed: u64) -> Graph {
-    let mut rng = StdRng::seed_from_u64(seed);
-    let mut g = Graph::new(n);
-    // Simple preferential attachment: maintain list of endpoints with multiplicity
-    let mut ends: Vec<usize> = Vec::new();
-    let start = m0.max(1).min(n);
-    for u in 0..start { for v in 0..start { if u!=v { g.add_edge(u,v,1); ends.push(u); } } }
-    for u in start..n {
-        for _ in 0..m { // pick endpoints proportional to degree
-            let t = if ends.is_empty() { rng.gen_range(0..u) } else { ends[rng.gen_range(0..ends.len())] };
-            let w = rng.gen_range(1..=maxw) as u64;
-            g.add_edge(u, t, w);
-            ends.push(t);
-            ends.push(u);
+/// Name, graph, sources and the bounds to record for one golden case.
+type GoldenCase = (&'static str, Graph, Vec<(usize,u64)>, Vec<u64>);
+
+/// Conformance cases for `goldens generate`. Each is small and aimed at one corner of the
+/// semantics: ties, zero weights, source offsets at or past B, unreachable parts, B = 0.
+fn golden_cases() -> Vec<GoldenCase> {
+    let mut line = Graph::new(10);
+    for i in 0..9 { line.add_edge(i, i + 1, 1 + i as u64); }
+    let mut offsets = Graph::new(6);
+    for i in 0..5 { offsets.add_undirected_edge(i, i + 1, 3); }
+    let mut disconnected = Graph::new(8);
+    for i in 0..3 { disconnected.add_undirected_edge(i, i + 1, 2); }
+    for i in 4..7 { disconnected.add_undirected_edge(i, i + 1, 1); }
+    let mut ties = Graph::new(16);
+    for r in 0..4 { for c in 0..4 {
+        if c + 1 < 4 { ties.add_undirected_edge(r * 4 + c, r * 4 + c + 1, 1); }
+        if r + 1 < 4 { ties.add_undirected_edge(r * 4 + c, (r + 1) * 4 + c, 1); }
+    }}
+    let mut zero = Graph::new(6);
+    for (u, v, w) in [(0, 1, 0), (1, 2, 0), (2, 3, 4), (0, 3, 5), (3, 4, 0), (4, 5, 1)] { zero.add_edge(u, v, w); }
+    let mut multi = Graph::new(4);
+    for (u, v, w) in [(0, 1, 7), (0, 1, 2), (1, 1, 1), (1, 2, 3), (2, 0, 1), (2, 3, 9), (2, 3, 4)] { multi.add_edge(u, v, w); }
+    let grid = make_grid(8, 8, 9, 1);
+    let grid_src = pick_sources(grid.len(), 3, 1);
+    let ba = make_ba(200, 3, 2, 20, 5);
+    let ba_src = pick_sources(ba.len(), 4, 5);
+    vec![
+        ("line", line, vec![(0, 0)], vec![0, 1, 10, 45, 1000]),
+        ("source_offsets", offsets, vec![(0, 0), (5, 4), (2, 9), (3, 12)], vec![5, 9, 12, 13]),
+        ("disconnected", disconnected, vec![(0, 0)], vec![3, 7, 100]),
+        ("ties", ties, vec![(0, 0), (15, 0)], vec![2, 3, 4]),
+        ("zero_weights", zero, vec![(0, 0)], vec![1, 5, 6]),
+        ("multi_edges", multi, vec![(0, 0)], vec![3, 5, 9, 10]),
+        ("grid_8x8", grid, grid_src, vec![5, 15, 40]),
+        ("ba_200", ba, ba_src, vec![10, 30, 80]),
+    ]
+}
+
+fn goldens_generate(dir: &Path) -> std::io::Result<usize> {
+    let mut files = 0;
+    for (name, g, sources, bounds) in golden_cases() {
+        let case = dir.join(name);
+        std::fs::create_dir_all(&case)?;
+        io::write_edge_list(&g, std::io::BufWriter::new(File::create(case.join("graph.txt"))?))?;
+        io::write_sources(&sources, std::io::BufWriter::new(File::create(case.join("sources.txt"))?))?;
+        for b in bounds {
+            let res = bounded_multi_source_shortest_paths(&g, &sources, b);
+            io::write_dists(&res, std::io::BufWriter::new(File::create(case.join(format!("B{b}.dist")))?))?;
+            files += 1;
         }
     }
-    g
+    Ok(files)
 }
 
-fn pick_sources(n: usize, k: usize, seed: u64) -> Vec<(usize,u64)> {
-    let mut rng = StdRng::seed_from_u64(seed ^ 0x9E3779B97F4A7C15);
-    let mut seen = std::collections::BTreeSet::new();
-    let mut out = Vec::with_capacity(k);
-    while out.len() < k && seen.len() < n {
-        let s = rng.gen_range(0..n);
-        if seen.insert(s) { out.push((s,0)); }
+/// Checks every `<case>/B<bound>.dist` under `dir`. Without `cmd` the Rust search is checked;
+/// with it, the command is run per fixture (`{graph_file}`, `{sources_file}`, `{B}`, `{k}`,
+/// `{dist_out}` substituted) and its first JSON row must match `popped`, `B_prime` and, when
+/// present, `dist_hash`. If the template uses `{dist_out}`, that file is diffed as well.
+fn goldens_check(dir: &Path, cmd: Option<&str>) -> std::io::Result<(usize, Vec<String>)> {
+    let mut cases: Vec<PathBuf> = std::fs::read_dir(dir)?.filter_map(|e| e.ok().map(|e| e.path())).filter(|p| p.is_dir()).collect();
+    cases.sort();
+    let (mut checked, mut failures) = (0, Vec::new());
+    for case in cases {
+        let (graph_file, sources_file) = (case.join("graph.txt"), case.join("sources.txt"));
+        let g = io::read_graph_file(&graph_file)?;
+        let sources = io::read_sources_file(&sources_file)?;
+        let mut fixtures: Vec<(u64, PathBuf)> = std::fs::read_dir(&case)?.filter_map(|e| {
+            let p = e.ok()?.path();
+            let b = p.file_name()?.to_str()?.strip_prefix('B')?.strip_suffix(".dist")?.parse().ok()?;
+            Some((b, p))
+        }).collect();
+        fixtures.sort();
+        for (b, path) in fixtures {
+            checked += 1;
+            let (expected, expected_bp) = io::read_dist_file(BufReader::new(File::open(&path)?))?;
+            let label = format!("{}/B{b}", case.file_name().unwrap().to_string_lossy());
+            let Some(template) = cmd else {
+                let res = bounded_multi_source_shortest_paths(&g, &sources, b);
+                let d = compare_results(&expected, &res.settled_pairs(), 5);
+                if !d.is_match() { failures.push(format!("{label}: {d}")); }
+                if expected_bp.is_some_and(|bp| bp != res.b_prime) { failures.push(format!("{label}: B' {} != expected {}", res.b_prime, expected_bp.unwrap())); }
+                continue;
+            };
+            let dist_out = std::env::temp_dir().join(format!("bmssp-golden-{}-{b}.dist", std::process::id()));
+            let argv: Vec<String> = template.split_whitespace().map(|t| t
+                .replace("{graph_file}", &graph_file.display().to_string())
+                .replace("{sources_file}", &sources_file.display().to_string())
+                .replace("{dist_out}", &dist_out.display().to_string())
+                .replace("{B}", &b.to_string())
+                .replace("{k}", &sources.len().to_string())).collect();
+            let out = match std::process::Command::new(&argv[0]).args(&argv[1..]).output() {
+                Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).into_owned(),
+                Ok(o) => { failures.push(format!("{label}: command exited with {}", o.status)); continue; }
+                Err(e) => { failures.push(format!("{label}: {}: {e}", argv[0])); continue; }
+            };
+            let row: Option<serde_json::Value> = out.lines().find(|l| l.trim_start().starts_with('{')).and_then(|l| serde_json::from_str(l).ok());
+            let Some(row) = row else { failures.push(format!("{label}: no JSON row on stdout")); continue; };
+            if row.get("popped").and_then(|v| v.as_u64()) != Some(expected.len() as u64) {
+                failures.push(format!("{label}: popped {} != expected {}", row["popped"], expected.len()));
+            }
+            if let Some(bp) = expected_bp {
+                if row.get("B_prime").and_then(|v| v.as_u64()) != Some(bp) { failures.push(format!("{label}: B_prime {} != expected {bp}", row["B_prime"])); }
+            }
+            if let Some(h) = row.get("dist_hash") {
+                let want = settled_digest(&expected);
+                let ok = h.as_u64() == Some(want) || h.as_str().and_then(|s| s.strip_prefix("0x")).and_then(|s| u64::from_str_radix(s, 16).ok()) == Some(want);
+                if !ok { failures.push(format!("{label}: dist_hash {h} != expected {want:#x}")); }
+            }
+            if template.contains("{dist_out}") {
+                match File::open(&dist_out).and_then(|f| io::read_dists(BufReader::new(f))) {
+                    Ok(got) => { let d = compare_results(&expected, &got, 5); if !d.is_match() { failures.push(format!("{label}: {d}")); } }
+                    Err(e) => failures.push(format!("{label}: reading {}: {e}", dist_out.display())),
+                }
+                let _ = std::fs::remove_file(&dist_out);
+            }
+        }
     }
-    out
+    Ok((checked, failures))
 }
 
-fn read_graph_from_file(path: &PathBuf) -> std::io::Result<Graph> {
-    let f = File::open(path)?;
-    let mut it = BufReader::new(f).lines();
-    let header = it.next().transpose()?.unwrap_or_default();
-    let mut parts = header.split_whitespace();
-    let n: usize = parts.next().unwrap_or("0").parse().unwrap_or(0);
-    let _m: usize = parts.next().unwrap_or("0").parse().unwrap_or(0);
-    let mut g = Graph::new(n);
-    for line in it {
-        let line = line?;
-        if line.trim().is_empty() { continue; }
-        let mut ps = line.split_whitespace();
-        let u: usize = ps.next().unwrap().parse().unwrap();
-        let v: usize = ps.next().unwrap().parse().unwrap();
-        let w: u64 = ps.next().unwrap().parse().unwrap();
-        g.add_edge(u, v, w);
+/// `bmssp-cli goldens generate|check [--dir goldens] [--cmd TEMPLATE]`.
+fn run_goldens(argv: &[String]) {
+    let mut dir = PathBuf::from("goldens");
+    let mut cmd = None;
+    let mut it = argv.iter().skip(1);
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--dir" => dir = PathBuf::from(it.next().expect("--dir value")),
+            "--cmd" => cmd = Some(it.next().expect("--cmd value").clone()),
+            other => { eprintln!("unknown goldens flag {other}"); std::process::exit(2); }
+        }
+    }
+    match argv.first().map(String::as_str) {
+        Some("generate") => {
+            let n = goldens_generate(&dir).unwrap_or_else(|e| { eprintln!("error: {}: {e}", dir.display()); std::process::exit(1) });
+            eprintln!("wrote {n} fixtures under {}", dir.display());
+        }
+        Some("check") => {
+            let (checked, failures) = goldens_check(&dir, cmd.as_deref()).unwrap_or_else(|e| { eprintln!("error: {}: {e}", dir.display()); std::process::exit(2) });
+            for f in &failures { println!("FAIL {}", f.trim_end()); }
+            println!("{} of {checked} fixtures passed", checked - failures.len().min(checked));
+            if !failures.is_empty() { std::process::exit(1); }
+        }
+        _ => { eprintln!("usage: bmssp-cli goldens generate|check [--dir DIR] [--cmd TEMPLATE]"); std::process::exit(2); }
     }
-    Ok(g)
 }
 
-fn read_sources_from_file(path: &PathBuf) -> std::io::Result<Vec<(usize,u64)>> {
-    let f = File::open(path)?;
-    let mut it = BufReader::new(f).lines();
-    let header = it.next().transpose()?.unwrap_or_default();
-    let k: usize = header.split_whitespace().next().unwrap_or("0").parse().unwrap_or(0);
-    let mut out: Vec<(usize,u64)> = Vec::with_capacity(k);
-    for line in it {
-        let line = line?;
-        if line.trim().is_empty() { continue; }
-        let mut ps = line.split_whitespace();
-        let s: usize = ps.next().unwrap().parse().unwrap();
-        let d0: u64 = ps.next().unwrap_or("0").parse().unwrap_or(0);
-        out.push((s, d0));
+fn main() {
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    match argv.first().map(String::as_str) {
+        Some("diff") => return run_diff(&argv[1..]),
+        Some("goldens") => return run_goldens(&argv[1..]),
+        _ => {}
+    }
+    let args = parse_args();
+    match args.config.clone() {
+        Some(path) => run_config(&path, &args),
+        None => run(args),
     }
-    Ok(out)
 }
 
-fn main() {
-    let (gtype, n, grid_rc, p, m0, m_ba, maxw, mut k, b, seed, trials, threads, json, graph_file, sources_file) = parse_args();
-    let (g, gname): (Graph, &'static str) = if let Some(path) = graph_file.as_ref() {
-        (read_graph_from_file(path).expect("failed to read graph file"), match gtype { GraphType::Grid => "grid", GraphType::ER => "er", GraphType::BA => "ba" })
+fn run(args: Args) {
+    let has_opts = !args.avoid.is_empty() || args.max_hops.is_some() || args.max_edges.is_some() || args.max_settled.is_some() || args.deadline_ms.is_some()
+        || args.overflow != OverflowPolicy::Saturate;
+    if let Some(cost) = args.cost {
+        if has_opts || args.threads > 1 || args.threads_sweep.is_some() || args.gpu || args.prepared.is_some() {
+            eprintln!("--cost {cost} only runs on the single-threaded CPU search without budgets or --avoid");
+            std::process::exit(2);
+        }
+    }
+    if args.queue != QueueKind::Binary
+        && (has_opts || args.cost.is_some() || args.queries.is_some() || args.threads > 1 || args.threads_sweep.is_some() || args.gpu || args.prepared.is_some()) {
+        eprintln!("--queue {} only runs on the single-threaded CPU search without --cost, --queries, budgets or --avoid", args.queue.as_str());
+        std::process::exit(2);
+    }
+    if args.batched
+        && (has_opts || args.queue != QueueKind::Binary || args.cost.is_some() || args.queries.is_some() || args.threads > 1 || args.threads_sweep.is_some() || args.gpu || args.prepared.is_some()) {
+        eprintln!("--batched only runs on the single-threaded binary-heap search without --queue, --cost, --queries, budgets or --avoid");
+        std::process::exit(2);
+    }
+    if args.rank_eval && args.queries.is_some() {
+        eprintln!("rank builds its own queries; drop --queries");
+        std::process::exit(2);
+    }
+    if (args.phased || args.reach_only) && (args.queries.is_some() || args.rank_eval) {
+        eprintln!("--mode phased|reach runs the instance's own sources; drop --queries and rank");
+        std::process::exit(2);
+    }
+    if (args.queries.is_some() || args.rank_eval || args.phased || args.reach_only) && (has_opts || args.cost.is_some() || args.threads > 1 || args.threads_sweep.is_some() || args.gpu || args.prepared.is_some() || args.reorder.is_some() || args.restrict_components) {
+        eprintln!("--queries, rank and --mode phased|reach run the single-threaded search only; drop the mode, budget, --reorder and --restrict-components flags");
+        std::process::exit(2);
+    }
+    let other_path = args.cost.is_some() || args.queue != QueueKind::Binary || args.batched || args.queries.is_some() || args.rank_eval || args.phased || args.reach_only || args.gpu || args.prepared.is_some()
+        || (args.threads > 1 && !matches!(args.parallel, ParMode::Sharded));
+    if args.tie_break == TieBreak::Insertion && other_path {
+        eprintln!("--tie-break insertion is only honored by the sequential and sharded paths; the others report id order");
+        std::process::exit(2);
+    }
+    if has_opts && !matches!(args.parallel, ParMode::Sharded) {
+        eprintln!("--avoid, --max-hops, --overflow and budget flags are only honored by the sequential and sharded paths, not --parallel {}", args.parallel.name());
+        std::process::exit(2);
+    }
+    if args.sources_file.is_some() && args.source_strategy != SourceStrategy::Uniform {
+        eprintln!("--source-strategy picks generated sources; drop it or --sources-file");
+        std::process::exit(2);
+    }
+    if args.source_offsets.is_some() && (args.queries.is_some() || args.rank_eval) {
+        eprintln!("--source-offsets sets the run's sources; --queries and rank bring their own");
+        std::process::exit(2);
+    }
+    let mut parse_ns = None;
+    let (mut g, gname): (Graph, &'static str) = if let Some(path) = args.graph_file.as_ref() {
+        let start = Instant::now();
+        let (g, format) = formats::load_graph_file(path, args.graph_format, args.parse_threads)
+            .unwrap_or_else(|e| { eprintln!("error: {}: {e}", path.display()); std::process::exit(2) });
+        parse_ns = Some(start.elapsed().as_nanos());
+        eprintln!("read {} as {} (n={} m={})", path.display(), format.as_str(), g.len(), g.num_edges());
+        (g, match args.graph { GraphType::Grid => "grid", GraphType::ER => "er", GraphType::BA => "ba" })
     } else {
-        match gtype {
+        match args.graph {
             GraphType::Grid => {
-                let (r,c) = grid_rc.unwrap_or_else(||{
-                    let side = (n as f64).sqrt() as usize; (side, side.max(1))
+                let (r,c) = args.grid_rc.unwrap_or_else(||{
+                    let side = (args.n as f64).sqrt() as usize; (side, side.max(1))
                 });
-                (make_grid(r,c,maxw,seed), "grid")
+                (make_grid(r,c,args.maxw,args.seed), "grid")
             }
-            GraphType::ER => (make_er(n, p, maxw, seed), "er"),
-            GraphType::BA => (make_ba(n, m0, m_ba, maxw, seed), "ba"),
+            GraphType::ER => (make_er(args.n, args.p, args.maxw, args.seed), "er"),
+            GraphType::BA => (make_ba(args.n, args.m0, args.m_ba, args.maxw, args.seed), "ba"),
         }
     };
+    if args.dedup {
+        let removed = g.dedup_min();
+        eprintln!("dedup: removed {} parallel edges", removed);
+    }
+    if args.validate_graph {
+        let rep = g.validate();
+        eprintln!("validate: {}", rep.summary());
+        if !rep.dangling.is_empty() {
+            eprintln!("error: graph has edges pointing outside 0..{}", g.len());
+            std::process::exit(2);
+        }
+    }
+    if let Some(kind) = args.workload {
+        let queries = match kind {
+            "clustered" => clustered_queries(&g, args.count, args.k, args.b, args.seed),
+            "rank" => rank_queries(&g, args.count, args.seed),
+            _ => uniform_queries(g.len(), args.count, args.k, args.b, args.seed),
+        };
+        let written = match args.out.as_ref() {
+            Some(path) => File::create(path).and_then(|f| write_queries(std::io::BufWriter::new(f), &queries)),
+            None => write_queries(std::io::stdout().lock(), &queries),
+        };
+        if let Err(e) = written { eprintln!("error: writing workload: {e}"); std::process::exit(1); }
+        eprintln!("workload {kind}: {} queries", queries.len());
+        return;
+    }
+    // Conversion only: write the binary CSR that `bmssp-server` maps, then stop.
+    if let Some(path) = args.save_csr.as_ref() {
+        let cg = CompactGraph::from_graph(&g).unwrap_or_else(|| { eprintln!("error: graph does not fit the u32 CSR layout"); std::process::exit(2) });
+        let f = File::create(path).expect("failed to create --save-csr file");
+        cg.write_binary(std::io::BufWriter::new(f)).expect("failed to write --save-csr file");
+        eprintln!("wrote {} (n={} m={})", path.display(), cg.len(), cg.num_edges());
+        return;
+    }
     let n = g.len();
-    let m: usize = g.adj.iter().map(|v| v.len()).sum();
-    let sources = if let Some(sp) = sources_file.as_ref() {
-        let s = read_sources_from_file(sp).expect("failed to read sources file");
-        k = s.len();
-        s
-    } else { pick_sources(n, k, seed) };
-    let mem = g.memory_estimate_bytes();
+    let sources = if let Some(sp) = args.sources_file.as_ref() {
+        io::read_sources_file(sp).unwrap_or_else(|e| { eprintln!("error: {}: {e}", sp.display()); std::process::exit(2) })
+    } else { pick_sources_with(&g, args.k, args.seed, args.source_strategy) };
+    if args.sources_file.is_none() && sources.len() < args.k.min(n) {
+        eprintln!("note: --source-strategy {} found {} of {} sources", args.source_strategy.as_str(), sources.len(), args.k);
+    }
+    // Staggered d0, as when the search is one phase of a larger computation.
+    let sources = match args.source_offsets.as_ref() {
+        None => sources,
+        Some(SourceOffsets::Random(max)) => with_random_offsets(&sources, *max, args.seed),
+        Some(SourceOffsets::File(path)) => {
+            let offsets = io::open_reader(path).and_then(io::read_offsets)
+                .unwrap_or_else(|e| { eprintln!("error: --source-offsets {}: {e}", path.display()); std::process::exit(2) });
+            if offsets.len() < sources.len() {
+                eprintln!("error: --source-offsets {}: {} offsets for {} sources", path.display(), offsets.len(), sources.len());
+                std::process::exit(2);
+            }
+            sources.iter().zip(offsets).map(|(&(s, _), d0)| (s, d0)).collect()
+        }
+    };
+    if args.stats {
+        let st = g.stats();
+        let (label, count) = weak_components(&g);
+        let mut size = vec![0usize; count];
+        for &c in &label { size[c] += 1; }
+        let largest = (0..count).max_by_key(|&c| (size[c], std::cmp::Reverse(c)));
+        let in_largest = sources.iter().filter(|&&(s, _)| Some(label[s]) == largest).count();
+        let mut row = st.to_json(10);
+        row["graph"] = gname.into();
+        row["sources"] = sources.len().into();
+        row["sources_in_largest_component"] = in_largest.into();
+        println!("{row}");
+        return;
+    }
+    // A source stranded in a small component settles a handful of nodes at any bound, which
+    // otherwise looks like a fast run.
+    let min_component = args.min_component.unwrap_or((n / 100).max(2));
+    let stranded = sources_in_small_components(&g, &sources, min_component);
+    if !stranded.is_empty() {
+        let shown: Vec<String> = stranded.iter().take(5).map(|(s, size)| format!("{s} ({size} nodes)")).collect();
+        let more = if stranded.len() > 5 { format!(", ... {} more", stranded.len() - 5) } else { String::new() };
+        let level = if args.strict_components { "error" } else { "warning" };
+        eprintln!("{level}: {} of {} sources lie in components smaller than {min_component} nodes: {}{more}", stranded.len(), sources.len(), shown.join(", "));
+        if args.strict_components { std::process::exit(2); }
+    }
+    // Reference distances in original ids, for `bmssp-cli diff` against other ports.
+    if let Some(path) = args.dump_dist.as_ref() {
+        let res = bounded_multi_source_shortest_paths(&g, &sources, args.b);
+        File::create(path).and_then(|f| io::write_dists(&res, std::io::BufWriter::new(f))).expect("failed to write --dump-dist file");
+    }
+    if let Some(path) = args.save_result.as_ref() {
+        let res = bmssp_with_options(&g, &sources, args.b, &BmsspOptions{ track_predecessors: true, ..Default::default() });
+        if let Err(e) = res.save(path) { eprintln!("error: --save-result {}: {e}", path.display()); std::process::exit(1); }
+    }
+    // Results for downstream analysis, so it doesn't have to rerun the search.
+    if args.out_distances.is_some() || args.out_labels.is_some() || args.out_paths.is_some() {
+        let res = bmssp_with_options(&g, &sources, args.b, &BmsspOptions{ track_predecessors: true, ..Default::default() });
+        let save = |flag: &str, path: &Option<PathBuf>, written: &dyn Fn(&Path) -> std::io::Result<()>| {
+            let Some(path) = path else { return };
+            if let Err(e) = written(path) { eprintln!("error: {flag} {}: {e}", path.display()); std::process::exit(1); }
+        };
+        save("--out-distances", &args.out_distances, &|p| if p.extension().is_some_and(|e| e == "bin") {
+            File::create(p).and_then(|f| io::write_dists_binary(&res, std::io::BufWriter::new(f)))
+        } else {
+            io::create_writer(p).and_then(|w| io::write_dists(&res, w))
+        });
+        save("--out-labels", &args.out_labels, &|p| io::create_writer(p).and_then(|w| io::write_labels(&res, w)));
+        save("--out-paths", &args.out_paths, &|p| io::create_writer(p).and_then(|w| io::write_paths(&res, w)));
+    }
+    // Where B sits in the distance distribution of this graph and source set.
+    if let Some(width) = args.histogram {
+        if !DistHistogram::fits(args.b, width) {
+            eprintln!("--histogram {width} needs more than {} buckets below B={}", DistHistogram::MAX_BUCKETS, args.b);
+            std::process::exit(2);
+        }
+        let res = bmssp_with_options(&g, &sources, args.b, &BmsspOptions{ histogram_width: Some(width), ..Default::default() });
+        let s = res.dist_summary();
+        eprintln!("distances: settled={} p50={} p90={} p99={} max={} B={}", res.settled_count(), s.p50, s.p90, s.p99, s.max, args.b);
+        eprint!("{}", res.dist_histogram.unwrap_or_default().render(40));
+    }
+    // Relaxation log of the same search, in original ids.
+    if let Some(path) = args.trace.as_ref() {
+        #[cfg(feature = "trace")]
+        {
+            let written = File::create(path).and_then(|f| trace::bmssp_traced(&g, &sources, args.b, &BmsspOptions::default(), std::io::BufWriter::new(f)));
+            if let Err(e) = written { eprintln!("error: --trace {}: {e}", path.display()); std::process::exit(1); }
+        }
+        #[cfg(not(feature = "trace"))]
+        {
+            eprintln!("error: --trace {} needs a build with the `trace` feature", path.display());
+            std::process::exit(2);
+        }
+    }
+    // Settles the same nodes; only `n`, and so the dist array and memory figure, shrink.
+    let (g, sources) = if args.restrict_components {
+        let seeds: Vec<Node> = sources.iter().map(|&(s, _)| s).collect();
+        let (h, kept) = component_subgraph(&g, &seeds);
+        let mut new_id = vec![0; n];
+        for (i, &v) in kept.iter().enumerate() { new_id[v] = i; }
+        eprintln!("restrict: kept {} of {n} nodes in the sources' components", h.len());
+        let s = sources.iter().map(|&(v, d)| (new_id[v], d)).collect();
+        (h, s)
+    } else { (g, sources) };
+    // Sources are picked in original ids so reordered runs solve the same instance.
+    let mut reorder_ns = None;
+    let (g, sources) = match args.reorder {
+        Some(kind) => {
+            let start = Instant::now();
+            let (h, perm) = if kind == "rcm" { reorder::rcm(&g) } else { reorder::bfs(&g) };
+            reorder_ns = Some(start.elapsed().as_nanos());
+            let s = reorder::permute_sources(&perm, &sources);
+            (h, s)
+        }
+        None => (g, sources),
+    };
+    // NUMA placement copies adjacency lists onto their owners' nodes before any timing starts.
+    let topo = args.numa.then(numa::detect_topology);
+    let max_threads = args.threads_sweep.as_ref().and_then(|s| s.iter().max().copied()).unwrap_or(args.threads);
+    let mut g = match topo.as_ref() {
+        Some(t) if max_threads > 1 => numa::place_graph_first_touch(&g, max_threads, Some(t)),
+        _ => g,
+    };
+    if args.shrink { g.shrink_to_fit_all(); }
+    let inst = Instance { g: &g, sources: &sources, gname, mem: g.memory_estimate_bytes(), mem_actual: g.memory_actual_bytes(), reorder_ns, parse_ns, topo: topo.as_ref() };
+
+    if let Some(path) = args.queries.as_ref() {
+        let queries = if path.as_os_str() == "-" {
+            read_queries(std::io::stdin().lock())
+        } else {
+            File::open(path).and_then(|f| read_queries(BufReader::new(f)))
+        }.unwrap_or_else(|e| { eprintln!("error: --queries {}: {e}", path.display()); std::process::exit(2) });
+        run_queries(&inst, &args, &queries);
+        return;
+    }
+    if args.rank_eval {
+        run_rank_eval(&inst, &args);
+        return;
+    }
+    if args.phased {
+        run_phased(&inst, &args);
+        return;
+    }
+    if args.reach_only {
+        run_reach(&inst, &args);
+        return;
+    }
+
+    if let Some(sweep) = args.threads_sweep.as_ref() {
+        // Baseline for speedup/efficiency: the 1-thread entry if swept, else the first entry.
+        let mut rows: Vec<OutputRow> = sweep.iter().filter_map(|&t| run_trials(&inst, &args, t, false)).collect();
+        let base_idx = rows.iter().position(|r| r.threads == 1).unwrap_or(0);
+        if let Some((base_ns, base_t)) = rows.get(base_idx).map(|r| (r.time_ns as f64, r.threads as f64)) {
+            for r in &mut rows {
+                let speedup = base_ns / r.time_ns.max(1) as f64;
+                r.speedup = Some(speedup);
+                r.efficiency = Some(speedup * base_t / r.threads as f64);
+            }
+        }
+        for r in &rows {
+            if args.json { println!("{}", serde_json::to_string(r).unwrap()); }
+            eprintln!("threads={} best ns={} speedup={:.2} efficiency={:.2}", r.threads, r.time_ns, r.speedup.unwrap_or(1.0), r.efficiency.unwrap_or(1.0));
+        }
+        return;
+    }
+
+    if args.gpu {
+        #[cfg(feature = "gpu")]
+        {
+            let best = run_gpu_trials(&inst, &args);
+            if let Some(b) = best { eprintln!("best ns={} popped={} B'={}", b.time_ns, b.popped, b.b_prime); }
+            return;
+        }
+        #[cfg(not(feature = "gpu"))]
+        {
+            eprintln!("error: --gpu needs a build with the `gpu` feature");
+            std::process::exit(2);
+        }
+    }
+
+    if let Some(algo) = args.prepared {
+        let start = Instant::now();
+        let Some(prep) = algo.prepare(&g) else {
+            eprintln!("error: --algo {} needs node ids and weights that fit in u32", algo.name());
+            std::process::exit(2);
+        };
+        let ns = start.elapsed().as_nanos();
+        if let Some(hl) = prep.hub_labels() {
+            eprintln!("hub labels: avg label size {:.1}, {} bytes", hl.avg_label_size(), hl.memory_estimate_bytes());
+        }
+        if algo == Algorithm::Bfs && prep.uniform_weight().is_none() {
+            eprintln!("note: --algo bfs: edge weights differ, running the binary-heap search");
+        }
+        if algo == Algorithm::Deque && prep.small_weight_kernel().is_none() {
+            eprintln!("note: --algo deque: weights exceed {MAX_BUCKET_WEIGHT}, running the binary-heap search");
+        }
+        let impl_ = match algo { Algorithm::Phast => "rust-bmssp-phast", Algorithm::HubLabels => "rust-bmssp-hl", Algorithm::Simd => "rust-bmssp-simd", Algorithm::Bfs => "rust-bmssp-bfs", Algorithm::Deque => "rust-bmssp-deque", _ => "rust-bmssp-compact" };
+        let best = run_prepared_trials(&inst, &args, impl_, ns, prep.memory_estimate_bytes(), prep.memory_actual_bytes(), || prep.run(&sources, args.b, 1));
+        if let Some(b) = best { eprintln!("best ns={} popped={} B'={} preprocess ns={}", b.time_ns, b.popped, b.b_prime, b.preprocess_ns.unwrap_or(0)); }
+        return;
+    }
 
+    let best = run_trials(&inst, &args, args.threads, args.json);
+    // Print best summary to stderr for human glance
+    if let Some(b) = best { eprintln!("best ns={} popped={} B'={}", b.time_ns, b.popped, b.b_prime); }
+}
+
+/// One row per query, each the best of `--trials`. The graph and the solver's label buffers
+/// are reused across queries, so rows measure query cost alone.
+fn run_queries(inst: &Instance, args: &Args, queries: &[Query]) {
+    let mut solver = Solver::new(inst.g.len());
+    let mut total_ns = 0u128;
+    for (qi, q) in queries.iter().enumerate() {
+        let row = time_query(inst, args, &mut solver, q, qi);
+        total_ns += row.time_ns;
+        if args.json { println!("{}", serde_json::to_string(&row).unwrap()); }
+    }
+    eprintln!("queries={} total best ns={}", queries.len(), total_ns);
+}
+
+/// Dijkstra-rank evaluation: for `--count` random sources, one row per target at rank 1, 2,
+/// 4, ... with the cost of the bounded search that just settles it, then per-rank medians.
+fn run_rank_eval(inst: &Instance, args: &Args) {
+    let pairs = rank_pairs(inst.g, args.count, args.seed);
+    let mut solver = Solver::new(inst.g.len());
+    let mut by_rank: Vec<(Vec<u64>, Vec<u64>)> = Vec::new();
+    for (qi, p) in pairs.iter().enumerate() {
+        let mut row = time_query(inst, args, &mut solver, &p.query(), qi);
+        row.rank = Some(p.rank);
+        row.target = Some(p.target);
+        let r = p.rank.trailing_zeros() as usize;
+        if by_rank.len() <= r { by_rank.resize_with(r + 1, Default::default); }
+        by_rank[r].0.push(row.time_ns as u64);
+        by_rank[r].1.push(row.popped as u64);
+        if args.json { println!("{}", serde_json::to_string(&row).unwrap()); }
+    }
+    for (r, (ns, popped)) in by_rank.iter_mut().enumerate() {
+        let (t, s) = (Summary::of(ns), Summary::of(popped));
+        eprintln!("rank=2^{r} pairs={} p50 ns={} p90 ns={} p50 popped={}", ns.len(), t.p50, t.p90, s.p50);
+    }
+}
+
+/// One phase of `--mode phased`, before it becomes a row.
+struct PhaseStat { lower: Weight, bound: Weight, sources: usize, time_ns: u128, settled: usize, edges_scanned: usize, heap_pushes: usize, b_prime: Weight }
+
+/// BMSSP as the subroutine of a recursive solver: phase `i` searches `[L_i, L_i + B)` from
+/// the previous phases' boundary (every unsettled node next to a settled one, at its best
+/// label), with already-settled nodes filtered out, and `L_{i+1}` is the phase's B'. Phases
+/// stop when the frontier is empty or at `--max-phases`. Emits one row per phase of the
+/// fastest trial; the boundary bookkeeping is part of each phase's time.
+fn run_phased(inst: &Instance, args: &Args) {
+    let g = inst.g;
+    let n = g.len();
+    let mut best: Option<(u128, Vec<PhaseStat>)> = None;
+    for _ in 0..args.trials.max(1) {
+        let mut done = vec![false; n];
+        let mut label = vec![Weight::MAX; n];
+        let mut frontier: Vec<Node> = Vec::new();
+        for &(s, d0) in inst.sources {
+            if s < n && d0 < label[s] { if label[s] == Weight::MAX { frontier.push(s); } label[s] = d0; }
+        }
+        let mut phases = Vec::new();
+        while args.max_phases.is_none_or(|m| phases.len() < m) {
+            let start = Instant::now();
+            let Some(lower) = frontier.iter().map(|&v| label[v]).min() else { break };
+            let bound = lower.saturating_add(args.b.max(1));
+            let sources: Vec<(Node, Weight)> = frontier.iter().map(|&v| (v, label[v])).collect();
+            let filter = |v: Node| !done[v];
+            let r = bmssp_with_options(g, &sources, bound, &BmsspOptions{ node_filter: Some(&filter), ..Default::default() });
+            for &u in &r.explored { done[u] = true; }
+            for &u in &r.explored {
+                for (v, w) in g.neighbors(u) {
+                    let nd = r.dist[u].saturating_add(w);
+                    if !done[v] && nd < label[v] { if label[v] == Weight::MAX { frontier.push(v); } label[v] = nd; }
+                }
+            }
+            frontier.retain(|&v| !done[v]);
+            let b_prime = frontier.iter().map(|&v| label[v]).min().unwrap_or(Weight::MAX);
+            phases.push(PhaseStat{ lower, bound, sources: sources.len(), time_ns: start.elapsed().as_nanos(), settled: r.settled_count(), edges_scanned: r.edges_scanned, heap_pushes: r.heap_pushes, b_prime });
+        }
+        let total = phases.iter().map(|p| p.time_ns).sum();
+        if best.as_ref().is_none_or(|b| total < b.0) { best = Some((total, phases)); }
+    }
+    let (total_ns, phases) = best.expect("at least one trial");
+    for (i, p) in phases.iter().enumerate() {
+        let row = OutputRow{
+            k: p.sources,
+            b: p.bound,
+            time_ns: p.time_ns,
+            popped: p.settled,
+            edges_scanned: p.edges_scanned,
+            heap_pushes: p.heap_pushes,
+            b_prime: p.b_prime,
+            reorder: None,
+            reorder_ns: None,
+            mode: Some("phased"),
+            phase: Some(i),
+            lower_bound: Some(p.lower),
+            ..OutputRow::base(inst, args)
+        };
+        if args.json { println!("{}", serde_json::to_string(&row).unwrap()); }
+    }
+    let settled: usize = phases.iter().map(|p| p.settled).sum();
+    eprintln!("phases={} settled={settled} total best ns={total_ns}", phases.len());
+}
+
+/// `--mode reach`: best of `--trials` runs of `reachable_within`; `popped` is the number of
+/// nodes within `--B`.
+fn run_reach(inst: &Instance, args: &Args) {
+    let g = inst.g;
     let mut best: Option<OutputRow> = None;
-    for t in 0..trials {
+    for t in 0..args.trials.max(1) {
         let start = Instant::now();
-    let res = if threads > 1 { bmssp_sharded(&g, &sources, b, threads) } else { bounded_multi_source_shortest_paths(&g, &sources, b) };
+        let r = reachable_within(g, inst.sources, args.b);
+        let elapsed = start.elapsed().as_nanos();
+        if best.as_ref().is_some_and(|b| b.time_ns <= elapsed) { continue; }
+        best = Some(OutputRow{
+            seed: args.seed + t as u64,
+            time_ns: elapsed,
+            popped: r.len(),
+            edges_scanned: r.edges_scanned,
+            heap_pushes: r.heap_pushes,
+            b_prime: r.b_prime,
+            reorder: None,
+            reorder_ns: None,
+            mode: Some("reach"),
+            ..OutputRow::base(inst, args)
+        });
+    }
+    let row = best.expect("at least one trial");
+    if args.json { println!("{}", serde_json::to_string(&row).unwrap()); }
+    eprintln!("reach best ns={} within={} B'={}", row.time_ns, row.popped, row.b_prime);
+}
+
+/// Best of `--trials` runs of one query on a reused solver.
+fn time_query(inst: &Instance, args: &Args, solver: &mut Solver, q: &Query, qi: usize) -> OutputRow {
+    let g = inst.g;
+    let mut best: Option<OutputRow> = None;
+    for _ in 0..args.trials.max(1) {
+        let start = Instant::now();
+        let b_prime = solver.run(g, &q.sources, q.bound);
+        let elapsed = start.elapsed().as_nanos();
+        if best.as_ref().is_some_and(|b| b.time_ns <= elapsed) { continue; }
+        best = Some(OutputRow{
+            k: q.sources.len(),
+            b: q.bound,
+            time_ns: elapsed,
+            popped: solver.settled().len(),
+            edges_scanned: solver.edges_scanned(),
+            heap_pushes: solver.heap_pushes(),
+            b_prime,
+            reorder: None,
+            reorder_ns: None,
+            query: Some(qi),
+            ..OutputRow::base(inst, args)
+        });
+    }
+    best.expect("at least one trial")
+}
+
+/// A prepared graph + sources, shared by every timed configuration.
+struct Instance<'a> {
+    g: &'a Graph,
+    sources: &'a [(usize, u64)],
+    gname: &'static str,
+    mem: usize,
+    mem_actual: usize,
+    reorder_ns: Option<u128>,
+    parse_ns: Option<u128>,
+    topo: Option<&'a numa::Topology>,
+}
+
+/// Run `args.trials` timed searches at `threads`, printing each row if `emit`. Returns the fastest.
+fn run_trials(inst: &Instance, args: &Args, threads: usize, emit: bool) -> Option<OutputRow> {
+    let (g, sources, b) = (inst.g, inst.sources, args.b);
+    // Built once outside the timed loop; only sharded runs use it.
+    #[cfg(feature = "rayon")]
+    let pool = (args.pool_reuse && threads > 1).then(|| {
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build().expect("failed to build thread pool")
+    });
+    #[cfg(not(feature = "rayon"))]
+    if args.pool_reuse { eprintln!("warning: --pool reuse needs the `rayon` feature; spawning threads per run"); }
+
+    let avoid = AvoidSet::new(&args.avoid);
+    let budgeted = args.max_edges.is_some() || args.max_settled.is_some() || args.deadline_ms.is_some() || args.overflow == OverflowPolicy::Error;
+    let mut best: Option<OutputRow> = None;
+    for t in 0..args.trials {
+        let start = Instant::now();
+        let opts = BmsspOptions{
+            max_edges_scanned: args.max_edges,
+            max_settled: args.max_settled,
+            deadline: args.deadline_ms.map(|ms| start + std::time::Duration::from_millis(ms)),
+            avoid: avoid.clone(),
+            max_hops: args.max_hops,
+            tie_break: args.tie_break,
+            overflow: args.overflow,
+            ..Default::default()
+        };
+        let res = match (threads > 1, args.parallel) {
+            (false, _) => match args.cost {
+                Some("bottleneck") => bmssp_semiring::<MinMax>(g, sources, b).into_result(),
+                Some("hops") => bmssp_semiring::<HopCount>(g, sources, b).into_result(),
+                _ if args.batched => bmssp_batched(g, sources, b),
+                _ if args.queue != QueueKind::Binary => bmssp_with_queue_kind(g, sources, b, args.queue),
+                _ => bmssp_with_options(g, sources, b, &opts),
+            },
+            #[cfg(feature = "rayon")]
+            (true, ParMode::Sharded) if pool.is_some() => bmssp_sharded_in_pool_with_options(pool.as_ref().unwrap(), g, sources, b, threads, &opts),
+            (true, ParMode::Sharded) => bmssp_sharded_with_options(g, sources, b, threads, &opts),
+            (true, ParMode::Atomic) => match inst.topo {
+                Some(topo) => bmssp_parallel_numa(g, sources, b, threads, topo),
+                None => bmssp_parallel(g, sources, b, threads),
+            },
+            (true, ParMode::Partitioned) => bmssp_partitioned(g, sources, b, threads),
+        };
         let elapsed = start.elapsed().as_nanos();
         let row = OutputRow{
-            impl_: "rust-bmssp",
-            lang: "Rust",
-            graph: gname,
-            n,
-            m,
             k: sources.len(),
             b,
-            seed: seed + t as u64,
+            seed: args.seed + t as u64,
             threads,
             time_ns: elapsed,
-            popped: res.explored.len(),
+            popped: res.settled_count(),
             edges_scanned: res.edges_scanned,
             heap_pushes: res.heap_pushes,
             b_prime: res.b_prime,
-            mem_bytes: mem,
+            parallel: (threads > 1).then_some(args.parallel.name()),
+            pool: (threads > 1 && args.parallel == ParMode::Sharded).then_some(if args.pool_reuse { "reuse" } else { "spawn" }),
+            numa: inst.topo.map(|t| t.describe()),
+            termination: budgeted.then_some(res.termination.as_str()),
+            effective_bound: budgeted.then(|| res.effective_bound(b)),
+            overflows: (res.overflows > 0).then_some(res.overflows),
+            cost: args.cost,
+            queue: (args.queue != QueueKind::Binary).then_some(args.queue.as_str()),
+            batched: args.batched.then_some(true),
+            ..OutputRow::base(inst, args)
         };
-        if json { println!("{}", serde_json::to_string(&row).unwrap()); }
+        if emit { println!("{}", serde_json::to_string(&row).unwrap()); }
         if best.as_ref().map(|b| row.time_ns < b.time_ns).unwrap_or(true) { best = Some(row); }
     }
-    // Print best summary to stderr for human glance
-    if let Some(b) = best { eprintln!("best ns={} popped={} B'={}", b.time_ns, b.popped, b.b_prime); }
+    best
+}
+
+/// Timed GPU runs. Upload and pipeline creation happen once, outside the timed region, like
+/// graph generation for the CPU rows.
+#[cfg(feature = "gpu")]
+fn run_gpu_trials(inst: &Instance, args: &Args) -> Option<OutputRow> {
+    let cg = CompactGraph::from_graph(inst.g).expect("graph doesn't fit u32 CSR");
+    let solver = match gpu::GpuSolver::new(&cg) {
+        Ok(s) => s,
+        Err(e) => { eprintln!("error: {e}"); std::process::exit(2); }
+    };
+    eprintln!("gpu adapter: {}", solver.adapter_name);
+    let mut best: Option<OutputRow> = None;
+    for t in 0..args.trials {
+        let start = Instant::now();
+        let res = solver.run(inst.sources, args.b).expect("gpu run failed");
+        let elapsed = start.elapsed().as_nanos();
+        let row = OutputRow{
+            impl_: "rust-bmssp-gpu",
+            seed: args.seed + t as u64,
+            time_ns: elapsed,
+            popped: res.settled_count(),
+            edges_scanned: res.edges_scanned,
+            heap_pushes: res.heap_pushes,
+            b_prime: res.b_prime,
+            mem_bytes: cg.memory_estimate_bytes(),
+            mem_actual_bytes: cg.memory_actual_bytes(),
+            ..OutputRow::base(inst, args)
+        };
+        if args.json { println!("{}", serde_json::to_string(&row).unwrap()); }
+        if best.as_ref().map(|b| row.time_ns < b.time_ns).unwrap_or(true) { best = Some(row); }
+    }
+    best
+}
+
+/// Time `query` like `run_trials` for an index built ahead of time in `preprocess_ns`.
+fn run_prepared_trials(
+    inst: &Instance,
+    args: &Args,
+    impl_: &'static str,
+    preprocess_ns: u128,
+    mem_bytes: usize,
+    mem_actual_bytes: usize,
+    query: impl Fn() -> BmsspResult,
+) -> Option<OutputRow> {
+    let mut best: Option<OutputRow> = None;
+    for t in 0..args.trials {
+        let start = Instant::now();
+        let res = query();
+        let elapsed = start.elapsed().as_nanos();
+        let row = OutputRow{
+            impl_,
+            seed: args.seed + t as u64,
+            time_ns: elapsed,
+            popped: res.settled_count(),
+            edges_scanned: res.edges_scanned,
+            heap_pushes: res.heap_pushes,
+            b_prime: res.b_prime,
+            mem_bytes,
+            mem_actual_bytes,
+            preprocess_ns: Some(preprocess_ns),
+            ..OutputRow::base(inst, args)
+        };
+        if args.json { println!("{}", serde_json::to_string(&row).unwrap()); }
+        if best.as_ref().map(|b| row.time_ns < b.time_ns).unwrap_or(true) { best = Some(row); }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn committed_goldens_pass() {
+        let dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../goldens"));
+        let (checked, failures) = goldens_check(dir, None).unwrap();
+        assert!(checked >= golden_cases().len(), "only {checked} fixtures found");
+        assert!(failures.is_empty(), "{failures:?}");
+    }
 }
@@ -0,0 +1,309 @@
+//! Cross-language runner: writes each instance's graph and sources once, runs every configured
+//! implementation on them, checks their rows against the in-process Rust oracle and merges the
+//! results.
+//!
+//!   bmssp-harness --config bench/harness.toml [--out DIR] [--only c,cpp]
+//!
+//! Command templates may use `{graph}`, `{graph_file}`, `{sources_file}`, `{n}`, `{rows}`,
+//! `{cols}`, `{k}`, `{B}`, `{trials}`, `{seed}` and `{maxw}`. Each implementation prints JSON
+//! rows on stdout; a row passes when `popped` and `B_prime` match the oracle and, if the row
+//! carries `dist_hash`, it equals `BmsspResult::dist_digest` (hex or decimal). Rows whose
+//! `schema_version` has another major version than `ROW_SCHEMA_VERSION` fail the run unmerged.
+//!
+//! `algos = ["all"]` (or a list of registry names, see `bmssp::Algorithm`) also runs those Rust
+//! algorithms in-process on every instance, reported as impl `rust:<name>`; `threads` is passed
+//! to the ones that use it.
+use bmssp::generators::{make_ba, make_er, make_grid, pick_sources};
+use bmssp::io::{write_edge_list, write_sources};
+use bmssp::*;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    out: Option<PathBuf>,
+    #[serde(default = "default_trials")] trials: usize,
+    /// Per-invocation wall-clock limit; 0 disables it.
+    #[serde(default)] timeout_s: u64,
+    #[serde(default)] algos: Vec<String>,
+    #[serde(default = "default_threads")] threads: usize,
+    #[serde(rename = "instance")] instances: Vec<InstanceSpec>,
+    #[serde(default, rename = "impl")] impls: Vec<ImplSpec>,
+}
+
+fn default_trials() -> usize { 3 }
+fn default_threads() -> usize { 1 }
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InstanceSpec {
+    name: String,
+    graph: String,
+    #[serde(default)] rows: usize,
+    #[serde(default)] cols: usize,
+    #[serde(default)] n: usize,
+    #[serde(default = "default_p")] p: f64,
+    #[serde(default = "default_deg")] m0: usize,
+    #[serde(default = "default_deg")] m: usize,
+    #[serde(default = "default_k")] k: usize,
+    #[serde(rename = "B")] bounds: Vec<u64>,
+    #[serde(default = "default_seed")] seed: u64,
+    #[serde(default = "default_maxw")] maxw: u32,
+}
+
+fn default_p() -> f64 { 0.0005 }
+fn default_deg() -> usize { 5 }
+fn default_k() -> usize { 16 }
+fn default_seed() -> u64 { 42 }
+fn default_maxw() -> u32 { 100 }
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImplSpec {
+    name: String,
+    cmd: Vec<String>,
+    cwd: Option<PathBuf>,
+}
+
+struct Oracle { popped: usize, b_prime: u64, digest: u64 }
+
+/// One report line: instance, B, impl, best time and the verification verdict.
+type SummaryRow = (String, u64, String, Option<u64>, Result<(), String>);
+
+fn die(msg: String) -> ! { eprintln!("error: {msg}"); std::process::exit(2) }
+
+fn build_instance(spec: &InstanceSpec) -> (Graph, Vec<(Node, Weight)>) {
+    let g = match spec.graph.as_str() {
+        "grid" => make_grid(spec.rows, spec.cols, spec.maxw, spec.seed),
+        "er" => make_er(spec.n, spec.p, spec.maxw, spec.seed),
+        "ba" => make_ba(spec.n, spec.m0, spec.m, spec.maxw, spec.seed),
+        other => die(format!("instance {}: unknown graph {other:?}", spec.name)),
+    };
+    let sources = pick_sources(g.len(), spec.k, spec.seed);
+    (g, sources)
+}
+
+fn expand(template: &[String], vars: &BTreeMap<&str, String>) -> Vec<String> {
+    template.iter().map(|t| vars.iter().fold(t.clone(), |acc, (k, v)| acc.replace(&format!("{{{k}}}"), v))).collect()
+}
+
+/// Runs `argv`, returning stdout, or an error describing the failure or timeout.
+fn run_command(argv: &[String], cwd: Option<&Path>, timeout: Option<Duration>) -> Result<String, String> {
+    let (prog, rest) = argv.split_first().ok_or("empty command")?;
+    let mut cmd = Command::new(prog);
+    cmd.args(rest).stdout(Stdio::piped()).stderr(Stdio::inherit());
+    if let Some(dir) = cwd { cmd.current_dir(dir); }
+    let mut child = cmd.spawn().map_err(|e| format!("spawn {prog}: {e}"))?;
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = std::thread::spawn(move || { let mut s = String::new(); stdout.read_to_string(&mut s).map(|_| s) });
+    let start = Instant::now();
+    let status = loop {
+        if let Some(st) = child.try_wait().map_err(|e| e.to_string())? { break st; }
+        if timeout.is_some_and(|t| start.elapsed() > t) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("timed out after {:?}", timeout.unwrap()));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    let out = reader.join().unwrap().map_err(|e| e.to_string())?;
+    if !status.success() { return Err(format!("exited with {status}")); }
+    Ok(out)
+}
+
+fn parse_hash(v: &Value) -> Option<u64> {
+    match v {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.strip_prefix("0x").map_or_else(|| s.parse().ok(), |h| u64::from_str_radix(h, 16).ok()),
+        _ => None,
+    }
+}
+
+/// Empty when the row agrees with the oracle, otherwise the list of disagreements.
+fn check_row(row: &Value, o: &Oracle) -> Vec<String> {
+    let mut bad = Vec::new();
+    match row.get("popped").and_then(Value::as_u64) {
+        Some(p) if p as usize == o.popped => {}
+        got => bad.push(format!("popped {got:?} != {}", o.popped)),
+    }
+    match row.get("B_prime").and_then(Value::as_u64) {
+        Some(b) if b == o.b_prime => {}
+        got => bad.push(format!("B_prime {got:?} != {}", o.b_prime)),
+    }
+    if let Some(h) = row.get("dist_hash") {
+        if parse_hash(h) != Some(o.digest) { bad.push(format!("dist_hash {h} != {:#x}", o.digest)); }
+    }
+    bad
+}
+
+/// Add the harness columns to a row before it goes to `merged.jsonl`.
+fn tag_row(row: &mut Value, instance: &str, imp: &str, bad: &[String]) {
+    let obj = row.as_object_mut().expect("JSON row is an object");
+    obj.insert("instance".into(), instance.into());
+    obj.insert("harness_impl".into(), imp.into());
+    obj.insert("verified".into(), bad.is_empty().into());
+    if !bad.is_empty() { obj.insert("mismatch".into(), bad.join("; ").into()); }
+}
+
+fn main() {
+    let mut config = None;
+    let mut out_override = None;
+    let mut only: Option<Vec<String>> = None;
+    let mut it = std::env::args().skip(1);
+    while let Some(flag) = it.next() {
+        match flag.as_str() {
+            "--config" => config = Some(PathBuf::from(it.next().expect("--config value"))),
+            "--out" => out_override = Some(PathBuf::from(it.next().expect("--out value"))),
+            "--only" => only = Some(it.next().expect("--only value").split(',').map(str::to_string).collect()),
+            other => die(format!("unknown flag {other}")),
+        }
+    }
+    let config = config.unwrap_or_else(|| die("--config FILE is required".into()));
+    let text = fs::read_to_string(&config).unwrap_or_else(|e| die(format!("{}: {e}", config.display())));
+    let cfg: Config = toml::from_str(&text).unwrap_or_else(|e| die(format!("{}: {e}", config.display())));
+    let out = out_override.or(cfg.out.clone()).unwrap_or_else(|| PathBuf::from("harness-out"));
+    fs::create_dir_all(&out).unwrap_or_else(|e| die(format!("{}: {e}", out.display())));
+    let timeout = (cfg.timeout_s > 0).then(|| Duration::from_secs(cfg.timeout_s));
+    let impls: Vec<&ImplSpec> = cfg.impls.iter().filter(|i| only.as_ref().is_none_or(|o| o.contains(&i.name))).collect();
+    let algos: Vec<Algorithm> = if cfg.algos == ["all"] { Algorithm::ALL.to_vec() } else {
+        cfg.algos.iter().map(|a| Algorithm::parse(a).unwrap_or_else(|| die(format!("unknown algo {a:?} (expected \"all\" or one of {})", Algorithm::names())))).collect()
+    };
+    let algos: Vec<Algorithm> = algos.into_iter().filter(|a| only.as_ref().is_none_or(|o| o.contains(&format!("rust:{}", a.name())))).collect();
+
+    let merged_path = out.join("merged.jsonl");
+    let mut merged = BufWriter::new(File::create(&merged_path).unwrap_or_else(|e| die(format!("{}: {e}", merged_path.display()))));
+    // (instance, B, impl) -> (best time_ns, verified)
+    let mut summary: Vec<SummaryRow> = Vec::new();
+
+    for spec in &cfg.instances {
+        let (g, sources) = build_instance(spec);
+        let dir = out.join(&spec.name);
+        fs::create_dir_all(&dir).unwrap_or_else(|e| die(format!("{}: {e}", dir.display())));
+        let (graph_file, sources_file) = (dir.join("graph.txt"), dir.join("sources.txt"));
+        File::create(&graph_file).and_then(|f| write_edge_list(&g, BufWriter::new(f))).unwrap_or_else(|e| die(format!("{}: {e}", graph_file.display())));
+        File::create(&sources_file).and_then(|f| write_sources(&sources, BufWriter::new(f))).unwrap_or_else(|e| die(format!("{}: {e}", sources_file.display())));
+        eprintln!("instance {}: n={} m={} k={}", spec.name, g.len(), g.num_edges(), sources.len());
+        let prepared: Vec<(Algorithm, Option<Prepared>, u128)> = algos.iter().map(|&a| {
+            let start = Instant::now();
+            let p = a.prepare(&g);
+            (a, p, start.elapsed().as_nanos())
+        }).collect();
+
+        for &b in &spec.bounds {
+            let res = bounded_multi_source_shortest_paths(&g, &sources, b);
+            let oracle = Oracle{ popped: res.settled_count(), b_prime: res.b_prime, digest: res.dist_digest() };
+            let vars: BTreeMap<&str, String> = [
+                ("graph", spec.graph.clone()),
+                ("graph_file", graph_file.display().to_string()),
+                ("sources_file", sources_file.display().to_string()),
+                ("n", g.len().to_string()),
+                ("rows", spec.rows.to_string()),
+                ("cols", spec.cols.to_string()),
+                ("k", sources.len().to_string()),
+                ("B", b.to_string()),
+                ("trials", cfg.trials.to_string()),
+                ("seed", spec.seed.to_string()),
+                ("maxw", spec.maxw.to_string()),
+            ].into_iter().collect();
+            for imp in &impls {
+                let argv = expand(&imp.cmd, &vars);
+                let outcome = run_command(&argv, imp.cwd.as_deref(), timeout).and_then(|stdout| {
+                    let rows: Vec<Value> = stdout.lines().filter(|l| l.trim_start().starts_with('{'))
+                        .map(|l| serde_json::from_str(l).map_err(|e| format!("bad JSON row: {e}")))
+                        .collect::<Result<_, _>>()?;
+                    if rows.is_empty() { return Err("no JSON rows on stdout".into()); }
+                    // A row of another major version may have renamed fields; keep it out of the merge.
+                    rows.iter().try_for_each(check_row_schema)?;
+                    Ok(rows)
+                });
+                let (best, verdict) = match outcome {
+                    Err(e) => (None, Err(e)),
+                    Ok(rows) => {
+                        let mut best: Option<u64> = None;
+                        let mut problems = Vec::new();
+                        for mut row in rows {
+                            let bad = check_row(&row, &oracle);
+                            if let Some(t) = row.get("time_ns").and_then(Value::as_u64) { best = Some(best.map_or(t, |b| b.min(t))); }
+                            tag_row(&mut row, &spec.name, &imp.name, &bad);
+                            writeln!(merged, "{row}").expect("write merged.jsonl");
+                            problems.extend(bad);
+                        }
+                        problems.dedup();
+                        (best, if problems.is_empty() { Ok(()) } else { Err(problems.join("; ")) })
+                    }
+                };
+                if let Err(e) = &verdict { eprintln!("  {} B={b}: {e}", imp.name); }
+                summary.push((spec.name.clone(), b, imp.name.clone(), best, verdict));
+            }
+            for (algo, prep, preprocess_ns) in &prepared {
+                let name = format!("rust:{}", algo.name());
+                let Some(prep) = prep else {
+                    eprintln!("  {name} B={b}: graph does not fit its layout");
+                    summary.push((spec.name.clone(), b, name, None, Err("graph does not fit its layout".into())));
+                    continue;
+                };
+                let threads = if algo.uses_threads() { cfg.threads } else { 1 };
+                let mut best: Option<u64> = None;
+                let mut problems = Vec::new();
+                for t in 0..cfg.trials.max(1) {
+                    let start = Instant::now();
+                    let res = prep.run(&sources, b, threads);
+                    let elapsed = start.elapsed().as_nanos() as u64;
+                    best = Some(best.map_or(elapsed, |x| x.min(elapsed)));
+                    let mut row = serde_json::json!({
+                        "schema_version": ROW_SCHEMA_VERSION, "impl": name, "lang": "Rust", "graph": spec.graph, "n": g.len(), "m": g.num_edges(),
+                        "k": sources.len(), "B": b, "seed": spec.seed + t as u64, "threads": threads, "time_ns": elapsed,
+                        "popped": res.settled_count(), "edges_scanned": res.edges_scanned, "heap_pushes": res.heap_pushes,
+                        "B_prime": res.b_prime, "dist_hash": format!("{:#x}", res.dist_digest()),
+                        "mem_bytes": prep.memory_estimate_bytes(), "mem_actual_bytes": prep.memory_actual_bytes(),
+                        "preprocess_ns": preprocess_ns, "algo": algo.name(),
+                    });
+                    let bad = check_row(&row, &oracle);
+                    tag_row(&mut row, &spec.name, &name, &bad);
+                    writeln!(merged, "{row}").expect("write merged.jsonl");
+                    problems.extend(bad);
+                }
+                problems.dedup();
+                let verdict = if problems.is_empty() { Ok(()) } else { Err(problems.join("; ")) };
+                if let Err(e) = &verdict { eprintln!("  {name} B={b}: {e}"); }
+                summary.push((spec.name.clone(), b, name, best, verdict));
+            }
+        }
+    }
+    merged.flush().expect("write merged.jsonl");
+
+    let report_path = out.join("report.md");
+    let mut report = String::from("| instance | B | impl | best time_ns | verified |\n|---|---:|---|---:|---|\n");
+    for (inst, b, imp, best, verdict) in &summary {
+        let v = match verdict { Ok(()) => "yes".to_string(), Err(e) => format!("**no**: {e}") };
+        report += &format!("| {inst} | {b} | {imp} | {} | {v} |\n", best.map_or("-".into(), |t| t.to_string()));
+    }
+    fs::write(&report_path, &report).unwrap_or_else(|e| die(format!("{}: {e}", report_path.display())));
+    print!("{report}");
+    let failed = summary.iter().filter(|s| s.4.is_err()).count();
+    eprintln!("wrote {} and {}; {failed} of {} runs failed verification", merged_path.display(), report_path.display(), summary.len());
+    if failed > 0 { std::process::exit(1); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_are_checked_against_oracle() {
+        let o = Oracle{ popped: 10, b_prime: 42, digest: 0xabc };
+        assert!(check_row(&serde_json::json!({"popped": 10, "B_prime": 42}), &o).is_empty());
+        assert!(check_row(&serde_json::json!({"popped": 10, "B_prime": 42, "dist_hash": "0xabc"}), &o).is_empty());
+        assert_eq!(check_row(&serde_json::json!({"popped": 9, "B_prime": 42, "dist_hash": 2748}), &o).len(), 1);
+        assert_eq!(check_row(&serde_json::json!({"popped": 10}), &o).len(), 1);
+        let vars: BTreeMap<&str, String> = [("B", "7".to_string()), ("graph_file", "g.txt".to_string())].into_iter().collect();
+        assert_eq!(expand(&["--B".into(), "{B}".into(), "{graph_file}".into()], &vars), ["--B", "7", "g.txt"]);
+    }
+}
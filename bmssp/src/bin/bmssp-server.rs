@@ -0,0 +1,331 @@
+//! Long-running query server: loads a binary CSR graph (`bmssp-cli --save-csr`) once and answers
+//! bounded searches over HTTP/JSON, so latency measurements don't include graph loading.
+//!
+//!   bmssp-server --graph g.csr [--addr 127.0.0.1:8080] [--workers 4]
+//!
+//! `GET /health` returns the graph size; `POST /query` takes
+//! `{"sources": [0, 7], "source_dists": [0, 3], "bound": 500, "output": "summary|explored|dist|result",
+//!   "max_edges": 100000, "max_settled": 5000, "deadline_ms": 20, "options": {...}}` (all but
+//! `sources` and `bound` optional; `options` takes `OptionsSpec` fields) and returns counters
+//! plus the requested output. `GET /metrics` exports
+//! Prometheus text: query latency and settled-node histograms, scan and push counters, error
+//! count and graph size / memory gauges.
+use bmssp::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+struct Args {
+    graph: PathBuf,
+    addr: String,
+    workers: usize,
+}
+
+fn parse_args() -> Args {
+    let mut graph = None;
+    let mut addr = "127.0.0.1:8080".to_string();
+    let mut workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut it = std::env::args().skip(1);
+    while let Some(flag) = it.next() {
+        match flag.as_str() {
+            "--graph" => graph = Some(PathBuf::from(it.next().expect("--graph value"))),
+            "--addr" => addr = it.next().expect("--addr value"),
+            "--workers" => workers = it.next().expect("--workers value").parse().expect("--workers takes a number"),
+            other => { eprintln!("unknown flag {other}"); std::process::exit(2); }
+        }
+    }
+    let Some(graph) = graph else { eprintln!("--graph FILE.csr is required"); std::process::exit(2) };
+    Args { graph, addr, workers: workers.max(1) }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Query {
+    sources: Vec<Node>,
+    #[serde(default)] source_dists: Vec<Weight>,
+    bound: Weight,
+    #[serde(default)] output: Output,
+    max_edges: Option<usize>,
+    max_settled: Option<usize>,
+    deadline_ms: Option<u64>,
+    /// Remaining `BmsspOptions` settings; the three fields above take precedence.
+    #[serde(default)] options: OptionsSpec,
+}
+
+#[derive(Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Output { #[default] Summary, Explored, Dist, Result }
+
+#[derive(Serialize)]
+struct Answer {
+    time_ns: u128,
+    popped: usize,
+    edges_scanned: usize,
+    heap_pushes: usize,
+    #[serde(rename = "B_prime")] b_prime: Weight,
+    termination: &'static str,
+    /// `bound`, or the first unsettled label if a budget stopped the search first.
+    effective_bound: Weight,
+    #[serde(skip_serializing_if = "Option::is_none")] explored: Option<Vec<Node>>,
+    /// `[node, dist]` pairs in settle order.
+    #[serde(skip_serializing_if = "Option::is_none")] dist: Option<Vec<(Node, Weight)>>,
+    /// The whole `BmsspResult`, dense `dist` array included.
+    #[serde(skip_serializing_if = "Option::is_none")] result: Option<BmsspResult>,
+}
+
+fn answer(g: &CompactGraph, q: &Query) -> Result<Answer, String> {
+    if !q.source_dists.is_empty() && q.source_dists.len() != q.sources.len() {
+        return Err("source_dists must be empty or match sources in length".into());
+    }
+    if let Some(&s) = q.sources.iter().find(|&&s| s >= g.len()) { return Err(format!("source {s} out of range")); }
+    q.options.validate(g.len(), q.bound)?;
+    let sources: Vec<(Node, Weight)> = q.sources.iter().enumerate().map(|(i, &s)| (s, q.source_dists.get(i).copied().unwrap_or(0))).collect();
+    let start = Instant::now();
+    let spec = OptionsSpec{
+        max_edges_scanned: q.max_edges.or(q.options.max_edges_scanned),
+        max_settled: q.max_settled.or(q.options.max_settled),
+        deadline_ms: q.deadline_ms.or(q.options.deadline_ms),
+        ..q.options.clone()
+    };
+    let res = bmssp_with_options(g, &sources, q.bound, &spec.to_options(start));
+    let time_ns = start.elapsed().as_nanos();
+    Ok(Answer{
+        time_ns,
+        popped: res.settled_count(),
+        edges_scanned: res.edges_scanned,
+        heap_pushes: res.heap_pushes,
+        b_prime: res.b_prime,
+        termination: res.termination.as_str(),
+        effective_bound: res.effective_bound(q.bound),
+        dist: (q.output == Output::Dist).then(|| res.explored.iter().map(|&v| (v, res.dist[v])).collect()),
+        explored: (q.output == Output::Explored).then(|| res.explored.clone()),
+        result: (q.output == Output::Result).then_some(res),
+    })
+}
+
+/// Prometheus histogram over integer observations with fixed upper bounds; `scale` divides
+/// bounds and sum when rendering (ns -> s).
+struct Histogram {
+    bounds: Vec<u64>,
+    /// One per bound plus `+Inf`; not cumulative until rendered.
+    buckets: Vec<AtomicU64>,
+    sum: AtomicU64,
+    scale: f64,
+}
+
+impl Histogram {
+    fn new(bounds: Vec<u64>, scale: f64) -> Self {
+        let buckets = (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect();
+        Self { bounds, buckets, sum: AtomicU64::new(0), scale }
+    }
+
+    fn observe(&self, x: u64) {
+        self.buckets[self.bounds.partition_point(|&b| b < x)].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(x, Ordering::Relaxed);
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} histogram");
+        let mut total = 0;
+        for (i, b) in self.buckets.iter().enumerate() {
+            total += b.load(Ordering::Relaxed);
+            let le = self.bounds.get(i).map_or("+Inf".to_string(), |&x| (x as f64 / self.scale).to_string());
+            let _ = writeln!(out, "{name}_bucket{{le=\"{le}\"}} {total}");
+        }
+        let _ = writeln!(out, "{name}_sum {}\n{name}_count {total}", self.sum.load(Ordering::Relaxed) as f64 / self.scale);
+    }
+}
+
+/// Counters shared by all workers.
+struct Metrics {
+    latency_ns: Histogram,
+    settled: Histogram,
+    edges_scanned: AtomicU64,
+    heap_pushes: AtomicU64,
+    errors: AtomicU64,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        // 10us .. ~2.6s by powers of 4; 1 .. ~4M nodes by powers of 4.
+        Self {
+            latency_ns: Histogram::new((0..10).map(|i| 10_000 << (2 * i)).collect(), 1e9),
+            settled: Histogram::new((0..12).map(|i| 1 << (2 * i)).collect(), 1.0),
+            edges_scanned: AtomicU64::new(0),
+            heap_pushes: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, a: &Answer) {
+        self.latency_ns.observe(a.time_ns as u64);
+        self.settled.observe(a.popped as u64);
+        self.edges_scanned.fetch_add(a.edges_scanned as u64, Ordering::Relaxed);
+        self.heap_pushes.fetch_add(a.heap_pushes as u64, Ordering::Relaxed);
+    }
+
+    fn render(&self, g: &CompactGraph) -> String {
+        let mut out = String::new();
+        self.latency_ns.render(&mut out, "bmssp_query_duration_seconds", "Search time per answered query.");
+        self.settled.render(&mut out, "bmssp_query_settled_nodes", "Nodes settled per answered query.");
+        let counters = [
+            ("bmssp_edges_scanned_total", "Edges scanned over all queries.", &self.edges_scanned),
+            ("bmssp_heap_pushes_total", "Heap pushes over all queries.", &self.heap_pushes),
+            ("bmssp_query_errors_total", "Rejected requests (bad JSON or invalid query).", &self.errors),
+        ];
+        for (name, help, c) in counters {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter\n{name} {}", c.load(Ordering::Relaxed));
+        }
+        let gauges = [
+            ("bmssp_graph_nodes", "Nodes in the loaded graph.", g.len()),
+            ("bmssp_graph_edges", "Edges in the loaded graph.", g.num_edges()),
+            ("bmssp_graph_memory_bytes", "CompactGraph::memory_estimate_bytes of the loaded graph.", g.memory_estimate_bytes()),
+            ("bmssp_graph_memory_actual_bytes", "CompactGraph::memory_actual_bytes of the loaded graph.", g.memory_actual_bytes()),
+        ];
+        for (name, help, v) in gauges {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} gauge\n{name} {v}");
+        }
+        out
+    }
+}
+
+fn json_response(status: u16, body: String) -> Response<std::io::Cursor<Vec<u8>>> {
+    let ct = Header::from_bytes("Content-Type", "application/json").unwrap();
+    Response::from_string(body).with_status_code(status).with_header(ct)
+}
+
+fn error_body(msg: &str) -> String { serde_json::json!({ "error": msg }).to_string() }
+
+/// Largest `POST /query` body read; longer ones get 413.
+const MAX_BODY_BYTES: usize = 1 << 20;
+
+fn read_body(req: &mut Request) -> Result<String, (u16, String)> {
+    let too_long = || (413, format!("request body over {MAX_BODY_BYTES} bytes"));
+    if req.body_length().is_some_and(|len| len > MAX_BODY_BYTES) { return Err(too_long()); }
+    let mut body = String::new();
+    req.as_reader().take(MAX_BODY_BYTES as u64 + 1).read_to_string(&mut body).map_err(|e| (400, e.to_string()))?;
+    if body.len() > MAX_BODY_BYTES { return Err(too_long()); }
+    Ok(body)
+}
+
+fn parse_query(body: &str, n: usize) -> Result<Query, (u16, String)> {
+    let v: serde_json::Value = serde_json::from_str(body).map_err(|e| (400, e.to_string()))?;
+    // `AvoidSet` allocates a bit per id up to its largest, so out-of-range ids are refused
+    // before one is built.
+    let avoid = v.pointer("/options/avoid").and_then(|a| a.as_array()).into_iter().flatten();
+    if let Some(id) = avoid.filter_map(|x| x.as_u64()).find(|&x| x >= n as u64) {
+        return Err((422, format!("avoid node {id} out of range")));
+    }
+    serde_json::from_value(v).map_err(|e| (400, e.to_string()))
+}
+
+fn handle(g: &CompactGraph, metrics: &Metrics, mut req: Request) {
+    let resp = match (req.method(), req.url()) {
+        (Method::Get, "/health") => json_response(200, serde_json::json!({ "n": g.len(), "m": g.num_edges() }).to_string()),
+        (Method::Get, "/metrics") => {
+            let ct = Header::from_bytes("Content-Type", "text/plain; version=0.0.4").unwrap();
+            Response::from_string(metrics.render(g)).with_header(ct)
+        }
+        (Method::Post, "/query") => match read_body(&mut req).and_then(|body| parse_query(&body, g.len())) {
+            Err((status, e)) => json_response(status, error_body(&e)),
+            Ok(q) => match answer(g, &q) {
+                Ok(a) => { metrics.observe(&a); json_response(200, serde_json::to_string(&a).unwrap()) }
+                Err(e) => json_response(422, error_body(&e)),
+            },
+        },
+        _ => json_response(404, error_body("try GET /health, GET /metrics or POST /query")),
+    };
+    if matches!(resp.status_code().0, 400 | 413 | 422) { metrics.errors.fetch_add(1, Ordering::Relaxed); }
+    if let Err(e) = req.respond(resp) { eprintln!("respond: {e}"); }
+}
+
+fn main() {
+    let args = parse_args();
+    let t0 = Instant::now();
+    let bytes = std::fs::read(&args.graph).unwrap_or_else(|e| { eprintln!("{}: {e}", args.graph.display()); std::process::exit(1) });
+    let g = CompactGraph::from_bytes(&bytes).unwrap_or_else(|e| { eprintln!("{}: {e}", args.graph.display()); std::process::exit(1) });
+    drop(bytes);
+    eprintln!("loaded n={} m={} in {:?}", g.len(), g.num_edges(), t0.elapsed());
+
+    let server = Server::http(&args.addr).unwrap_or_else(|e| { eprintln!("bind {}: {e}", args.addr); std::process::exit(1) });
+    eprintln!("listening on http://{} with {} workers", args.addr, args.workers);
+    let metrics = Metrics::new();
+    std::thread::scope(|s| {
+        for _ in 0..args.workers {
+            s.spawn(|| { while let Ok(req) = server.recv() { handle(&g, &metrics, req); } });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line() -> CompactGraph {
+        let mut g = Graph::new(4);
+        g.add_edge(0, 1, 2);
+        g.add_edge(1, 2, 3);
+        g.add_edge(2, 3, 10);
+        CompactGraph::from_graph(&g).unwrap()
+    }
+
+    #[test]
+    fn answers_query_with_requested_output() {
+        let q: Query = serde_json::from_str(r#"{"sources":[0],"bound":8,"output":"dist"}"#).unwrap();
+        let a = answer(&line(), &q).unwrap();
+        assert_eq!((a.popped, a.b_prime, a.termination), (3, 15, "bound"));
+        assert_eq!(a.dist, Some(vec![(0, 0), (1, 2), (2, 5)]));
+        assert!(a.explored.is_none());
+        assert_eq!(a.effective_bound, 8);
+        let capped: Query = serde_json::from_str(r#"{"sources":[0],"bound":8,"max_settled":2}"#).unwrap();
+        let a = answer(&line(), &capped).unwrap();
+        assert_eq!((a.popped, a.termination, a.effective_bound), (2, "settled_budget", 5));
+        let bad: Query = serde_json::from_str(r#"{"sources":[9],"bound":8}"#).unwrap();
+        assert!(answer(&line(), &bad).is_err());
+        assert!(serde_json::from_str::<Query>(r#"{"sources":[0],"bound":8,"bogus":1}"#).is_err());
+    }
+
+    #[test]
+    fn metrics_render_cumulative_buckets_and_gauges() {
+        let g = line();
+        let m = Metrics::new();
+        let q: Query = serde_json::from_str(r#"{"sources":[0],"bound":8}"#).unwrap();
+        m.observe(&answer(&g, &q).unwrap());
+        m.observe(&answer(&g, &q).unwrap());
+        let text = m.render(&g);
+        assert!(text.contains("# TYPE bmssp_query_duration_seconds histogram"));
+        assert!(text.contains("bmssp_query_settled_nodes_bucket{le=\"1\"} 0\n"));
+        assert!(text.contains("bmssp_query_settled_nodes_bucket{le=\"4\"} 2\n"));
+        assert!(text.contains("bmssp_query_settled_nodes_bucket{le=\"+Inf\"} 2\nbmssp_query_settled_nodes_sum 6\nbmssp_query_settled_nodes_count 2\n"));
+        assert!(text.contains("bmssp_heap_pushes_total 4\n"));
+        assert!(text.contains("bmssp_graph_nodes 4\n"));
+    }
+
+    #[test]
+    fn takes_options_and_returns_full_result() {
+        let q: Query = serde_json::from_str(r#"{"sources":[0],"bound":8,"output":"result","options":{"avoid":[2],"track_predecessors":true}}"#).unwrap();
+        let a = answer(&line(), &q).unwrap();
+        let r = a.result.unwrap();
+        assert_eq!((r.explored, r.termination), (vec![0, 1], Termination::Bound));
+        assert_eq!(r.pred[1], 0);
+        let json = serde_json::to_value(answer(&line(), &q).unwrap()).unwrap();
+        assert_eq!(json["result"]["termination"], "bound");
+    }
+
+    #[test]
+    fn rejects_out_of_range_options() {
+        let g = line();
+        let err = |body: &str| parse_query(body, g.len()).and_then(|q| answer(&g, &q).map_err(|e| (422, e))).err();
+        assert_eq!(err(r#"{"sources":[0],"bound":8,"options":{"avoid":[4]}}"#).map(|e| e.0), Some(422));
+        assert_eq!(err(r#"{"sources":[0],"bound":8,"options":{"avoid":[18446744073709551615]}}"#).map(|e| e.0), Some(422));
+        assert_eq!(err(r#"{"sources":[0],"bound":8,"options":{"stop_at":4}}"#).map(|e| e.0), Some(422));
+        assert_eq!(err(r#"{"sources":[0],"bound":8,"options":{"histogram_width":0}}"#).map(|e| e.0), Some(422));
+        assert_eq!(err(r#"{"sources":[0],"bound":18446744073709551615,"options":{"histogram_width":1}}"#).map(|e| e.0), Some(422));
+        assert_eq!(err(r#"{"sources":[0],"bound":8,"options":{"histogram_width":2}}"#), None);
+        assert_eq!(err(r#"{"sources":[0]"#).map(|e| e.0), Some(400));
+    }
+}
@@ -0,0 +1,104 @@
+//! Building dense graphs from arbitrary external node identifiers (OSM ids, strings, ...).
+use crate::{BmsspResult, Graph, Node, Weight};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Accumulates edges keyed by external ids and assigns dense indices in first-seen order.
+#[derive(Clone, Debug)]
+pub struct GraphBuilder<K> {
+    ids: IdMap<K>,
+    edges: Vec<(Node, Node, Weight)>,
+}
+
+impl<K: Eq + Hash + Clone> Default for GraphBuilder<K> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<K: Eq + Hash + Clone> GraphBuilder<K> {
+    pub fn new() -> Self { Self { ids: IdMap{ index: HashMap::new(), keys: Vec::new() }, edges: Vec::new() } }
+
+    /// Dense index for `key`, interning it if unseen. Also useful to register isolated nodes.
+    pub fn node(&mut self, key: K) -> Node {
+        if let Some(&i) = self.ids.index.get(&key) { return i; }
+        let i = self.ids.keys.len();
+        self.ids.keys.push(key.clone());
+        self.ids.index.insert(key, i);
+        i
+    }
+
+    pub fn add_edge(&mut self, u: K, v: K, w: Weight) {
+        let (u, v) = (self.node(u), self.node(v));
+        self.edges.push((u, v, w));
+    }
+
+    pub fn add_undirected_edge(&mut self, u: K, v: K, w: Weight) {
+        let (u, v) = (self.node(u), self.node(v));
+        self.edges.push((u, v, w));
+        self.edges.push((v, u, w));
+    }
+
+    /// Materialize the dense graph plus the bidirectional id map.
+    pub fn build(self) -> (Graph, IdMap<K>) {
+        let mut g = Graph::new(self.ids.keys.len());
+        for (u, v, w) in self.edges { g.add_edge(u, v, w); }
+        (g, self.ids)
+    }
+}
+
+/// Bidirectional mapping between external ids and dense node indices.
+#[derive(Clone, Debug)]
+pub struct IdMap<K> {
+    index: HashMap<K, Node>,
+    keys: Vec<K>,
+}
+
+impl<K: Eq + Hash + Clone> IdMap<K> {
+    pub fn len(&self) -> usize { self.keys.len() }
+    pub fn is_empty(&self) -> bool { self.keys.is_empty() }
+    pub fn dense(&self, key: &K) -> Option<Node> { self.index.get(key).copied() }
+    pub fn external(&self, v: Node) -> &K { &self.keys[v] }
+
+    /// Translate external `(id, d0)` sources. Unknown ids are dropped, just as the search
+    /// ignores out-of-range dense sources.
+    pub fn translate_sources(&self, sources: &[(K, Weight)]) -> Vec<(Node, Weight)> {
+        sources.iter().filter_map(|(k, d0)| self.dense(k).map(|v| (v, *d0))).collect()
+    }
+
+    /// Settled nodes of `res` as `(external id, distance)` in settle order.
+    pub fn settled(&self, res: &BmsspResult) -> Vec<(K, Weight)> {
+        res.explored.iter().map(|&v| (self.keys[v].clone(), res.dist[v])).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+
+    #[test]
+    fn osm_ids_roundtrip() {
+        let mut b = GraphBuilder::<u64>::new();
+        b.add_edge(9_000_000_001, 42, 3);
+        b.add_edge(42, 7_700_000_000, 4);
+        b.add_undirected_edge(7_700_000_000, 5, 1);
+        let (g, ids) = b.build();
+        assert_eq!(g.len(), 4);
+        assert_eq!(ids.dense(&42), Some(1));
+        assert_eq!(*ids.external(2), 7_700_000_000);
+
+        let src = ids.translate_sources(&[(9_000_000_001, 0), (123, 0)]);
+        assert_eq!(src, vec![(0, 0)]);
+        let res = bounded_multi_source_shortest_paths(&g, &src, 100);
+        assert_eq!(ids.settled(&res), vec![(9_000_000_001, 0), (42, 3), (7_700_000_000, 7), (5, 8)]);
+    }
+
+    #[test]
+    fn string_ids() {
+        let mut b = GraphBuilder::new();
+        b.add_edge("a".to_string(), "b".to_string(), 2);
+        b.node("lonely".to_string());
+        let (g, ids) = b.build();
+        assert_eq!(g.len(), 3);
+        assert_eq!(ids.dense(&"lonely".to_string()), Some(2));
+    }
+}
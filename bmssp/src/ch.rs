@@ -0,0 +1,294 @@
+//! Contraction hierarchies: node ordering by edge difference with lazy updates, shortcut
+//! insertion backed by bounded witness searches, and bounded point-to-point / many-to-many
+//! queries on the resulting upward graphs.
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::{settle_order_and_boundary, BmsspResult, Graph, Node, Weight};
+
+/// Witness searches give up after settling this many nodes; a failed search only costs an
+/// unnecessary shortcut, never a wrong distance.
+const WITNESS_SETTLE_LIMIT: usize = 500;
+
+#[derive(Debug, Clone)]
+pub struct ContractionHierarchy {
+    /// Contraction order: `rank[v] = i` if `v` was contracted `i`-th.
+    pub rank: Vec<usize>,
+    /// Forward upward graph: edges `u -> x` with `rank[x] > rank[u]`.
+    pub(crate) up: Vec<Vec<(Node, Weight)>>,
+    /// Backward upward graph: for each edge `u -> x` with `rank[u] > rank[x]`, `(u, w)` stored at `x`.
+    pub(crate) down: Vec<Vec<(Node, Weight)>>,
+    /// Nodes by increasing rank; PHAST sweeps it backwards.
+    pub(crate) order: Vec<Node>,
+    pub shortcuts: usize,
+}
+
+/// Mutable overlay used during contraction; edge lists only hold uncontracted endpoints.
+struct Overlay {
+    out: Vec<Vec<(Node, Weight)>>,
+    inn: Vec<Vec<(Node, Weight)>>,
+    dist: Vec<Weight>,
+    touched: Vec<Node>,
+}
+
+fn upsert(list: &mut Vec<(Node, Weight)>, to: Node, w: Weight) -> bool {
+    match list.iter_mut().find(|e| e.0 == to) {
+        Some(e) if w < e.1 => { e.1 = w; true }
+        Some(_) => false,
+        None => { list.push((to, w)); true }
+    }
+}
+
+impl Overlay {
+    fn new(g: &Graph) -> Self {
+        let n = g.len();
+        let mut out = vec![Vec::new(); n];
+        let mut inn = vec![Vec::new(); n];
+        for (u, adj) in g.adj.iter().enumerate() {
+            if g.is_removed(u) { continue; }
+            for &(v, w) in adj {
+                if v == u || g.is_removed(v) { continue; }
+                upsert(&mut out[u], v, w);
+                upsert(&mut inn[v], u, w);
+            }
+        }
+        Self { out, inn, dist: vec![Weight::MAX; n], touched: Vec::new() }
+    }
+
+    /// Bounded Dijkstra from `s` over uncontracted nodes, skipping `via`.
+    fn witness(&mut self, s: Node, via: Node, max_d: Weight) {
+        for &v in &self.touched { self.dist[v] = Weight::MAX; }
+        self.touched.clear();
+        let mut heap = BinaryHeap::new();
+        self.dist[s] = 0;
+        self.touched.push(s);
+        heap.push(Reverse((0, s)));
+        let mut settled = 0;
+        while let Some(Reverse((d, u))) = heap.pop() {
+            if d != self.dist[u] { continue; }
+            if d > max_d || settled == WITNESS_SETTLE_LIMIT { break; }
+            settled += 1;
+            for &(v, w) in &self.out[u] {
+                if v == via { continue; }
+                let nd = d.saturating_add(w);
+                if nd < self.dist[v] {
+                    if self.dist[v] == Weight::MAX { self.touched.push(v); }
+                    self.dist[v] = nd;
+                    heap.push(Reverse((nd, v)));
+                }
+            }
+        }
+    }
+
+    /// Shortcuts needed to contract `v`, as `(from, to, weight)`.
+    fn shortcuts_for(&mut self, v: Node) -> Vec<(Node, Node, Weight)> {
+        let ins = self.inn[v].clone();
+        let outs = self.out[v].clone();
+        let mut sc = Vec::new();
+        for &(u, wu) in &ins {
+            let max_d = outs.iter().map(|&(_, wo)| wu.saturating_add(wo)).max().unwrap_or(0);
+            self.witness(u, v, max_d);
+            for &(x, wo) in &outs {
+                if x == u { continue; }
+                let l = wu.saturating_add(wo);
+                if self.dist[x] > l { sc.push((u, x, l)); }
+            }
+        }
+        sc
+    }
+
+    fn priority(&mut self, v: Node, contracted_neighbors: &[usize]) -> i64 {
+        let sc = self.shortcuts_for(v).len() as i64;
+        let deg = (self.inn[v].len() + self.out[v].len()) as i64;
+        sc - deg + contracted_neighbors[v] as i64
+    }
+}
+
+impl ContractionHierarchy {
+    /// Contract every node of `g`. Tombstoned nodes and self-loops are dropped; parallel edges
+    /// keep their minimum weight.
+    pub fn build(g: &Graph) -> Self {
+        let n = g.len();
+        let mut ov = Overlay::new(g);
+        let mut contracted_neighbors = vec![0usize; n];
+        let mut pq: BinaryHeap<Reverse<(i64, Node)>> = (0..n).map(|v| Reverse((ov.priority(v, &contracted_neighbors), v))).collect();
+        let mut rank = vec![usize::MAX; n];
+        let (mut up, mut down) = (vec![Vec::new(); n], vec![Vec::new(); n]);
+        let mut order = Vec::with_capacity(n);
+        let mut next = 0;
+        let mut shortcuts = 0;
+        while let Some(Reverse((p, v))) = pq.pop() {
+            if rank[v] != usize::MAX { continue; }
+            // Lazy update: contract only if the fresh priority still beats the next candidate.
+            let fresh = ov.priority(v, &contracted_neighbors);
+            if fresh > p && pq.peek().is_some_and(|Reverse((q, _))| fresh > *q) {
+                pq.push(Reverse((fresh, v)));
+                continue;
+            }
+            for (u, x, l) in ov.shortcuts_for(v) {
+                if upsert(&mut ov.out[u], x, l) { upsert(&mut ov.inn[x], u, l); shortcuts += 1; }
+            }
+            rank[v] = next;
+            order.push(v);
+            next += 1;
+            // Every remaining neighbor is contracted later, i.e. ranks higher than `v`.
+            let (outs, ins) = (std::mem::take(&mut ov.out[v]), std::mem::take(&mut ov.inn[v]));
+            for &(x, _) in &outs { ov.inn[x].retain(|e| e.0 != v); contracted_neighbors[x] += 1; }
+            for &(u, _) in &ins { ov.out[u].retain(|e| e.0 != v); contracted_neighbors[u] += 1; }
+            up[v] = outs;
+            down[v] = ins;
+        }
+        Self { rank, up, down, order, shortcuts }
+    }
+
+    pub fn len(&self) -> usize { self.rank.len() }
+    pub fn is_empty(&self) -> bool { self.rank.is_empty() }
+    pub fn memory_estimate_bytes(&self) -> usize {
+        let edges: usize = self.up.iter().chain(&self.down).map(|v| v.len()).sum();
+        self.rank.len() * (std::mem::size_of::<usize>() + 2 * std::mem::size_of::<Vec<(Node, Weight)>>())
+            + edges * std::mem::size_of::<(Node, Weight)>()
+    }
+    /// Index bytes at allocated capacities.
+    pub fn memory_actual_bytes(&self) -> usize {
+        (self.rank.capacity() + self.order.capacity()) * std::mem::size_of::<usize>()
+            + crate::nested_capacity_bytes(&self.up) + crate::nested_capacity_bytes(&self.down)
+    }
+
+    /// Upward Dijkstra from `sources` over `adj`, pruned at `bound`; returns settled `(node, dist)`.
+    pub(crate) fn upward(adj: &[Vec<(Node, Weight)>], sources: &[(Node, Weight)], bound: Weight) -> HashMap<Node, Weight> {
+        let mut dist: HashMap<Node, Weight> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        for &(s, d0) in sources {
+            if s < adj.len() && d0 < bound && d0 < *dist.get(&s).unwrap_or(&Weight::MAX) {
+                dist.insert(s, d0);
+                heap.push(Reverse((d0, s)));
+            }
+        }
+        while let Some(Reverse((d, u))) = heap.pop() {
+            if d != dist[&u] { continue; }
+            for &(v, w) in &adj[u] {
+                let nd = d.saturating_add(w);
+                if nd < bound && nd < *dist.get(&v).unwrap_or(&Weight::MAX) {
+                    dist.insert(v, nd);
+                    heap.push(Reverse((nd, v)));
+                }
+            }
+        }
+        dist
+    }
+
+    /// `dist(s, t)` if it is below `bound`.
+    pub fn query_bounded(&self, s: Node, t: Node, bound: Weight) -> Option<Weight> {
+        let fwd = Self::upward(&self.up, &[(s, 0)], bound);
+        let bwd = Self::upward(&self.down, &[(t, 0)], bound);
+        let (small, large) = if fwd.len() <= bwd.len() { (&fwd, &bwd) } else { (&bwd, &fwd) };
+        small.iter()
+            .filter_map(|(v, &a)| large.get(v).map(|&b| a.saturating_add(b)))
+            .min()
+            .filter(|&d| d < bound)
+    }
+
+    pub fn query(&self, s: Node, t: Node) -> Option<Weight> { self.query_bounded(s, t, Weight::MAX) }
+
+    /// `out[i][j] = dist(sources[i], targets[j])`, or `Weight::MAX` when not below `bound`.
+    /// Bucket-based: one backward upward search per target, one forward per source.
+    pub fn many_to_many(&self, sources: &[Node], targets: &[Node], bound: Weight) -> Vec<Vec<Weight>> {
+        let mut buckets: HashMap<Node, Vec<(usize, Weight)>> = HashMap::new();
+        for (j, &t) in targets.iter().enumerate() {
+            for (v, d) in Self::upward(&self.down, &[(t, 0)], bound) { buckets.entry(v).or_default().push((j, d)); }
+        }
+        sources.iter().map(|&s| {
+            let mut row = vec![Weight::MAX; targets.len()];
+            for (v, a) in Self::upward(&self.up, &[(s, 0)], bound) {
+                for &(j, b) in buckets.get(&v).map_or(&[][..], |b| b) {
+                    let d = a.saturating_add(b);
+                    if d < bound && d < row[j] { row[j] = d; }
+                }
+            }
+            row
+        }).collect()
+    }
+}
+
+impl ContractionHierarchy {
+    /// PHAST one/many-to-all: an upward search from `sources` followed by one linear sweep over
+    /// nodes in decreasing rank, relaxing each node from its higher-ranked in-neighbors. Labels at
+    /// or above `bound` are dropped; `explored` and `b_prime` follow the sequential definitions
+    /// and are recovered from `g`, which must be the graph this hierarchy was built from.
+    /// `edges_scanned` counts upward-graph and sweep edges.
+    pub fn phast(&self, g: &Graph, sources: &[(Node, Weight)], bound: Weight) -> BmsspResult {
+        let n = self.len();
+        let mut dist = vec![Weight::MAX; n];
+        let mut edges_scanned = 0;
+        for (v, d) in Self::upward(&self.up, sources, bound) {
+            dist[v] = d;
+            edges_scanned += self.up[v].len();
+        }
+        for &v in self.order.iter().rev() {
+            let mut best = dist[v];
+            for &(u, w) in &self.down[v] {
+                let nd = dist[u].saturating_add(w);
+                if nd < best { best = nd; }
+            }
+            edges_scanned += self.down[v].len();
+            if best < bound { dist[v] = best; }
+        }
+        let (explored, b_prime) = settle_order_and_boundary(g, &dist, bound);
+        BmsspResult{ dist, explored, b_prime, edges_scanned, ..Default::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn random_graph(n: usize, m: usize, seed: u64) -> Graph {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut g = Graph::new(n);
+        for _ in 0..m { g.add_edge(rng.gen_range(0..n), rng.gen_range(0..n), rng.gen_range(1..20)); }
+        g
+    }
+
+    #[test]
+    fn point_to_point_matches_dijkstra() {
+        let g = random_graph(150, 600, 3);
+        let ch = ContractionHierarchy::build(&g);
+        assert!(ch.rank.iter().all(|&r| r < 150));
+        for s in (0..150).step_by(7) {
+            let full = bounded_multi_source_shortest_paths(&g, &[(s, 0)], Weight::MAX);
+            let bounded = bounded_multi_source_shortest_paths(&g, &[(s, 0)], 25);
+            for t in 0..150 {
+                assert_eq!(ch.query(s, t), full.dist_of(t), "{s}->{t}");
+                assert_eq!(ch.query_bounded(s, t, 25), bounded.dist_of(t));
+            }
+        }
+    }
+
+    #[test]
+    fn phast_matches_bounded_search() {
+        let g = random_graph(200, 900, 17);
+        let ch = ContractionHierarchy::build(&g);
+        for (sources, b) in [(vec![(0, 0)], Weight::MAX), (vec![(3, 0), (50, 4), (120, 1)], 20), (vec![(7, 30)], 10)] {
+            let expect = bounded_multi_source_shortest_paths(&g, &sources, b);
+            let r = ch.phast(&g, &sources, b);
+            assert_eq!(r.dist, expect.dist);
+            assert_eq!(r.explored, expect.explored);
+            assert_eq!(r.b_prime, expect.b_prime);
+        }
+    }
+
+    #[test]
+    fn many_to_many_matches_dijkstra() {
+        let g = random_graph(120, 400, 9);
+        let ch = ContractionHierarchy::build(&g);
+        let sources = [0, 5, 17, 60];
+        let targets: Vec<Node> = (0..120).step_by(3).collect();
+        let table = ch.many_to_many(&sources, &targets, 30);
+        for (i, &s) in sources.iter().enumerate() {
+            let r = bounded_multi_source_shortest_paths(&g, &[(s, 0)], 30);
+            for (j, &t) in targets.iter().enumerate() { assert_eq!(table[i][j], r.dist[t]); }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Checkpoint and resume for long plain bounded searches (`bounded_multi_source_shortest_paths`
+//! semantics: no filters or budgets). A checkpoint holds the labelled nodes, the live heap
+//! entries, the settle order so far, the smallest boundary label and the counters, so a
+//! resumed search finishes with exactly the result of an uninterrupted one.
+//!
+//! ```no_run
+//! # use bmssp::{checkpoint::*, generators::make_grid};
+//! # use std::{path::Path, time::Duration};
+//! let g = make_grid(3000, 3000, 9, 1);
+//! let path = Path::new("run.ckpt");
+//! let save = |cp: &Checkpoint| cp.save(path);
+//! let res = if path.exists() {
+//!     resume_from_checkpoint(&g, Checkpoint::load(path)?, Duration::from_secs(60), save)?
+//! } else {
+//!     bmssp_checkpointed(&g, &[(0, 0)], 50_000, Duration::from_secs(60), save)?
+//! };
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::io::{read_frame, save_atomically, write_frame_header};
+use crate::options::CHECK_INTERVAL;
+use crate::{BmsspResult, Entry, GraphLike, Node, Weight};
+
+/// Leading bytes of the format written by `Checkpoint::write_binary`.
+pub const CHECKPOINT_MAGIC: &[u8; 8] = b"BMSSPCKP";
+const CHECKPOINT_VERSION: u32 = 2;
+
+/// Search state between two settles.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    bound: Weight,
+    /// Node and edge counts of the graph, checked on resume.
+    n: usize,
+    m: usize,
+    /// `(node, label)` for every node with a finite label.
+    labels: Vec<(Node, Weight)>,
+    /// Live heap entries as `(label, node)`.
+    heap: Vec<(Weight, Node)>,
+    explored: Vec<Node>,
+    /// Smallest relaxation at or above the bound so far.
+    b_prime: Weight,
+    edges_scanned: usize,
+    heap_pushes: usize,
+}
+
+impl Checkpoint {
+    pub fn bound(&self) -> Weight { self.bound }
+    /// Nodes settled before the checkpoint was taken.
+    pub fn settled_count(&self) -> usize { self.explored.len() }
+    /// Heap entries still to process.
+    pub fn frontier_len(&self) -> usize { self.heap.len() }
+
+    /// `CHECKPOINT_MAGIC`, version and reserved `u32`s, then `bound`, `n`, `m`, B' so far,
+    /// `edges_scanned`, `heap_pushes` and the three list lengths as `u64`, then the lists (pairs
+    /// as two `u64`s), all little-endian.
+    pub fn write_binary<W: Write>(&self, mut w: W) -> io::Result<()> {
+        write_frame_header(&mut w, CHECKPOINT_MAGIC, CHECKPOINT_VERSION, 0)?;
+        let header = [self.bound, self.n as u64, self.m as u64, self.b_prime, self.edges_scanned as u64, self.heap_pushes as u64,
+            self.labels.len() as u64, self.heap.len() as u64, self.explored.len() as u64];
+        for x in header { w.write_all(&x.to_le_bytes())?; }
+        for &(a, b) in &self.labels { w.write_all(&(a as u64).to_le_bytes())?; w.write_all(&b.to_le_bytes())?; }
+        for &(a, b) in &self.heap { w.write_all(&a.to_le_bytes())?; w.write_all(&(b as u64).to_le_bytes())?; }
+        for &v in &self.explored { w.write_all(&(v as u64).to_le_bytes())?; }
+        w.flush()
+    }
+
+    pub fn read_binary<R: Read>(r: R) -> io::Result<Self> {
+        let bad = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let (_, words) = read_frame(r, CHECKPOINT_MAGIC, CHECKPOINT_VERSION, 9)?;
+        let [bound, n, m, b_prime, edges_scanned, heap_pushes, nl, nh, ne] = words[..9].try_into().unwrap();
+        let body = [nl, nh, ne].iter().zip([2, 2, 1]).try_fold(0u64, |acc, (&len, k)| len.checked_mul(k).and_then(|x| x.checked_add(acc)));
+        if body != Some(words.len() as u64 - 9) { return Err(bad("BMSSPCKP length does not match header")); }
+        let mut it = words[9..].iter().map(|&x| x as usize);
+        let mut pairs = |len: u64| (0..len).map(|_| (it.next().unwrap(), it.next().unwrap())).collect::<Vec<_>>();
+        let labels: Vec<(Node, Weight)> = pairs(nl).into_iter().map(|(v, d)| (v, d as Weight)).collect();
+        let heap: Vec<(Weight, Node)> = pairs(nh).into_iter().map(|(d, v)| (d as Weight, v)).collect();
+        let explored: Vec<Node> = (0..ne).map(|_| it.next().unwrap()).collect();
+        let n = n as usize;
+        let in_range = labels.iter().map(|e| e.0).chain(heap.iter().map(|e| e.1)).chain(explored.iter().copied()).all(|v| v < n);
+        if !in_range { return Err(bad("BMSSPCKP node id out of range")); }
+        Ok(Self { bound, n, m: m as usize, labels, heap, explored, b_prime, edges_scanned: edges_scanned as usize, heap_pushes: heap_pushes as usize })
+    }
+
+    /// Write to `path` through a temporary file and a rename, so a kill mid-write leaves the
+    /// previous checkpoint intact.
+    pub fn save(&self, path: &Path) -> io::Result<()> { save_atomically(path, |w| self.write_binary(w)) }
+
+    pub fn load(path: &Path) -> io::Result<Self> { Self::read_binary(File::open(path)?) }
+}
+
+struct State {
+    bound: Weight,
+    n: usize,
+    m: usize,
+    dist: Vec<Weight>,
+    touched: Vec<Node>,
+    heap: BinaryHeap<Reverse<Entry>>,
+    explored: Vec<Node>,
+    b_prime: Weight,
+    edges_scanned: usize,
+    heap_pushes: usize,
+}
+
+impl State {
+    fn snapshot(&self) -> Checkpoint {
+        Checkpoint{
+            bound: self.bound,
+            n: self.n,
+            m: self.m,
+            labels: self.touched.iter().map(|&v| (v, self.dist[v])).collect(),
+            heap: self.heap.iter().filter(|e| e.0.d == self.dist[e.0.v]).map(|e| (e.0.d, e.0.v)).collect(),
+            explored: self.explored.clone(),
+            b_prime: self.b_prime,
+            edges_scanned: self.edges_scanned,
+            heap_pushes: self.heap_pushes,
+        }
+    }
+
+    /// Label `v` with `d`, remembering it for the next snapshot.
+    fn label(&mut self, v: Node, d: Weight) {
+        if self.dist[v] == Weight::MAX { self.touched.push(v); }
+        self.dist[v] = d;
+        self.heap.push(Reverse(Entry{ d, v }));
+    }
+}
+
+fn edge_count<G: GraphLike>(g: &G) -> usize { (0..g.num_nodes()).map(|u| g.neighbors(u).count()).sum() }
+
+/// `bounded_multi_source_shortest_paths` that hands a `Checkpoint` to `save` whenever at least
+/// `interval` has passed since the last one (polled every `CHECK_INTERVAL` settles). An error
+/// from `save` aborts the search and is returned.
+pub fn bmssp_checkpointed<G: GraphLike>(
+    g: &G,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    interval: Duration,
+    save: impl FnMut(&Checkpoint) -> io::Result<()>,
+) -> io::Result<BmsspResult> {
+    let n = g.num_nodes();
+    let mut st = State{
+        bound, n, m: edge_count(g), dist: vec![Weight::MAX; n], touched: Vec::new(), heap: BinaryHeap::new(),
+        explored: Vec::new(), b_prime: Weight::MAX, edges_scanned: 0, heap_pushes: 0,
+    };
+    for &(s, d0) in sources {
+        if s < n && d0 < bound && d0 < st.dist[s] && !g.is_removed(s) { st.label(s, d0); }
+    }
+    search(g, st, interval, save)
+}
+
+/// Continue the search saved in `cp` on the same graph, checkpointing as `bmssp_checkpointed`
+/// does. Fails with `InvalidInput` if `g` has a different node or edge count.
+pub fn resume_from_checkpoint<G: GraphLike>(
+    g: &G,
+    cp: Checkpoint,
+    interval: Duration,
+    save: impl FnMut(&Checkpoint) -> io::Result<()>,
+) -> io::Result<BmsspResult> {
+    if cp.n != g.num_nodes() || cp.m != edge_count(g) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!(
+            "checkpoint is for a graph with n={} m={}, not n={} m={}", cp.n, cp.m, g.num_nodes(), edge_count(g))));
+    }
+    let mut dist = vec![Weight::MAX; cp.n];
+    for &(v, d) in &cp.labels { dist[v] = d; }
+    let st = State{
+        bound: cp.bound, n: cp.n, m: cp.m, dist,
+        touched: cp.labels.into_iter().map(|(v, _)| v).collect(),
+        heap: cp.heap.into_iter().map(|(d, v)| Reverse(Entry{ d, v })).collect(),
+        explored: cp.explored, b_prime: cp.b_prime, edges_scanned: cp.edges_scanned, heap_pushes: cp.heap_pushes,
+    };
+    search(g, st, interval, save)
+}
+
+fn search<G: GraphLike>(
+    g: &G,
+    mut st: State,
+    interval: Duration,
+    mut save: impl FnMut(&Checkpoint) -> io::Result<()>,
+) -> io::Result<BmsspResult> {
+    let bound = st.bound;
+    let mut last = Instant::now();
+    let mut next_check = st.explored.len() + CHECK_INTERVAL;
+    while let Some(&Reverse(Entry{ d, v })) = st.heap.peek() {
+        // Snapshot with the entry still queued, i.e. between two settles.
+        if st.explored.len() >= next_check {
+            next_check = st.explored.len() + CHECK_INTERVAL;
+            if last.elapsed() >= interval {
+                save(&st.snapshot())?;
+                last = Instant::now();
+            }
+        }
+        st.heap.pop();
+        if d != st.dist[v] { continue; }
+        st.explored.push(v);
+        for (to, w) in g.neighbors(v) {
+            st.edges_scanned += 1;
+            let nd = d.saturating_add(w);
+            if nd < st.dist[to] && nd < bound {
+                st.label(to, nd);
+                st.heap_pushes += 1;
+            } else if nd >= bound && nd < st.b_prime {
+                st.b_prime = nd;
+            }
+        }
+    }
+    Ok(BmsspResult{ dist: st.dist, explored: st.explored, b_prime: st.b_prime, edges_scanned: st.edges_scanned, heap_pushes: st.heap_pushes, ..Default::default() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use crate::generators::{make_grid, pick_sources};
+
+    #[test]
+    fn resumed_search_matches_uninterrupted() {
+        let g = make_grid(40, 40, 9, 3);
+        let sources = pick_sources(g.len(), 3, 5);
+        let expect = bounded_multi_source_shortest_paths(&g, &sources, 60);
+        // Simulate a preemption: keep the second checkpoint, then fail the save.
+        let mut saved = Vec::new();
+        let mut calls = 0;
+        let err = bmssp_checkpointed(&g, &sources, 60, Duration::ZERO, |cp| {
+            calls += 1;
+            if calls == 3 { return Err(io::Error::other("preempted")); }
+            saved.clear();
+            cp.write_binary(&mut saved)
+        });
+        assert!(err.is_err());
+        let cp = Checkpoint::read_binary(&saved[..]).unwrap();
+        assert_eq!((cp.bound(), cp.settled_count()), (60, 2 * CHECK_INTERVAL));
+        let mut more = 0;
+        let res = resume_from_checkpoint(&g, cp, Duration::ZERO, |_| { more += 1; Ok(()) }).unwrap();
+        assert!(more > 0);
+        assert_eq!((res.dist, res.explored, res.b_prime), (expect.dist, expect.explored, expect.b_prime));
+        assert_eq!((res.edges_scanned, res.heap_pushes), (expect.edges_scanned, expect.heap_pushes));
+        assert!(resume_from_checkpoint(&make_grid(40, 41, 9, 3), Checkpoint::read_binary(&saved[..]).unwrap(), Duration::ZERO, |_| Ok(())).is_err());
+        assert!(Checkpoint::read_binary(&saved[..saved.len() - 8]).is_err());
+    }
+}
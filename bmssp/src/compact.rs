@@ -0,0 +1,217 @@
+//! Compact CSR graph with `u32` node indices and `u32` weights.
+//! Half the per-edge footprint of `Graph` (8 bytes vs 16) and contiguous adjacency, at the cost
+//! of immutability and the < 2^32 node / weight limits.
+use crate::prefetch::PREFETCH_DISTANCE;
+use crate::{BmsspResult, Graph, GraphLike, Node, Weight};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io::{self, Read, Write};
+
+/// Leading bytes of the binary CSR format written by `CompactGraph::write_binary`.
+pub const CSR_MAGIC: &[u8; 8] = b"BMSSPCSR";
+const CSR_VERSION: u32 = 1;
+
+#[derive(Clone, Debug)]
+pub struct CompactGraph {
+    /// `offsets[u]..offsets[u+1]` indexes the out-edges of `u` in `targets` / `weights`.
+    pub offsets: Vec<usize>,
+    pub targets: Vec<u32>,
+    pub weights: Vec<u32>,
+}
+
+impl CompactGraph {
+    /// Convert from the adjacency-list graph. Returns `None` if a node id or weight doesn't fit in u32.
+    pub fn from_graph(g: &Graph) -> Option<Self> {
+        if g.len() > u32::MAX as usize { return None; }
+        let m = g.num_edges();
+        let mut offsets = Vec::with_capacity(g.len() + 1);
+        let mut targets = Vec::with_capacity(m);
+        let mut weights = Vec::with_capacity(m);
+        offsets.push(0);
+        for edges in &g.adj {
+            for &(v, w) in edges {
+                targets.push(u32::try_from(v).ok()?);
+                weights.push(u32::try_from(w).ok()?);
+            }
+            offsets.push(targets.len());
+        }
+        Some(Self { offsets, targets, weights })
+    }
+
+    pub fn len(&self) -> usize { self.offsets.len().saturating_sub(1) }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    pub fn num_edges(&self) -> usize { self.targets.len() }
+
+    #[inline]
+    pub fn neighbors(&self, u: Node) -> impl Iterator<Item = (Node, Weight)> + '_ {
+        let (a, b) = (self.offsets[u], self.offsets[u + 1]);
+        self.targets[a..b].iter().zip(&self.weights[a..b]).map(|(&v, &w)| (v as Node, w as Weight))
+    }
+
+    /// Same accounting as `Graph::memory_estimate_bytes`: graph arrays plus search working set.
+    pub fn memory_estimate_bytes(&self) -> usize {
+        let n = self.len();
+        let csr = self.offsets.len() * std::mem::size_of::<usize>()
+            + self.num_edges() * (2 * std::mem::size_of::<u32>());
+        let headers = 3 * 3 * std::mem::size_of::<usize>();
+        let dist_bytes = n * std::mem::size_of::<u64>();
+        let flags_bytes = n * std::mem::size_of::<u8>() * 2;
+        csr + headers + dist_bytes + flags_bytes
+    }
+
+    /// `memory_estimate_bytes` with the three CSR arrays at their allocated capacities.
+    pub fn memory_actual_bytes(&self) -> usize {
+        let n = self.len();
+        std::mem::size_of::<Self>() + self.offsets.capacity() * std::mem::size_of::<usize>()
+            + (self.targets.capacity() + self.weights.capacity()) * std::mem::size_of::<u32>()
+            + n * std::mem::size_of::<u64>() + n * std::mem::size_of::<u8>() * 2
+    }
+
+    pub fn shrink_to_fit_all(&mut self) {
+        self.offsets.shrink_to_fit();
+        self.targets.shrink_to_fit();
+        self.weights.shrink_to_fit();
+    }
+
+    /// Binary CSR: `CSR_MAGIC`, version and reserved `u32`s, `n` and `m` as `u64`, then
+    /// `n + 1` `u64` offsets, `m` `u32` targets and `m` `u32` weights, all little-endian.
+    /// Loading is a straight decode with no parsing, so a server can start on a large graph in
+    /// roughly the time it takes to read the file.
+    pub fn write_binary<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(CSR_MAGIC)?;
+        w.write_all(&CSR_VERSION.to_le_bytes())?;
+        w.write_all(&0u32.to_le_bytes())?;
+        w.write_all(&(self.len() as u64).to_le_bytes())?;
+        w.write_all(&(self.num_edges() as u64).to_le_bytes())?;
+        for &o in &self.offsets { w.write_all(&(o as u64).to_le_bytes())?; }
+        for &t in &self.targets { w.write_all(&t.to_le_bytes())?; }
+        for &x in &self.weights { w.write_all(&x.to_le_bytes())?; }
+        w.flush()
+    }
+
+    pub fn read_binary<R: Read>(mut r: R) -> io::Result<Self> {
+        let mut buf = Vec::new();
+        r.read_to_end(&mut buf)?;
+        Self::from_bytes(&buf)
+    }
+
+    /// Decode the `write_binary` format from memory (e.g. a mapped file).
+    pub fn from_bytes(b: &[u8]) -> io::Result<Self> {
+        let bad = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        if b.len() < 32 || &b[..8] != CSR_MAGIC { return Err(bad("not a BMSSPCSR file")); }
+        let u32_at = |i: usize| u32::from_le_bytes(b[i..i + 4].try_into().unwrap());
+        let u64_at = |i: usize| u64::from_le_bytes(b[i..i + 8].try_into().unwrap());
+        if u32_at(8) != CSR_VERSION { return Err(bad("unsupported BMSSPCSR version")); }
+        let (n, m) = (u64_at(16) as usize, u64_at(24) as usize);
+        let body = n.checked_add(1).and_then(|x| x.checked_mul(8))
+            .and_then(|x| m.checked_mul(8).and_then(|y| x.checked_add(y)))
+            .ok_or_else(|| bad("BMSSPCSR header overflows"))?;
+        if b.len() != 32 + body { return Err(bad("BMSSPCSR length does not match header")); }
+        let mut at = 32;
+        let offsets: Vec<usize> = (0..=n).map(|i| u64_at(at + 8 * i) as usize).collect();
+        at += 8 * (n + 1);
+        let targets: Vec<u32> = (0..m).map(|i| u32_at(at + 4 * i)).collect();
+        at += 4 * m;
+        let weights: Vec<u32> = (0..m).map(|i| u32_at(at + 4 * i)).collect();
+        if offsets[0] != 0 || offsets[n] != m || offsets.windows(2).any(|w| w[0] > w[1]) {
+            return Err(bad("BMSSPCSR offsets are not a valid CSR index"));
+        }
+        if targets.iter().any(|&t| t as usize >= n) { return Err(bad("BMSSPCSR edge target out of range")); }
+        Ok(Self { offsets, targets, weights })
+    }
+}
+
+/// `bounded_multi_source_shortest_paths` over a `CompactGraph`. Distances stay `u64` since
+/// sums of u32 weights can overflow u32; heap entries are `(u64, u32)`.
+pub fn bounded_multi_source_shortest_paths_compact(
+    g: &CompactGraph,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+) -> BmsspResult {
+    let n = g.len();
+    let mut dist = vec![Weight::MAX; n];
+    let mut heap: BinaryHeap<Reverse<(Weight, u32)>> = BinaryHeap::new();
+    let mut explored = Vec::<Node>::new();
+
+    for &(s, d0) in sources {
+        if s < n && d0 < bound && d0 < dist[s] {
+            dist[s] = d0;
+            heap.push(Reverse((d0, s as u32)));
+        }
+    }
+    let mut b_prime = Weight::MAX;
+    let mut edges_scanned: usize = 0;
+    let mut heap_pushes: usize = 0;
+
+    while let Some(Reverse((d, v))) = heap.pop() {
+        let v = v as usize;
+        if d != dist[v] { continue; }
+        if d >= bound { b_prime = d; break; }
+
+        explored.push(v);
+        let (a, b) = (g.offsets[v], g.offsets[v + 1]);
+        for (i, (&to, &w)) in g.targets[a..b].iter().zip(&g.weights[a..b]).enumerate() {
+            GraphLike::prefetch_edge_target(g, v, i + PREFETCH_DISTANCE, &dist);
+            edges_scanned += 1;
+            let nd = d.saturating_add(w as Weight);
+            let ti = to as usize;
+            if nd < dist[ti] && nd < bound {
+                dist[ti] = nd;
+                heap.push(Reverse((nd, to)));
+                heap_pushes += 1;
+            } else if nd >= bound && nd < b_prime {
+                b_prime = nd;
+            }
+        }
+    }
+
+    BmsspResult{ dist, explored, b_prime, edges_scanned, heap_pushes, ..Default::default() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+
+    #[test]
+    fn compact_matches_graph() {
+        let mut g = Graph::new(50);
+        for u in 0..50 {
+            g.add_edge(u, (u * 7 + 3) % 50, 1 + (u % 9) as u64);
+            g.add_edge(u, (u + 1) % 50, 4);
+        }
+        let cg = CompactGraph::from_graph(&g).unwrap();
+        assert_eq!(cg.num_edges(), g.num_edges());
+        assert!(cg.memory_estimate_bytes() < g.memory_estimate_bytes());
+        let sources = [(0, 0), (25, 2)];
+        let a = bounded_multi_source_shortest_paths(&g, &sources, 30);
+        let b = bounded_multi_source_shortest_paths_compact(&cg, &sources, 30);
+        assert_eq!(a.dist, b.dist);
+        assert_eq!(a.explored, b.explored);
+        assert_eq!(a.b_prime, b.b_prime);
+        assert_eq!((a.edges_scanned, a.heap_pushes), (b.edges_scanned, b.heap_pushes));
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let mut g = Graph::new(5);
+        g.add_edge(0, 1, 3);
+        g.add_edge(0, 4, 9);
+        g.add_edge(3, 2, 1);
+        let cg = CompactGraph::from_graph(&g).unwrap();
+        let mut buf = Vec::new();
+        cg.write_binary(&mut buf).unwrap();
+        let back = CompactGraph::read_binary(&buf[..]).unwrap();
+        assert_eq!((back.offsets, back.targets, back.weights), (cg.offsets, cg.targets, cg.weights));
+        assert!(CompactGraph::from_bytes(&buf[..buf.len() - 1]).is_err());
+        buf[32 + 8 * 6] = 7; // first target -> 7, out of range
+        assert!(CompactGraph::from_bytes(&buf).is_err());
+    }
+
+    #[test]
+    fn rejects_wide_weights() {
+        let mut g = Graph::new(2);
+        g.add_edge(0, 1, u32::MAX as u64 + 1);
+        assert!(CompactGraph::from_graph(&g).is_none());
+    }
+}
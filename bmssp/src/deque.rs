@@ -0,0 +1,172 @@
+//! Heap-free searches for graphs whose weights come from a tiny range (`--algo deque`): 0-1 BFS
+//! on a deque for weights in {0, 1}, and circular buckets (Dial) over a window of `max_w + 1`
+//! distances for weights up to `MAX_BUCKET_WEIGHT`. `GraphStats::small_weight_kernel` picks one.
+//! Distances, B' and the settled set match `bounded_multi_source_shortest_paths`; the buckets
+//! also settle in the same order, while 0-1 BFS settles ties at one distance in deque order.
+use std::collections::VecDeque;
+
+use crate::{BmsspResult, GraphLike, Node, Weight};
+
+/// Largest edge weight the bucket kernel is used for; above it the window of buckets stops
+/// paying for itself against a heap.
+pub const MAX_BUCKET_WEIGHT: Weight = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmallWeightKernel {
+    /// Every weight is 0 or 1.
+    ZeroOne,
+    /// Every weight is at most this (and at most `MAX_BUCKET_WEIGHT`).
+    Buckets(Weight),
+}
+
+impl SmallWeightKernel {
+    /// Kernel for weights in `min..=max`, or `None` if they are too wide for either.
+    pub fn for_weights(min: Weight, max: Weight) -> Option<Self> {
+        if min > max { return None; }
+        if max <= 1 { Some(Self::ZeroOne) } else if max <= MAX_BUCKET_WEIGHT { Some(Self::Buckets(max)) } else { None }
+    }
+}
+
+/// Sources in range, below the bound and not removed, as `(offset, node)` in increasing order.
+fn sorted_seeds<G: GraphLike>(g: &G, sources: &[(Node, Weight)], bound: Weight) -> Vec<(Weight, Node)> {
+    let n = g.num_nodes();
+    let mut seeds: Vec<(Weight, Node)> = sources.iter().filter(|&&(s, d0)| s < n && d0 < bound && !g.is_removed(s)).map(|&(s, d0)| (d0, s)).collect();
+    seeds.sort_unstable();
+    seeds
+}
+
+/// Run the kernel; the caller guarantees the weights fit it (see `for_weights`).
+pub fn bmssp_small_weights<G: GraphLike>(g: &G, sources: &[(Node, Weight)], bound: Weight, kernel: SmallWeightKernel) -> BmsspResult {
+    match kernel {
+        SmallWeightKernel::ZeroOne => bmssp_01bfs(g, sources, bound),
+        SmallWeightKernel::Buckets(max_w) => bmssp_buckets(g, sources, bound, max_w),
+    }
+}
+
+/// 0-1 BFS: weight-0 relaxations go to the front of the deque, weight-1 ones to the back, so
+/// it stays sorted without a heap.
+pub fn bmssp_01bfs<G: GraphLike>(g: &G, sources: &[(Node, Weight)], bound: Weight) -> BmsspResult {
+    let mut dist = vec![Weight::MAX; g.num_nodes()];
+    let seeds = sorted_seeds(g, sources, bound);
+    let mut next_seed = 0;
+    let mut deque: VecDeque<(Weight, Node)> = VecDeque::new();
+    let (mut explored, mut b_prime) = (Vec::new(), Weight::MAX);
+    let (mut edges_scanned, mut heap_pushes) = (0, 0);
+    loop {
+        // Seeds at or below the head's label go in front, keeping the deque sorted.
+        while let Some(&(d0, s)) = seeds.get(next_seed) {
+            if deque.front().is_some_and(|&(d, _)| d < d0) { break; }
+            next_seed += 1;
+            if d0 < dist[s] {
+                dist[s] = d0;
+                deque.push_front((d0, s));
+            }
+        }
+        let Some((d, v)) = deque.pop_front() else { break };
+        if d != dist[v] { continue; }
+        explored.push(v);
+        for (to, w) in g.neighbors(v) {
+            debug_assert!(w <= 1, "bmssp_01bfs needs weights in {{0, 1}}");
+            edges_scanned += 1;
+            let nd = d.saturating_add(w);
+            if nd < dist[to] && nd < bound {
+                dist[to] = nd;
+                if w == 0 { deque.push_front((nd, to)); } else { deque.push_back((nd, to)); }
+                heap_pushes += 1;
+            } else if nd >= bound && nd < b_prime {
+                b_prime = nd;
+            }
+        }
+    }
+    BmsspResult{ dist, explored, b_prime, edges_scanned, heap_pushes, ..Default::default() }
+}
+
+/// Dial's circular buckets: every queued label lies in `cur..=cur + max_w`, so bucket
+/// `d % (max_w + 1)` holds exactly the nodes at label `d`. Empty distances are stepped over one
+/// at a time, which is cheap while `max_w` is small. Each bucket is sorted when reached (and
+/// weight-0 relaxations are inserted in place), so `explored` comes out in `(dist, node)` order.
+pub fn bmssp_buckets<G: GraphLike>(g: &G, sources: &[(Node, Weight)], bound: Weight, max_w: Weight) -> BmsspResult {
+    let mut dist = vec![Weight::MAX; g.num_nodes()];
+    let seeds = sorted_seeds(g, sources, bound);
+    let width = max_w as usize + 1;
+    let mut buckets: Vec<Vec<Node>> = vec![Vec::new(); width];
+    let (mut next_seed, mut queued) = (0, 0usize);
+    let (mut explored, mut b_prime) = (Vec::new(), Weight::MAX);
+    let (mut edges_scanned, mut heap_pushes) = (0, 0);
+    let Some(&(mut cur, _)) = seeds.first() else { return BmsspResult{ dist, b_prime: Weight::MAX, ..Default::default() } };
+    loop {
+        while let Some(&(d0, s)) = seeds.get(next_seed) {
+            if d0 > cur { break; }
+            next_seed += 1;
+            if d0 < dist[s] {
+                dist[s] = d0;
+                buckets[(d0 % width as Weight) as usize].push(s);
+                queued += 1;
+            }
+        }
+        if queued == 0 {
+            // Nothing in the window: jump to the next seed, or finish.
+            match seeds.get(next_seed) { Some(&(d0, _)) => { cur = d0; continue; } None => break }
+        }
+        let slot = (cur % width as Weight) as usize;
+        buckets[slot].sort_unstable_by(|a, b| b.cmp(a));
+        while let Some(v) = buckets[slot].pop() {
+            queued -= 1;
+            if dist[v] != cur { continue; }
+            explored.push(v);
+            for (to, w) in g.neighbors(v) {
+                debug_assert!(w <= max_w, "bmssp_buckets needs weights <= max_w");
+                edges_scanned += 1;
+                let nd = cur.saturating_add(w);
+                if nd < dist[to] && nd < bound {
+                    dist[to] = nd;
+                    let b = &mut buckets[(nd % width as Weight) as usize];
+                    if nd == cur { b.insert(b.partition_point(|&u| u > to), to); } else { b.push(to); }
+                    queued += 1;
+                    heap_pushes += 1;
+                } else if nd >= bound && nd < b_prime {
+                    b_prime = nd;
+                }
+            }
+        }
+        cur += 1;
+    }
+    BmsspResult{ dist, explored, b_prime, edges_scanned, heap_pushes, ..Default::default() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use crate::Graph;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn random_graph(n: usize, m: usize, weights: &[Weight], seed: u64) -> Graph {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut g = Graph::new(n);
+        for _ in 0..m { g.add_edge(rng.gen_range(0..n), rng.gen_range(0..n), weights[rng.gen_range(0..weights.len())]); }
+        g
+    }
+
+    #[test]
+    fn small_weight_kernels_match_dijkstra() {
+        let sources = [(1, 0), (50, 3), (200, 1), (1, 2), (333, 9)];
+        for (weights, kernel) in [(&[0, 1][..], SmallWeightKernel::ZeroOne), (&[1, 2, 3], SmallWeightKernel::Buckets(3)), (&[0, 5, 7], SmallWeightKernel::Buckets(7))] {
+            let g = random_graph(400, 1600, weights, 3);
+            let stats = g.stats();
+            assert_eq!(stats.small_weight_kernel(), Some(kernel));
+            for bound in [0, 1, 4, 10, 25, Weight::MAX] {
+                let (a, b) = (bounded_multi_source_shortest_paths(&g, &sources, bound), bmssp_small_weights(&g, &sources, bound, kernel));
+                assert_eq!((&a.dist, a.b_prime), (&b.dist, b.b_prime), "{kernel:?} B={bound}");
+                let settled = |r: &BmsspResult| { let mut u = r.explored.clone(); u.sort_unstable(); u };
+                assert_eq!(settled(&a), settled(&b));
+                assert!(b.explored.windows(2).all(|p| b.dist[p[0]] <= b.dist[p[1]]));
+                assert_eq!(a.edges_scanned, b.edges_scanned);
+            }
+        }
+        // Buckets also handle 0/1 weights.
+        let g = random_graph(400, 1600, &[0, 1], 3);
+        assert_eq!(bmssp_buckets(&g, &sources, 25, 1).dist, bounded_multi_source_shortest_paths(&g, &sources, 25).dist);
+        assert_eq!(SmallWeightKernel::for_weights(1, MAX_BUCKET_WEIGHT + 1), None);
+    }
+}
@@ -0,0 +1,106 @@
+//! Comparing settled distance sets from two runs, typically the Rust oracle against a port.
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{BmsspResult, Node, Weight};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mismatch {
+    /// Settled in the expected output only.
+    Missing { node: Node, expected: Weight },
+    /// Settled in the actual output only.
+    Extra { node: Node, got: Weight },
+    WrongDistance { node: Node, expected: Weight, got: Weight },
+}
+
+impl Mismatch {
+    pub fn node(&self) -> Node {
+        match *self { Mismatch::Missing{ node, .. } | Mismatch::Extra{ node, .. } | Mismatch::WrongDistance{ node, .. } => node }
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Mismatch::Missing{ node, expected } => write!(f, "node {node}: missing (expected {expected})"),
+            Mismatch::Extra{ node, got } => write!(f, "node {node}: extra (got {got})"),
+            Mismatch::WrongDistance{ node, expected, got } => write!(f, "node {node}: expected {expected}, got {got}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResultDiff {
+    pub missing: usize,
+    pub extra: usize,
+    pub wrong: usize,
+    /// Largest `|expected - got|` over wrong-distance nodes.
+    pub max_abs_diff: Weight,
+    /// Up to `k` mismatches, smallest node ids first.
+    pub first: Vec<Mismatch>,
+}
+
+impl ResultDiff {
+    pub fn is_match(&self) -> bool { self.missing == 0 && self.extra == 0 && self.wrong == 0 }
+}
+
+impl fmt::Display for ResultDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_match() { return write!(f, "identical"); }
+        writeln!(f, "missing={} extra={} wrong={} max_abs_diff={}", self.missing, self.extra, self.wrong, self.max_abs_diff)?;
+        for m in &self.first { writeln!(f, "  {m}")?; }
+        Ok(())
+    }
+}
+
+/// Compare two sets of settled `(node, dist)` pairs; order doesn't matter. Duplicate nodes keep
+/// their last entry.
+pub fn compare_results(expected: &[(Node, Weight)], actual: &[(Node, Weight)], k: usize) -> ResultDiff {
+    let exp: HashMap<Node, Weight> = expected.iter().copied().collect();
+    let act: HashMap<Node, Weight> = actual.iter().copied().collect();
+    let mut out = ResultDiff::default();
+    let mut all = Vec::new();
+    for (&node, &e) in &exp {
+        match act.get(&node) {
+            None => { out.missing += 1; all.push(Mismatch::Missing{ node, expected: e }); }
+            Some(&g) if g != e => {
+                out.wrong += 1;
+                out.max_abs_diff = out.max_abs_diff.max(e.abs_diff(g));
+                all.push(Mismatch::WrongDistance{ node, expected: e, got: g });
+            }
+            Some(_) => {}
+        }
+    }
+    for (&node, &g) in &act {
+        if !exp.contains_key(&node) { out.extra += 1; all.push(Mismatch::Extra{ node, got: g }); }
+    }
+    all.sort_unstable_by_key(Mismatch::node);
+    all.truncate(k);
+    out.first = all;
+    out
+}
+
+impl BmsspResult {
+    /// Settled `(node, dist)` pairs in settle order.
+    pub fn settled_pairs(&self) -> Vec<(Node, Weight)> { self.explored.iter().map(|&v| (v, self.dist[v])).collect() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_each_error_kind() {
+        let a = [(0, 0), (1, 5), (2, 9), (3, 4)];
+        let b = [(3, 4), (0, 0), (1, 6), (7, 1), (2, 2)];
+        let d = compare_results(&a, &b, 2);
+        assert_eq!((d.missing, d.extra, d.wrong, d.max_abs_diff), (0, 1, 2, 7));
+        assert_eq!(d.first, vec![
+            Mismatch::WrongDistance{ node: 1, expected: 5, got: 6 },
+            Mismatch::WrongDistance{ node: 2, expected: 9, got: 2 },
+        ]);
+        let d = compare_results(&a, &a[..2], 10);
+        assert_eq!((d.missing, d.first.len()), (2, 2));
+        assert!(compare_results(&a, &a, 10).is_match());
+    }
+}
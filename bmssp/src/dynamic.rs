@@ -0,0 +1,99 @@
+//! Incremental maintenance of a previous bounded search after the graph changes.
+use crate::{settle_order_and_boundary, BmsspResult, Entry, Graph, Node, Weight};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// When more than `explored / REPAIR_FALLBACK_RATIO` edges changed, local repair is unlikely to beat
+/// re-relaxing everything, so we reseed from every settled label instead.
+const REPAIR_FALLBACK_RATIO: usize = 8;
+
+/// Repair `prev` after the weights of `changed_edges` (pairs `(u, v)`) were decreased in `g`.
+/// Only nodes whose distance improves are re-settled; the counters in the returned result count
+/// the repair work alone. Because distances can only shrink, the old labels are valid upper bounds,
+/// so the fallback reseeds from all of them and needs no source list.
+pub fn repair_after_decrease(
+    g: &Graph,
+    prev: &BmsspResult,
+    changed_edges: &[(Node, Node)],
+    bound: Weight,
+) -> BmsspResult {
+    let n = g.len();
+    let mut dist = prev.dist.clone();
+    dist.resize(n, Weight::MAX);
+    let mut heap: BinaryHeap<Reverse<Entry>> = BinaryHeap::new();
+    let mut edges_scanned: usize = 0;
+    let mut heap_pushes: usize = 0;
+
+    if changed_edges.len() * REPAIR_FALLBACK_RATIO > prev.explored.len().max(1) {
+        for &v in &prev.explored { heap.push(Reverse(Entry{ d: dist[v], v })); }
+    } else {
+        for &(u, v) in changed_edges {
+            if u >= n || dist[u] >= bound { continue; }
+            let du = dist[u];
+            for &(to, w) in g.adj[u].iter().filter(|e| e.0 == v) {
+                edges_scanned += 1;
+                let nd = du.saturating_add(w);
+                if nd < dist[to] && nd < bound {
+                    dist[to] = nd;
+                    heap.push(Reverse(Entry{ d: nd, v: to }));
+                    heap_pushes += 1;
+                }
+            }
+        }
+    }
+
+    while let Some(Reverse(Entry{ d, v })) = heap.pop() {
+        if d != dist[v] { continue; }
+        for &(to, w) in &g.adj[v] {
+            edges_scanned += 1;
+            let nd = d.saturating_add(w);
+            if nd < dist[to] && nd < bound {
+                dist[to] = nd;
+                heap.push(Reverse(Entry{ d: nd, v: to }));
+                heap_pushes += 1;
+            }
+        }
+    }
+
+    // B' is recomputed from scratch: an improved label can pull a former boundary candidate
+    // below the bound, so the old B' may no longer exist.
+    let (explored, b_prime) = settle_order_and_boundary(g, &dist, bound);
+
+    BmsspResult{ dist, explored, b_prime, edges_scanned, heap_pushes, ..Default::default() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use crate::generators::make_grid;
+
+    #[test]
+    fn repair_matches_rerun() {
+        let mut g = make_grid(12, 12, 5, 1);
+        let sources = [(0, 0), (77, 3)];
+        let bound = 25;
+        let prev = bounded_multi_source_shortest_paths(&g, &sources, bound);
+        g.update_edge_weight(5, 6, 0);
+        g.update_edge_weight(40, 52, 0);
+        let fixed = repair_after_decrease(&g, &prev, &[(5, 6), (40, 52)], bound);
+        let fresh = bounded_multi_source_shortest_paths(&g, &sources, bound);
+        assert_eq!(fixed.dist, fresh.dist);
+        assert_eq!(fixed.explored, fresh.explored);
+        assert_eq!(fixed.b_prime, fresh.b_prime);
+    }
+
+    #[test]
+    fn repair_fallback_matches_rerun() {
+        let mut g = make_grid(6, 6, 5, 1);
+        let sources = [(14, 0)];
+        let bound = 9;
+        let prev = bounded_multi_source_shortest_paths(&g, &sources, bound);
+        let changed: Vec<(Node, Node)> = (0..35).filter(|v| v % 6 != 5).map(|v| (v, v + 1)).collect();
+        for &(u, v) in &changed { g.update_edge_weight(u, v, 1); }
+        let fixed = repair_after_decrease(&g, &prev, &changed, bound);
+        let fresh = bounded_multi_source_shortest_paths(&g, &sources, bound);
+        assert_eq!(fixed.dist, fresh.dist);
+        assert_eq!(fixed.b_prime, fresh.b_prime);
+    }
+}
@@ -0,0 +1,151 @@
+//! Per-edge IDs and attributes kept beside a graph instead of inside it: `EdgeData<T>` holds
+//! one value per adjacency slot, `values[u][i]` for the `i`-th out-edge of `u`. Searches with
+//! `BmsspOptions::track_predecessors` record the slot of each tree edge in
+//! `BmsspResult::pred_slot`, so a reconstructed path maps back to original road segments, OSM
+//! way ids, road classes or whatever the caller attached. `Graph::with_weight_fn` searches under
+//! a cost model computed from those attributes on the fly, without copying the graph.
+use crate::{BmsspResult, Graph, GraphLike, Node, Weight};
+
+/// Stable edge identifier, e.g. the line of the input file the edge came from.
+pub type EdgeId = u64;
+
+/// Values parallel to a graph's adjacency lists. Edits that reorder or drop edges
+/// (`dedup_min`, `remove_edge`, `remove_node`, ...) are not mirrored; rebuild or check
+/// `is_aligned` afterwards.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdgeData<T> {
+    values: Vec<Vec<T>>,
+}
+
+impl<T> EdgeData<T> {
+    /// Empty lists for `n` nodes; `push` alongside every `Graph::add_edge`.
+    pub fn new(n: usize) -> Self { Self{ values: (0..n).map(|_| Vec::new()).collect() } }
+
+    /// `f(u, slot, v, w)` for every edge of `g`.
+    pub fn from_fn(g: &Graph, mut f: impl FnMut(Node, usize, Node, Weight) -> T) -> Self {
+        Self{ values: g.adj.iter().enumerate().map(|(u, edges)| edges.iter().enumerate().map(|(i, &(v, w))| f(u, i, v, w)).collect()).collect() }
+    }
+
+    /// Value for the next edge added out of `u`; grows the node range if needed.
+    pub fn push(&mut self, u: Node, value: T) {
+        if u >= self.values.len() { self.values.resize_with(u + 1, Vec::new); }
+        self.values[u].push(value);
+    }
+
+    pub fn get(&self, u: Node, slot: usize) -> Option<&T> { self.values.get(u)?.get(slot) }
+
+    pub fn get_mut(&mut self, u: Node, slot: usize) -> Option<&mut T> { self.values.get_mut(u)?.get_mut(slot) }
+
+    /// Whether every node has exactly one value per out-edge of `g`.
+    pub fn is_aligned(&self, g: &Graph) -> bool {
+        self.values.len() == g.len() && self.values.iter().zip(&g.adj).all(|(a, b)| a.len() == b.len())
+    }
+
+    /// Values of the tree edges from the root to `t`, in path order; `None` if `t` wasn't
+    /// settled or the search didn't track predecessors.
+    pub fn along_path(&self, res: &BmsspResult, t: Node) -> Option<Vec<&T>> {
+        res.path_edges_to(t)?.into_iter().map(|(u, slot)| self.get(u, slot)).collect()
+    }
+}
+
+impl EdgeData<EdgeId> {
+    /// Ids `0..m` in adjacency order (node by node, slot by slot).
+    pub fn sequential(g: &Graph) -> Self {
+        let mut next = 0;
+        Self::from_fn(g, |_, _, _, _| { next += 1; next - 1 })
+    }
+}
+
+/// `g` with every edge weight replaced by `f(u, v, w, attr)`; see `Graph::with_weight_fn`.
+pub struct WeightFnView<'g, T, F> {
+    g: &'g Graph,
+    attrs: &'g EdgeData<T>,
+    f: F,
+}
+
+impl Graph {
+    /// View whose edge `u -> v` of stored weight `w` and attribute `attrs[u][slot]` costs
+    /// `f(u, v, w, attr)`, e.g. travel time from length and road class. `f` runs on every edge
+    /// scan, so keep it cheap, or `materialize` a model that is searched many times. Panics if
+    /// `attrs` is not aligned with `self`; `EdgeData::<()>::from_fn(g, |..| ())` stands in when
+    /// the cost needs no attributes.
+    pub fn with_weight_fn<'g, T, F: Fn(Node, Node, Weight, &T) -> Weight>(&'g self, attrs: &'g EdgeData<T>, f: F) -> WeightFnView<'g, T, F> {
+        assert!(attrs.is_aligned(self), "with_weight_fn needs one attribute per edge");
+        WeightFnView{ g: self, attrs, f }
+    }
+}
+
+impl<T, F: Fn(Node, Node, Weight, &T) -> Weight> WeightFnView<'_, T, F> {
+    /// Copy of the graph with the computed weights stored.
+    pub fn materialize(&self) -> Graph {
+        let mut out = self.g.clone();
+        for (u, edges) in out.adj.iter_mut().enumerate() {
+            for (e, a) in edges.iter_mut().zip(&self.attrs.values[u]) { e.1 = (self.f)(u, e.0, e.1, a); }
+        }
+        out
+    }
+}
+
+impl<T, F: Fn(Node, Node, Weight, &T) -> Weight> GraphLike for WeightFnView<'_, T, F> {
+    #[inline]
+    fn num_nodes(&self) -> usize { self.g.len() }
+    #[inline]
+    fn neighbors(&self, u: Node) -> impl Iterator<Item = (Node, Weight)> + '_ {
+        self.g.adj[u].iter().zip(&self.attrs.values[u]).map(move |(&(v, w), a)| (v, (self.f)(u, v, w, a)))
+    }
+    #[inline]
+    fn is_removed(&self, u: Node) -> bool { self.g.is_removed(u) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bmssp_with_options, bounded_multi_source_shortest_paths, BmsspOptions};
+    use crate::generators::make_grid;
+
+    #[test]
+    fn paths_map_back_to_edge_ids() {
+        // Two parallel 0 -> 1 edges; the search must report the cheaper one's id.
+        let mut g = Graph::new(4);
+        let mut ids = EdgeData::new(4);
+        for (u, v, w, id) in [(0, 1, 5, 100), (0, 1, 2, 101), (1, 2, 1, 102), (0, 2, 9, 103), (2, 3, 1, 104)] {
+            g.add_edge(u, v, w);
+            ids.push(u, id);
+        }
+        assert!(ids.is_aligned(&g));
+        let opts = BmsspOptions{ track_predecessors: true, ..Default::default() };
+        let r = bmssp_with_options(&g, &[(0, 0)], 100, &opts);
+        assert_eq!(r.path_edges_to(3), Some(vec![(0, 1), (1, 0), (2, 0)]));
+        assert_eq!(ids.along_path(&r, 3), Some(vec![&101, &102, &104]));
+        assert_eq!(ids.along_path(&r, 0), Some(vec![]));
+        let hop = bmssp_with_options(&g, &[(0, 0)], 100, &BmsspOptions{ max_hops: Some(1), ..opts });
+        assert_eq!(ids.along_path(&hop, 2), Some(vec![&103]));
+        assert_eq!(ids.along_path(&bmssp_with_options(&g, &[(0, 0)], 100, &BmsspOptions::default()), 3), None);
+        let seq = EdgeData::sequential(&g);
+        assert_eq!((seq.get(0, 2), seq.get(2, 0), seq.get(3, 0)), (Some(&2), Some(&4), None));
+        g.add_edge(3, 0, 1);
+        assert!(!ids.is_aligned(&g));
+    }
+
+    #[test]
+    fn weight_fn_view_matches_the_rebuilt_graph() {
+        // Lengths in metres, road class 0..3 with speeds in m/s; cost is seconds.
+        let g = make_grid(30, 30, 500, 8);
+        let class = EdgeData::from_fn(&g, |u, _, v, _| ((u + v) % 3) as u8);
+        let speed = [30, 20, 8];
+        let time = g.with_weight_fn(&class, |_, _, len, &c| len / speed[c as usize] + 1);
+        let rebuilt = time.materialize();
+        assert_eq!(rebuilt.num_edges(), g.num_edges());
+        for bound in [10, 100, Weight::MAX] {
+            let (a, b) = (bounded_multi_source_shortest_paths(&time, &[(0, 0), (450, 3)], bound), bounded_multi_source_shortest_paths(&rebuilt, &[(0, 0), (450, 3)], bound));
+            assert_eq!((a.dist, a.explored, a.b_prime), (b.dist, b.explored, b.b_prime));
+        }
+        // Slots still line up, so paths found under the view map back to the attributes.
+        let r = bmssp_with_options(&time, &[(0, 0)], Weight::MAX, &BmsspOptions{ track_predecessors: true, ..Default::default() });
+        let classes = class.along_path(&r, 899).unwrap();
+        assert_eq!(classes.len() + 1, r.path_to(899).unwrap().len());
+        let unit = EdgeData::<()>::from_fn(&g, |_, _, _, _| ());
+        assert_eq!(bounded_multi_source_shortest_paths(&g.with_weight_fn(&unit, |_, _, _, _| 1), &[(0, 0)], Weight::MAX).dist[899], 58);
+    }
+}
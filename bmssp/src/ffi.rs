@@ -0,0 +1,177 @@
+//! C ABI for the benchmark harness: other language entries link `libbmssp` and use it as a
+//! correctness oracle. The header is `include/bmssp.h`, regenerated by `build.rs` (cbindgen)
+//! whenever the `ffi` feature is built.
+//!
+//! Handles are opaque and owned by the caller once returned; release them with the matching
+//! `*_free`. Functions taking a handle accept NULL and report it through their return value.
+use std::ptr;
+use std::slice;
+
+use crate::{bounded_multi_source_shortest_paths, BmsspResult, Graph, Node};
+
+/// Opaque graph handle.
+pub struct BmsspGraph(Graph);
+
+/// Distance reported for nodes not settled below the bound.
+pub const BMSSP_UNREACHED: u64 = u64::MAX;
+
+/// New graph with `n` nodes and no edges.
+#[no_mangle]
+pub extern "C" fn bmssp_graph_new(n: usize) -> *mut BmsspGraph {
+    Box::into_raw(Box::new(BmsspGraph(Graph::new(n))))
+}
+
+/// # Safety
+/// `g` must be NULL or a handle from `bmssp_graph_new` not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_graph_free(g: *mut BmsspGraph) {
+    if !g.is_null() { drop(Box::from_raw(g)); }
+}
+
+/// Node count, 0 for NULL.
+///
+/// # Safety
+/// `g` must be NULL or a live graph handle.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_graph_num_nodes(g: *const BmsspGraph) -> usize {
+    g.as_ref().map_or(0, |g| g.0.len())
+}
+
+/// Adds the directed edge `u -> v`. Returns 0 on success, -1 for NULL or out-of-range nodes.
+///
+/// # Safety
+/// `g` must be NULL or a live graph handle.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_add_edge(g: *mut BmsspGraph, u: usize, v: usize, w: u64) -> i32 {
+    let Some(g) = g.as_mut() else { return -1 };
+    let n = g.0.len();
+    if u >= n || v >= n { return -1; }
+    g.0.add_edge(u, v, w);
+    0
+}
+
+/// Runs `bounded_multi_source_shortest_paths` from `k` sources. `source_dists` may be NULL,
+/// meaning every source starts at 0. Returns NULL for a NULL graph or an out-of-range source.
+///
+/// # Safety
+/// `g` must be NULL or a live graph handle; `sources` (and `source_dists` unless NULL) must
+/// point to `k` readable elements unless `k` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_run(
+    g: *const BmsspGraph,
+    sources: *const usize,
+    source_dists: *const u64,
+    k: usize,
+    bound: u64,
+) -> *mut BmsspResult {
+    let Some(g) = g.as_ref() else { return ptr::null_mut() };
+    let nodes: &[Node] = if k == 0 { &[] } else { slice::from_raw_parts(sources, k) };
+    let dists: &[u64] = if k == 0 || source_dists.is_null() { &[] } else { slice::from_raw_parts(source_dists, k) };
+    if nodes.iter().any(|&s| s >= g.0.len()) { return ptr::null_mut(); }
+    let srcs: Vec<(Node, u64)> = nodes.iter().enumerate().map(|(i, &s)| (s, dists.get(i).copied().unwrap_or(0))).collect();
+    Box::into_raw(Box::new(bounded_multi_source_shortest_paths(&g.0, &srcs, bound)))
+}
+
+/// # Safety
+/// `r` must be NULL or a handle from `bmssp_run` not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_result_free(r: *mut BmsspResult) {
+    if !r.is_null() { drop(Box::from_raw(r)); }
+}
+
+/// Length of the distance array (the graph's node count), 0 for NULL.
+///
+/// # Safety
+/// `r` must be NULL or a live result handle.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_result_num_nodes(r: *const BmsspResult) -> usize {
+    r.as_ref().map_or(0, |r| r.dist.len())
+}
+
+/// Distance to `v`, or `BMSSP_UNREACHED` if `v` was not settled (or is out of range).
+///
+/// # Safety
+/// `r` must be NULL or a live result handle.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_result_dist(r: *const BmsspResult, v: usize) -> u64 {
+    r.as_ref().and_then(|r| r.dist.get(v).copied()).unwrap_or(BMSSP_UNREACHED)
+}
+
+/// Borrowed view of all distances (`bmssp_result_num_nodes` entries), valid until the result
+/// is freed.
+///
+/// # Safety
+/// `r` must be NULL or a live result handle.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_result_dists(r: *const BmsspResult) -> *const u64 {
+    r.as_ref().map_or(ptr::null(), |r| r.dist.as_ptr())
+}
+
+/// Number of settled nodes.
+///
+/// # Safety
+/// `r` must be NULL or a live result handle.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_result_explored_len(r: *const BmsspResult) -> usize {
+    r.as_ref().map_or(0, |r| r.explored.len())
+}
+
+/// Settled nodes in (distance, node) order, `bmssp_result_explored_len` entries, valid until
+/// the result is freed.
+///
+/// # Safety
+/// `r` must be NULL or a live result handle.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_result_explored(r: *const BmsspResult) -> *const usize {
+    r.as_ref().map_or(ptr::null(), |r| r.explored.as_ptr())
+}
+
+/// Tight boundary B'; `BMSSP_UNREACHED` when nothing was cut off by the bound.
+///
+/// # Safety
+/// `r` must be NULL or a live result handle.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_result_b_prime(r: *const BmsspResult) -> u64 {
+    r.as_ref().map_or(BMSSP_UNREACHED, |r| r.b_prime)
+}
+
+/// # Safety
+/// `r` must be NULL or a live result handle.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_result_edges_scanned(r: *const BmsspResult) -> usize {
+    r.as_ref().map_or(0, |r| r.edges_scanned)
+}
+
+/// # Safety
+/// `r` must be NULL or a live result handle.
+#[no_mangle]
+pub unsafe extern "C" fn bmssp_result_heap_pushes(r: *const BmsspResult) -> usize {
+    r.as_ref().map_or(0, |r| r.heap_pushes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_through_c_abi() {
+        unsafe {
+            let g = bmssp_graph_new(4);
+            assert_eq!(bmssp_add_edge(g, 0, 1, 2), 0);
+            assert_eq!(bmssp_add_edge(g, 1, 2, 3), 0);
+            assert_eq!(bmssp_add_edge(g, 2, 3, 10), 0);
+            assert_eq!(bmssp_add_edge(g, 0, 9, 1), -1);
+            let srcs = [0usize];
+            let r = bmssp_run(g, srcs.as_ptr(), ptr::null(), 1, 8);
+            assert!(!r.is_null());
+            assert_eq!(bmssp_result_dist(r, 2), 5);
+            assert_eq!(bmssp_result_dist(r, 3), BMSSP_UNREACHED);
+            assert_eq!(slice::from_raw_parts(bmssp_result_explored(r), bmssp_result_explored_len(r)), &[0, 1, 2]);
+            assert_eq!(bmssp_result_b_prime(r), 15);
+            assert!(bmssp_run(g, [7usize].as_ptr(), ptr::null(), 1, 8).is_null());
+            bmssp_result_free(r);
+            bmssp_graph_free(g);
+            assert!(bmssp_run(ptr::null(), srcs.as_ptr(), ptr::null(), 1, 8).is_null());
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Readers for graph formats that public benchmark collections ship in. All of them produce a
+//! directed `Graph` with 0-based node ids; the native edge list lives in `io`.
+//!
+//! METIS: `n m [fmt [ncon]]` header, then line `i` lists node `i`'s neighbors (1-based), as
+//! `v w` pairs when `fmt` has the edge-weight digit; `%` lines are comments. An empty line is
+//! a node without neighbors. Each undirected edge is listed from both ends, so every line
+//! becomes that node's out-edges.
+//! MatrixMarket: `coordinate` matrices; entry `(i, j, v)` is the edge `i -> j` with weight `v`
+//! (`pattern` matrices get weight 1, `symmetric` ones both directions).
+//! DIMACS (9th challenge `.gr`): `c` comments, a `p sp n m` line, then `a u v w` arcs (1-based).
+//! GraphML: `<node>`s numbered in document order; an edge's weight is its `<data>` for the
+//! edge key named `weight` (else that key's default, else 1). Undirected edges go both ways.
+//! OSM PBF: decoded by `osm::read_pbf` when built with the `osm` feature.
+//!
+//! `load_graph_file` picks the reader from the content (or the extension when the content is
+//! ambiguous), so a DIMACS or METIS header is never read as an edge.
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read};
+use std::path::Path;
+
+use crate::{io as native, Graph, Weight};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// The native `n m` + `u v w` edge list (`io::read_edge_list`).
+    EdgeList,
+    Dimacs,
+    Metis,
+    MatrixMarket,
+    GraphMl,
+    OsmPbf,
+}
+
+impl GraphFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GraphFormat::EdgeList => "edges",
+            GraphFormat::Dimacs => "dimacs",
+            GraphFormat::Metis => "metis",
+            GraphFormat::MatrixMarket => "mtx",
+            GraphFormat::GraphMl => "graphml",
+            GraphFormat::OsmPbf => "osm",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        [GraphFormat::EdgeList, GraphFormat::Dimacs, GraphFormat::Metis, GraphFormat::MatrixMarket, GraphFormat::GraphMl, GraphFormat::OsmPbf]
+            .into_iter().find(|f| f.as_str() == s)
+    }
+
+    /// From the file extension, ignoring a trailing `.gz` / `.zst`.
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        let mut p = path.to_path_buf();
+        if matches!(p.extension().and_then(|e| e.to_str()), Some("gz" | "zst")) { p.set_extension(""); }
+        match p.extension()?.to_str()? {
+            "gr" | "dimacs" => Some(GraphFormat::Dimacs),
+            "graph" | "metis" => Some(GraphFormat::Metis),
+            "mtx" => Some(GraphFormat::MatrixMarket),
+            "graphml" => Some(GraphFormat::GraphMl),
+            "pbf" => Some(GraphFormat::OsmPbf),
+            _ => None,
+        }
+    }
+
+    /// Format with an unmistakable marker in the first bytes (XML, `%%MatrixMarket`, DIMACS
+    /// `c`/`p` lines, METIS `%` comments or a 3-4 field header), else `None`.
+    pub fn sniff(head: &[u8]) -> Option<Self> {
+        // A PBF file opens with a 4-byte length and the `OSMHeader` blob header.
+        if head.get(4..head.len().min(24)).is_some_and(|h| h.windows(9).any(|w| w == b"OSMHeader")) { return Some(GraphFormat::OsmPbf); }
+        let text = String::from_utf8_lossy(head);
+        let t = text.trim_start();
+        if t.starts_with("<?xml") || t.starts_with("<graphml") { return Some(GraphFormat::GraphMl); }
+        if t.starts_with("%%MatrixMarket") { return Some(GraphFormat::MatrixMarket); }
+        let first = t.lines().next().unwrap_or("");
+        if first.starts_with('%') { return Some(GraphFormat::Metis); }
+        if first.starts_with("c ") || first == "c" || first.starts_with("p ") { return Some(GraphFormat::Dimacs); }
+        let header: Vec<&str> = first.split_whitespace().collect();
+        if (3..=4).contains(&header.len()) && header.iter().all(|x| x.parse::<u64>().is_ok()) { return Some(GraphFormat::Metis); }
+        None
+    }
+
+    /// `sniff`, then the extension, then the first data lines: three fields on every line is
+    /// the native edge list, anything else under an `n m` header is METIS. Pass the format
+    /// explicitly for METIS files whose first nodes all have exactly three neighbors.
+    pub fn detect(path: Option<&Path>, head: &[u8]) -> Self {
+        if let Some(f) = Self::sniff(head).or_else(|| path.and_then(Self::from_extension)) { return f; }
+        let text = String::from_utf8_lossy(head);
+        let mut lines: Vec<&str> = text.lines().skip(1).filter(|l| !l.trim().is_empty()).collect();
+        // `head` is a buffer's worth, so its last line may be cut mid-way.
+        if !text.ends_with('\n') && lines.len() > 1 { lines.pop(); }
+        lines.truncate(64);
+        if lines.iter().all(|l| l.split_whitespace().count() == 3) { GraphFormat::EdgeList } else { GraphFormat::Metis }
+    }
+}
+
+/// Read `r` as `format`.
+pub fn read_graph<R: BufRead>(mut r: R, format: GraphFormat) -> io::Result<Graph> {
+    match format {
+        GraphFormat::EdgeList => native::read_edge_list(r),
+        GraphFormat::Dimacs => read_dimacs(r),
+        GraphFormat::Metis => read_metis(r),
+        GraphFormat::MatrixMarket => read_matrix_market(r),
+        GraphFormat::GraphMl => { let mut s = String::new(); r.read_to_string(&mut s)?; read_graphml(&s) }
+        #[cfg(feature = "osm")]
+        GraphFormat::OsmPbf => crate::osm::read_pbf(r).map(|o| o.graph),
+        #[cfg(not(feature = "osm"))]
+        GraphFormat::OsmPbf => Err(io::Error::new(io::ErrorKind::Unsupported, "OSM PBF input needs the `osm` feature")),
+    }
+}
+
+/// Open `path` (compressed or `-` as in `io::open_reader`) and read it as `format`, or as the
+/// detected format when `None`. Edge lists use `io::read_edge_list_parallel` if `threads > 1`.
+pub fn load_graph_file(path: &Path, format: Option<GraphFormat>, threads: usize) -> io::Result<(Graph, GraphFormat)> {
+    let _span = telemetry_span!(INFO, "bmssp.load_graph", path = %path.display(), threads);
+    let mut r = native::open_reader(path)?;
+    let format = match format { Some(f) => f, None => GraphFormat::detect(Some(path), r.fill_buf()?) };
+    let g = if format == GraphFormat::EdgeList && threads > 1 {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+        native::read_edge_list_parallel(&bytes, threads)?
+    } else {
+        read_graph(r, format)?
+    };
+    telemetry_event!(INFO, n = g.len(), m = g.num_edges(), format = format.as_str(), "graph loaded");
+    Ok((g, format))
+}
+
+fn bad(line: usize, what: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {line}: {what}"))
+}
+
+/// Unweighted METIS graphs get weight 1; vertex weights and sizes are skipped.
+pub fn read_metis<R: BufRead>(r: R) -> io::Result<Graph> {
+    let mut lines = r.lines().enumerate().map(|(i, l)| l.map(|l| (i + 1, l)));
+    let mut next_line = || -> io::Result<Option<(usize, String)>> {
+        for l in lines.by_ref() {
+            let (i, l) = l?;
+            if !l.trim_start().starts_with('%') { return Ok(Some((i, l))); }
+        }
+        Ok(None)
+    };
+    let (hl, header) = next_line()?.ok_or_else(|| bad(1, "missing METIS header"))?;
+    let h: Vec<&str> = header.split_whitespace().collect();
+    let num = |i: usize| h.get(i).map(|t| t.parse::<usize>().map_err(|_| bad(hl, "expected `n m [fmt [ncon]]`"))).transpose();
+    let n = num(0)?.ok_or_else(|| bad(hl, "expected `n m [fmt [ncon]]`"))?;
+    let fmt = h.get(2).copied().unwrap_or("0");
+    if fmt.len() > 3 || !fmt.bytes().all(|c| c == b'0' || c == b'1') { return Err(bad(hl, format!("bad fmt {fmt:?}"))); }
+    let digit = |k: usize| fmt.len() > k && fmt.as_bytes()[fmt.len() - 1 - k] == b'1';
+    let (edge_w, vertex_w, vertex_size) = (digit(0), digit(1), digit(2));
+    let skip = usize::from(vertex_size) + if vertex_w { num(3)?.unwrap_or(1) } else { 0 };
+    let mut g = Graph::new(n);
+    for u in 0..n {
+        let Some((i, line)) = next_line()? else { break };
+        let mut toks = line.split_whitespace().skip(skip).map(|t| t.parse::<u64>().map_err(|_| bad(i, format!("bad number {t:?}"))));
+        while let Some(v) = toks.next() {
+            let v = v? as usize;
+            if v == 0 || v > n { return Err(bad(i, format!("neighbor {v} outside 1..={n}"))); }
+            let w = if edge_w { toks.next().ok_or_else(|| bad(i, "neighbor without a weight"))?? } else { 1 };
+            g.add_edge(u, v - 1, w);
+        }
+    }
+    Ok(g)
+}
+
+/// Arcs before the `p` line, or ids outside `1..=n`, are errors.
+pub fn read_dimacs<R: BufRead>(r: R) -> io::Result<Graph> {
+    let mut g: Option<Graph> = None;
+    for (i, line) in r.lines().enumerate() {
+        let (i, line) = (i + 1, line?);
+        let t: Vec<&str> = line.split_whitespace().collect();
+        match t.first().copied() {
+            None | Some("c") => {}
+            Some("p") => {
+                let n = t.get(2).and_then(|x| x.parse().ok()).ok_or_else(|| bad(i, "expected `p sp n m`"))?;
+                g = Some(Graph::new(n));
+            }
+            Some("a") => {
+                let graph = g.as_mut().ok_or_else(|| bad(i, "arc before the `p` line"))?;
+                let n = graph.len();
+                let num = |k: usize| t.get(k).and_then(|x| x.parse::<u64>().ok());
+                match (num(1), num(2), num(3)) {
+                    (Some(u), Some(v), Some(w)) if (1..=n as u64).contains(&u) && (1..=n as u64).contains(&v) => graph.add_edge(u as usize - 1, v as usize - 1, w),
+                    _ => return Err(bad(i, format!("expected `a u v w` with u, v in 1..={n}"))),
+                }
+            }
+            Some(other) => return Err(bad(i, format!("unknown DIMACS line type {other:?}"))),
+        }
+    }
+    g.ok_or_else(|| bad(1, "missing `p sp n m` line"))
+}
+
+/// Only `<node>`, `<edge>`, `<key>` and `<data>` are read; nested graphs and hyperedges are not
+/// supported. Weights are rounded like MatrixMarket values.
+pub fn read_graphml(text: &str) -> io::Result<Graph> {
+    let doc = roxmltree::Document::parse(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    let at = |n: roxmltree::Node| doc.text_pos_at(n.range().start).row as usize;
+    let weight = |n: roxmltree::Node, s: &str| -> io::Result<Weight> {
+        let x: f64 = s.trim().parse().map_err(|_| bad(at(n), format!("bad weight {s:?}")))?;
+        if x.is_nan() || x < 0.0 { return Err(bad(at(n), format!("negative weight {x}"))); }
+        Ok(x.round() as Weight)
+    };
+    let root = doc.root_element();
+    let mut key = None;
+    let mut default_w = 1;
+    for k in root.children().filter(|k| k.has_tag_name("key")) {
+        let for_edges = matches!(k.attribute("for"), Some("edge" | "all") | None);
+        if for_edges && k.attribute("attr.name").is_some_and(|a| a.eq_ignore_ascii_case("weight")) {
+            key = k.attribute("id");
+            if let Some(d) = k.children().find(|d| d.has_tag_name("default")) { default_w = weight(d, d.text().unwrap_or(""))?; }
+        }
+    }
+    let graph = root.children().find(|g| g.has_tag_name("graph")).ok_or_else(|| bad(at(root), "no <graph> element"))?;
+    let directed = graph.attribute("edgedefault") == Some("directed");
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    for n in graph.children().filter(|n| n.has_tag_name("node")) {
+        let id = n.attribute("id").ok_or_else(|| bad(at(n), "<node> without id"))?;
+        let next = ids.len();
+        ids.entry(id).or_insert(next);
+    }
+    let mut g = Graph::new(ids.len());
+    for e in graph.children().filter(|e| e.has_tag_name("edge")) {
+        let end = |a: &str| e.attribute(a).and_then(|id| ids.get(id).copied()).ok_or_else(|| bad(at(e), format!("<edge> {a} is not a known node")));
+        let (u, v) = (end("source")?, end("target")?);
+        let data = e.children().find(|d| d.has_tag_name("data") && d.attribute("key") == key && key.is_some());
+        let w = match data { Some(d) => weight(d, d.text().unwrap_or(""))?, None => default_w };
+        let dir = e.attribute("directed").map_or(directed, |d| d == "true");
+        g.add_edge(u, v, w);
+        if !dir && u != v { g.add_edge(v, u, w); }
+    }
+    Ok(g)
+}
+
+/// `real` values are rounded to the nearest integer, so scale them first if that loses
+/// precision; negative or complex entries are rejected.
+pub fn read_matrix_market<R: BufRead>(r: R) -> io::Result<Graph> {
+    let mut lines = r.lines();
+    let banner = lines.next().transpose()?.unwrap_or_default().to_ascii_lowercase();
+    let b: Vec<&str> = banner.split_whitespace().collect();
+    if b.len() < 5 || b[0] != "%%matrixmarket" || b[1] != "matrix" || b[2] != "coordinate" {
+        return Err(bad(1, "expected `%%MatrixMarket matrix coordinate <field> <symmetry>`"));
+    }
+    let (field, symmetry) = (b[3], b[4]);
+    if !matches!(field, "real" | "integer" | "pattern") { return Err(bad(1, format!("unsupported field {field:?}"))); }
+    let mirror = match symmetry { "general" => false, "symmetric" => true, s => return Err(bad(1, format!("unsupported symmetry {s:?}"))) };
+    let mut g: Option<Graph> = None;
+    let mut n = 0;
+    for (i, line) in lines.enumerate() {
+        let (i, line) = (i + 2, line?);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('%') { continue; }
+        let t: Vec<&str> = line.split_whitespace().collect();
+        let Some(graph) = g.as_mut() else {
+            let dims: Vec<usize> = t.iter().map(|x| x.parse().map_err(|_| bad(i, "expected `rows cols nnz`"))).collect::<io::Result<_>>()?;
+            if dims.len() != 3 { return Err(bad(i, "expected `rows cols nnz`")); }
+            n = dims[0].max(dims[1]);
+            g = Some(Graph::new(n));
+            continue;
+        };
+        let idx = |k: usize| t.get(k).and_then(|x| x.parse::<usize>().ok()).filter(|&x| x >= 1 && x <= n).ok_or_else(|| bad(i, format!("expected indices in 1..={n}")));
+        let (u, v) = (idx(0)? - 1, idx(1)? - 1);
+        let w: Weight = if field == "pattern" { 1 } else {
+            let x: f64 = t.get(2).and_then(|x| x.parse().ok()).ok_or_else(|| bad(i, "missing value"))?;
+            if x.is_nan() || x < 0.0 { return Err(bad(i, format!("negative weight {x}"))); }
+            x.round() as Weight
+        };
+        graph.add_edge(u, v, w);
+        if mirror && u != v { graph.add_edge(v, u, w); }
+    }
+    g.ok_or_else(|| bad(1, "missing size line"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metis_fixtures() {
+        // Triangle 1-2-3 plus isolated node 4 (empty line), unweighted then edge-weighted.
+        let g = read_metis("% comment\n4 3\n2 3\n1 3\n1 2\n\n".as_bytes()).unwrap();
+        assert_eq!(g.adj, vec![vec![(1, 1), (2, 1)], vec![(0, 1), (2, 1)], vec![(0, 1), (1, 1)], vec![]]);
+        let g = read_metis("3 2 1\n2 5\n1 5 3 7\n2 7\n".as_bytes()).unwrap();
+        assert_eq!(g.adj, vec![vec![(1, 5)], vec![(0, 5), (2, 7)], vec![(1, 7)]]);
+        // Vertex sizes and two vertex weights per node come first and are skipped.
+        let g = read_metis("2 1 111 2\n9 4 4 2 6\n9 4 4 1 6\n".as_bytes()).unwrap();
+        assert_eq!(g.adj, vec![vec![(1, 6)], vec![(0, 6)]]);
+        assert!(read_metis("2 1\n3\n\n".as_bytes()).is_err());
+        assert!(read_metis("2 1 1\n2\n1 4\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn dimacs_and_graphml_fixtures() {
+        let gr = "c tiny\np sp 3 2\nc arcs\na 1 2 4\na 2 3 5\n";
+        assert_eq!(read_dimacs(gr.as_bytes()).unwrap().adj, vec![vec![(1, 4)], vec![(2, 5)], vec![]]);
+        assert!(read_dimacs("a 1 2 3\n".as_bytes()).is_err());
+        assert!(read_dimacs("p sp 2 1\na 1 3 1\n".as_bytes()).is_err());
+        let xml = r#"<?xml version="1.0"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="edge" attr.name="weight" attr.type="double"><default>2</default></key>
+  <graph edgedefault="undirected">
+    <node id="a"/><node id="b"/><node id="c"/>
+    <edge source="a" target="b"><data key="d0">7.0</data></edge>
+    <edge source="b" target="c" directed="true"/>
+  </graph>
+</graphml>"#;
+        assert_eq!(read_graphml(xml).unwrap().adj, vec![vec![(1, 7)], vec![(0, 7), (2, 2)], vec![]]);
+        assert!(read_graphml(&xml.replace(r#"target="c""#, r#"target="z""#)).is_err());
+    }
+
+    #[test]
+    fn formats_are_detected() {
+        let detect = |s: &str| GraphFormat::detect(None, s.as_bytes());
+        assert_eq!(detect("3 2\n0 1 5\n1 2 5\n"), GraphFormat::EdgeList);
+        assert_eq!(detect("3 2\n2\n1 3\n2\n"), GraphFormat::Metis);
+        assert_eq!(detect("3 2 1\n2 5\n1 5 3 7\n2 7\n"), GraphFormat::Metis);
+        assert_eq!(detect("% metis\n3 2\n2\n1 3\n2\n"), GraphFormat::Metis);
+        assert_eq!(detect("c x\np sp 3 2\na 1 2 4\n"), GraphFormat::Dimacs);
+        assert_eq!(detect("%%MatrixMarket matrix coordinate real general\n"), GraphFormat::MatrixMarket);
+        assert_eq!(detect("<?xml version=\"1.0\"?><graphml/>"), GraphFormat::GraphMl);
+        assert_eq!(GraphFormat::detect(Some(Path::new("x.graph.gz")), b"2 1\n2 3 1\n"), GraphFormat::Metis);
+        assert_eq!(detect("\0\0\0\x0d\x0a\x09OSMHeader\x18"), GraphFormat::OsmPbf);
+        for f in ["edges", "dimacs", "metis", "mtx", "graphml", "osm"] { assert_eq!(GraphFormat::parse(f).unwrap().as_str(), f); }
+    }
+
+    #[test]
+    fn matrix_market_fixtures() {
+        let general = "%%MatrixMarket matrix coordinate real general\n% c\n3 3 3\n1 2 2.4\n2 3 7\n3 1 0.5\n";
+        let g = read_matrix_market(general.as_bytes()).unwrap();
+        assert_eq!(g.adj, vec![vec![(1, 2)], vec![(2, 7)], vec![(0, 1)]]);
+        let sym = "%%MatrixMarket matrix coordinate pattern symmetric\n2 2 2\n1 1\n2 1\n";
+        assert_eq!(read_matrix_market(sym.as_bytes()).unwrap().adj, vec![vec![(0, 1), (1, 1)], vec![(0, 1)]]);
+        assert!(read_matrix_market("%%MatrixMarket matrix array real general\n1 1\n1\n".as_bytes()).is_err());
+        assert!(read_matrix_market("%%MatrixMarket matrix coordinate real general\n2 2 1\n1 2 -3\n".as_bytes()).is_err());
+        assert!(read_matrix_market("%%MatrixMarket matrix coordinate integer general\n2 2 1\n1 3 1\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Seeded synthetic graphs and source sets shared by the CLI and the harness. The RNG streams
+//! are part of the benchmark contract: the same seed must keep producing the same instance.
+use std::collections::VecDeque;
+
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::seq::SliceRandom;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::analytics::k_center_greedy;
+use crate::{bounded_multi_source_shortest_paths, Graph, Weight};
+
+/// Undirected `rows x cols` grid, weights uniform in `1..=maxw`.
+pub fn make_grid(rows: usize, cols: usize, maxw: u32, seed: u64) -> Graph {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut g = Graph::new(rows * cols);
+    let idx = |r: usize, c: usize| -> usize { r * cols + c };
+    for r in 0..rows {
+        for c in 0..cols {
+            let u = idx(r,c);
+            if r + 1 < rows {
+                let w = rng.gen_range(1..=maxw) as u64;
+                g.add_undirected_edge(u, idx(r+1,c), w);
+            }
+            if c + 1 < cols {
+                let w = rng.gen_range(1..=maxw) as u64;
+                g.add_undirected_edge(u, idx(r,c+1), w);
+            }
+        }
+    }
+    g
+}
+
+/// Directed Erdős–Rényi G(n, p); O(n²) to generate.
+pub fn make_er(n: usize, p: f64, maxw: u32, seed: u64) -> Graph {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut g = Graph::new(n);
+    for u in 0..n {
+        for v in 0..n {
+            if u == v { continue; }
+            if rng.gen::<f64>() < p {
+                let w = rng.gen_range(1..=maxw) as u64;
+                g.add_edge(u, v, w);
+            }
+        }
+    }
+    g
+}
+
+/// Directed Barabási–Albert-style preferential attachment: `m` out-edges per new node.
+pub fn make_ba(n: usize, m0: usize, m: usize, maxw: u32, seed: u64) -> Graph {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut g = Graph::new(n);
+    // Simple preferential attachment: maintain list of endpoints with multiplicity
+    let mut ends: Vec<usize> = Vec::new();
+    let start = m0.max(1).min(n);
+    for u in 0..start { for v in 0..start { if u!=v { g.add_edge(u,v,1); ends.push(u); } } }
+    for u in start..n {
+        for _ in 0..m { // pick endpoints proportional to degree
+            let t = if ends.is_empty() { rng.gen_range(0..u) } else { ends[rng.gen_range(0..ends.len())] };
+            let w = rng.gen_range(1..=maxw) as u64;
+            g.add_edge(u, t, w);
+            ends.push(t);
+            ends.push(u);
+        }
+    }
+    g
+}
+
+/// `k` distinct sources at distance 0 (fewer if `n < k`).
+pub fn pick_sources(n: usize, k: usize, seed: u64) -> Vec<(usize,u64)> {
+    let mut rng = StdRng::seed_from_u64(seed ^ 0x9E3779B97F4A7C15);
+    let mut seen = std::collections::BTreeSet::new();
+    let mut out = Vec::with_capacity(k);
+    while out.len() < k && seen.len() < n {
+        let s = rng.gen_range(0..n);
+        if seen.insert(s) { out.push((s,0)); }
+    }
+    out
+}
+
+/// `sources` with each `d0` replaced by a seeded draw from `0..=max`, so multi-source runs
+/// start from staggered labels the way a phase of a larger computation does.
+pub fn with_random_offsets(sources: &[(usize,u64)], max: u64, seed: u64) -> Vec<(usize,u64)> {
+    let mut rng = StdRng::seed_from_u64(seed ^ 0xD1B54A32D192ED03);
+    sources.iter().map(|&(s, _)| (s, rng.gen_range(0..=max))).collect()
+}
+
+/// How `pick_sources_with` places sources. Placement changes how much of the graph the bound
+/// covers far more than `k` alone does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SourceStrategy {
+    /// `pick_sources`: distinct nodes uniformly at random.
+    #[default]
+    Uniform,
+    /// Distinct nodes with probability proportional to out-degree, so hubs are favoured.
+    Degree,
+    /// `k` nodes out of the first `4k` a BFS from a random center reaches.
+    Clustered,
+    /// k-means++ spreading: each next source is drawn with probability proportional to the
+    /// squared distance to the nearest one already chosen.
+    FarApart,
+    /// Greedy k-center (`analytics::k_center_greedy`): deterministic farthest-point picks
+    /// from the first node; ignores the seed.
+    KCenter,
+}
+
+impl SourceStrategy {
+    pub const ALL: [SourceStrategy; 5] = [SourceStrategy::Uniform, SourceStrategy::Degree, SourceStrategy::Clustered, SourceStrategy::FarApart, SourceStrategy::KCenter];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SourceStrategy::Uniform => "uniform",
+            SourceStrategy::Degree => "degree",
+            SourceStrategy::Clustered => "clustered",
+            SourceStrategy::FarApart => "far-apart",
+            SourceStrategy::KCenter => "k-center",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> { Self::ALL.into_iter().find(|x| x.as_str() == s) }
+}
+
+/// `k` distinct sources at distance 0 placed by `strategy`. `Uniform` is exactly
+/// `pick_sources`; `Clustered` returns fewer than `k` if the center's ball is smaller.
+pub fn pick_sources_with(g: &Graph, k: usize, seed: u64, strategy: SourceStrategy) -> Vec<(usize,u64)> {
+    let n = g.len();
+    let mut rng = StdRng::seed_from_u64(seed ^ 0x9E3779B97F4A7C15);
+    if n == 0 || k == 0 { return Vec::new(); }
+    match strategy {
+        SourceStrategy::Uniform => pick_sources(n, k, seed),
+        SourceStrategy::Degree => {
+            let nodes: Vec<usize> = (0..n).collect();
+            // Zero-degree nodes only fill in once every positive-degree node is taken.
+            nodes.choose_multiple_weighted(&mut rng, k.min(n), |&v| g.adj[v].len() as f64 + 1e-9)
+                .expect("degree weights are finite").map(|&v| (v, 0)).collect()
+        }
+        SourceStrategy::Clustered => {
+            let center = rng.gen_range(0..n);
+            let mut seen = vec![false; n];
+            let (mut ball, mut queue) = (vec![center], VecDeque::from([center]));
+            seen[center] = true;
+            while let Some(u) = queue.pop_front() {
+                if ball.len() >= 4 * k { break; }
+                for &(v, _) in &g.adj[u] {
+                    if !seen[v] && ball.len() < 4 * k { seen[v] = true; ball.push(v); queue.push_back(v); }
+                }
+            }
+            ball.choose_multiple(&mut rng, k.min(ball.len())).map(|&v| (v, 0)).collect()
+        }
+        SourceStrategy::FarApart => {
+            let mut out = vec![(rng.gen_range(0..n), 0)];
+            let mut nearest = bounded_multi_source_shortest_paths(g, &out, Weight::MAX).dist;
+            while out.len() < k.min(n) {
+                // Unreached nodes count as one step past the farthest reached node.
+                let far = nearest.iter().copied().filter(|&d| d != Weight::MAX).max().unwrap_or(0) as f64 + 1.0;
+                let weights = nearest.iter().map(|&d| { let d = if d == Weight::MAX { far } else { d as f64 }; d * d });
+                let Ok(pick) = WeightedIndex::new(weights) else { break };
+                let s = pick.sample(&mut rng);
+                out.push((s, 0));
+                let r = bounded_multi_source_shortest_paths(g, &[(s, 0)], Weight::MAX);
+                for (a, b) in nearest.iter_mut().zip(r.dist) { *a = (*a).min(b); }
+            }
+            out
+        }
+        SourceStrategy::KCenter => k_center_greedy(g, k, Weight::MAX).0.into_iter().map(|v| (v, 0)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_strategies_are_seeded_and_distinct() {
+        let g = make_grid(30, 30, 5, 1);
+        for st in SourceStrategy::ALL {
+            let a = pick_sources_with(&g, 12, 3, st);
+            assert_eq!(a, pick_sources_with(&g, 12, 3, st), "{st:?}");
+            assert_eq!(a.len(), 12);
+            let mut ids: Vec<usize> = a.iter().map(|s| s.0).collect();
+            ids.sort_unstable();
+            ids.dedup();
+            assert_eq!(ids.len(), 12, "{st:?}");
+            assert_eq!(SourceStrategy::parse(st.as_str()), Some(st));
+        }
+        assert_eq!(pick_sources_with(&g, 12, 3, SourceStrategy::Uniform), pick_sources(900, 12, 3));
+        // Spread: nearest-pair distance well above the clustered pick's.
+        let min_gap = |src: &[(usize, u64)]| src.iter().map(|&(s, _)| {
+            let d = bounded_multi_source_shortest_paths(&g, &[(s, 0)], Weight::MAX).dist;
+            src.iter().filter(|&&(t, _)| t != s).map(|&(t, _)| d[t]).min().unwrap()
+        }).min().unwrap();
+        assert!(min_gap(&pick_sources_with(&g, 6, 3, SourceStrategy::FarApart)) > min_gap(&pick_sources_with(&g, 6, 3, SourceStrategy::Clustered)));
+        // Only the star's center has out-edges, so it is always picked.
+        let mut star = Graph::new(50);
+        for v in 1..50 { star.add_edge(0, v, 1); }
+        assert_eq!(pick_sources_with(&star, 1, 9, SourceStrategy::Degree), vec![(0, 0)]);
+        let src = pick_sources(900, 40, 3);
+        let off = with_random_offsets(&src, 7, 3);
+        assert_eq!(off, with_random_offsets(&src, 7, 3));
+        assert!(off.iter().zip(&src).all(|(a, b)| a.0 == b.0 && a.1 <= 7) && off.iter().any(|s| s.1 > 0));
+    }
+}
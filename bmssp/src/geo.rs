@@ -0,0 +1,113 @@
+//! Isochrones for graphs with planar or lon/lat node coordinates, plus a GeoJSON writer.
+use std::io::{self, Write};
+
+use serde_json::json;
+
+use crate::{bounded_multi_source_shortest_paths, Graph, Node, Weight};
+
+/// Reachable region of a bounded search in coordinate space.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Isochrone {
+    pub bound: Weight,
+    /// Settled node positions plus the points where edges leaving the settled set cross `bound`
+    /// (linearly interpolated along the edge).
+    pub points: Vec<(f64, f64)>,
+    /// Convex hull of `points`, counter-clockwise and closed (first point repeated at the end).
+    /// Empty when fewer than three non-collinear points were reached.
+    pub hull: Vec<(f64, f64)>,
+}
+
+/// Run a bounded search from `sources` and outline what it reached. `coords[v]` is `(x, y)`,
+/// i.e. `(lon, lat)` for geographic data.
+pub fn isochrone(g: &Graph, coords: &[(f64, f64)], sources: &[(Node, Weight)], bound: Weight) -> Isochrone {
+    assert_eq!(coords.len(), g.len(), "one coordinate per node");
+    let r = bounded_multi_source_shortest_paths(g, sources, bound);
+    let mut points: Vec<(f64, f64)> = r.explored.iter().map(|&v| coords[v]).collect();
+    for &u in &r.explored {
+        let du = r.dist[u];
+        for &(v, w) in &g.adj[u] {
+            if w > 0 && du.saturating_add(w) >= bound {
+                let t = (bound - du) as f64 / w as f64;
+                let (a, b) = (coords[u], coords[v]);
+                points.push((a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1)));
+            }
+        }
+    }
+    let hull = convex_hull(&points);
+    Isochrone { bound, points, hull }
+}
+
+/// Andrew's monotone chain; returns a closed counter-clockwise ring, or empty if degenerate.
+fn convex_hull(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut p = points.to_vec();
+    p.sort_by(|a, b| a.partial_cmp(b).expect("NaN coordinate"));
+    p.dedup();
+    if p.len() < 3 { return Vec::new(); }
+    let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+    let mut hull: Vec<(f64, f64)> = Vec::with_capacity(2 * p.len());
+    for pass in 0..2 {
+        let start = hull.len();
+        let iter: Box<dyn Iterator<Item = &(f64, f64)>> = if pass == 0 { Box::new(p.iter()) } else { Box::new(p.iter().rev()) };
+        for &q in iter {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], q) <= 0.0 { hull.pop(); }
+            hull.push(q);
+        }
+        hull.pop();
+    }
+    if hull.len() < 3 { return Vec::new(); }
+    hull.push(hull[0]);
+    hull
+}
+
+impl Isochrone {
+    /// GeoJSON `Feature` with the hull as a `Polygon` (or the points as a `MultiPoint` when the
+    /// hull is degenerate) and `bound` in `properties`.
+    pub fn to_geojson(&self) -> serde_json::Value {
+        let ring = |pts: &[(f64, f64)]| pts.iter().map(|&(x, y)| json!([x, y])).collect::<Vec<_>>();
+        let geometry = if self.hull.is_empty() {
+            json!({ "type": "MultiPoint", "coordinates": ring(&self.points) })
+        } else {
+            json!({ "type": "Polygon", "coordinates": [ring(&self.hull)] })
+        };
+        json!({ "type": "Feature", "properties": { "bound": self.bound }, "geometry": geometry })
+    }
+
+    pub fn write_geojson<W: Write>(&self, w: W) -> io::Result<()> {
+        serde_json::to_writer(w, &self.to_geojson()).map_err(io::Error::other)
+    }
+}
+
+/// Several isochrones (e.g. nested bounds) as one GeoJSON `FeatureCollection`.
+pub fn write_geojson_collection<W: Write>(isos: &[Isochrone], w: W) -> io::Result<()> {
+    let features: Vec<_> = isos.iter().map(Isochrone::to_geojson).collect();
+    serde_json::to_writer(w, &json!({ "type": "FeatureCollection", "features": features })).map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_isochrone_is_clipped_diamond() {
+        // Center 0 at the origin with four spokes of weight 4 to (+-2, 0), (0, +-2).
+        let mut g = Graph::new(5);
+        let coords = vec![(0.0, 0.0), (2.0, 0.0), (0.0, 2.0), (-2.0, 0.0), (0.0, -2.0)];
+        for v in 1..5 { g.add_undirected_edge(0, v, 4); }
+        let iso = isochrone(&g, &coords, &[(0, 0)], 2);
+        assert_eq!(iso.hull, vec![(-1.0, 0.0), (0.0, -1.0), (1.0, 0.0), (0.0, 1.0), (-1.0, 0.0)]);
+        let v = iso.to_geojson();
+        assert_eq!(v["geometry"]["type"], "Polygon");
+        assert_eq!(v["properties"]["bound"], 2);
+    }
+
+    #[test]
+    fn degenerate_falls_back_to_points() {
+        let g = Graph::new(2);
+        let iso = isochrone(&g, &[(1.0, 1.0), (5.0, 5.0)], &[(0, 0)], 10);
+        assert!(iso.hull.is_empty());
+        let mut buf = Vec::new();
+        write_geojson_collection(&[iso], &mut buf).unwrap();
+        let s = String::from_utf8(buf).unwrap();
+        assert!(s.contains("\"FeatureCollection\"") && s.contains("\"MultiPoint\""));
+    }
+}
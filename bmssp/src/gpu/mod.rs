@@ -0,0 +1,225 @@
+//! wgpu compute backend: bounded near-far SSSP over the `CompactGraph` CSR arrays.
+//!
+//! Each iteration dispatches one thread per node; active nodes below the current threshold relax
+//! their edges with `atomicMin`, the rest wait in the far pile. When a near phase goes quiet the
+//! threshold advances by `delta`. Labels are u32 on the device, so `bound` must fit in u32.
+//! Results have the same shape as the CPU solvers: `explored` is in `(dist, node)` order and B'
+//! is recomputed on the host from the final labels.
+use crate::compact::CompactGraph;
+use crate::{BmsspResult, Node, Weight};
+use wgpu::util::DeviceExt;
+
+#[derive(Debug)]
+pub enum GpuError {
+    /// No adapter (GPU or software fallback) is available.
+    NoAdapter,
+    Device(String),
+    /// Edge count or bound doesn't fit the device's u32 indexing.
+    TooLarge,
+}
+
+impl std::fmt::Display for GpuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GpuError::NoAdapter => write!(f, "no wgpu adapter available"),
+            GpuError::Device(e) => write!(f, "wgpu device error: {e}"),
+            GpuError::TooLarge => write!(f, "graph or bound exceeds u32 device limits"),
+        }
+    }
+}
+
+impl std::error::Error for GpuError {}
+
+/// A device with the graph uploaded once; `run` can then be called per query.
+pub struct GpuSolver {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    n: usize,
+    graph: CompactGraph,
+    offsets: wgpu::Buffer,
+    targets: wgpu::Buffer,
+    weights: wgpu::Buffer,
+    /// Human-readable adapter name, for benchmark rows.
+    pub adapter_name: String,
+}
+
+const WORKGROUP: u32 = 256;
+const MAX_GROUPS_X: u32 = 65_535;
+
+impl GpuSolver {
+    pub fn new(g: &CompactGraph) -> Result<Self, GpuError> {
+        if g.num_edges() > u32::MAX as usize { return Err(GpuError::TooLarge); }
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle_from_env());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+            .map_err(|_| GpuError::NoAdapter)?;
+        let adapter_name = adapter.get_info().name;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default()))
+            .map_err(|e| GpuError::Device(e.to_string()))?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("bmssp-near-far"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("near_far.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("bmssp-relax"),
+            layout: None,
+            module: &module,
+            entry_point: Some("relax"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        let offsets: Vec<u32> = g.offsets.iter().map(|&o| o as u32).collect();
+        let init = |label, data: &[u32]| {
+            // Zero-sized bindings are invalid, so pad empty arrays to one element.
+            let data = if data.is_empty() { &[0u32][..] } else { data };
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(data),
+                usage: wgpu::BufferUsages::STORAGE,
+            })
+        };
+        let (offsets, targets, weights) = (init("offsets", &offsets), init("targets", &g.targets), init("weights", &g.weights));
+        Ok(Self { device, queue, pipeline, n: g.len(), graph: g.clone(), offsets, targets, weights, adapter_name })
+    }
+
+    /// Bounded multi-source search with near-far step `delta = bound / 16` (at least 1).
+    pub fn run(&self, sources: &[(Node, Weight)], bound: Weight) -> Result<BmsspResult, GpuError> {
+        self.run_delta(sources, bound, (bound / 16).max(1))
+    }
+
+    pub fn run_delta(&self, sources: &[(Node, Weight)], bound: Weight, delta: Weight) -> Result<BmsspResult, GpuError> {
+        let bound32 = u32::try_from(bound).map_err(|_| GpuError::TooLarge)?;
+        let delta32 = u32::try_from(delta.max(1)).unwrap_or(u32::MAX);
+        let n = self.n.max(1);
+        let mut dist = vec![u32::MAX; n];
+        let mut active = vec![0u32; n];
+        for &(s, d0) in sources {
+            if s < self.n && d0 < bound && (d0 as u32) < dist[s] {
+                dist[s] = d0 as u32;
+                active[s] = 1;
+            }
+        }
+
+        let dev = &self.device;
+        let rw = wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST;
+        let buf = |label, data: &[u32]| dev.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: Some(label), contents: bytemuck::cast_slice(data), usage: rw });
+        let dist_buf = buf("dist", &dist);
+        let flags = [buf("flags-a", &active), buf("flags-b", &vec![0u32; n])];
+        let counters = buf("counters", &[0u32; 4]);
+        let params = dev.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("params"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let layout = self.pipeline.get_bind_group_layout(0);
+        // Two bind groups ping-pong the frontier/next flag buffers between iterations.
+        let groups: Vec<wgpu::BindGroup> = (0..2)
+            .map(|i| {
+                let entries = [&params, &self.offsets, &self.targets, &self.weights, &dist_buf, &flags[i], &flags[1 - i], &counters];
+                let entries: Vec<wgpu::BindGroupEntry> = entries
+                    .iter()
+                    .enumerate()
+                    .map(|(b, buf)| wgpu::BindGroupEntry { binding: b as u32, resource: buf.as_entire_binding() })
+                    .collect();
+                dev.create_bind_group(&wgpu::BindGroupDescriptor { label: None, layout: &layout, entries: &entries })
+            })
+            .collect();
+
+        let groups_needed = (self.n as u32).div_ceil(WORKGROUP).max(1);
+        let (gx, gy) = (groups_needed.min(MAX_GROUPS_X), groups_needed.div_ceil(MAX_GROUPS_X));
+        let mut threshold = delta32.min(bound32);
+        let (mut edges_scanned, mut heap_pushes) = (0usize, 0usize);
+        let mut cur = 0;
+        loop {
+            self.queue.write_buffer(&params, 0, bytemuck::cast_slice(&[self.n as u32, bound32, threshold, 0]));
+            let mut enc = dev.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            enc.clear_buffer(&flags[1 - cur], 0, None);
+            enc.clear_buffer(&counters, 0, None);
+            {
+                let mut pass = enc.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+                pass.set_pipeline(&self.pipeline);
+                pass.set_bind_group(0, &groups[cur], &[]);
+                pass.dispatch_workgroups(gx, gy, 1);
+            }
+            self.queue.submit([enc.finish()]);
+            let c = self.read_back(&counters, 4)?;
+            edges_scanned += c[2] as usize;
+            heap_pushes += c[3] as usize;
+            cur = 1 - cur;
+            if c[0] == 0 {
+                if c[1] == 0 { break; }
+                threshold = threshold.saturating_add(delta32).min(bound32);
+            }
+        }
+
+        let dist: Vec<Weight> = self.read_back(&dist_buf, n)?
+            .into_iter()
+            .take(self.n)
+            .map(|d| if d == u32::MAX { Weight::MAX } else { d as Weight })
+            .collect();
+        let mut explored: Vec<Node> = (0..self.n).filter(|&v| dist[v] < bound).collect();
+        explored.sort_unstable_by_key(|&v| (dist[v], v));
+        let mut b_prime = Weight::MAX;
+        for &v in &explored {
+            for (_, w) in self.graph.neighbors(v) {
+                let nd = dist[v].saturating_add(w);
+                if nd >= bound && nd < b_prime { b_prime = nd; }
+            }
+        }
+        Ok(BmsspResult{ dist, explored, b_prime, edges_scanned, heap_pushes, ..Default::default() })
+    }
+
+    fn read_back(&self, src: &wgpu::Buffer, len: usize) -> Result<Vec<u32>, GpuError> {
+        let size = (len * 4) as u64;
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("staging"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut enc = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        enc.copy_buffer_to_buffer(src, 0, &staging, 0, size);
+        self.queue.submit([enc.finish()]);
+        let slice = staging.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        self.device.poll(wgpu::PollType::wait_indefinitely()).map_err(|e| GpuError::Device(e.to_string()))?;
+        let out = bytemuck::cast_slice::<u8, u32>(&slice.get_mapped_range()).to_vec();
+        staging.unmap();
+        Ok(out)
+    }
+}
+
+/// One-shot convenience: upload `g`, run once. Prefer `GpuSolver` for repeated queries.
+pub fn bmssp_gpu(g: &CompactGraph, sources: &[(Node, Weight)], bound: Weight) -> Result<BmsspResult, GpuError> {
+    GpuSolver::new(g)?.run(sources, bound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bounded_multi_source_shortest_paths, Graph};
+
+    #[test]
+    fn gpu_matches_cpu_when_adapter_present() {
+        let mut g = Graph::new(400);
+        for u in 0..400 {
+            g.add_edge(u, (u * 17 + 5) % 400, 1 + (u % 7) as u64);
+            g.add_edge(u, (u + 1) % 400, 3);
+        }
+        let cg = CompactGraph::from_graph(&g).unwrap();
+        let solver = match GpuSolver::new(&cg) {
+            Ok(s) => s,
+            Err(GpuError::NoAdapter) => return, // headless CI without any adapter
+            Err(e) => panic!("{e}"),
+        };
+        let sources = [(0, 0), (200, 4)];
+        for bound in [10, 60, 500] {
+            let a = bounded_multi_source_shortest_paths(&g, &sources, bound);
+            let b = solver.run(&sources, bound).unwrap();
+            assert_eq!(a.dist, b.dist);
+            assert_eq!(a.explored, b.explored);
+            assert_eq!(a.b_prime, b.b_prime);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+// Bounded near-far relaxation over a u32 CSR graph.
+// A node is active while its flag in `frontier` is set. Active nodes below `threshold` (near)
+// relax their out-edges; active nodes at or above it (far) are carried over to `next` untouched.
+
+struct Params {
+    n: u32,
+    bound: u32,
+    threshold: u32,
+    _pad: u32,
+};
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> offsets: array<u32>;
+@group(0) @binding(2) var<storage, read> targets: array<u32>;
+@group(0) @binding(3) var<storage, read> weights: array<u32>;
+@group(0) @binding(4) var<storage, read_write> dist: array<atomic<u32>>;
+@group(0) @binding(5) var<storage, read> frontier: array<u32>;
+@group(0) @binding(6) var<storage, read_write> next: array<u32>;
+// [0] near improvements, [1] far nodes carried, [2] edges scanned, [3] successful relaxations
+@group(0) @binding(7) var<storage, read_write> counters: array<atomic<u32>, 4>;
+
+@compute @workgroup_size(256)
+fn relax(@builtin(global_invocation_id) gid: vec3<u32>, @builtin(num_workgroups) nwg: vec3<u32>) {
+    let u = gid.x + gid.y * nwg.x * 256u;
+    if (u >= params.n || frontier[u] == 0u) {
+        return;
+    }
+    let du = atomicLoad(&dist[u]);
+    if (du >= params.threshold) {
+        next[u] = 1u;
+        atomicAdd(&counters[1], 1u);
+        return;
+    }
+    let start = offsets[u];
+    let end = offsets[u + 1u];
+    atomicAdd(&counters[2], end - start);
+    for (var e = start; e < end; e = e + 1u) {
+        let w = weights[e];
+        // du < bound, so `bound - du` can't underflow; this also rules out u32 overflow of du + w.
+        if (w >= params.bound - du) {
+            continue;
+        }
+        let nd = du + w;
+        let v = targets[e];
+        if (nd < atomicMin(&dist[v], nd)) {
+            next[v] = 1u;
+            atomicAdd(&counters[0], 1u);
+            atomicAdd(&counters[3], 1u);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Read-only adjacency access, so searches can run over storage the caller already has
+//! (implicit graphs, adapters, compressed layouts) without copying into a `Graph`.
+use crate::compact::CompactGraph;
+use crate::prefetch;
+use crate::{Graph, Node, Weight};
+
+/// Minimal graph view the searches are generic over: node count and weighted out-neighbors.
+pub trait GraphLike {
+    fn num_nodes(&self) -> usize;
+    fn neighbors(&self, u: Node) -> impl Iterator<Item = (Node, Weight)> + '_;
+    /// Tombstoned nodes are never used as sources. Storage without deletions keeps the default.
+    #[inline]
+    fn is_removed(&self, _u: Node) -> bool { false }
+    /// Prefetch `dist[to]` and `to`'s adjacency for the `i`-th out-edge `u -> to`, if there is
+    /// one. Only does anything with the `prefetch` feature; the default is a no-op.
+    #[inline]
+    fn prefetch_edge_target<L>(&self, _u: Node, _i: usize, _dist: &[L]) {}
+}
+
+impl GraphLike for Graph {
+    #[inline]
+    fn num_nodes(&self) -> usize { self.len() }
+    #[inline]
+    fn neighbors(&self, u: Node) -> impl Iterator<Item = (Node, Weight)> + '_ { self.adj[u].iter().copied() }
+    #[inline]
+    fn is_removed(&self, u: Node) -> bool { Graph::is_removed(self, u) }
+    #[inline(always)]
+    fn prefetch_edge_target<L>(&self, u: Node, i: usize, dist: &[L]) {
+        if !cfg!(feature = "prefetch") { return; }
+        if let Some(&(to, _)) = self.adj[u].get(i) {
+            prefetch::read(dist.as_ptr().wrapping_add(to));
+            prefetch::read(self.adj.as_ptr().wrapping_add(to));
+        }
+    }
+}
+
+impl GraphLike for CompactGraph {
+    fn num_nodes(&self) -> usize { self.len() }
+    fn neighbors(&self, u: Node) -> impl Iterator<Item = (Node, Weight)> + '_ { CompactGraph::neighbors(self, u) }
+    #[inline(always)]
+    fn prefetch_edge_target<L>(&self, u: Node, i: usize, dist: &[L]) {
+        if !cfg!(feature = "prefetch") { return; }
+        let e = self.offsets[u] + i;
+        if e < self.offsets[u + 1] {
+            let to = self.targets[e] as usize;
+            prefetch::read(dist.as_ptr().wrapping_add(to));
+            prefetch::read(self.offsets.as_ptr().wrapping_add(to));
+        }
+    }
+}
+
+impl<G: GraphLike + ?Sized> GraphLike for &G {
+    fn num_nodes(&self) -> usize { (**self).num_nodes() }
+    fn neighbors(&self, u: Node) -> impl Iterator<Item = (Node, Weight)> + '_ { (**self).neighbors(u) }
+    fn is_removed(&self, u: Node) -> bool { (**self).is_removed(u) }
+    #[inline(always)]
+    fn prefetch_edge_target<L>(&self, u: Node, i: usize, dist: &[L]) { (**self).prefetch_edge_target(u, i, dist) }
+}
+
+#[cfg(feature = "petgraph")]
+mod petgraph_impl {
+    use petgraph::graph::{EdgeReference, IndexType, NodeIndex};
+    use petgraph::visit::EdgeRef;
+    use petgraph::EdgeType;
+
+    use super::GraphLike;
+    use crate::{Graph, Node, Weight};
+
+    fn other_end<Ix: IndexType>(u: Node, e: EdgeReference<'_, Weight, Ix>) -> (Node, Weight) {
+        let (s, t) = (e.source().index(), e.target().index());
+        (if s == u { t } else { s }, *e.weight())
+    }
+
+    /// Directed graphs borrow out-edges; undirected edges are traversable both ways.
+    impl<N, Ty: EdgeType, Ix: IndexType> GraphLike for petgraph::Graph<N, Weight, Ty, Ix> {
+        fn num_nodes(&self) -> usize { self.node_count() }
+        fn neighbors(&self, u: Node) -> impl Iterator<Item = (Node, Weight)> + '_ {
+            self.edges(NodeIndex::new(u)).map(move |e| other_end(u, e))
+        }
+    }
+
+    /// Copies the edges; undirected petgraph edges become two directed ones.
+    impl<N, Ty: EdgeType, Ix: IndexType> From<&petgraph::Graph<N, Weight, Ty, Ix>> for Graph {
+        fn from(pg: &petgraph::Graph<N, Weight, Ty, Ix>) -> Self {
+            let mut g = Graph::new(pg.node_count());
+            for u in 0..pg.node_count() {
+                for (v, w) in GraphLike::neighbors(pg, u) { g.add_edge(u, v, w); }
+            }
+            g
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+
+    #[test]
+    fn searches_run_over_any_storage() {
+        let mut g = Graph::new(30);
+        for i in 0..29 { g.add_undirected_edge(i, i + 1, 1 + (i as Weight % 4)); }
+        g.add_edge(2, 20, 3);
+        let expect = bounded_multi_source_shortest_paths(&g, &[(0, 0), (25, 1)], 15);
+        let cg = CompactGraph::from_graph(&g).unwrap();
+        let a = bounded_multi_source_shortest_paths(&cg, &[(0, 0), (25, 1)], 15);
+        let b = crate::bmssp_semiring::<crate::MinPlus>(&&cg, &[(0, 0), (25, 1)], 15).into_result();
+        for r in [a, b] { assert_eq!((&r.dist, &r.explored, r.b_prime), (&expect.dist, &expect.explored, expect.b_prime)); }
+    }
+
+    #[cfg(feature = "petgraph")]
+    #[test]
+    fn petgraph_adapters() {
+        let mut pg = petgraph::graph::UnGraph::<(), Weight>::new_undirected();
+        let n: Vec<_> = (0..4).map(|_| pg.add_node(())).collect();
+        pg.add_edge(n[0], n[1], 2);
+        pg.add_edge(n[2], n[1], 3);
+        pg.add_edge(n[2], n[3], 1);
+        let g = Graph::from(&pg);
+        assert_eq!(g.num_edges(), 6);
+        let direct = bounded_multi_source_shortest_paths(&pg, &[(3, 0)], 10);
+        assert_eq!(direct.dist, vec![6, 4, 1, 0]);
+        assert_eq!(bounded_multi_source_shortest_paths(&g, &[(3, 0)], 10).dist, direct.dist);
+    }
+}
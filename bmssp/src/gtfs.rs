@@ -0,0 +1,285 @@
+//! GTFS public-transit import (feature `gtfs`) into a time-dependent `TemporalGraph` for
+//! `earliest_arrival`.
+//!
+//! Every stop becomes a node. Each pair of consecutive `stop_times` of a trip becomes an edge
+//! boardable only at the trip's departure second, with the ride time as its duration;
+//! `transfers.txt` entries between different stops become always-open walking edges taking
+//! `min_transfer_time` (0 if missing). Times are seconds after midnight of the service day
+//! (past 24:00:00 for overnight trips). Every trip is assumed to run: `calendar.txt` is not
+//! applied, and a same-stop change between trips takes no time. Reads an extracted feed
+//! directory or the `.zip` itself (stored or deflated entries; no zip64).
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::{Node, TemporalGraph, Weight};
+
+/// Transit graph plus the GTFS stop each node stands for.
+#[derive(Debug, Clone, Default)]
+pub struct GtfsGraph {
+    pub graph: TemporalGraph,
+    /// `stop_id` of every node.
+    pub stop_ids: Vec<String>,
+    /// `(lon, lat)` in degrees, as `geo::isochrone` expects.
+    pub coords: Vec<(f64, f64)>,
+    /// Number of trips that contributed edges.
+    pub trips: usize,
+}
+
+impl GtfsGraph {
+    /// Node of a `stop_id`. Linear scan; build a map for repeated lookups.
+    pub fn stop(&self, stop_id: &str) -> Option<Node> { self.stop_ids.iter().position(|s| s == stop_id) }
+}
+
+/// Read a feed from an extracted directory or a `.zip` file.
+pub fn read_gtfs(path: &Path) -> io::Result<GtfsGraph> {
+    if path.is_dir() {
+        let file = |name: &str| -> io::Result<Option<String>> {
+            match std::fs::read_to_string(path.join(name)) {
+                Ok(s) => Ok(Some(s)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e),
+            }
+        };
+        build(file("stops.txt")?, file("stop_times.txt")?, file("transfers.txt")?)
+    } else {
+        let mut zip = File::open(path)?;
+        let entries = zip_entries(&mut zip)?;
+        let mut file = |name: &str| match entries.iter().find(|e| e.name.rsplit('/').next() == Some(name)) {
+            Some(e) => zip_read(&mut zip, e).map(Some),
+            None => Ok(None),
+        };
+        let (stops, times, transfers) = (file("stops.txt")?, file("stop_times.txt")?, file("transfers.txt")?);
+        build(stops, times, transfers)
+    }
+}
+
+fn invalid(what: &str) -> io::Error { io::Error::new(io::ErrorKind::InvalidData, format!("gtfs: {what}")) }
+
+fn build(stops: Option<String>, stop_times: Option<String>, transfers: Option<String>) -> io::Result<GtfsGraph> {
+    let (stops, stop_times) = (stops.ok_or_else(|| invalid("missing stops.txt"))?, stop_times.ok_or_else(|| invalid("missing stop_times.txt"))?);
+    let mut out = GtfsGraph::default();
+    let mut index: HashMap<String, Node> = HashMap::new();
+    let mut rows = Csv::new(&stops)?;
+    let (id, lat, lon) = (rows.column("stop_id")?, rows.column("stop_lat").ok(), rows.column("stop_lon").ok());
+    while let Some(r) = rows.next_row()? {
+        let coord = |c: Option<usize>| c.and_then(|c| r.get(c)).and_then(|s| s.parse().ok()).unwrap_or(f64::NAN);
+        index.insert(r[id].clone(), out.stop_ids.len());
+        out.stop_ids.push(r[id].clone());
+        out.coords.push((coord(lon), coord(lat)));
+    }
+    out.graph = TemporalGraph::new(out.stop_ids.len());
+
+    let mut rows = Csv::new(&stop_times)?;
+    let (trip, seq, stop) = (rows.column("trip_id")?, rows.column("stop_sequence")?, rows.column("stop_id")?);
+    let (arr, dep) = (rows.column("arrival_time")?, rows.column("departure_time")?);
+    // (trip, sequence, stop, arrival, departure); times left blank between timepoints are skipped.
+    let mut visits: Vec<(String, u32, Node, Weight, Weight)> = Vec::new();
+    while let Some(r) = rows.next_row()? {
+        let node = *index.get(&r[stop]).ok_or_else(|| invalid(&format!("unknown stop {:?}", r[stop])))?;
+        let sequence = r[seq].trim().parse().map_err(|_| invalid(&format!("bad stop_sequence {:?}", r[seq])))?;
+        let (Some(a), Some(d)) = (parse_time(&r[arr])?, parse_time(&r[dep])?) else { continue };
+        visits.push((r[trip].clone(), sequence, node, a, d));
+    }
+    visits.sort_unstable_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+    for w in visits.windows(2) {
+        let (a, b) = (&w[0], &w[1]);
+        if a.0 != b.0 { continue; }
+        let ride = b.3.checked_sub(a.4).ok_or_else(|| invalid(&format!("trip {} arrives before it departs", a.0)))?;
+        out.graph.add_edge(a.2, b.2, ride, a.4, a.4 + 1);
+    }
+    out.trips = visits.windows(2).filter(|w| w[0].0 != w[1].0).count() + usize::from(!visits.is_empty());
+
+    if let Some(transfers) = transfers {
+        let mut rows = Csv::new(&transfers)?;
+        let (from, to, time) = (rows.column("from_stop_id")?, rows.column("to_stop_id")?, rows.column("min_transfer_time").ok());
+        while let Some(r) = rows.next_row()? {
+            let (Some(&u), Some(&v)) = (index.get(&r[from]), index.get(&r[to])) else { continue };
+            if u == v { continue; }
+            let secs = time.and_then(|c| r.get(c)).and_then(|s| s.trim().parse().ok()).unwrap_or(0);
+            out.graph.add_edge(u, v, secs, 0, Weight::MAX);
+        }
+    }
+    Ok(out)
+}
+
+/// `HH:MM:SS` (hours may exceed 23) to seconds; `None` for an empty field.
+fn parse_time(s: &str) -> io::Result<Option<Weight>> {
+    let s = s.trim();
+    if s.is_empty() { return Ok(None); }
+    let parts: Vec<&str> = s.split(':').collect();
+    let bad = || invalid(&format!("bad time {s:?}"));
+    let [h, m, sec] = parts[..] else { return Err(bad()) };
+    let num = |x: &str| x.parse::<Weight>().map_err(|_| bad());
+    Ok(Some(num(h)? * 3600 + num(m)? * 60 + num(sec)?))
+}
+
+/// Minimal RFC 4180 reader: header row, quoted fields with `""` escapes, CRLF or LF lines.
+struct Csv<'a> {
+    rest: &'a str,
+    header: Vec<String>,
+}
+
+impl<'a> Csv<'a> {
+    fn new(text: &'a str) -> io::Result<Self> {
+        let mut csv = Csv{ rest: text.strip_prefix('\u{feff}').unwrap_or(text), header: Vec::new() };
+        csv.header = csv.next_row()?.ok_or_else(|| invalid("empty file"))?.into_iter().map(|h| h.trim().to_string()).collect();
+        Ok(csv)
+    }
+
+    fn column(&self, name: &str) -> io::Result<usize> {
+        self.header.iter().position(|h| h == name).ok_or_else(|| invalid(&format!("missing column {name}")))
+    }
+
+    /// Next non-blank record, padded with empty fields to the header's width.
+    fn next_row(&mut self) -> io::Result<Option<Vec<String>>> {
+        loop {
+            if self.rest.is_empty() { return Ok(None); }
+            let (mut fields, mut field, mut quoted) = (Vec::new(), String::new(), false);
+            let mut chars = self.rest.char_indices().peekable();
+            let mut end = self.rest.len();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '"' if quoted && chars.peek().map(|p| p.1) == Some('"') => { chars.next(); field.push('"'); }
+                    '"' => quoted = !quoted,
+                    ',' if !quoted => fields.push(std::mem::take(&mut field)),
+                    '\n' if !quoted => { end = i + 1; break; }
+                    '\r' if !quoted => {}
+                    _ => field.push(c),
+                }
+            }
+            if quoted { return Err(invalid("unterminated quote")); }
+            self.rest = &self.rest[end..];
+            fields.push(field);
+            if fields.len() == 1 && fields[0].trim().is_empty() { continue; }
+            if fields.len() < self.header.len() { fields.resize(self.header.len(), String::new()); }
+            return Ok(Some(fields));
+        }
+    }
+}
+
+struct ZipEntry {
+    name: String,
+    method: u16,
+    compressed: u64,
+    offset: u64,
+}
+
+fn u16_at(b: &[u8], i: usize) -> u16 { u16::from_le_bytes([b[i], b[i + 1]]) }
+fn u32_at(b: &[u8], i: usize) -> u32 { u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]) }
+
+/// Central directory of a zip file.
+fn zip_entries<R: Read + Seek>(r: &mut R) -> io::Result<Vec<ZipEntry>> {
+    let len = r.seek(SeekFrom::End(0))?;
+    // The end record is 22 bytes plus a comment of up to 64 KiB.
+    let tail_len = len.min(22 + 65_535);
+    r.seek(SeekFrom::Start(len - tail_len))?;
+    let mut tail = vec![0; tail_len as usize];
+    r.read_exact(&mut tail)?;
+    let eocd = (0..tail.len().saturating_sub(21)).rev().find(|&i| u32_at(&tail, i) == 0x0605_4b50).ok_or_else(|| invalid("not a zip file"))?;
+    let (count, size, start) = (u16_at(&tail, eocd + 10) as usize, u32_at(&tail, eocd + 12) as usize, u32_at(&tail, eocd + 16) as u64);
+    r.seek(SeekFrom::Start(start))?;
+    let mut dir = vec![0; size];
+    r.read_exact(&mut dir)?;
+    let mut entries = Vec::with_capacity(count);
+    let mut i = 0;
+    for _ in 0..count {
+        if i + 46 > dir.len() || u32_at(&dir, i) != 0x0201_4b50 { return Err(invalid("bad zip central directory")); }
+        let (name_len, extra_len, comment_len) = (u16_at(&dir, i + 28) as usize, u16_at(&dir, i + 30) as usize, u16_at(&dir, i + 32) as usize);
+        let name = dir.get(i + 46..i + 46 + name_len).ok_or_else(|| invalid("bad zip central directory"))?;
+        entries.push(ZipEntry{ name: String::from_utf8_lossy(name).into_owned(), method: u16_at(&dir, i + 10), compressed: u32_at(&dir, i + 20) as u64, offset: u32_at(&dir, i + 42) as u64 });
+        i += 46 + name_len + extra_len + comment_len;
+    }
+    Ok(entries)
+}
+
+/// Contents of one zip entry as text.
+fn zip_read<R: Read + Seek>(r: &mut R, e: &ZipEntry) -> io::Result<String> {
+    let mut local = [0u8; 30];
+    r.seek(SeekFrom::Start(e.offset))?;
+    r.read_exact(&mut local)?;
+    if u32_at(&local, 0) != 0x0403_4b50 { return Err(invalid("bad zip local header")); }
+    r.seek(SeekFrom::Current(u16_at(&local, 26) as i64 + u16_at(&local, 28) as i64))?;
+    let data = r.take(e.compressed);
+    let mut out = String::new();
+    match e.method {
+        0 => { let mut data = data; data.read_to_string(&mut out)?; }
+        8 => { flate2::read::DeflateDecoder::new(data).read_to_string(&mut out)?; }
+        m => return Err(invalid(&format!("{}: unsupported zip compression {m}", e.name))),
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::earliest_arrival;
+    use std::io::Write;
+
+    const STOPS: &str = "\u{feff}stop_id,stop_name,stop_lat,stop_lon\r\nA,\"Main St, North\",52.5,13.4\r\nB,Central,52.51,13.41\r\nC,End,52.52,13.42\r\nD,Annex,52.52,13.43\r\n";
+    const TIMES: &str = "trip_id,arrival_time,departure_time,stop_id,stop_sequence\nt2,08:30:00,08:30:00,A,1\nt2,08:40:00,08:41:00,B,2\nt1,08:00:00,08:00:00,A,1\nt1,08:10:00,08:12:00,B,2\nt1,,,C,3\nt1,08:30:00,08:30:00,C,4\nnight,24:10:00,24:10:00,B,1\nnight,24:20:00,24:20:00,C,2\n";
+    const TRANSFERS: &str = "from_stop_id,to_stop_id,transfer_type,min_transfer_time\nC,D,2,120\nC,C,1,\n";
+
+    fn check(feed: &GtfsGraph) {
+        assert_eq!(feed.stop_ids, ["A", "B", "C", "D"]);
+        assert_eq!((feed.trips, feed.graph.num_edges()), (3, 5));
+        assert_eq!(feed.coords[0], (13.4, 52.5));
+        let (a, d) = (feed.stop("A").unwrap(), feed.stop("D").unwrap());
+        // Leaving A at 07:55 catches t1: C at 08:30, D two minutes later.
+        let r = earliest_arrival(&feed.graph, &[(a, 7 * 3600 + 55 * 60)], Weight::MAX);
+        assert_eq!((r.dist[2], r.dist[d]), (8 * 3600 + 30 * 60, 8 * 3600 + 32 * 60));
+        // After 08:00, t2 only reaches B; C waits for the night trip.
+        let r = earliest_arrival(&feed.graph, &[(a, 8 * 3600 + 1)], Weight::MAX);
+        assert_eq!((r.dist[1], r.dist[2]), (8 * 3600 + 40 * 60, 24 * 3600 + 20 * 60));
+    }
+
+    #[test]
+    fn reads_a_feed_directory_and_zip() {
+        let dir = std::env::temp_dir().join(format!("bmssp-gtfs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, text) in [("stops.txt", STOPS), ("stop_times.txt", TIMES), ("transfers.txt", TRANSFERS)] {
+            std::fs::write(dir.join(name), text).unwrap();
+        }
+        check(&read_gtfs(&dir).unwrap());
+
+        // Same feed as a zip: stops stored, the rest deflated, under a folder.
+        let mut zip = Vec::new();
+        let mut central = Vec::new();
+        for (i, (name, text)) in [("feed/stops.txt", STOPS), ("feed/stop_times.txt", TIMES), ("feed/transfers.txt", TRANSFERS)].into_iter().enumerate() {
+            let (method, data) = if i == 0 { (0u16, text.as_bytes().to_vec()) } else {
+                let mut enc = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                enc.write_all(text.as_bytes()).unwrap();
+                (8, enc.finish().unwrap())
+            };
+            let offset = zip.len() as u32;
+            let header = |sig: u32, central_dir: bool| {
+                let mut h = sig.to_le_bytes().to_vec();
+                if central_dir { h.extend(20u16.to_le_bytes()); }
+                h.extend([20u16, 0, method, 0, 0].iter().flat_map(|x| x.to_le_bytes()));
+                h.extend([0u32, data.len() as u32, text.len() as u32].iter().flat_map(|x| x.to_le_bytes()));
+                h.extend([name.len() as u16, 0].iter().flat_map(|x| x.to_le_bytes()));
+                if central_dir { h.extend([0u16, 0, 0].iter().flat_map(|x| x.to_le_bytes())); h.extend([0u32, offset].iter().flat_map(|x| x.to_le_bytes())); }
+                h.extend(name.as_bytes());
+                h
+            };
+            let local = header(0x0403_4b50, false);
+            central.extend(header(0x0201_4b50, true));
+            zip.extend(local);
+            zip.extend(data);
+        }
+        let start = zip.len() as u32;
+        zip.extend(0x0605_4b50u32.to_le_bytes());
+        zip.extend([0u16, 0, 3, 3].iter().flat_map(|x| x.to_le_bytes()));
+        zip.extend([central.len() as u32, start].iter().flat_map(|x| x.to_le_bytes()));
+        zip.extend(0u16.to_le_bytes());
+        zip.splice(start as usize..start as usize, central);
+        let path = dir.join("feed.zip");
+        std::fs::File::create(&path).unwrap().write_all(&zip).unwrap();
+        check(&read_gtfs(&path).unwrap());
+
+        std::fs::write(dir.join("stop_times.txt"), "trip_id,stop_id\n").unwrap();
+        assert!(read_gtfs(&dir).unwrap_err().to_string().contains("missing column stop_sequence"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,169 @@
+//! Hub labels (2-hop cover) via pruned landmark labeling: an offline distance oracle for
+//! comparing against online BMSSP on repeated small-radius queries.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::ch::ContractionHierarchy;
+use crate::{settle_order_and_boundary, BmsspResult, Graph, Node, Weight};
+
+#[derive(Debug, Clone)]
+pub struct HubLabels {
+    /// `out[u]`: `(hub rank, dist(u, hub))`, sorted by rank.
+    out: Vec<Vec<(usize, Weight)>>,
+    /// `inn[v]`: `(hub rank, dist(hub, v))`, sorted by rank.
+    inn: Vec<Vec<(usize, Weight)>>,
+    /// `by_hub[r]`: `(v, dist(hub r, v))` for every `inn` entry, sorted by distance.
+    by_hub: Vec<Vec<(Node, Weight)>>,
+}
+
+/// Build labels for `g`, taking hubs in decreasing contraction-hierarchy rank (a plain degree
+/// order gives labels several times larger on road-like graphs). Exact for any non-negative weights.
+pub fn build(g: &Graph) -> HubLabels {
+    let order: Vec<Node> = ContractionHierarchy::build(g).order.into_iter().rev().collect();
+    build_with_order(g, &order)
+}
+
+/// Build labels processing hubs in `order` (a permutation of the node ids); earlier is more important.
+pub fn build_with_order(g: &Graph, order: &[Node]) -> HubLabels {
+    let n = g.len();
+    assert_eq!(order.len(), n, "order must cover every node");
+    let rev = g.transpose();
+    let mut hl = HubLabels { out: vec![Vec::new(); n], inn: vec![Vec::new(); n], by_hub: Vec::new() };
+    let mut dist = vec![Weight::MAX; n];
+    let mut touched = Vec::new();
+    let mut hub_dist = vec![Weight::MAX; n];
+    for (r, &h) in order.iter().enumerate() {
+        if g.is_removed(h) { continue; }
+        // Forward search fills `inn` labels, backward search fills `out` labels.
+        for forward in [true, false] {
+            let (adj, own) = if forward { (g, &hl.out[h]) } else { (&rev, &hl.inn[h]) };
+            for &(hr, d) in own { hub_dist[hr] = d; }
+            let mut heap = BinaryHeap::new();
+            dist[h] = 0;
+            touched.push(h);
+            heap.push(Reverse((0, h)));
+            let mut added = Vec::new();
+            while let Some(Reverse((d, u))) = heap.pop() {
+                if d != dist[u] { continue; }
+                // Prune when existing labels already certify a path of length <= d.
+                let other = if forward { &hl.inn[u] } else { &hl.out[u] };
+                if other.iter().any(|&(hr, d2)| hub_dist[hr] != Weight::MAX && hub_dist[hr].saturating_add(d2) <= d) { continue; }
+                added.push((u, d));
+                for &(v, w) in &adj.adj[u] {
+                    let nd = d.saturating_add(w);
+                    if nd < dist[v] {
+                        if dist[v] == Weight::MAX { touched.push(v); }
+                        dist[v] = nd;
+                        heap.push(Reverse((nd, v)));
+                    }
+                }
+            }
+            for &(hr, _) in own { hub_dist[hr] = Weight::MAX; }
+            for &v in &touched { dist[v] = Weight::MAX; }
+            touched.clear();
+            for (u, d) in added {
+                if forward { hl.inn[u].push((r, d)); } else { hl.out[u].push((r, d)); }
+            }
+        }
+    }
+    hl.by_hub = vec![Vec::new(); n];
+    for (v, labels) in hl.inn.iter().enumerate() {
+        for &(r, d) in labels { hl.by_hub[r].push((v, d)); }
+    }
+    for list in &mut hl.by_hub { list.sort_unstable_by_key(|&(v, d)| (d, v)); }
+    hl
+}
+
+impl HubLabels {
+    pub fn len(&self) -> usize { self.out.len() }
+    pub fn is_empty(&self) -> bool { self.out.is_empty() }
+    /// Total number of `(hub, dist)` entries over both label sides.
+    pub fn label_entries(&self) -> usize { self.out.iter().chain(&self.inn).map(|l| l.len()).sum() }
+    pub fn avg_label_size(&self) -> f64 {
+        if self.is_empty() { 0.0 } else { self.label_entries() as f64 / (2 * self.len()) as f64 }
+    }
+    /// Labels plus the per-hub inverted index used by `bounded_neighborhood`.
+    pub fn memory_estimate_bytes(&self) -> usize {
+        let entry = std::mem::size_of::<(usize, Weight)>();
+        let vec = std::mem::size_of::<Vec<(usize, Weight)>>();
+        let inverted: usize = self.by_hub.iter().map(|l| l.len()).sum();
+        (self.label_entries() + inverted) * entry + 3 * self.len() * vec
+    }
+    /// `memory_estimate_bytes` at allocated capacities.
+    pub fn memory_actual_bytes(&self) -> usize {
+        [&self.out, &self.inn, &self.by_hub].into_iter().map(crate::nested_capacity_bytes).sum()
+    }
+
+    /// `dist(u, v)` by merging the two sorted labels.
+    pub fn query(&self, u: Node, v: Node) -> Option<Weight> {
+        let (a, b) = (&self.out[u], &self.inn[v]);
+        let (mut i, mut j, mut best) = (0, 0, Weight::MAX);
+        while i < a.len() && j < b.len() {
+            match a[i].0.cmp(&b[j].0) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => { best = best.min(a[i].1.saturating_add(b[j].1)); i += 1; j += 1; }
+            }
+        }
+        (best != Weight::MAX).then_some(best)
+    }
+
+    /// Every `(v, dist(u, v))` with `dist < bound`, in `(dist, node)` order.
+    pub fn bounded_neighborhood(&self, u: Node, bound: Weight) -> Vec<(Node, Weight)> {
+        let dist = self.bounded_dist(&[(u, 0)], bound);
+        let mut out: Vec<(Node, Weight)> = dist.iter().enumerate().filter(|e| *e.1 != Weight::MAX).map(|(v, &d)| (v, d)).collect();
+        out.sort_unstable_by_key(|&(v, d)| (d, v));
+        out
+    }
+
+    fn bounded_dist(&self, sources: &[(Node, Weight)], bound: Weight) -> Vec<Weight> {
+        let mut dist = vec![Weight::MAX; self.len()];
+        for &(s, d0) in sources {
+            if s >= self.len() || d0 >= bound { continue; }
+            for &(r, d1) in &self.out[s] {
+                let base = d0.saturating_add(d1);
+                for &(v, d2) in &self.by_hub[r] {
+                    let d = base.saturating_add(d2);
+                    if d >= bound { break; }
+                    if d < dist[v] { dist[v] = d; }
+                }
+            }
+        }
+        dist
+    }
+
+    /// Multi-source bounded search answered from labels; same result shape as the online solver.
+    /// `g` must be the labelled graph (used for `explored` order and B').
+    pub fn bmssp(&self, g: &Graph, sources: &[(Node, Weight)], bound: Weight) -> BmsspResult {
+        let dist = self.bounded_dist(sources, bound);
+        let (explored, b_prime) = settle_order_and_boundary(g, &dist, bound);
+        BmsspResult{ dist, explored, b_prime, ..Default::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn labels_answer_exact_distances() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut g = Graph::new(120);
+        for _ in 0..450 { g.add_edge(rng.gen_range(0..120), rng.gen_range(0..120), rng.gen_range(1..15)); }
+        let hl = build(&g);
+        assert!(hl.avg_label_size() > 0.0 && hl.memory_estimate_bytes() > 0);
+        for s in (0..120).step_by(5) {
+            let r = bounded_multi_source_shortest_paths(&g, &[(s, 0)], Weight::MAX);
+            for t in 0..120 { assert_eq!(hl.query(s, t), r.dist_of(t)); }
+            let b = bounded_multi_source_shortest_paths(&g, &[(s, 0)], 12);
+            let nb = hl.bounded_neighborhood(s, 12);
+            assert_eq!(nb.iter().map(|e| e.0).collect::<Vec<_>>(), b.explored);
+        }
+        let sources = [(1, 0), (40, 3), (99, 1)];
+        let expect = bounded_multi_source_shortest_paths(&g, &sources, 15);
+        let r = hl.bmssp(&g, &sources, 15);
+        assert_eq!((r.dist, r.explored, r.b_prime), (expect.dist, expect.explored, expect.b_prime));
+    }
+}
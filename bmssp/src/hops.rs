@@ -0,0 +1,106 @@
+//! Hop-limited search behind `BmsspOptions::max_hops`. With a hop cap the cheapest label is no
+//! longer enough: a costlier path with fewer edges may reach further. Labels carry their hop
+//! count and a node keeps accepting labels while they improve its best hop count.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::options::{BmsspOptions, Termination, TieBreak};
+use crate::visit::SearchVisitor;
+use crate::{BmsspResult, GraphLike, Node, Weight, NO_PRED};
+
+/// `(dist, tie, hops, node, parent, slot)`: keyed like the plain search's `(dist, node)` so
+/// settle order matches it, then by hops. The tie is the node id, or a push sequence number
+/// under `TieBreak::Insertion`; `slot` is the edge's position in the parent's neighbor list.
+type Label = (Weight, usize, u32, Node, Node, usize);
+
+pub(crate) fn hop_limited<G: GraphLike, V: SearchVisitor>(
+    g: &G,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    max_hops: u32,
+    opts: &BmsspOptions,
+    visitor: &mut V,
+) -> BmsspResult {
+    let n = g.num_nodes();
+    let mut dist = vec![Weight::MAX; n];
+    // Fewest hops among labels already popped for each node; labels popped later cost at least
+    // as much, so they're only useful with strictly fewer hops.
+    let mut best_hops = vec![u32::MAX; n];
+    let mut pred: Vec<Node> = if opts.track_predecessors { vec![NO_PRED; n] } else { Vec::new() };
+    let mut pred_slot: Vec<usize> = if opts.track_predecessors { vec![0; n] } else { Vec::new() };
+    let mut heap: BinaryHeap<Reverse<Label>> = BinaryHeap::new();
+    let insertion = opts.tie_break == TieBreak::Insertion;
+    let mut seq = 0usize;
+    let mut tie = |v: Node| if insertion { seq += 1; seq } else { v };
+    for &(s, d0) in sources {
+        if s < n && d0 < bound && !g.is_removed(s) && opts.allows_node(s) {
+            heap.push(Reverse((d0, tie(s), 0, s, NO_PRED, 0)));
+        }
+    }
+    let mut explored = Vec::new();
+    let (mut b_prime, mut edges_scanned, mut heap_pushes) = (Weight::MAX, 0, 0);
+    let mut termination = Termination::Bound;
+    let mut overflows = 0;
+    'search: while let Some(Reverse((d, _, h, v, parent, slot))) = heap.pop() {
+        if h >= best_hops[v] { continue; }
+        if dist[v] == Weight::MAX {
+            if let Some(t) = opts.stop_reason(&explored, edges_scanned) {
+                termination = t;
+                b_prime = b_prime.min(d);
+                break;
+            }
+            dist[v] = d;
+            if opts.track_predecessors { pred[v] = parent; pred_slot[v] = slot; }
+            visitor.on_settle(v, d);
+            explored.push(v);
+        }
+        best_hops[v] = h;
+        if h == max_hops { continue; }
+        for (i, (to, w)) in g.neighbors(v).enumerate() {
+            edges_scanned += 1;
+            if !opts.allows(v, to, w) { continue; }
+            let Some(nd) = opts.extend(d, w, &mut overflows) else {
+                termination = Termination::Overflow;
+                b_prime = d;
+                break 'search;
+            };
+            if nd >= bound {
+                visitor.on_boundary_candidate(to, nd);
+                if nd < b_prime { b_prime = nd; }
+            } else if h + 1 < best_hops[to] {
+                if dist[to] == Weight::MAX { visitor.on_relax(v, to, Weight::MAX, nd); }
+                heap.push(Reverse((nd, tie(to), h + 1, to, v, i)));
+                heap_pushes += 1;
+            }
+        }
+    }
+    BmsspResult{ dist, explored, b_prime, edges_scanned, heap_pushes, termination, overflows, pred, pred_slot, ..Default::default() }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{bmssp_with_options, BmsspOptions, Graph, Weight};
+
+    #[test]
+    fn cheap_long_path_does_not_block_short_one() {
+        // 0 -> 1 costs 1 via 0 -> 2 -> 1 (2 hops) but 5 directly; 1 -> 3 costs 1.
+        let mut g = Graph::new(4);
+        g.add_edge(0, 2, 0); g.add_edge(2, 1, 1); g.add_edge(0, 1, 5); g.add_edge(1, 3, 1);
+        let opts = BmsspOptions{ max_hops: Some(2), track_predecessors: true, ..Default::default() };
+        let r = bmssp_with_options(&g, &[(0, 0)], 100, &opts);
+        assert_eq!(r.dist, vec![0, 1, 0, 6]);
+        assert_eq!(r.pred, vec![crate::NO_PRED, 2, 0, 1]);
+        let r = bmssp_with_options(&g, &[(0, 0)], 100, &BmsspOptions{ max_hops: Some(1), ..Default::default() });
+        assert_eq!(r.dist, vec![0, 5, 0, Weight::MAX]);
+    }
+
+    #[test]
+    fn large_cap_matches_unlimited() {
+        let mut g = Graph::new(60);
+        for i in 0..59 { g.add_undirected_edge(i, i + 1, 1 + (i as Weight * 7) % 5); }
+        g.add_edge(0, 40, 9);
+        let plain = bmssp_with_options(&g, &[(0, 0), (30, 2)], 25, &BmsspOptions::default());
+        let capped = bmssp_with_options(&g, &[(0, 0), (30, 2)], 25, &BmsspOptions{ max_hops: Some(60), ..Default::default() });
+        assert_eq!((plain.dist, plain.explored, plain.b_prime), (capped.dist, capped.explored, capped.b_prime));
+    }
+}
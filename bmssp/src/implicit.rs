@@ -0,0 +1,122 @@
+//! Graphs defined by a neighbor function instead of stored edges, and a search whose memory
+//! scales with the explored region rather than the node count.
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry as MapEntry;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::{Entry, GraphLike, Node, Weight};
+
+/// `GraphLike` over a closure returning the out-edges of a node. Nothing is materialized; the
+/// closure is called once per settled node.
+pub struct ImplicitGraph<F> {
+    n: usize,
+    f: F,
+}
+
+impl<F: Fn(Node) -> Vec<(Node, Weight)>> ImplicitGraph<F> {
+    pub fn new(n: usize, f: F) -> Self { Self { n, f } }
+}
+
+impl ImplicitGraph<fn(Node) -> Vec<(Node, Weight)>> {
+    /// 4-connected `rows x cols` grid, node `r * cols + c`, every step costing `w`.
+    pub fn grid(rows: usize, cols: usize, w: Weight) -> ImplicitGraph<impl Fn(Node) -> Vec<(Node, Weight)>> {
+        ImplicitGraph::new(rows * cols, move |u| {
+            let (r, c) = (u / cols, u % cols);
+            let mut out = Vec::with_capacity(4);
+            if c > 0 { out.push((u - 1, w)); }
+            if c + 1 < cols { out.push((u + 1, w)); }
+            if r > 0 { out.push((u - cols, w)); }
+            if r + 1 < rows { out.push((u + cols, w)); }
+            out
+        })
+    }
+}
+
+impl<F: Fn(Node) -> Vec<(Node, Weight)>> GraphLike for ImplicitGraph<F> {
+    fn num_nodes(&self) -> usize { self.n }
+    fn neighbors(&self, u: Node) -> impl Iterator<Item = (Node, Weight)> + '_ { (self.f)(u).into_iter() }
+}
+
+/// Result of `bmssp_sparse`: labels only for touched nodes.
+#[derive(Debug, Clone, Default)]
+pub struct SparseResult {
+    /// Settled nodes and their distances; nodes absent here were not settled.
+    pub dist: HashMap<Node, Weight>,
+    pub explored: Vec<Node>,
+    pub b_prime: Weight,
+    pub edges_scanned: usize,
+    pub heap_pushes: usize,
+}
+
+/// `bounded_multi_source_shortest_paths` with hashed labels: O(explored) memory, so it works
+/// on implicit graphs far too large for a dense distance array.
+pub fn bmssp_sparse<G: GraphLike>(g: &G, sources: &[(Node, Weight)], bound: Weight) -> SparseResult {
+    let n = g.num_nodes();
+    let mut labels: HashMap<Node, Weight> = HashMap::new();
+    let mut heap: BinaryHeap<Reverse<Entry>> = BinaryHeap::new();
+    for &(s, d0) in sources {
+        if s < n && d0 < bound && d0 < *labels.get(&s).unwrap_or(&Weight::MAX) && !g.is_removed(s) {
+            labels.insert(s, d0);
+            heap.push(Reverse(Entry{ d: d0, v: s }));
+        }
+    }
+    let mut out = SparseResult{ b_prime: Weight::MAX, ..Default::default() };
+    while let Some(Reverse(Entry{ d, v })) = heap.pop() {
+        if labels[&v] != d { continue; }
+        out.explored.push(v);
+        for (to, w) in g.neighbors(v) {
+            out.edges_scanned += 1;
+            let nd = d.saturating_add(w);
+            if nd >= bound {
+                if nd < out.b_prime { out.b_prime = nd; }
+                continue;
+            }
+            let improved = match labels.entry(to) {
+                MapEntry::Vacant(e) => { e.insert(nd); true }
+                MapEntry::Occupied(mut e) if nd < *e.get() => { e.insert(nd); true }
+                MapEntry::Occupied(_) => false,
+            };
+            if improved {
+                heap.push(Reverse(Entry{ d: nd, v: to }));
+                out.heap_pushes += 1;
+            }
+        }
+    }
+    out.dist = labels;
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bounded_multi_source_shortest_paths, Graph};
+
+    #[test]
+    fn implicit_grid_matches_materialized() {
+        let (r, c) = (12, 9);
+        let mut g = Graph::new(r * c);
+        for u in 0..r * c {
+            if u % c + 1 < c { g.add_undirected_edge(u, u + 1, 2); }
+            if u / c + 1 < r { g.add_undirected_edge(u, u + c, 2); }
+        }
+        let ig = ImplicitGraph::grid(r, c, 2);
+        let sources = [(0, 0), (50, 3)];
+        let expect = bounded_multi_source_shortest_paths(&g, &sources, 11);
+        let dense = bounded_multi_source_shortest_paths(&ig, &sources, 11);
+        assert_eq!((&dense.dist, &dense.explored, dense.b_prime), (&expect.dist, &expect.explored, expect.b_prime));
+        let sparse = bmssp_sparse(&ig, &sources, 11);
+        assert_eq!(sparse.explored, expect.explored);
+        assert_eq!(sparse.b_prime, expect.b_prime);
+        assert!(sparse.explored.iter().all(|v| sparse.dist[v] == expect.dist[*v]));
+    }
+
+    #[test]
+    fn sparse_search_on_huge_implicit_grid() {
+        let ig = ImplicitGraph::grid(100_000, 100_000, 1);
+        let center = 50_000 * 100_000 + 50_000;
+        let r = bmssp_sparse(&ig, &[(center, 0)], 10);
+        // Diamond of L1 radius 9: 1 + 4 * (1 + ... + 9) nodes.
+        assert_eq!(r.explored.len(), 1 + 4 * 45);
+        assert_eq!(r.b_prime, 10);
+    }
+}
@@ -0,0 +1,429 @@
+//! Plain-text graph and source files shared with the other language entries.
+//!
+//! Edge list: a `n m` header line, then one `u v w` line per directed edge.
+//! Sources: a `k` header line, then one `s d0` line per source.
+//! Distances (`.dist`): one `v d` line per settled node, any order; `#` lines are comments.
+//! Labels: one `v s` line per settled node, `s` its nearest source. Paths: one `v d: s ... v`
+//! line per settled node.
+//!
+//! `open_reader` / `create_writer` pick a codec from the extension: `.gz` and `.zst` need the
+//! `compress` feature, anything else is plain text.
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::{BmsspResult, Graph, Node, Weight};
+
+pub fn write_edge_list<W: Write>(g: &Graph, mut w: W) -> io::Result<()> {
+    writeln!(w, "{} {}", g.len(), g.num_edges())?;
+    for (u, edges) in g.adj.iter().enumerate() {
+        for &(v, wt) in edges { writeln!(w, "{u} {v} {wt}")?; }
+    }
+    w.flush()
+}
+
+/// Edge list in the format `write_edge_list` produces. The `m` header is only a hint.
+pub fn read_edge_list<R: BufRead>(r: R) -> io::Result<Graph> {
+    let edges = EdgeStream::new(r)?;
+    let mut g = Graph::new(edges.num_nodes());
+    for e in edges {
+        let (u, v, w) = e?;
+        g.add_edge(u, v, w);
+    }
+    Ok(g)
+}
+
+/// `u v w` as parsed from one edge-list line.
+type Edge = (Node, Node, Weight);
+
+/// `read_edge_list` over an in-memory file, split into `threads` byte ranges cut at line
+/// boundaries. Each range is parsed on its own thread and the edges are placed in file order,
+/// so the graph is identical to the sequential reader's.
+pub fn read_edge_list_parallel(bytes: &[u8], threads: usize) -> io::Result<Graph> {
+    let header_end = bytes.iter().position(|&c| c == b'\n').map_or(bytes.len(), |i| i + 1);
+    let n = EdgeStream::new(&bytes[..header_end])?.num_nodes();
+    let body = &bytes[header_end..];
+    let t = threads.max(1).min(body.len() / (1 << 16) + 1);
+    // Cut after the first newline at or past each even split point.
+    let mut cuts = vec![0];
+    for i in 1..t {
+        let at = (body.len() * i / t).max(*cuts.last().unwrap());
+        cuts.push(body[at..].iter().position(|&c| c == b'\n').map_or(body.len(), |j| at + j + 1));
+    }
+    cuts.push(body.len());
+    // Each chunk yields its edges, or the chunk-local line number of the first bad line.
+    let chunks: Vec<Result<Vec<Edge>, usize>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = cuts.windows(2).map(|w| {
+            let chunk = &body[w[0]..w[1]];
+            scope.spawn(move || {
+                let mut edges = EdgeStream{ r: chunk, line: String::new(), line_no: 0, n };
+                let mut out = Vec::new();
+                while let Some(e) = edges.next() { out.push(e.map_err(|_| edges.line_no)?); }
+                Ok(out)
+            })
+        }).collect();
+        handles.into_iter().map(|h| h.join().expect("parser thread panicked")).collect()
+    });
+    let mut parsed = Vec::with_capacity(chunks.len());
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        match chunk {
+            Ok(edges) => parsed.push(edges),
+            Err(local) => {
+                let before = 1 + body[..cuts[i]].iter().filter(|&&c| c == b'\n').count();
+                return Err(bad_line(before + local, "expected `u v w` with u, v < n"));
+            }
+        }
+    }
+    let mut deg = vec![0usize; n];
+    for &(u, _, _) in parsed.iter().flatten() { deg[u] += 1; }
+    let mut g = Graph::from_adj(deg.into_iter().map(Vec::with_capacity).collect());
+    for (u, v, w) in parsed.into_iter().flatten() { g.add_edge(u, v, w); }
+    Ok(g)
+}
+
+/// Edges of an edge list, parsed one line at a time into a reused buffer, so arbitrarily large
+/// inputs (a pipe from another tool, say) are never held in memory as text.
+pub struct EdgeStream<R> {
+    r: R,
+    line: String,
+    line_no: usize,
+    n: usize,
+}
+
+impl<R: BufRead> EdgeStream<R> {
+    /// Reads the `n m` header.
+    pub fn new(mut r: R) -> io::Result<Self> {
+        let mut line = String::new();
+        r.read_line(&mut line)?;
+        let n = line.split_whitespace().next().unwrap_or("0").parse().map_err(|_| bad_line(1, "expected `n m` header"))?;
+        Ok(Self{ r, line, line_no: 1, n })
+    }
+
+    pub fn num_nodes(&self) -> usize { self.n }
+}
+
+impl<R: BufRead> Iterator for EdgeStream<R> {
+    type Item = io::Result<Edge>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            match self.r.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => self.line_no += 1,
+                Err(e) => return Some(Err(e)),
+            }
+            if self.line.trim().is_empty() { continue; }
+            let mut ps = self.line.split_whitespace().map(|t| t.parse::<u64>().ok());
+            return Some(match (ps.next().flatten(), ps.next().flatten(), ps.next().flatten()) {
+                (Some(u), Some(v), Some(w)) if (u as usize) < self.n && (v as usize) < self.n => Ok((u as usize, v as usize, w)),
+                _ => Err(bad_line(self.line_no, "expected `u v w` with u, v < n")),
+            });
+        }
+    }
+}
+
+/// Sources in the format `write_sources` produces; a missing `d0` is 0.
+pub fn read_sources<R: BufRead>(r: R) -> io::Result<Vec<(Node, Weight)>> {
+    let mut lines = r.lines();
+    let header = lines.next().transpose()?.unwrap_or_default();
+    let k: usize = header.split_whitespace().next().unwrap_or("0").parse().map_err(|_| bad_line(1, "expected `k` header"))?;
+    let mut out = Vec::with_capacity(k);
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() { continue; }
+        let mut ps = line.split_whitespace();
+        let s = ps.next().and_then(|s| s.parse().ok()).ok_or_else(|| bad_line(i + 2, "expected `s d0`"))?;
+        let d0 = match ps.next() { Some(d) => d.parse().map_err(|_| bad_line(i + 2, "expected `s d0`"))?, None => 0 };
+        out.push((s, d0));
+    }
+    Ok(out)
+}
+
+/// One source offset `d0` per line, for `--source-offsets FILE`; blank lines are skipped.
+pub fn read_offsets<R: BufRead>(r: R) -> io::Result<Vec<Weight>> {
+    let mut out = Vec::new();
+    for (i, line) in r.lines().enumerate() {
+        let line = line?;
+        let t = line.trim();
+        if t.is_empty() { continue; }
+        out.push(t.parse().map_err(|_| bad_line(i + 1, "expected `d0`"))?);
+    }
+    Ok(out)
+}
+
+fn bad_line(line: usize, what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {line}: {what}"))
+}
+
+/// Buffered reader over `path`, decompressing `.gz` / `.zst` on the fly; `-` is stdin.
+pub fn open_reader(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    if path == Path::new("-") { return Ok(Box::new(io::stdin().lock())); }
+    let codec = codec(path)?;
+    let f = File::open(path)?;
+    match codec {
+        None => Ok(Box::new(BufReader::new(f))),
+        #[cfg(feature = "compress")]
+        Some("gz") => Ok(Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(BufReader::new(f))))),
+        #[cfg(feature = "compress")]
+        Some(_) => Ok(Box::new(BufReader::new(zstd::Decoder::new(f)?))),
+        #[cfg(not(feature = "compress"))]
+        Some(_) => unreachable!(),
+    }
+}
+
+/// Buffered writer to `path`, compressing for `.gz` / `.zst`. Compressed streams are finished
+/// when the writer is dropped.
+pub fn create_writer(path: &Path) -> io::Result<Box<dyn Write>> {
+    let codec = codec(path)?;
+    let f = File::create(path)?;
+    match codec {
+        None => Ok(Box::new(BufWriter::new(f))),
+        #[cfg(feature = "compress")]
+        Some("gz") => Ok(Box::new(BufWriter::new(flate2::write::GzEncoder::new(f, flate2::Compression::default())))),
+        #[cfg(feature = "compress")]
+        Some(_) => Ok(Box::new(BufWriter::new(zstd::Encoder::new(f, 0)?.auto_finish()))),
+        #[cfg(not(feature = "compress"))]
+        Some(_) => unreachable!(),
+    }
+}
+
+pub fn read_graph_file(path: &Path) -> io::Result<Graph> {
+    let _span = telemetry_span!(INFO, "bmssp.read_graph", path = %path.display());
+    read_edge_list(open_reader(path)?)
+}
+
+/// `read_graph_file` with `read_edge_list_parallel` when `threads > 1`. The whole (decompressed)
+/// file is held in memory while parsing.
+pub fn read_graph_file_parallel(path: &Path, threads: usize) -> io::Result<Graph> {
+    if threads <= 1 { return read_graph_file(path); }
+    let _span = telemetry_span!(INFO, "bmssp.read_graph", path = %path.display(), threads);
+    let mut bytes = Vec::new();
+    open_reader(path)?.read_to_end(&mut bytes)?;
+    read_edge_list_parallel(&bytes, threads)
+}
+
+pub fn read_sources_file(path: &Path) -> io::Result<Vec<(Node, Weight)>> { read_sources(open_reader(path)?) }
+
+pub fn write_graph_file(g: &Graph, path: &Path) -> io::Result<()> {
+    let _span = telemetry_span!(INFO, "bmssp.write_graph", path = %path.display(), n = g.len(), m = g.num_edges());
+    write_edge_list(g, create_writer(path)?)
+}
+
+/// `Some("gz" | "zst")` for compressed paths; an error if the `compress` feature is off.
+fn codec(path: &Path) -> io::Result<Option<&'static str>> {
+    let c = match path.extension().and_then(|e| e.to_str()) { Some("gz") => "gz", Some("zst") => "zst", _ => return Ok(None) };
+    if cfg!(feature = "compress") { return Ok(Some(c)); }
+    Err(io::Error::new(io::ErrorKind::Unsupported, format!("{}: .{c} files need the `compress` feature", path.display())))
+}
+
+pub fn write_sources<W: Write>(sources: &[(Node, Weight)], mut w: W) -> io::Result<()> {
+    writeln!(w, "{}", sources.len())?;
+    for &(s, d0) in sources { writeln!(w, "{s} {d0}")?; }
+    w.flush()
+}
+
+/// Settled distances in settle order, with B' in a leading comment.
+pub fn write_dists<W: Write>(res: &BmsspResult, mut w: W) -> io::Result<()> {
+    writeln!(w, "# B' {}", res.b_prime)?;
+    for &v in &res.explored { writeln!(w, "{v} {}", res.dist[v])?; }
+    w.flush()
+}
+
+/// Header shared by the crate's binary formats: 8 magic bytes, a `u32` version and a `u32` of
+/// flags, little-endian.
+pub(crate) fn write_frame_header<W: Write>(w: &mut W, magic: &[u8; 8], version: u32, flags: u32) -> io::Result<()> {
+    w.write_all(magic)?;
+    w.write_all(&version.to_le_bytes())?;
+    w.write_all(&flags.to_le_bytes())
+}
+
+/// Reads a whole file framed by `write_frame_header`, checking magic and version; returns the
+/// flags and the rest as little-endian `u64`s, at least `min_words` of them.
+pub(crate) fn read_frame<R: Read>(mut r: R, magic: &[u8; 8], version: u32, min_words: usize) -> io::Result<(u32, Vec<u64>)> {
+    let mut b = Vec::new();
+    r.read_to_end(&mut b)?;
+    let name = String::from_utf8_lossy(magic);
+    let bad = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    if b.len() < 16 + 8 * min_words || &b[..8] != magic { return Err(bad(format!("not a {name} file"))); }
+    if u32::from_le_bytes(b[8..12].try_into().unwrap()) != version { return Err(bad(format!("unsupported {name} version"))); }
+    let flags = u32::from_le_bytes(b[12..16].try_into().unwrap());
+    let words = b[16..].chunks(8).map(|c| c.try_into().map(u64::from_le_bytes)).collect::<Result<_, _>>()
+        .map_err(|_| bad(format!("{name} length is not a whole number of words")))?;
+    Ok((flags, words))
+}
+
+/// Write to `path` through a synced temporary file and a rename, so a kill mid-write leaves the
+/// previous file intact.
+pub(crate) fn save_atomically(path: &Path, write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let mut w = BufWriter::new(File::create(&tmp)?);
+    write(&mut w)?;
+    w.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+/// Magic bytes of a `write_dists_binary` file.
+pub const DISTS_MAGIC: &[u8; 8] = b"BMSSPDST";
+const DISTS_VERSION: u32 = 1;
+
+/// Dense distance array: `DISTS_MAGIC`, version and reserved `u32`s, then `n`, B' and one
+/// `u64` per node (`Weight::MAX` if unsettled), all little-endian. Fixed stride, so readers can
+/// map it and index by node id.
+pub fn write_dists_binary<W: Write>(res: &BmsspResult, mut w: W) -> io::Result<()> {
+    write_frame_header(&mut w, DISTS_MAGIC, DISTS_VERSION, 0)?;
+    w.write_all(&(res.dist.len() as u64).to_le_bytes())?;
+    w.write_all(&res.b_prime.to_le_bytes())?;
+    for (&d, settled) in res.dist.iter().zip(res.settled_mask()) {
+        let d = if settled { d } else { Weight::MAX };
+        w.write_all(&d.to_le_bytes())?;
+    }
+    w.flush()
+}
+
+/// Distances and B' of a `write_dists_binary` file.
+pub fn read_dists_binary<R: Read>(r: R) -> io::Result<(Vec<Weight>, Weight)> {
+    let (_, mut words) = read_frame(r, DISTS_MAGIC, DISTS_VERSION, 2)?;
+    let (n, b_prime) = (words[0], words[1]);
+    if n != words.len() as u64 - 2 { return Err(io::Error::new(io::ErrorKind::InvalidData, "BMSSPDST length does not match header")); }
+    words.drain(..2);
+    Ok((words, b_prime))
+}
+
+/// `node source` per settled node in settle order, `source` being the root of its tree path.
+/// Needs a search with tracked predecessors.
+pub fn write_labels<W: Write>(res: &BmsspResult, mut w: W) -> io::Result<()> {
+    let roots = res.roots().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "labels need tracked predecessors"))?;
+    for &v in &res.explored { writeln!(w, "{v} {}", roots[v])?; }
+    w.flush()
+}
+
+/// `node dist: source ... node` per settled node in settle order. Output grows with the path
+/// depth; needs a search with tracked predecessors.
+pub fn write_paths<W: Write>(res: &BmsspResult, mut w: W) -> io::Result<()> {
+    if res.pred.is_empty() { return Err(io::Error::new(io::ErrorKind::InvalidInput, "paths need tracked predecessors")); }
+    for &v in &res.explored {
+        write!(w, "{v} {}:", res.dist[v])?;
+        for u in res.path_to(v).unwrap_or_default() { write!(w, " {u}")?; }
+        writeln!(w)?;
+    }
+    w.flush()
+}
+
+pub fn read_dists<R: BufRead>(r: R) -> io::Result<Vec<(Node, Weight)>> { read_dist_file(r).map(|(pairs, _)| pairs) }
+
+/// Settled `(node, dist)` pairs of a `.dist` file and the B' from its header, if any.
+pub type DistFile = (Vec<(Node, Weight)>, Option<Weight>);
+
+/// Like `read_dists`, also returning B' from a `# B' x` comment if the file has one.
+pub fn read_dist_file<R: BufRead>(r: R) -> io::Result<DistFile> {
+    let mut out = Vec::new();
+    let mut b_prime = None;
+    for (i, line) in r.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if let Some(b) = line.strip_prefix("# B'") { b_prime = b.trim().parse().ok(); continue; }
+        if line.is_empty() || line.starts_with('#') { continue; }
+        let mut ps = line.split_whitespace();
+        let pair = ps.next().and_then(|v| v.parse().ok()).zip(ps.next().and_then(|d| d.parse().ok()));
+        match pair {
+            Some(p) => out.push(p),
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("line {}: expected `node dist`", i + 1))),
+        }
+    }
+    Ok((out, b_prime))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+
+    #[test]
+    fn dists_round_trip() {
+        let mut g = Graph::new(4);
+        g.add_edge(0, 1, 2);
+        g.add_edge(1, 2, 3);
+        g.add_edge(2, 3, 10);
+        let res = bounded_multi_source_shortest_paths(&g, &[(0, 0)], 8);
+        let mut buf = Vec::new();
+        write_dists(&res, &mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf.clone()).unwrap(), "# B' 15\n0 0\n1 2\n2 5\n");
+        assert_eq!(read_dist_file(&buf[..]).unwrap(), (res.settled_pairs(), Some(15)));
+        assert!(read_dists("1 x\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn binary_dists_labels_and_paths() {
+        // Two sources on a path 0 - 1 - 2 - 3 - 4; node 4 is past the bound.
+        let mut g = Graph::new(5);
+        for i in 0..4 { g.add_undirected_edge(i, i + 1, 1); }
+        let opts = crate::BmsspOptions{ track_predecessors: true, ..Default::default() };
+        let res = crate::bmssp_with_options(&g, &[(0, 0), (3, 1)], 2, &opts);
+        let mut buf = Vec::new();
+        write_dists_binary(&res, &mut buf).unwrap();
+        assert_eq!(buf.len(), 32 + 5 * 8);
+        assert_eq!(read_dists_binary(&buf[..]).unwrap(), (vec![0, 1, Weight::MAX, 1, Weight::MAX], 2));
+        assert!(read_dists_binary(&buf[..buf.len() - 1]).is_err());
+        let mut labels = Vec::new();
+        write_labels(&res, &mut labels).unwrap();
+        assert_eq!(String::from_utf8(labels).unwrap(), "0 0\n1 0\n3 3\n");
+        let mut paths = Vec::new();
+        write_paths(&res, &mut paths).unwrap();
+        assert_eq!(String::from_utf8(paths).unwrap(), "0 0: 0\n1 1: 0 1\n3 1: 3\n");
+        let plain = crate::bounded_multi_source_shortest_paths(&g, &[(0, 0)], 2);
+        assert_eq!(write_labels(&plain, Vec::new()).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn edge_list_and_sources_round_trip() {
+        let g = crate::generators::make_er(50, 0.1, 9, 3);
+        let mut buf = Vec::new();
+        write_edge_list(&g, &mut buf).unwrap();
+        assert_eq!(read_edge_list(&buf[..]).unwrap().adj, g.adj);
+        let sources = vec![(3, 0), (7, 5)];
+        let mut buf = Vec::new();
+        write_sources(&sources, &mut buf).unwrap();
+        assert_eq!(read_sources(&buf[..]).unwrap(), sources);
+        assert_eq!(read_sources("1\n4\n".as_bytes()).unwrap(), [(4, 0)]);
+        assert_eq!(read_offsets("3\n\n0\n17\n".as_bytes()).unwrap(), [3, 0, 17]);
+        assert!(read_offsets("3\nx\n".as_bytes()).unwrap_err().to_string().contains("line 2"));
+        let err = read_edge_list("2 1\n0 1 1\n\n0 5 1\n".as_bytes()).unwrap_err();
+        assert!(err.to_string().starts_with("line 4:"), "{err}");
+    }
+
+    #[test]
+    fn parallel_parse_matches_sequential() {
+        let g = crate::generators::make_er(2000, 0.01, 50, 9);
+        let mut buf = Vec::new();
+        write_edge_list(&g, &mut buf).unwrap();
+        buf.extend_from_slice(b"\n");
+        for t in [1, 2, 3, 8] {
+            assert_eq!(read_edge_list_parallel(&buf, t).unwrap().adj, g.adj, "threads={t}");
+        }
+        let bad_at = buf.len() / 2;
+        let line = 1 + buf[..bad_at].iter().filter(|&&c| c == b'\n').count();
+        buf.splice(bad_at..bad_at, b"x".iter().copied());
+        let err = read_edge_list_parallel(&buf, 4).unwrap_err();
+        assert!(err.to_string().starts_with(&format!("line {line}:")), "{err}");
+    }
+
+    #[test]
+    fn compressed_files_by_extension() {
+        let g = crate::generators::make_grid(6, 6, 9, 1);
+        let dir = std::env::temp_dir().join(format!("bmssp-io-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["g.txt", "g.txt.gz", "g.txt.zst"] {
+            let path = dir.join(name);
+            let written = write_graph_file(&g, &path);
+            if cfg!(feature = "compress") || name == "g.txt" {
+                written.unwrap();
+                assert_eq!(read_graph_file(&path).unwrap().adj, g.adj, "{name}");
+            } else {
+                assert_eq!(written.unwrap_err().kind(), io::ErrorKind::Unsupported);
+            }
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+//! Multimodal graphs: several graphs over the same `n` nodes stacked as layers (walk, bike,
+//! transit, ...), joined by transfer edges between copies of a node. `LayeredGraph` is a
+//! `GraphLike` view over the layers, so every bounded search runs across layers without
+//! copying them; node `v` of layer `l` has id `l * n + v`.
+use crate::{BmsspResult, GraphLike, Node, Weight};
+
+/// Transfer `(from_layer, to_layer, cost)`.
+type Transfer = (usize, usize, Weight);
+
+pub struct LayeredGraph<'g, G> {
+    layers: Vec<&'g G>,
+    n: usize,
+    /// Transfers available at every node.
+    everywhere: Vec<Transfer>,
+    /// Transfers at single nodes (stations, parking); empty until the first `transfer_at`.
+    at: Vec<Vec<Transfer>>,
+}
+
+impl<'g, G: GraphLike> LayeredGraph<'g, G> {
+    /// Stack `layers`, which must all have the same node count.
+    pub fn new(layers: Vec<&'g G>) -> Self {
+        let n = layers.first().map_or(0, |g| g.num_nodes());
+        assert!(layers.iter().all(|g| g.num_nodes() == n), "LayeredGraph layers need the same node count");
+        Self{ layers, n, everywhere: Vec::new(), at: Vec::new() }
+    }
+
+    /// Let every node switch from layer `from` to layer `to` at `cost`.
+    pub fn transfer(mut self, from: usize, to: usize, cost: Weight) -> Self {
+        assert!(from < self.layers.len() && to < self.layers.len(), "no such layer");
+        self.everywhere.push((from, to, cost));
+        self
+    }
+
+    /// Let node `v` alone switch from layer `from` to layer `to` at `cost`.
+    pub fn transfer_at(mut self, v: Node, from: usize, to: usize, cost: Weight) -> Self {
+        assert!(from < self.layers.len() && to < self.layers.len() && v < self.n, "no such layer or node");
+        if self.at.is_empty() { self.at = vec![Vec::new(); self.n]; }
+        self.at[v].push((from, to, cost));
+        self
+    }
+
+    pub fn num_layers(&self) -> usize { self.layers.len() }
+
+    /// Id of node `v` in layer `layer`.
+    #[inline]
+    pub fn id(&self, layer: usize, v: Node) -> Node {
+        assert!(layer < self.layers.len() && v < self.n, "no such layer or node");
+        layer * self.n + v
+    }
+
+    /// `(layer, node)` of an id.
+    #[inline]
+    pub fn split(&self, id: Node) -> (usize, Node) { (id / self.n, id % self.n) }
+
+    /// `sources` placed in `layer`. Nodes out of range are dropped, as the searches ignore them
+    /// on a plain graph, instead of landing in the next layer.
+    pub fn sources(&self, layer: usize, sources: &[(Node, Weight)]) -> Vec<(Node, Weight)> {
+        sources.iter().filter(|s| s.0 < self.n).map(|&(v, d0)| (self.id(layer, v), d0)).collect()
+    }
+
+    /// Best distance to each original node over all its layers, `Weight::MAX` if unsettled.
+    pub fn project(&self, res: &BmsspResult) -> Vec<Weight> {
+        let mut out = vec![Weight::MAX; self.n];
+        for &id in &res.explored { let v = id % self.n; out[v] = out[v].min(res.dist[id]); }
+        out
+    }
+}
+
+impl<G: GraphLike> GraphLike for LayeredGraph<'_, G> {
+    fn num_nodes(&self) -> usize { self.layers.len() * self.n }
+
+    fn neighbors(&self, id: Node) -> impl Iterator<Item = (Node, Weight)> + '_ {
+        let (layer, v) = self.split(id);
+        let base = layer * self.n;
+        let here = self.at.get(v).map_or(&[][..], |t| &t[..]);
+        let switch = move |&(from, to, cost): &Transfer| (from == layer).then_some((to * self.n + v, cost));
+        self.layers[layer].neighbors(v).map(move |(to, w)| (base + to, w))
+            .chain(self.everywhere.iter().filter_map(switch))
+            .chain(here.iter().filter_map(switch))
+    }
+
+    fn is_removed(&self, id: Node) -> bool {
+        let (layer, v) = self.split(id);
+        self.layers[layer].is_removed(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::make_grid;
+    use crate::{bounded_multi_source_shortest_paths, Graph};
+
+    #[test]
+    fn walk_and_transit_match_the_manual_stack() {
+        // Walking on a 20x20 grid; a fast line 0 -> 210 -> 399 boarded at its stations only.
+        let walk = make_grid(20, 20, 9, 1);
+        let mut transit = Graph::new(400);
+        transit.add_edge(0, 210, 3);
+        transit.add_edge(210, 399, 3);
+        let mut layered = LayeredGraph::new(vec![&walk, &transit]).transfer(1, 0, 0);
+        for station in [0, 210, 399] { layered = layered.transfer_at(station, 0, 1, 5); }
+
+        let mut manual = Graph::new(800);
+        for u in 0..400 {
+            for &(v, w) in &walk.adj[u] { manual.add_edge(u, v, w); }
+            for &(v, w) in &transit.adj[u] { manual.add_edge(400 + u, 400 + v, w); }
+            manual.add_edge(400 + u, u, 0);
+        }
+        for s in [0, 210, 399] { manual.add_edge(s, 400 + s, 5); }
+        assert_eq!(layered.num_nodes(), 800);
+        for v in 0..800 {
+            let (mut a, mut b): (Vec<_>, Vec<_>) = (layered.neighbors(v).collect(), manual.adj[v].clone());
+            a.sort_unstable();
+            b.sort_unstable();
+            assert_eq!(a, b, "id {v}");
+        }
+        let src = layered.sources(0, &[(0, 0)]);
+        for bound in [10, 30, Weight::MAX] {
+            let (a, b) = (bounded_multi_source_shortest_paths(&layered, &src, bound), bounded_multi_source_shortest_paths(&manual, &src, bound));
+            assert_eq!((&a.dist, a.b_prime), (&b.dist, b.b_prime));
+        }
+        let r = bounded_multi_source_shortest_paths(&layered, &src, Weight::MAX);
+        let best = layered.project(&r);
+        // Board (5), ride two legs (6), get off for free.
+        assert_eq!((best[399], r.dist[layered.id(1, 399)], layered.split(layered.id(1, 399))), (11, 11, (1, 399)));
+        assert!(best[399] < bounded_multi_source_shortest_paths(&walk, &[(0, 0)], Weight::MAX).dist[399]);
+    }
+
+    #[test]
+    fn out_of_range_sources_stay_out() {
+        let g = make_grid(3, 3, 4, 1);
+        let layered = LayeredGraph::new(vec![&g, &g]);
+        // Node 9 of layer 0 would otherwise be node 0 of layer 1.
+        assert_eq!(layered.sources(0, &[(9, 0), (4, 1)]), vec![(4, 1)]);
+        assert!(bounded_multi_source_shortest_paths(&layered, &layered.sources(0, &[(9, 0)]), 50).explored.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "no such layer or node")]
+    fn id_checks_the_node() {
+        let g = make_grid(3, 3, 4, 1);
+        LayeredGraph::new(vec![&g, &g]).id(0, 9);
+    }
+}
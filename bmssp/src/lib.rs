@@ -3,17 +3,174 @@
 //! Returns distances for nodes with d < B, explored set U, and tight boundary B'.
 use std::cmp::{Ordering, Reverse};
 use std::collections::BinaryHeap;
+use prefetch::PREFETCH_DISTANCE;
+use semiring::SemiringResult;
+
+// Telemetry: `tracing` spans and events with the `tracing` feature, zero-sized no-ops without.
+// `telemetry_span!(LEVEL, "name", fields..)` returns an entered guard; `telemetry_current!()`
+// captures the current span and subscriber so `telemetry_enter!(ctx)` can carry both onto
+// worker threads (a scoped default subscriber isn't inherited by spawned threads).
+#[cfg(feature = "tracing")]
+macro_rules! telemetry_span { ($lvl:ident, $($t:tt)*) => { tracing::span!(tracing::Level::$lvl, $($t)*).entered() } }
+#[cfg(feature = "tracing")]
+macro_rules! telemetry_event { ($lvl:ident, $($t:tt)*) => { tracing::event!(tracing::Level::$lvl, $($t)*) } }
+#[cfg(feature = "tracing")]
+macro_rules! telemetry_current { () => { (tracing::Span::current(), tracing::dispatcher::get_default(|d| d.clone())) } }
+#[cfg(feature = "tracing")]
+macro_rules! telemetry_enter {
+    ($ctx:expr) => {{
+        let (span, dispatch) = $ctx;
+        let default = tracing::dispatcher::set_default(&dispatch);
+        (span.entered(), default)
+    }};
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! telemetry_span { ($($t:tt)*) => { $crate::NoSpan } }
+#[cfg(not(feature = "tracing"))]
+macro_rules! telemetry_event { ($($t:tt)*) => {{}} }
+#[cfg(not(feature = "tracing"))]
+macro_rules! telemetry_current { () => { $crate::NoSpan } }
+#[cfg(not(feature = "tracing"))]
+macro_rules! telemetry_enter { ($span:expr) => { $span } }
+
+/// Stand-in for a span and its guard without the `tracing` feature.
+#[cfg(not(feature = "tracing"))]
+#[derive(Clone)]
+pub(crate) struct NoSpan;
+
+pub mod algo;
+pub mod analytics;
+pub mod batched;
+pub mod bfs;
+pub mod builder;
+pub mod ch;
+pub mod checkpoint;
+pub mod compact;
+pub mod coverage;
+pub mod deque;
+pub mod diff;
+pub mod dynamic;
+pub mod edge_data;
+pub mod formats;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod generators;
+pub mod geo;
+pub mod graph_like;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+#[cfg(feature = "gtfs")]
+pub mod gtfs;
+pub mod hl;
+mod hops;
+pub mod implicit;
+pub mod io;
+pub mod layered;
+pub mod multi_bound;
+pub mod numa;
+pub mod options;
+#[cfg(feature = "osm")]
+pub mod osm;
+pub mod parallel;
+pub mod pareto;
+pub mod partition;
+pub mod paths;
+pub mod per_source;
+pub mod phased;
+mod prefetch;
+pub mod queries;
+pub mod queue;
+pub mod reach;
+pub mod reorder;
+pub mod schema;
+pub mod semiring;
+pub mod simd;
+pub mod snapshot;
+pub mod solver;
+pub mod stats;
+pub mod stochastic;
+pub mod temporal;
+#[cfg(feature = "trace")]
+pub mod trace;
+pub mod turns;
+pub mod undirected;
+pub mod visit;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod widest;
+pub mod workload;
+pub use algo::{Algorithm, Prepared};
+pub use analytics::{approx_betweenness, bounded_closeness, component_subgraph, condensation, k_center_greedy, scc, sources_in_small_components, weak_components};
+pub use batched::bmssp_batched;
+pub use bfs::{bmssp_bfs, uniform_weight};
+pub use builder::{GraphBuilder, IdMap};
+pub use ch::ContractionHierarchy;
+pub use checkpoint::{bmssp_checkpointed, resume_from_checkpoint, Checkpoint};
+pub use compact::{bounded_multi_source_shortest_paths_compact, CompactGraph};
+pub use coverage::{coverage, coverage_threaded, Coverage, Selection};
+pub use deque::{bmssp_01bfs, bmssp_buckets, bmssp_small_weights, SmallWeightKernel, MAX_BUCKET_WEIGHT};
+pub use diff::{compare_results, Mismatch, ResultDiff};
+pub use dynamic::repair_after_decrease;
+pub use edge_data::{EdgeData, EdgeId, WeightFnView};
+pub use geo::{isochrone, Isochrone};
+pub use graph_like::GraphLike;
+/// Earlier name of [`GraphLike`].
+pub use graph_like::GraphLike as GraphRef;
+pub use hl::HubLabels;
+pub use implicit::{bmssp_sparse, ImplicitGraph, SparseResult};
+pub use layered::LayeredGraph;
+pub use multi_bound::{run_multi_bound, BoundSnapshot};
+pub use options::{AvoidSet, BmsspOptions, EdgeFilter, NodeFilter, OptionsSpec, OverflowPolicy, Termination, TieBreak};
+pub use parallel::{bmssp_parallel, bmssp_parallel_delta, bmssp_parallel_numa};
+pub use pareto::{pareto_bounded, ParetoResult};
+pub use partition::bmssp_partitioned;
+pub use paths::{bounded_shortest_path, k_shortest_bounded, shortest_path_dag};
+pub use per_source::bmssp_per_source_bounds;
+pub use phased::{phased_sssp, Phase, PhasedSssp};
+pub use queries::{read_queries, write_queries, Query};
+pub use queue::{bmssp_with_queue, bmssp_with_queue_kind, BinaryQueue, FrontierQueue, MultiLevelBuckets, PairingHeap, QueueKind};
+pub use reach::{reachable_within, Reachability};
+pub use schema::{check_row_schema, SchemaVersion, ROW_SCHEMA_VERSION};
+pub use semiring::{bmssp_semiring, CostSemiring, HopCount, MinMax, MinPlus, Reliability};
+pub use simd::bounded_multi_source_shortest_paths_simd;
+pub use solver::Solver;
+pub use stats::{DistHistogram, GraphStats, Summary};
+pub use stochastic::{normal_quantile, percentile_bounded, PercentileResult};
+pub use temporal::{earliest_arrival, TemporalEdge, TemporalGraph};
+pub use turns::TurnGraph;
+pub use undirected::UndirectedCsr;
+pub use visit::{NoopVisitor, SearchVisitor};
+pub use widest::bounded_widest_paths;
+pub use workload::{clustered_queries, rank_pairs, rank_queries, uniform_queries, RankPair};
+
+/// Heap bytes of a vector of vectors at their allocated capacities.
+pub(crate) fn nested_capacity_bytes<T>(v: &Vec<Vec<T>>) -> usize {
+    v.capacity() * std::mem::size_of::<Vec<T>>() + v.iter().map(|e| e.capacity()).sum::<usize>() * std::mem::size_of::<T>()
+}
 
 pub type Node = usize;
 pub type Weight = u64;
 
+/// With the `serde` feature, deserialized edges are not range-checked; run `validate` on
+/// untrusted input.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Graph {
     pub adj: Vec<Vec<(Node, Weight)>>,
+    /// Tombstones set by [`Graph::remove_node`]; empty until the first removal.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    removed: Vec<bool>,
+}
+impl From<Vec<Vec<(Node, Weight)>>> for Graph {
+    fn from(adj: Vec<Vec<(Node, Weight)>>) -> Self { Self::from_adj(adj) }
 }
 impl Graph {
-    pub fn new(n: usize) -> Self { Self { adj: vec![Vec::new(); n] } }
+    pub fn new(n: usize) -> Self { Self::from_adj(vec![Vec::new(); n]) }
+    /// Graph over prebuilt adjacency lists, with no removed nodes.
+    pub fn from_adj(adj: Vec<Vec<(Node, Weight)>>) -> Self { Self { adj, removed: Vec::new() } }
     pub fn len(&self) -> usize { self.adj.len() }
+    pub fn is_empty(&self) -> bool { self.adj.is_empty() }
+    pub fn num_edges(&self) -> usize { self.adj.iter().map(|v| v.len()).sum() }
     pub fn add_edge(&mut self, u: Node, v: Node, w: Weight) { self.adj[u].push((v,w)); }
     pub fn add_undirected_edge(&mut self, u: Node, v: Node, w: Weight) {
         self.add_edge(u,v,w); self.add_edge(v,u,w);
@@ -28,6 +185,112 @@ impl Graph {
         let flags_bytes = n * std::mem::size_of::<u8>() * 2;
         edge_bytes + vec_headers + outer_vec_header + dist_bytes + flags_bytes
     }
+
+    /// Like `memory_estimate_bytes`, but counting each `Vec` at its allocated capacity, so push
+    /// slack from incremental builds and file readers shows up. The search working set is modeled
+    /// the same way in both; allocator headers and fragmentation are not counted.
+    pub fn memory_actual_bytes(&self) -> usize {
+        let n = self.adj.len();
+        std::mem::size_of::<Self>() + nested_capacity_bytes(&self.adj) + self.removed.capacity()
+            + n * std::mem::size_of::<u64>() + n * std::mem::size_of::<u8>() * 2
+    }
+
+    /// Release spare capacity in every adjacency list (and the outer vectors), so
+    /// `memory_actual_bytes` drops to the exact-size figure.
+    pub fn shrink_to_fit_all(&mut self) {
+        for edges in &mut self.adj { edges.shrink_to_fit(); }
+        self.adj.shrink_to_fit();
+        self.removed.shrink_to_fit();
+    }
+
+    /// Remove every `u -> v` edge (including parallel copies). Returns whether any existed.
+    pub fn remove_edge(&mut self, u: Node, v: Node) -> bool {
+        let before = self.adj[u].len();
+        self.adj[u].retain(|&(to, _)| to != v);
+        self.adj[u].len() != before
+    }
+
+    /// Set the weight of every `u -> v` edge to `w`. Returns whether any existed.
+    pub fn update_edge_weight(&mut self, u: Node, v: Node, w: Weight) -> bool {
+        let mut found = false;
+        for e in self.adj[u].iter_mut().filter(|e| e.0 == v) { e.1 = w; found = true; }
+        found
+    }
+
+    /// Tombstone `v`: drop all incident edges and mark it removed so searches never settle it.
+    /// Node ids are not compacted, so existing source lists and results stay index-compatible.
+    pub fn remove_node(&mut self, v: Node) {
+        if self.removed.is_empty() { self.removed = vec![false; self.adj.len()]; }
+        self.removed[v] = true;
+        self.adj[v].clear();
+        for edges in &mut self.adj { edges.retain(|&(to, _)| to != v); }
+    }
+
+    pub fn is_removed(&self, v: Node) -> bool { self.removed.get(v).copied().unwrap_or(false) }
+
+    /// Graph with every edge reversed (`v -> u` for each `u -> v`); tombstones carry over.
+    pub fn transpose(&self) -> Graph {
+        let n = self.adj.len();
+        let mut indeg = vec![0usize; n];
+        for edges in &self.adj { for &(v, _) in edges { indeg[v] += 1; } }
+        let mut adj: Vec<Vec<(Node, Weight)>> = indeg.into_iter().map(Vec::with_capacity).collect();
+        for (u, edges) in self.adj.iter().enumerate() {
+            for &(v, w) in edges { adj[v].push((u, w)); }
+        }
+        Graph{ adj, removed: self.removed.clone() }
+    }
+
+    /// Scan for structural issues that break (or subtly skew) bounded searches:
+    /// self-loops, zero-weight edges, duplicate parallel edges, and targets >= n.
+    pub fn validate(&self) -> ValidationReport {
+        let n = self.adj.len();
+        let mut rep = ValidationReport::default();
+        let mut seen: Vec<usize> = vec![usize::MAX; n];
+        for (u, edges) in self.adj.iter().enumerate() {
+            for &(v, w) in edges {
+                if v >= n { rep.dangling.push((u, v)); continue; }
+                if u == v { rep.self_loops.push(u); }
+                if w == 0 { rep.zero_weight.push((u, v)); }
+                // `seen[v] == u` marks that u -> v was already encountered in this adjacency list.
+                if seen[v] == u { rep.parallel.push((u, v)); } else { seen[v] = u; }
+            }
+        }
+        rep
+    }
+
+    /// Sort every adjacency list by target and collapse parallel edges to the minimum weight.
+    /// Returns the number of edges removed.
+    pub fn dedup_min(&mut self) -> usize {
+        let mut removed = 0;
+        for edges in &mut self.adj {
+            let before = edges.len();
+            edges.sort_unstable();
+            // Sorted by (v, w), so the first entry of each run of equal targets has the min weight.
+            edges.dedup_by_key(|e| e.0);
+            removed += before - edges.len();
+        }
+        removed
+    }
+}
+
+/// Structured result of [`Graph::validate`]. Each list holds offending `(u, v)` pairs
+/// (or nodes for self-loops); parallel edges are reported once per extra copy.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub self_loops: Vec<Node>,
+    pub zero_weight: Vec<(Node, Node)>,
+    pub parallel: Vec<(Node, Node)>,
+    pub dangling: Vec<(Node, Node)>,
+}
+impl ValidationReport {
+    pub fn is_clean(&self) -> bool {
+        self.self_loops.is_empty() && self.zero_weight.is_empty() && self.parallel.is_empty() && self.dangling.is_empty()
+    }
+    /// One-line human summary, e.g. for the CLI's `--validate-graph`.
+    pub fn summary(&self) -> String {
+        format!("self_loops={} zero_weight={} parallel={} dangling={}",
+            self.self_loops.len(), self.zero_weight.len(), self.parallel.len(), self.dangling.len())
+    }
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -41,97 +304,365 @@ impl PartialOrd for Entry {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
 pub struct BmsspResult {
     pub dist: Vec<Weight>,
+    /// Settled nodes in settle order: `dist` is non-decreasing along it. Variants that settle
+    /// out of order (sharded, parallel, partitioned, ...) report `(dist, node)` order, which is
+    /// the sequential order unless zero-weight edges join nodes with equal labels.
     pub explored: Vec<Node>,
     pub b_prime: Weight,
     pub edges_scanned: usize,
     pub heap_pushes: usize,
+    pub termination: Termination,
+    /// Relaxations whose `dist + w` overflowed `Weight` (see `OverflowPolicy`).
+    pub overflows: usize,
+    /// Number of distinct shortest paths to each node (saturating); empty unless
+    /// `BmsspOptions::count_paths` was set.
+    pub path_counts: Vec<u64>,
+    /// Tree parent of each settled node (`NO_PRED` for roots and unreached nodes); empty unless
+    /// `BmsspOptions::track_predecessors` was set.
+    pub pred: Vec<Node>,
+    /// Slot of the tree edge `pred[v] -> v` in `pred[v]`'s neighbor list, parallel to `pred`;
+    /// index an `EdgeData` with it to get edge ids or attributes.
+    pub pred_slot: Vec<usize>,
+    /// Settled distances in buckets; `None` unless `BmsspOptions::histogram_width` was set and
+    /// they fit in `DistHistogram::MAX_BUCKETS` buckets.
+    pub dist_histogram: Option<DistHistogram>,
+}
+
+impl BmsspResult {
+    /// Implementation-independent fingerprint of the settled labels: 64-bit FNV-1a over the
+    /// text `"{v} {dist}\n"` for every settled `v` in increasing id order. Any port can
+    /// compute it without sharing code, and it doesn't depend on settle order or tie-breaking.
+    pub fn dist_digest(&self) -> u64 { settled_digest(&self.explored.iter().map(|&v| (v, self.dist[v])).collect::<Vec<_>>()) }
+
+    /// Whether `v` was settled. Unlike `dist[v] != Weight::MAX` this is also right after an
+    /// early stop, when unsettled nodes can hold tentative labels. Scans `explored`; to test
+    /// many nodes, build `settled_mask` once instead.
+    pub fn is_settled(&self, v: Node) -> bool { self.explored.contains(&v) }
+
+    /// `mask[v]` is whether `v` was settled, for every id below `dist.len()`.
+    pub fn settled_mask(&self) -> Vec<bool> {
+        let mut mask = vec![false; self.dist.len()];
+        for &v in &self.explored { mask[v] = true; }
+        mask
+    }
+
+    /// Number of settled nodes.
+    pub fn settled_count(&self) -> usize { self.explored.len() }
+
+    /// Exact order statistics (p50, p90, p99, ...) of the settled distances.
+    pub fn dist_summary(&self) -> Summary { Summary::of(&mut self.explored.iter().map(|&v| self.dist[v]).collect::<Vec<_>>()) }
+
+    /// Distance of `v` if it was settled. Prefer this over reading `dist`, where `Weight::MAX`
+    /// marks unreached nodes and early stops leave tentative labels.
+    pub fn dist_of(&self, v: Node) -> Option<Weight> { self.is_settled(v).then(|| self.dist[v]) }
+
+    /// `b_prime` as the bound for a follow-up phase; `None` if nothing lies past the bound.
+    pub fn next_bound(&self) -> Option<Weight> { (self.b_prime != Weight::MAX).then_some(self.b_prime) }
+
+    /// Bound the search actually reached: `bound` itself when it ran to the bound, the first
+    /// unsettled label after an early stop (`max_settled`, `max_edges_scanned`, ...). Every node
+    /// closer than this was settled, so `termination` plus this value describe a capped query
+    /// like "the closest 50k nodes under distance 600" in one pass. A cap can split a tie, leaving
+    /// some nodes at exactly this distance settled and others not.
+    pub fn effective_bound(&self, bound: Weight) -> Weight { self.b_prime.min(bound) }
+}
+
+/// `BmsspResult::dist_digest` for bare `(node, dist)` pairs, e.g. read back from a file.
+pub fn settled_digest(pairs: &[(Node, Weight)]) -> u64 {
+    let mut sorted = pairs.to_vec();
+    sorted.sort_unstable();
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for (v, d) in sorted {
+        for b in format!("{v} {d}\n").bytes() {
+            h = (h ^ b as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    h
 }
 
+/// Marker in `BmsspResult::pred` for sources and unreached nodes.
+pub const NO_PRED: Node = Node::MAX;
+
 /// Multi-source Dijkstra bounded by `bound`.
-pub fn bounded_multi_source_shortest_paths(
-    g: &Graph,
+pub fn bounded_multi_source_shortest_paths<G: GraphLike>(
+    g: &G,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+) -> BmsspResult {
+    bmssp_with_options(g, sources, bound, &BmsspOptions::default())
+}
+
+/// `bounded_multi_source_shortest_paths` with work budgets, a deadline and cancellation.
+/// On early stop, `b_prime` is lowered to the label of the first unsettled node so the result
+/// can be resumed as a phase boundary; `termination` says which limit fired.
+pub fn bmssp_with_options<G: GraphLike>(
+    g: &G,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    opts: &BmsspOptions,
+) -> BmsspResult {
+    bmssp_with_visitor(g, sources, bound, opts, &mut NoopVisitor)
+}
+
+/// `bmssp_with_options` that reports settles, improving relaxations and boundary candidates
+/// to `visitor`. Results are identical to the non-visiting call.
+pub fn bmssp_with_visitor<G: GraphLike, V: SearchVisitor>(
+    g: &G,
     sources: &[(Node, Weight)],
     bound: Weight,
+    opts: &BmsspOptions,
+    visitor: &mut V,
 ) -> BmsspResult {
-    let n = g.len();
-    let mut dist = vec![Weight::MAX; n];
-    let mut heap: BinaryHeap<Reverse<Entry>> = BinaryHeap::new();
+    let _span = telemetry_span!(DEBUG, "bmssp.search", n = g.num_nodes(), sources = sources.len(), bound);
+    if let Some(h) = opts.max_hops {
+        let mut res = hops::hop_limited(g, sources, bound, h, opts, visitor);
+        res.dist_histogram = opts.histogram_width.and_then(|w| DistHistogram::of(res.explored.iter().map(|&v| res.dist[v]), w));
+        return res;
+    }
+    let r = bmssp_core::<MinPlus, _, _>(g, sources, bound, opts, visitor);
+    telemetry_event!(DEBUG, settled = r.explored.len(), edges_scanned = r.edges_scanned, heap_pushes = r.heap_pushes, b_prime = r.b_prime,
+        termination = r.termination.as_str(), "search done");
+    let mut res = r.into_result();
+    res.dist_histogram = opts.histogram_width.and_then(|w| DistHistogram::of(res.explored.iter().map(|&v| res.dist[v]), w));
+    res
+}
+
+/// The sequential search over any `CostSemiring`; `bmssp_with_visitor` is its `MinPlus`
+/// instance and `bmssp_semiring` the option-less entry point for the others.
+pub(crate) fn bmssp_core<S: CostSemiring, G: GraphLike, V: SearchVisitor<S::Cost>>(
+    g: &G,
+    sources: &[(Node, S::Cost)],
+    bound: S::Cost,
+    opts: &BmsspOptions,
+    visitor: &mut V,
+) -> SemiringResult<S::Cost> {
+    let n = g.num_nodes();
+    let mut dist = vec![S::infinity(); n];
+    let mut heap: BinaryHeap<Reverse<(S::Cost, Node)>> = BinaryHeap::new();
     let mut explored = Vec::<Node>::new();
+    let mut counts: Vec<u64> = if opts.count_paths { vec![0; n] } else { Vec::new() };
+    let mut pred: Vec<Node> = if opts.track_predecessors { vec![NO_PRED; n] } else { Vec::new() };
+    let mut pred_slot: Vec<usize> = if opts.track_predecessors { vec![0; n] } else { Vec::new() };
+    // Insertion order: heap entries carry a push sequence number instead of the node id.
+    let insertion = opts.tie_break == TieBreak::Insertion;
+    let mut pushed: Vec<Node> = Vec::new();
 
     for &(s, d0) in sources {
-        if s < n && d0 < bound && d0 < dist[s] {
+        if s < n && d0 < bound && d0 < dist[s] && !g.is_removed(s) && opts.allows_node(s) {
             dist[s] = d0;
-            heap.push(Reverse(Entry{ d: d0, v: s }));
+            if opts.count_paths { counts[s] = 1; }
+            let key = if insertion { pushed.push(s); pushed.len() - 1 } else { s };
+            heap.push(Reverse((d0, key)));
         }
     }
-    let mut b_prime = Weight::MAX;
+    let mut b_prime = S::infinity();
     let mut edges_scanned: usize = 0;
     let mut heap_pushes: usize = 0;
 
-    while let Some(Reverse(Entry{ d, v })) = heap.pop() {
+    let mut termination = Termination::Bound;
+    let mut overflows = 0;
+
+    'search: while let Some(Reverse((d, v))) = heap.pop() {
+        let v = if insertion { pushed[v] } else { v };
         if d != dist[v] { continue; }
-    if d >= bound { b_prime = d; break; }
+        if d >= bound { visitor.on_boundary_candidate(v, d); b_prime = d; break; }
+        if let Some(t) = opts.stop_reason(&explored, edges_scanned) {
+            termination = t;
+            b_prime = b_prime.min(d);
+            break;
+        }
 
+        visitor.on_settle(v, d);
         explored.push(v);
-        for &(to, w) in &g.adj[v] {
+        for (i, (to, w)) in g.neighbors(v).enumerate() {
+            g.prefetch_edge_target(v, i + PREFETCH_DISTANCE, &dist);
             edges_scanned += 1;
-            let nd = d.saturating_add(w);
+            if !opts.allows(v, to, w) { continue; }
+            let Some(nd) = S::extend_with(d, w, opts, &mut overflows) else {
+                termination = Termination::Overflow;
+                b_prime = d;
+                break 'search;
+            };
             if nd < dist[to] && nd < bound {
+                visitor.on_relax(v, to, dist[to], nd);
                 dist[to] = nd;
-                heap.push(Reverse(Entry{ d: nd, v: to }));
+                if opts.count_paths { counts[to] = counts[v]; }
+                if opts.track_predecessors { pred[to] = v; pred_slot[to] = i; }
+                let key = if insertion { pushed.push(to); pushed.len() - 1 } else { to };
+                heap.push(Reverse((nd, key)));
                 heap_pushes += 1;
-            } else if nd >= bound && nd < b_prime {
-                b_prime = nd;
+            } else {
+                visitor.on_relax_rejected(v, to, dist[to], nd);
+                if nd >= bound {
+                    visitor.on_boundary_candidate(to, nd);
+                    if nd < b_prime { b_prime = nd; }
+                } else if opts.count_paths && nd == dist[to] {
+                    counts[to] = counts[to].saturating_add(counts[v]);
+                }
             }
         }
     }
+    SemiringResult{ dist, explored, b_prime, edges_scanned, heap_pushes, termination, overflows, path_counts: counts, pred, pred_slot }
+}
+
+/// Rebuild `explored` (sorted by `(dist, node)`, i.e. sequential settle order) and B' from final
+/// labels. Used by variants that settle out of order; B' follows the sequential definition:
+/// min over edges leaving U of `dist[u] + w` that land at or above the bound.
+pub(crate) fn settle_order_and_boundary<G: GraphLike>(g: &G, dist: &[Weight], bound: Weight) -> (Vec<Node>, Weight) {
+    let mut explored: Vec<Node> = (0..dist.len()).filter(|&v| dist[v] < bound).collect();
+    explored.sort_unstable_by_key(|&v| (dist[v], v));
+    let b_prime = boundary_of(g, &explored, dist, bound, &BmsspOptions::default());
+    (explored, b_prime)
+}
+
+/// Min of `dist[u] + w` at or above `bound` over edges `u -> v` from settled `u` that `opts` allows.
+fn boundary_of<G: GraphLike>(g: &G, explored: &[Node], dist: &[Weight], bound: Weight, opts: &BmsspOptions) -> Weight {
+    let mut b_prime = Weight::MAX;
+    for &v in explored {
+        for (to, w) in g.neighbors(v) {
+            if !opts.allows(v, to, w) { continue; }
+            let nd = dist[v].saturating_add(w);
+            if nd >= bound && nd < b_prime { b_prime = nd; }
+        }
+    }
+    b_prime
+}
 
-    BmsspResult{ dist, explored, b_prime, edges_scanned, heap_pushes }
+/// Backward bounded search: `dist[v]` is the distance from `v` to the nearest target (plus the
+/// target's offset), for all `v` that can reach a target within `bound`. Builds `g.transpose()`;
+/// callers issuing many backward queries should transpose once and use the forward search.
+pub fn bounded_reverse_search(
+    g: &Graph,
+    targets: &[(Node, Weight)],
+    bound: Weight,
+) -> BmsspResult {
+    bounded_multi_source_shortest_paths(&g.transpose(), targets, bound)
 }
 
 /// Parallel variant: split sources into `threads` shards, run bounded BMSSP per shard, and merge.
-/// Correct distances are the pointwise min over shard distances; b' is min over shard b'.
+/// Correct distances are the pointwise min over shard distances; `explored` and B' are rebuilt
+/// from the merged labels, so they match the sequential run (see `BmsspResult::explored`).
 /// Note: may do extra work vs true multi-source but is embarrassingly parallel when k is large.
-pub fn bmssp_sharded(
-    g: &Graph,
+/// Spawns fresh OS threads on every call; see `bmssp_sharded_in_pool` to reuse workers.
+pub fn bmssp_sharded<G: GraphLike + Sync>(
+    g: &G,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    threads: usize,
+) -> BmsspResult {
+    bmssp_sharded_with_options(g, sources, bound, threads, &BmsspOptions::default())
+}
+
+/// `bmssp_sharded` where every shard runs `bmssp_with_options`. Filters and `avoid` apply
+/// to each shard, so the merge matches the sequential filtered search; budgets are per shard.
+pub fn bmssp_sharded_with_options<G: GraphLike + Sync>(
+    g: &G,
     sources: &[(Node, Weight)],
     bound: Weight,
     threads: usize,
+    opts: &BmsspOptions,
 ) -> BmsspResult {
     let t = threads.max(1).min(sources.len().max(1));
-    if t <= 1 { return bounded_multi_source_shortest_paths(g, sources, bound); }
-    let mut shards: Vec<Vec<(Node,Weight)>> = vec![Vec::new(); t];
-    for (i, &sw) in sources.iter().enumerate() { shards[i % t].push(sw); }
+    if t <= 1 { return bmssp_with_options(g, sources, bound, opts); }
+    let _span = telemetry_span!(DEBUG, "bmssp.sharded", shards = t, sources = sources.len(), bound);
+    let shards = shard_sources(sources, t);
 
     let mut parts: Vec<BmsspResult> = Vec::with_capacity(t);
+    let parent = telemetry_current!();
     std::thread::scope(|scope| {
         let handles: Vec<_> = shards
             .into_iter()
-            .map(|shard| scope.spawn(move || bounded_multi_source_shortest_paths(g, &shard, bound)))
+            .map(|shard| {
+                let parent = parent.clone();
+                scope.spawn(move || { let _in = telemetry_enter!(parent); bmssp_with_options(g, &shard, bound, opts) })
+            })
             .collect();
         for h in handles {
             parts.push(h.join().expect("thread panicked"));
         }
     });
+    merge_shards(g, bound, opts, parts)
+}
+
+/// `bmssp_sharded` on a caller-owned rayon pool, so repeated small queries don't pay thread
+/// creation. Shard count is still `threads`; the pool decides how many run at once.
+#[cfg(feature = "rayon")]
+pub fn bmssp_sharded_in_pool<G: GraphLike + Sync>(
+    pool: &rayon::ThreadPool,
+    g: &G,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    threads: usize,
+) -> BmsspResult {
+    bmssp_sharded_in_pool_with_options(pool, g, sources, bound, threads, &BmsspOptions::default())
+}
 
+/// `bmssp_sharded_with_options` on a caller-owned rayon pool.
+#[cfg(feature = "rayon")]
+pub fn bmssp_sharded_in_pool_with_options<G: GraphLike + Sync>(
+    pool: &rayon::ThreadPool,
+    g: &G,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    threads: usize,
+    opts: &BmsspOptions,
+) -> BmsspResult {
+    use rayon::prelude::*;
+    let t = threads.max(1).min(sources.len().max(1));
+    if t <= 1 { return bmssp_with_options(g, sources, bound, opts); }
+    let _span = telemetry_span!(DEBUG, "bmssp.sharded", shards = t, sources = sources.len(), bound);
+    let shards = shard_sources(sources, t);
+    let parent = telemetry_current!();
+    let parts: Vec<BmsspResult> = pool.install(|| {
+        shards.par_iter().map(|shard| { let _in = telemetry_enter!(parent.clone()); bmssp_with_options(g, shard, bound, opts) }).collect()
+    });
+    merge_shards(g, bound, opts, parts)
+}
+
+fn shard_sources(sources: &[(Node, Weight)], t: usize) -> Vec<Vec<(Node, Weight)>> {
+    let mut shards: Vec<Vec<(Node,Weight)>> = vec![Vec::new(); t];
+    for (i, &sw) in sources.iter().enumerate() { shards[i % t].push(sw); }
+    shards
+}
+
+/// Pointwise-min the shard labels and put `explored` in `(dist, node)` order. A shard's B' can
+/// come from a node another shard settles, so complete runs recompute it from the merged labels;
+/// if any shard stopped early (or labels carry hop counts) B' stays the min over shards.
+/// Public so callers scheduling shards themselves (and the benches) can run or time the merge.
+pub fn merge_shards<G: GraphLike>(g: &G, bound: Weight, opts: &BmsspOptions, parts: Vec<BmsspResult>) -> BmsspResult {
+    let _span = telemetry_span!(DEBUG, "bmssp.merge", shards = parts.len());
     let mut merged = BmsspResult{
-        dist: vec![Weight::MAX; g.len()],
-        explored: Vec::new(),
+        dist: vec![Weight::MAX; g.num_nodes()],
         b_prime: Weight::MAX,
-        edges_scanned: 0,
-        heap_pushes: 0,
+        ..Default::default()
     };
-    use std::collections::HashSet;
-    let mut seen: HashSet<Node> = HashSet::new();
-    for r in parts {
+    for r in &parts {
         for (i, &d) in r.dist.iter().enumerate() { if d < merged.dist[i] { merged.dist[i] = d; } }
-        for &v in &r.explored { if seen.insert(v) { merged.explored.push(v); } }
         if r.b_prime < merged.b_prime { merged.b_prime = r.b_prime; }
         merged.edges_scanned += r.edges_scanned;
         merged.heap_pushes += r.heap_pushes;
+        merged.overflows += r.overflows;
+        if merged.termination == Termination::Bound { merged.termination = r.termination; }
+    }
+    // Each node once, from the first shard that settled it at its merged distance, in shard order.
+    let mut taken = vec![false; g.num_nodes()];
+    for r in &parts {
+        for &v in &r.explored {
+            if !taken[v] && r.dist[v] == merged.dist[v] { taken[v] = true; merged.explored.push(v); }
+        }
+    }
+    let dist = &merged.dist;
+    match opts.tie_break {
+        TieBreak::NodeId => merged.explored.sort_unstable_by_key(|&v| (dist[v], v)),
+        TieBreak::Insertion => merged.explored.sort_by_key(|&v| dist[v]),
+        TieBreak::Unspecified => merged.explored.sort_unstable_by_key(|&v| dist[v]),
+    }
+    if merged.termination == Termination::Bound && opts.max_hops.is_none() {
+        merged.b_prime = boundary_of(g, &merged.explored, &merged.dist, bound, opts);
     }
     merged
 }
@@ -221,6 +752,20 @@ mod tests {
         assert_eq!(res.b_prime, 7);
     }
 
+    #[test]
+    fn dist_digest_ignores_settle_order() {
+        let g = line_graph(6, 3);
+        let a = bounded_multi_source_shortest_paths(&g, &[(0,0),(5,0)], 7);
+        let mut b = a.clone();
+        b.explored.reverse();
+        assert_eq!(a.dist_digest(), b.dist_digest());
+        b.dist[b.explored[0]] += 1;
+        assert_ne!(a.dist_digest(), b.dist_digest());
+        // FNV-1a of "0 0\n": pins the byte format other ports must reproduce.
+        let single = bounded_multi_source_shortest_paths(&Graph::new(1), &[(0,0)], 1);
+        assert_eq!(single.dist_digest(), 0x37a8_3bf9_9ce7_e807);
+    }
+
     #[test]
     fn memory_estimate() {
         let mut g = Graph::new(5);
@@ -231,6 +776,289 @@ mod tests {
         assert!(g.memory_estimate_bytes() > 0);
     }
 
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn emits_search_shard_and_merge_spans() {
+        use std::sync::{Arc, Mutex};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        /// Records span names and counts events.
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<(Vec<&'static str>, usize)>>);
+        impl Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+            fn new_span(&self, a: &Attributes<'_>) -> Id {
+                let mut log = self.0.lock().unwrap();
+                log.0.push(a.metadata().name());
+                Id::from_u64(log.0.len() as u64)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &Event<'_>) { self.0.lock().unwrap().1 += 1; }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let rec = Recorder::default();
+        let g = generators::make_grid(10, 10, 5, 1);
+        tracing::subscriber::with_default(rec.clone(), || bmssp_sharded(&g, &generators::pick_sources(100, 4, 2), 30, 2));
+        let (names, events) = rec.0.lock().unwrap().clone();
+        // The shard searches run on worker threads and still reach the subscriber.
+        assert_eq!(names.iter().filter(|&&n| n == "bmssp.search").count(), 2);
+        assert_eq!((names[0], names.last().copied()), ("bmssp.sharded", Some("bmssp.merge")));
+        assert_eq!(events, 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trips() {
+        let mut g = Graph::new(4);
+        g.add_edge(0, 1, 2); g.add_edge(1, 2, 3); g.add_edge(2, 3, 1);
+        g.remove_node(3);
+        let h: Graph = serde_json::from_str(&serde_json::to_string(&g).unwrap()).unwrap();
+        assert_eq!((h.adj.clone(), h.is_removed(3)), (g.adj.clone(), true));
+        let spec: OptionsSpec = serde_json::from_str(r#"{"avoid":[2],"tie_break":"insertion","overflow":"wrap"}"#).unwrap();
+        assert_eq!((spec.avoid.nodes(), spec.tie_break, spec.overflow), (vec![2], TieBreak::Insertion, OverflowPolicy::WrapDebugPanic));
+        assert_eq!(serde_json::from_str::<OptionsSpec>(&serde_json::to_string(&spec).unwrap()).unwrap(), spec);
+        let r = bmssp_with_options(&h, &[(0, 0)], 10, &spec.to_options(std::time::Instant::now()));
+        let back: BmsspResult = serde_json::from_str(&serde_json::to_string(&r).unwrap()).unwrap();
+        assert!(back.is_settled(1) && !back.is_settled(2));
+        assert_eq!((back.dist, back.explored, back.b_prime), (r.dist, r.explored, r.b_prime));
+    }
+
+    #[test]
+    fn graph_from_adjacency_lists() {
+        let adj = vec![vec![(1, 2)], vec![(2, 3)], vec![]];
+        let g = Graph::from(adj.clone());
+        assert_eq!((g.num_edges(), g.is_removed(2)), (2, false));
+        assert_eq!(Graph::from_adj(adj).adj, g.adj);
+        assert_eq!(bounded_multi_source_shortest_paths(&g, &[(0, 0)], 10).dist, vec![0, 2, 5]);
+    }
+
+    #[test]
+    fn memory_actual_counts_capacity_slack() {
+        let mut g = Graph::new(4);
+        for v in 0..4 { g.adj[v].reserve(64); g.add_edge(v, (v + 1) % 4, 1); }
+        let slack = g.memory_actual_bytes();
+        assert!(slack > g.memory_estimate_bytes() + 4 * 60 * std::mem::size_of::<(Node, Weight)>());
+        g.shrink_to_fit_all();
+        assert!(g.memory_actual_bytes() < slack);
+        assert_eq!(g.memory_actual_bytes(), g.memory_estimate_bytes() + std::mem::size_of::<Graph>() - 3 * std::mem::size_of::<usize>());
+    }
+
+    #[test]
+    fn validate_reports_issues() {
+        let mut g = Graph::new(3);
+        g.add_edge(0,1,2);
+        g.add_edge(0,1,3);
+        g.add_edge(1,1,1);
+        g.add_edge(1,2,0);
+        g.add_edge(2,7,1);
+        let rep = g.validate();
+        assert!(!rep.is_clean());
+        assert_eq!(rep.self_loops, vec![1]);
+        assert_eq!(rep.zero_weight, vec![(1,2)]);
+        assert_eq!(rep.parallel, vec![(0,1)]);
+        assert_eq!(rep.dangling, vec![(2,7)]);
+        assert!(line_graph(4, 1).validate().is_clean());
+    }
+
+    #[test]
+    fn dedup_keeps_min_weight() {
+        let mut g = Graph::new(3);
+        g.add_edge(0,2,9);
+        g.add_edge(0,1,4);
+        g.add_edge(0,2,3);
+        g.add_edge(0,2,5);
+        let before = bounded_multi_source_shortest_paths(&g, &[(0,0)], 100);
+        assert_eq!(g.dedup_min(), 2);
+        assert_eq!(g.adj[0], vec![(1,4),(2,3)]);
+        assert!(g.validate().parallel.is_empty());
+        let after = bounded_multi_source_shortest_paths(&g, &[(0,0)], 100);
+        assert_eq!(before.dist, after.dist);
+    }
+
+    #[test]
+    fn mutations_reflected_in_search() {
+        let mut g = line_graph(5, 2);
+        g.add_edge(0, 4, 20);
+        let r = bounded_multi_source_shortest_paths(&g, &[(0,0)], 100);
+        assert_eq!(r.dist[4], 8);
+
+        assert!(g.update_edge_weight(0, 4, 1));
+        let r = bounded_multi_source_shortest_paths(&g, &[(0,0)], 100);
+        assert_eq!(r.dist[4], 1);
+
+        assert!(g.remove_edge(0, 4));
+        assert!(!g.remove_edge(0, 4));
+        let r = bounded_multi_source_shortest_paths(&g, &[(0,0)], 100);
+        assert_eq!(r.dist[4], 8);
+
+        g.remove_node(2);
+        assert!(g.is_removed(2));
+        let r = bounded_multi_source_shortest_paths(&g, &[(0,0),(2,0)], 100);
+        assert_eq!(r.dist[2], Weight::MAX);
+        assert_eq!(r.dist[3], Weight::MAX);
+        assert_eq!(r.explored, vec![0,1]);
+    }
+
+    #[test]
+    fn reverse_search_reaches_targets() {
+        let mut g = Graph::new(4);
+        g.add_edge(0,1,2);
+        g.add_edge(1,3,2);
+        g.add_edge(2,3,9);
+        g.add_edge(3,0,1);
+        let t = g.transpose();
+        assert_eq!(t.num_edges(), g.num_edges());
+        assert_eq!(t.adj[3], vec![(1,2),(2,9)]);
+        let r = bounded_reverse_search(&g, &[(3,0)], 5);
+        assert_eq!(r.dist, vec![4, 2, Weight::MAX, 0]);
+        assert_eq!(r.b_prime, 5);
+    }
+
+    #[test]
+    fn settled_membership_after_early_stop() {
+        let g = line_graph(10, 1);
+        let r = bmssp_with_options(&g, &[(0, 0)], 100, &BmsspOptions{ max_settled: Some(3), ..Default::default() });
+        assert_eq!(r.settled_count(), 3);
+        assert!(r.is_settled(2) && !r.is_settled(3) && !r.is_settled(1_000));
+        assert_ne!(r.dist[3], Weight::MAX, "node 3 holds a tentative label");
+        assert_eq!((r.dist_of(2), r.dist_of(3), r.next_bound()), (Some(2), None, Some(3)));
+        let full = bounded_multi_source_shortest_paths(&g, &[(0, 0)], 100);
+        assert_eq!((full.dist_of(9), full.next_bound()), (Some(9), None));
+    }
+
+    #[test]
+    fn membership_follows_edits_to_explored() {
+        let g = line_graph(10, 1);
+        let mut r = bmssp_with_options(&g, &[(0, 0)], 100, &BmsspOptions{ max_settled: Some(3), ..Default::default() });
+        assert!(r.is_settled(2) && !r.is_settled(3));
+        r.explored.retain(|&v| v != 2);
+        r.explored.push(3);
+        assert!(!r.is_settled(2) && r.is_settled(3));
+        assert_eq!(r.dist_of(3), Some(3));
+        assert_eq!(r.settled_mask()[..5], [true, true, false, true, false]);
+    }
+
+    #[test]
+    fn overflow_policies() {
+        let mut g = Graph::new(3);
+        g.add_edge(0, 1, Weight::MAX - 1);
+        g.add_edge(0, 2, 1);
+        let run = |overflow| bmssp_with_options(&g, &[(0, 5)], Weight::MAX, &BmsspOptions{ overflow, ..Default::default() });
+        let sat = run(OverflowPolicy::Saturate);
+        assert_eq!((sat.explored.clone(), sat.overflows, sat.termination), (vec![0, 2], 1, Termination::Bound));
+        let err = run(OverflowPolicy::Error);
+        assert_eq!((err.explored.clone(), err.overflows, err.termination), (vec![0], 1, Termination::Overflow));
+        assert_eq!(err.b_prime, 5);
+        let hops = bmssp_with_options(&g, &[(0, 5)], Weight::MAX, &BmsspOptions{ overflow: OverflowPolicy::Error, max_hops: Some(2), ..Default::default() });
+        assert_eq!((hops.overflows, hops.termination), (1, Termination::Overflow));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "distance overflow")]
+    fn overflow_wrap_panics_in_debug() {
+        let mut g = Graph::new(2);
+        g.add_edge(0, 1, Weight::MAX);
+        bmssp_with_options(&g, &[(0, 1)], Weight::MAX, &BmsspOptions{ overflow: OverflowPolicy::WrapDebugPanic, ..Default::default() });
+    }
+
+    #[test]
+    fn budgets_and_cancellation() {
+        use std::sync::atomic::AtomicBool;
+        let g = line_graph(50, 1);
+        let full = bounded_multi_source_shortest_paths(&g, &[(0,0)], 100);
+        assert_eq!(full.termination, Termination::Bound);
+
+        let opts = BmsspOptions{ max_settled: Some(10), ..Default::default() };
+        let r = bmssp_with_options(&g, &[(0,0)], 100, &opts);
+        assert_eq!(r.termination, Termination::SettledBudget);
+        assert_eq!(r.explored, full.explored[..10].to_vec());
+        assert_eq!(r.b_prime, 10);
+        assert_eq!((r.effective_bound(100), full.effective_bound(100)), (10, 100));
+        // Whichever of the cap and the bound comes first fires.
+        let capped = |bound| { let r = bmssp_with_options(&g, &[(0,0)], bound, &opts); (r.termination, r.settled_count(), r.effective_bound(bound)) };
+        assert_eq!(capped(6), (Termination::Bound, 6, 6));
+        assert_eq!(capped(30), (Termination::SettledBudget, 10, 10));
+
+        let opts = BmsspOptions{ max_edges_scanned: Some(7), ..Default::default() };
+        let r = bmssp_with_options(&g, &[(0,0)], 100, &opts);
+        assert_eq!(r.termination, Termination::EdgeBudget);
+        assert!(r.edges_scanned >= 7 && r.explored.len() < 50);
+
+        let cancel = AtomicBool::new(true);
+        let opts = BmsspOptions{ cancel: Some(&cancel), ..Default::default() };
+        let r = bmssp_with_options(&g, &[(0,0)], 100, &opts);
+        assert_eq!(r.termination, Termination::Cancelled);
+        assert!(r.explored.is_empty());
+
+        let opts = BmsspOptions{ deadline: Some(std::time::Instant::now()), ..Default::default() };
+        assert_eq!(bmssp_with_options(&g, &[(0,0)], 100, &opts).termination, Termination::Deadline);
+    }
+
+    #[test]
+    fn visitor_sees_settles_relaxes_and_boundary() {
+        #[derive(Default)]
+        struct Rec { settled: Vec<(Node, Weight)>, relaxes: usize, min_boundary: Option<Weight> }
+        impl SearchVisitor for Rec {
+            fn on_settle(&mut self, v: Node, d: Weight) { self.settled.push((v, d)); }
+            fn on_relax(&mut self, _u: Node, _v: Node, old: Weight, new: Weight) { assert!(new < old); self.relaxes += 1; }
+            fn on_boundary_candidate(&mut self, _v: Node, d: Weight) {
+                self.min_boundary = Some(self.min_boundary.map_or(d, |m| m.min(d)));
+            }
+        }
+        let g = random_graph_er(300, 0.02, 9, 21);
+        let sources = pick_sources(300, 4, 8);
+        let plain = bounded_multi_source_shortest_paths(&g, &sources, 25);
+        let mut rec = Rec::default();
+        let r = bmssp_with_visitor(&g, &sources, 25, &BmsspOptions::default(), &mut rec);
+        assert_eq!(r.dist, plain.dist);
+        assert_eq!(rec.settled.iter().map(|&(v, _)| v).collect::<Vec<_>>(), r.explored);
+        assert!(rec.settled.iter().all(|&(v, d)| r.dist[v] == d));
+        assert_eq!(rec.relaxes, r.heap_pushes);
+        assert_eq!(rec.min_boundary.unwrap_or(Weight::MAX), r.b_prime);
+    }
+
+    #[test]
+    fn filters_match_physically_filtered_graph() {
+        let g = random_graph_er(300, 0.02, 9, 33);
+        let sources = pick_sources(300, 5, 4);
+        let keep_node = |v: Node| v % 7 != 3;
+        let keep_edge = |u: Node, v: Node, w: Weight| w < 8 && !(u + v).is_multiple_of(5);
+        let mut h = Graph::new(300);
+        for u in 0..300 {
+            for &(v, w) in &g.adj[u] {
+                if keep_node(u) && keep_node(v) && keep_edge(u, v, w) { h.add_edge(u, v, w); }
+            }
+        }
+        let kept: Vec<_> = sources.iter().copied().filter(|&(s, _)| keep_node(s)).collect();
+        let expect = bounded_multi_source_shortest_paths(&h, &kept, 30);
+        let opts = BmsspOptions{ edge_filter: Some(&keep_edge), node_filter: Some(&keep_node), ..Default::default() };
+        let r = bmssp_with_options(&g, &sources, 30, &opts);
+        assert_eq!(r.dist, expect.dist);
+        assert_eq!(r.explored, expect.explored);
+        assert_eq!(r.b_prime, expect.b_prime);
+    }
+
+    #[test]
+    fn avoid_honored_by_sequential_and_sharded() {
+        let g = random_graph_er(300, 0.02, 9, 5);
+        let sources = pick_sources(300, 8, 6);
+        let avoid: Vec<Node> = (0..300).filter(|v| v % 11 == 2).chain([sources[0].0]).collect();
+        let opts = BmsspOptions::avoiding(&avoid);
+        let seq = bmssp_with_options(&g, &sources, 30, &opts);
+        assert!(avoid.iter().all(|&v| !seq.is_settled(v)));
+        let mut h = g.clone();
+        for &v in &avoid { h.remove_node(v); }
+        assert_eq!(seq.dist, bounded_multi_source_shortest_paths(&h, &sources, 30).dist);
+        let par = bmssp_sharded_with_options(&g, &sources, 30, 4, &opts);
+        assert_eq!(par.dist, seq.dist);
+        assert!(par.explored.iter().all(|v| !opts.avoid.contains(*v)));
+    }
+
     #[test]
     fn sharded_equivalence_on_er() {
         let n = 200usize;
@@ -244,6 +1072,46 @@ mod tests {
         assert_eq!(r_ref.dist.len(), r_sh.dist.len());
         for i in 0..n { assert_eq!(r_ref.dist[i], r_sh.dist[i], "dist mismatch at {}", i); }
         assert_eq!(r_ref.b_prime, r_sh.b_prime);
+        assert_eq!(r_ref.explored, r_sh.explored, "sharded merge should keep sequential settle order");
+    }
+
+    #[test]
+    fn tie_break_policies() {
+        let mut g = Graph::new(5);
+        g.add_edge(0, 3, 1);
+        g.add_edge(0, 1, 1);
+        g.add_edge(4, 2, 1);
+        let with = |t| BmsspOptions{ tie_break: t, ..Default::default() };
+        let seq = |sources: &[(Node, Weight)], o: &BmsspOptions| bmssp_with_options(&g, sources, 10, o).explored;
+        assert_eq!(seq(&[(0, 0)], &with(TieBreak::NodeId)), vec![0, 1, 3]);
+        assert_eq!(seq(&[(0, 0)], &with(TieBreak::Insertion)), vec![0, 3, 1]);
+        let hops = BmsspOptions{ max_hops: Some(3), ..with(TieBreak::Insertion) };
+        assert_eq!(seq(&[(0, 0)], &hops), vec![0, 3, 1]);
+
+        // One source per shard: insertion order survives the merge, ties across shards go by shard.
+        let sources = [(0, 0), (4, 0)];
+        let sharded = |t| bmssp_sharded_with_options(&g, &sources, 10, 2, &with(t)).explored;
+        assert_eq!(sharded(TieBreak::NodeId), vec![0, 4, 1, 2, 3]);
+        assert_eq!(sharded(TieBreak::NodeId), seq(&sources, &with(TieBreak::NodeId)));
+        assert_eq!(sharded(TieBreak::Insertion), vec![0, 4, 3, 1, 2]);
+        assert_eq!(sharded(TieBreak::Insertion), seq(&sources, &with(TieBreak::Insertion)));
+        let any = sharded(TieBreak::Unspecified);
+        assert_eq!(any.iter().map(|&v| [0, 1, 1, 1, 0][v]).collect::<Vec<Weight>>(), vec![0, 0, 1, 1, 1]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn sharded_pool_equivalence() {
+        let g = random_graph_er(200, 0.02, 5, 4321);
+        let sources = pick_sources(200, 10, 99);
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+        let a = bmssp_sharded(&g, &sources, 40, 4);
+        for _ in 0..3 {
+            let b = bmssp_sharded_in_pool(&pool, &g, &sources, 40, 4);
+            assert_eq!(a.dist, b.dist);
+            assert_eq!(a.explored, b.explored);
+            assert_eq!(a.b_prime, b.b_prime);
+        }
     }
 
     #[test]
@@ -254,13 +1122,11 @@ mod tests {
         let b1: Weight = 20; let b2: Weight = 40;
         let r1 = bounded_multi_source_shortest_paths(&g, &sources, b1);
         let r2 = bounded_multi_source_shortest_paths(&g, &sources, b2);
-        let f1 = r1.dist.iter().filter(|&&d| d < Weight::MAX).count();
-        let f2 = r2.dist.iter().filter(|&&d| d < Weight::MAX).count();
-        assert!(f2 >= f1, "more nodes should be settled with larger bound");
-        assert!(r1.b_prime == Weight::MAX || r1.b_prime >= b1);
-        assert!(r2.b_prime == Weight::MAX || r2.b_prime >= b2);
-        if r1.b_prime != Weight::MAX && r2.b_prime != Weight::MAX {
-            assert!(r2.b_prime >= r1.b_prime);
+        assert!(r2.settled_count() >= r1.settled_count(), "more nodes should be settled with larger bound");
+        assert!(r1.next_bound().is_none_or(|b| b >= b1));
+        assert!(r2.next_bound().is_none_or(|b| b >= b2));
+        if let (Some(a), Some(b)) = (r1.next_bound(), r2.next_bound()) {
+            assert!(b >= a);
         }
     }
 
@@ -271,12 +1137,10 @@ mod tests {
         let sources = pick_sources(n, 6, 1312);
         let r_small = bounded_multi_source_shortest_paths(&g, &sources, 15);
         let r_big = bounded_multi_source_shortest_paths(&g, &sources, 35);
-        assert!(r_small.explored.len() >= 1);
-        let f_small = r_small.dist.iter().filter(|&&d| d < Weight::MAX).count();
-        let f_big = r_big.dist.iter().filter(|&&d| d < Weight::MAX).count();
-        assert!(f_big >= f_small);
-        assert!(r_small.b_prime == Weight::MAX || r_small.b_prime >= 15);
-        assert!(r_big.b_prime == Weight::MAX || r_big.b_prime >= 35);
+        assert!(!r_small.explored.is_empty());
+        assert!(r_big.settled_count() >= r_small.settled_count());
+        assert!(r_small.next_bound().is_none_or(|b| b >= 15));
+        assert!(r_big.next_bound().is_none_or(|b| b >= 35));
     }
 
     fn make_er(n: usize, p: f64, maxw: u32, seed: u64) -> Graph {
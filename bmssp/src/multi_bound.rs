@@ -0,0 +1,104 @@
+//! Several bounds from one search, for bound-sensitivity sweeps. A single Dijkstra runs to the
+//! largest bound and records a `BoundSnapshot` whenever the next settle would reach the next
+//! bound; each snapshot equals what a separate `bounded_multi_source_shortest_paths` call at
+//! that bound reports.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::{Entry, GraphLike, Node, Weight};
+
+/// The search state at one bound.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoundSnapshot {
+    pub bound: Weight,
+    /// Nodes with a distance below `bound`.
+    pub settled: usize,
+    pub b_prime: Weight,
+    pub edges_scanned: usize,
+    /// Labels below `bound`, `Weight::MAX` elsewhere; only with `keep_dist`.
+    pub dist: Option<Vec<Weight>>,
+}
+
+/// One snapshot per entry of `bounds`, which must be sorted (repeats are fine). `keep_dist`
+/// copies the labels at every bound, O(n) each.
+///
+/// Sources join the heap only once the frontier reaches their offset, as if a source at or past
+/// a smaller bound did not exist. When a bound is crossed every relaxation so far came from a
+/// node below it, so its B' is the smallest of them at or above the bound: the queued labels,
+/// plus the rejected and superseded ones kept in a second heap.
+pub fn run_multi_bound<G: GraphLike>(g: &G, sources: &[(Node, Weight)], bounds: &[Weight], keep_dist: bool) -> Vec<BoundSnapshot> {
+    assert!(bounds.windows(2).all(|w| w[0] <= w[1]), "run_multi_bound needs sorted bounds");
+    let Some(&max) = bounds.last() else { return Vec::new() };
+    let n = g.num_nodes();
+    let mut dist = vec![Weight::MAX; n];
+    let mut seeds: Vec<(Weight, Node)> = sources.iter().filter(|&&(s, d0)| s < n && d0 < max && !g.is_removed(s)).map(|&(s, d0)| (d0, s)).collect();
+    seeds.sort_unstable();
+    let mut next_seed = 0;
+    let mut heap: BinaryHeap<Reverse<Entry>> = BinaryHeap::new();
+    let mut rejected: BinaryHeap<Reverse<Weight>> = BinaryHeap::new();
+    let (mut settled, mut edges_scanned) = (0, 0);
+    let mut snapshots = Vec::with_capacity(bounds.len());
+    loop {
+        while let Some(stale) = heap.peek().filter(|e| e.0.d != dist[e.0.v]).map(|e| e.0.d) {
+            heap.pop();
+            rejected.push(Reverse(stale));
+        }
+        let top = heap.peek().map(|e| e.0.d);
+        let seed = seeds.get(next_seed).map(|e| e.0);
+        let next = top.unwrap_or(Weight::MAX).min(seed.unwrap_or(Weight::MAX));
+        while let Some(&bound) = bounds.get(snapshots.len()).filter(|&&b| b <= next) {
+            while rejected.peek().is_some_and(|r| r.0 < bound) { rejected.pop(); }
+            let b_prime = top.unwrap_or(Weight::MAX).min(rejected.peek().map_or(Weight::MAX, |r| r.0));
+            let dist = keep_dist.then(|| dist.iter().map(|&d| if d < bound { d } else { Weight::MAX }).collect());
+            snapshots.push(BoundSnapshot{ bound, settled, b_prime, edges_scanned, dist });
+        }
+        if next == Weight::MAX { break; }
+        if seed == Some(next) {
+            let s = seeds[next_seed].1;
+            next_seed += 1;
+            if next < dist[s] {
+                dist[s] = next;
+                heap.push(Reverse(Entry{ d: next, v: s }));
+            }
+            continue;
+        }
+        let Some(Reverse(Entry{ d, v })) = heap.pop() else { break };
+        settled += 1;
+        for (to, w) in g.neighbors(v) {
+            edges_scanned += 1;
+            let nd = d.saturating_add(w);
+            if nd < dist[to] && nd < max {
+                dist[to] = nd;
+                heap.push(Reverse(Entry{ d: nd, v: to }));
+            } else {
+                rejected.push(Reverse(nd));
+            }
+        }
+    }
+    snapshots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use crate::generators::make_grid;
+
+    #[test]
+    fn snapshots_match_separate_runs() {
+        let mut g = make_grid(25, 25, 6, 4);
+        g.add_edge(3, 600, 0);
+        g.add_edge(600, 7, 0);
+        let sources = [(0, 0), (312, 9), (600, 30), (624, 55), (0, 4)];
+        let bounds = [0, 1, 9, 10, 10, 25, 31, 56, 80, 500];
+        let snaps = run_multi_bound(&g, &sources, &bounds, true);
+        assert_eq!(snaps.len(), bounds.len());
+        for s in &snaps {
+            let r = bounded_multi_source_shortest_paths(&g, &sources, s.bound);
+            assert_eq!((s.settled, s.b_prime, s.edges_scanned), (r.settled_count(), r.b_prime, r.edges_scanned), "B={}", s.bound);
+            assert_eq!(s.dist.as_ref(), Some(&r.dist), "B={}", s.bound);
+        }
+        assert!(run_multi_bound(&g, &sources, &[], false).is_empty());
+        assert_eq!(run_multi_bound(&g, &sources, &[40], false)[0].dist, None);
+    }
+}
@@ -0,0 +1,144 @@
+//! NUMA topology detection and placement helpers for the parallel variants.
+//!
+//! Topology is read from `/sys/devices/system/node` (Linux); elsewhere a single node spanning
+//! all available CPUs is assumed. Placement relies on first-touch: pages of arrays initialized by
+//! a worker land on that worker's node. Thread pinning needs the `numa` feature; without it the
+//! pin request is ignored and placement is best-effort.
+use crate::{Graph, Node, Weight};
+use std::sync::atomic::AtomicU64;
+
+/// CPU ids grouped by NUMA node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Topology {
+    pub nodes: Vec<Vec<usize>>,
+}
+
+impl Topology {
+    pub fn num_nodes(&self) -> usize { self.nodes.len() }
+
+    /// CPU for worker `i`: workers are spread round-robin across nodes, then across that node's CPUs.
+    pub fn cpu_for_worker(&self, i: usize) -> Option<usize> {
+        let node = self.nodes.get(i % self.nodes.len().max(1))?;
+        node.get((i / self.nodes.len()) % node.len().max(1)).copied()
+    }
+
+    /// Human-readable summary for benchmark rows, e.g. `2x16`.
+    pub fn describe(&self) -> String {
+        let per: Vec<String> = self.nodes.iter().map(|c| c.len().to_string()).collect();
+        if per.iter().all(|p| *p == per[0]) { format!("{}x{}", per.len(), per[0]) } else { per.join("+") }
+    }
+}
+
+/// Detect the machine's NUMA layout, falling back to one node with every available CPU.
+pub fn detect_topology() -> Topology {
+    let mut nodes: Vec<(usize, Vec<usize>)> = Vec::new();
+    if let Ok(entries) = std::fs::read_dir("/sys/devices/system/node") {
+        for e in entries.flatten() {
+            let name = e.file_name().to_string_lossy().into_owned();
+            let Some(id) = name.strip_prefix("node").and_then(|s| s.parse::<usize>().ok()) else { continue };
+            if let Ok(list) = std::fs::read_to_string(e.path().join("cpulist")) {
+                let cpus = parse_cpulist(&list);
+                if !cpus.is_empty() { nodes.push((id, cpus)); }
+            }
+        }
+    }
+    if nodes.is_empty() {
+        let n = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        return Topology { nodes: vec![(0..n).collect()] };
+    }
+    nodes.sort();
+    Topology { nodes: nodes.into_iter().map(|(_, c)| c).collect() }
+}
+
+/// Parse the kernel's cpulist syntax, e.g. `0-3,8,10-11`.
+pub fn parse_cpulist(s: &str) -> Vec<usize> {
+    let mut out = Vec::new();
+    for part in s.trim().split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((a, b)) => if let (Ok(a), Ok(b)) = (a.parse::<usize>(), b.parse::<usize>()) { out.extend(a..=b) },
+            None => if let Ok(c) = part.parse() { out.push(c) },
+        }
+    }
+    out
+}
+
+/// Pin the calling thread to `cpu`. Returns whether pinning took effect.
+#[cfg(feature = "numa")]
+pub fn pin_current_thread(cpu: usize) -> bool {
+    core_affinity::set_for_current(core_affinity::CoreId { id: cpu })
+}
+
+/// Pin the calling thread to `cpu`. No-op without the `numa` feature.
+#[cfg(not(feature = "numa"))]
+pub fn pin_current_thread(_cpu: usize) -> bool { false }
+
+/// Contiguous node range owned by worker `i` of `parts` (same split used by placement and relaxation).
+pub fn owner_range(n: usize, parts: usize, i: usize) -> std::ops::Range<Node> {
+    let chunk = n.div_ceil(parts.max(1));
+    (i * chunk).min(n)..((i + 1) * chunk).min(n)
+}
+
+/// Distance array initialized to `Weight::MAX` by `parts` workers, each writing its
+/// `owner_range` first so those pages are placed on the worker's node.
+pub fn alloc_dist_first_touch(n: usize, parts: usize, topo: Option<&Topology>) -> Vec<AtomicU64> {
+    let parts = parts.max(1);
+    if parts == 1 { return (0..n).map(|_| AtomicU64::new(Weight::MAX)).collect(); }
+    let mut out: Vec<AtomicU64> = Vec::with_capacity(n);
+    std::thread::scope(|scope| {
+        let mut rest = &mut out.spare_capacity_mut()[..n];
+        for i in 0..parts {
+            let (mine, tail) = rest.split_at_mut(owner_range(n, parts, i).len());
+            rest = tail;
+            scope.spawn(move || {
+                if let Some(cpu) = topo.and_then(|t| t.cpu_for_worker(i)) { pin_current_thread(cpu); }
+                for slot in mine { slot.write(AtomicU64::new(Weight::MAX)); }
+            });
+        }
+    });
+    // SAFETY: the owner ranges tile 0..n, and every slot was written by exactly one worker above.
+    unsafe { out.set_len(n) };
+    out
+}
+
+/// Copy of `g` whose adjacency lists are allocated by the worker that owns them.
+pub fn place_graph_first_touch(g: &Graph, parts: usize, topo: Option<&Topology>) -> Graph {
+    let n = g.len();
+    let parts = parts.max(1);
+    let chunks: Vec<Vec<Vec<(Node, Weight)>>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..parts)
+            .map(|i| scope.spawn(move || {
+                if let Some(cpu) = topo.and_then(|t| t.cpu_for_worker(i)) { pin_current_thread(cpu); }
+                owner_range(n, parts, i).map(|u| g.adj[u].clone()).collect()
+            }))
+            .collect();
+        handles.into_iter().map(|h| h.join().expect("thread panicked")).collect()
+    });
+    Graph { adj: chunks.into_iter().flatten().collect(), removed: g.removed.clone() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpulist_parsing() {
+        assert_eq!(parse_cpulist("0-3,8,10-11\n"), vec![0, 1, 2, 3, 8, 10, 11]);
+        assert!(parse_cpulist("").is_empty());
+    }
+
+    #[test]
+    fn worker_spread() {
+        let t = Topology { nodes: vec![vec![0, 1], vec![2, 3]] };
+        let cpus: Vec<_> = (0..4).map(|i| t.cpu_for_worker(i).unwrap()).collect();
+        assert_eq!(cpus, vec![0, 2, 1, 3]);
+        assert_eq!(t.describe(), "2x2");
+        assert!(detect_topology().num_nodes() >= 1);
+    }
+
+    #[test]
+    fn owner_ranges_cover() {
+        let covered: Vec<Node> = (0..3).flat_map(|i| owner_range(10, 3, i)).collect();
+        assert_eq!(covered, (0..10).collect::<Vec<_>>());
+        assert_eq!(alloc_dist_first_touch(10, 3, None).len(), 10);
+    }
+}
@@ -0,0 +1,325 @@
+//! Per-query knobs for `bmssp_with_options`. `BmsspOptions::default()` reproduces
+//! `bounded_multi_source_shortest_paths` exactly.
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::{DistHistogram, Node, Weight};
+
+/// Edge predicate `(u, v, w) -> keep`.
+pub type EdgeFilter<'a> = &'a (dyn Fn(Node, Node, Weight) -> bool + Sync);
+/// Node predicate `v -> keep`.
+pub type NodeFilter<'a> = &'a (dyn Fn(Node) -> bool + Sync);
+
+/// Deadline and cancellation are polled every this many settled nodes, keeping
+/// `Instant::now()` and the atomic load out of the per-node cost.
+pub(crate) const CHECK_INTERVAL: usize = 256;
+
+/// Fixed set of nodes a search must not label, stored as a bitset over node ids. Serializes as
+/// the sorted node list.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(from = "Vec<Node>", into = "Vec<Node>"))]
+pub struct AvoidSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl AvoidSet {
+    pub fn new(nodes: &[Node]) -> Self {
+        let words_needed = nodes.iter().max().map_or(0, |&m| m / 64 + 1);
+        let mut words = vec![0u64; words_needed];
+        let mut len = 0;
+        for &v in nodes {
+            let (w, b) = (v / 64, 1u64 << (v % 64));
+            if words[w] & b == 0 { words[w] |= b; len += 1; }
+        }
+        Self { words, len }
+    }
+    pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+    #[inline]
+    pub fn contains(&self, v: Node) -> bool {
+        self.words.get(v / 64).is_some_and(|&w| w & (1u64 << (v % 64)) != 0)
+    }
+    /// Largest member.
+    pub fn max(&self) -> Option<Node> {
+        let (i, w) = self.words.iter().enumerate().rfind(|e| *e.1 != 0)?;
+        Some(i * 64 + 63 - w.leading_zeros() as usize)
+    }
+    /// Members in increasing id order.
+    pub fn nodes(&self) -> Vec<Node> { (0..self.words.len() * 64).filter(|&v| self.contains(v)).collect() }
+}
+
+impl From<Vec<Node>> for AvoidSet {
+    fn from(nodes: Vec<Node>) -> Self { Self::new(&nodes) }
+}
+
+impl From<AvoidSet> for Vec<Node> {
+    fn from(a: AvoidSet) -> Self { a.nodes() }
+}
+
+#[derive(Clone, Default)]
+pub struct BmsspOptions<'a> {
+    /// Stop before settling another node once this many edges were scanned.
+    pub max_edges_scanned: Option<usize>,
+    /// Stop once this many nodes are settled.
+    pub max_settled: Option<usize>,
+    /// Wall-clock cutoff, checked every `CHECK_INTERVAL` settles.
+    pub deadline: Option<Instant>,
+    /// Cooperative cancellation token, checked every `CHECK_INTERVAL` settles.
+    pub cancel: Option<&'a AtomicBool>,
+    /// Edges rejected here are neither relaxed nor counted towards B'; they still count as scanned.
+    pub edge_filter: Option<EdgeFilter<'a>>,
+    /// Rejected nodes are never labelled, including when passed as sources.
+    pub node_filter: Option<NodeFilter<'a>>,
+    /// Nodes treated as deleted for this query; cheaper than an equivalent `node_filter`.
+    pub avoid: AvoidSet,
+    /// Fill `BmsspResult::path_counts`. Exact for positive weights; zero-weight edges between
+    /// equal labels can be undercounted because such nodes may settle in either order.
+    pub count_paths: bool,
+    /// Fill `BmsspResult::pred` with the parent that last improved each label.
+    pub track_predecessors: bool,
+    /// Only settle nodes reachable within `bound` using at most this many edges. Switches to a
+    /// hop-aware label search (up to `max_hops + 1` labels per node); `count_paths` is ignored.
+    pub max_hops: Option<u32>,
+    /// Order among nodes settled at the same distance.
+    pub tie_break: TieBreak,
+    /// What a relaxation does when `dist + w` doesn't fit in `Weight`.
+    pub overflow: OverflowPolicy,
+    /// Fill `BmsspResult::dist_histogram` with buckets of this width; see `DistHistogram::of`.
+    pub histogram_width: Option<Weight>,
+    /// Stop with `Termination::Target` once this node is settled and expanded, for
+    /// point-to-point queries.
+    pub stop_at: Option<Node>,
+}
+
+/// The plain-data part of `BmsspOptions` (no filters, cancel token or `Instant`), for storing
+/// query settings or taking them over the wire. Missing fields deserialize to the defaults.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct OptionsSpec {
+    pub max_edges_scanned: Option<usize>,
+    pub max_settled: Option<usize>,
+    /// Deadline in milliseconds after the query starts.
+    pub deadline_ms: Option<u64>,
+    pub avoid: AvoidSet,
+    pub count_paths: bool,
+    pub track_predecessors: bool,
+    pub max_hops: Option<u32>,
+    pub tie_break: TieBreak,
+    pub overflow: OverflowPolicy,
+    pub histogram_width: Option<Weight>,
+    pub stop_at: Option<Node>,
+}
+
+impl OptionsSpec {
+    /// `Err` with the reason when these settings don't fit a search over `n` nodes with
+    /// bound `bound`: an `avoid` or `stop_at` id out of range, or a histogram width that is 0 or
+    /// needs more than `DistHistogram::MAX_BUCKETS` buckets below the bound.
+    pub fn validate(&self, n: usize, bound: Weight) -> Result<(), String> {
+        if let Some(v) = self.avoid.max().filter(|&v| v >= n) { return Err(format!("avoid node {v} out of range")); }
+        if let Some(v) = self.stop_at.filter(|&v| v >= n) { return Err(format!("stop_at node {v} out of range")); }
+        if let Some(w) = self.histogram_width.filter(|&w| !DistHistogram::fits(bound, w)) {
+            return Err(format!("histogram_width {w} must be positive and give at most {} buckets below the bound", DistHistogram::MAX_BUCKETS));
+        }
+        Ok(())
+    }
+
+    /// Options for a query that starts at `start`.
+    pub fn to_options(&self, start: Instant) -> BmsspOptions<'static> {
+        BmsspOptions{
+            max_edges_scanned: self.max_edges_scanned,
+            max_settled: self.max_settled,
+            deadline: self.deadline_ms.map(|ms| start + std::time::Duration::from_millis(ms)),
+            avoid: self.avoid.clone(),
+            count_paths: self.count_paths,
+            track_predecessors: self.track_predecessors,
+            max_hops: self.max_hops,
+            tie_break: self.tie_break,
+            overflow: self.overflow,
+            histogram_width: self.histogram_width,
+            stop_at: self.stop_at,
+            ..Default::default()
+        }
+    }
+}
+
+/// Handling of `dist + w > Weight::MAX` in `bmssp_with_options` and the sharded searches (the
+/// option-less variants always saturate). Every overflow is counted in `BmsspResult::overflows`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum OverflowPolicy {
+    /// Clamp to `Weight::MAX`, which is never below a bound, so the target is not reached
+    /// through that edge.
+    #[default]
+    Saturate,
+    /// Stop with `Termination::Overflow`; `b_prime` is lowered to the label being scanned.
+    Error,
+    /// Panic in debug builds and wrap in release, like plain `+`. For flushing out bad inputs.
+    #[cfg_attr(feature = "serde", serde(rename = "wrap"))]
+    WrapDebugPanic,
+}
+
+/// How `explored` orders nodes with equal distance. Variants without options (atomic,
+/// partitioned, PHAST, hub labels, ...) always report `NodeId` order.
+/// Serializes as `as_str`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TieBreak {
+    /// Smallest id first among labels queued together; merged variants sort by `(dist, node)`.
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "id"))]
+    NodeId,
+    /// First labelled, first settled. Sharded merges keep each shard's order and break the
+    /// remaining ties by shard.
+    #[cfg_attr(feature = "serde", serde(rename = "insertion"))]
+    Insertion,
+    /// Any order with non-decreasing `dist`; merged variants skip sorting ties.
+    #[cfg_attr(feature = "serde", serde(rename = "any"))]
+    Unspecified,
+}
+
+impl TieBreak {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TieBreak::NodeId => "id",
+            TieBreak::Insertion => "insertion",
+            TieBreak::Unspecified => "any",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        [TieBreak::NodeId, TieBreak::Insertion, TieBreak::Unspecified].into_iter().find(|t| t.as_str() == s)
+    }
+}
+
+impl fmt::Debug for BmsspOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BmsspOptions")
+            .field("max_edges_scanned", &self.max_edges_scanned)
+            .field("max_settled", &self.max_settled)
+            .field("deadline", &self.deadline)
+            .field("cancel", &self.cancel)
+            .field("edge_filter", &self.edge_filter.is_some())
+            .field("node_filter", &self.node_filter.is_some())
+            .field("avoid", &self.avoid.len())
+            .field("count_paths", &self.count_paths)
+            .field("track_predecessors", &self.track_predecessors)
+            .field("max_hops", &self.max_hops)
+            .field("tie_break", &self.tie_break)
+            .field("overflow", &self.overflow)
+            .field("histogram_width", &self.histogram_width)
+            .field("stop_at", &self.stop_at)
+            .finish()
+    }
+}
+
+/// Why a search stopped. Anything but `Bound` means the result is a valid prefix of the full
+/// answer: settled distances are exact and `b_prime` is the smallest label not yet settled.
+/// Serializes as `as_str`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Termination {
+    /// Frontier exhausted or reached the bound.
+    #[default]
+    Bound,
+    EdgeBudget,
+    SettledBudget,
+    Deadline,
+    Cancelled,
+    /// A relaxation overflowed under `OverflowPolicy::Error`.
+    Overflow,
+    /// `BmsspOptions::stop_at` was settled.
+    Target,
+}
+
+impl Termination {
+    pub const ALL: [Termination; 7] = [Termination::Bound, Termination::EdgeBudget, Termination::SettledBudget,
+        Termination::Deadline, Termination::Cancelled, Termination::Overflow, Termination::Target];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Termination::Bound => "bound",
+            Termination::EdgeBudget => "edge_budget",
+            Termination::SettledBudget => "settled_budget",
+            Termination::Deadline => "deadline",
+            Termination::Cancelled => "cancelled",
+            Termination::Overflow => "overflow",
+            Termination::Target => "target",
+        }
+    }
+}
+
+impl BmsspOptions<'_> {
+    /// Whether relaxing `u -> v` with weight `w` is allowed by the filters.
+    #[inline]
+    pub(crate) fn allows(&self, u: Node, v: Node, w: Weight) -> bool {
+        self.allows_node(v) && self.edge_filter.is_none_or(|f| f(u, v, w))
+    }
+
+    #[inline]
+    pub(crate) fn allows_node(&self, v: Node) -> bool {
+        !self.avoid.contains(v) && self.node_filter.is_none_or(|f| f(v))
+    }
+
+    /// `d + w` under `self.overflow`, bumping `overflows` when it doesn't fit. `None` means the
+    /// search has to stop (`OverflowPolicy::Error`).
+    #[inline]
+    pub(crate) fn extend(&self, d: Weight, w: Weight, overflows: &mut usize) -> Option<Weight> {
+        if let Some(nd) = d.checked_add(w) { return Some(nd); }
+        *overflows += 1;
+        match self.overflow {
+            OverflowPolicy::Saturate => Some(Weight::MAX),
+            OverflowPolicy::Error => None,
+            OverflowPolicy::WrapDebugPanic => {
+                debug_assert!(false, "distance overflow: {d} + {w}");
+                Some(d.wrapping_add(w))
+            }
+        }
+    }
+
+    /// Options that only avoid `nodes`.
+    pub fn avoiding(nodes: &[Node]) -> Self {
+        Self { avoid: AvoidSet::new(nodes), ..Default::default() }
+    }
+
+    /// Target/budget/cancellation check before settling the next node; `explored` is what has
+    /// been settled so far.
+    #[inline]
+    pub(crate) fn stop_reason(&self, explored: &[Node], scanned: usize) -> Option<Termination> {
+        let settled = explored.len();
+        if self.stop_at.is_some_and(|t| explored.last() == Some(&t)) { return Some(Termination::Target); }
+        if self.max_settled.is_some_and(|m| settled >= m) { return Some(Termination::SettledBudget); }
+        if self.max_edges_scanned.is_some_and(|m| scanned >= m) { return Some(Termination::EdgeBudget); }
+        if settled.is_multiple_of(CHECK_INTERVAL) {
+            if self.cancel.is_some_and(|c| c.load(Ordering::Relaxed)) { return Some(Termination::Cancelled); }
+            if self.deadline.is_some_and(|dl| Instant::now() >= dl) { return Some(Termination::Deadline); }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avoid_set_membership() {
+        let a = AvoidSet::new(&[3, 64, 3, 200]);
+        assert_eq!(a.len(), 3);
+        assert!(a.contains(3) && a.contains(64) && a.contains(200));
+        assert!(!a.contains(4) && !a.contains(63) && !a.contains(10_000));
+        assert!(AvoidSet::default().is_empty());
+        assert_eq!((a.max(), AvoidSet::new(&[0]).max(), AvoidSet::default().max()), (Some(200), Some(0), None));
+    }
+
+    #[test]
+    fn spec_validation() {
+        let ok = OptionsSpec{ avoid: AvoidSet::new(&[9]), stop_at: Some(9), histogram_width: Some(1), ..Default::default() };
+        assert_eq!(ok.validate(10, 100), Ok(()));
+        assert!(ok.validate(9, 100).unwrap_err().contains("avoid node 9"));
+        assert!(OptionsSpec{ stop_at: Some(10), ..Default::default() }.validate(10, 100).unwrap_err().contains("stop_at"));
+        assert!(OptionsSpec{ histogram_width: Some(0), ..Default::default() }.validate(10, 100).is_err());
+        assert!(OptionsSpec{ histogram_width: Some(1), ..Default::default() }.validate(10, Weight::MAX).is_err());
+    }
+}
@@ -0,0 +1,378 @@
+//! OpenStreetMap PBF import for road-network benchmarks (feature `osm`).
+//!
+//! Decodes the PBF container directly (raw and zlib blobs, dense and plain nodes, ways) with a
+//! small protobuf reader, keeps ways with a drivable `highway` tag and turns each consecutive
+//! pair of way nodes into an edge weighted by travel time in milliseconds: haversine length
+//! over `maxspeed` (or a per-class default). `oneway=yes|-1`, `junction=roundabout` and
+//! motorways are one-directional. Every way node becomes a graph node; degree-2 chains are
+//! not contracted. Node coordinates are buffered for the whole file, so memory grows with the
+//! extract's node count, not just the road nodes.
+use std::collections::HashMap;
+use std::io::{self, Read};
+
+use crate::{EdgeData, Graph, Node, Weight};
+
+/// Routing graph plus per-node positions and the OSM ids they came from, and per-edge way ids.
+#[derive(Debug, Clone)]
+pub struct OsmGraph {
+    pub graph: Graph,
+    /// `(lon, lat)` in degrees, as `geo::isochrone` expects.
+    pub coords: Vec<(f64, f64)>,
+    pub osm_ids: Vec<i64>,
+    /// Id of the way each edge was cut from, parallel to `graph.adj`.
+    pub way_ids: EdgeData<i64>,
+}
+
+/// Read a whole `.osm.pbf` stream.
+pub fn read_pbf<R: Read>(mut r: R) -> io::Result<OsmGraph> {
+    let mut nodes: Vec<(i64, f64, f64)> = Vec::new();
+    let mut ways: Vec<Road> = Vec::new();
+    loop {
+        let mut len = [0u8; 4];
+        match r.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+        let header = read_vec(&mut r, u32::from_be_bytes(len) as usize)?;
+        let (mut kind, mut size) = (&b""[..], 0);
+        for f in Pb(&header) {
+            match f? {
+                (1, Field::Bytes(b)) => kind = b,
+                (3, Field::Varint(v)) => size = v as usize,
+                _ => {}
+            }
+        }
+        let blob = read_vec(&mut r, size)?;
+        if kind == b"OSMData" { parse_block(&blob_data(&blob)?, &mut nodes, &mut ways)?; }
+    }
+    if !nodes.windows(2).all(|w| w[0].0 <= w[1].0) { nodes.sort_unstable_by_key(|n| n.0); }
+
+    let mut index: HashMap<i64, Node> = HashMap::new();
+    let mut out = OsmGraph{ graph: Graph::new(0), coords: Vec::new(), osm_ids: Vec::new(), way_ids: EdgeData::new(0) };
+    let mut node_of = |id: i64, out: &mut OsmGraph| -> Option<Node> {
+        if let Some(&v) = index.get(&id) { return Some(v); }
+        let i = nodes.binary_search_by_key(&id, |n| n.0).ok()?;
+        let v = out.osm_ids.len();
+        out.osm_ids.push(id);
+        out.coords.push((nodes[i].1, nodes[i].2));
+        out.graph.adj.push(Vec::new());
+        index.insert(id, v);
+        Some(v)
+    };
+    for way in &ways {
+        // Refs missing from a clipped extract break the way rather than bridging the gap.
+        let mut prev: Option<Node> = None;
+        for &id in &way.refs {
+            let cur = node_of(id, &mut out);
+            if let (Some(a), Some(b)) = (prev, cur) {
+                let metres = haversine_m(out.coords[a], out.coords[b]);
+                let w = ((metres / (way.kmh / 3.6) * 1000.0).round() as Weight).max(1);
+                if way.forward { out.graph.add_edge(a, b, w); out.way_ids.push(a, way.id); }
+                if way.backward { out.graph.add_edge(b, a, w); out.way_ids.push(b, way.id); }
+            }
+            prev = cur;
+        }
+    }
+    Ok(out)
+}
+
+struct Road {
+    id: i64,
+    refs: Vec<i64>,
+    forward: bool,
+    backward: bool,
+    kmh: f64,
+}
+
+fn read_vec<R: Read>(r: &mut R, n: usize) -> io::Result<Vec<u8>> {
+    let mut v = vec![0u8; n];
+    r.read_exact(&mut v)?;
+    Ok(v)
+}
+
+fn invalid(what: &str) -> io::Error { io::Error::new(io::ErrorKind::InvalidData, format!("osm pbf: {what}")) }
+
+/// Payload of a `Blob` message: `raw` or `zlib_data`; other codecs are rejected.
+fn blob_data(blob: &[u8]) -> io::Result<Vec<u8>> {
+    for f in Pb(blob) {
+        match f? {
+            (1, Field::Bytes(b)) => return Ok(b.to_vec()),
+            (3, Field::Bytes(b)) => {
+                let mut out = Vec::new();
+                flate2::read::ZlibDecoder::new(b).read_to_end(&mut out)?;
+                return Ok(out);
+            }
+            (4..=7, Field::Bytes(_)) => return Err(invalid("only raw and zlib blobs are supported")),
+            _ => {}
+        }
+    }
+    Err(invalid("empty blob"))
+}
+
+fn parse_block(block: &[u8], nodes: &mut Vec<(i64, f64, f64)>, ways: &mut Vec<Road>) -> io::Result<()> {
+    let (mut strings, mut groups) = (Vec::new(), Vec::new());
+    let (mut granularity, mut lat_off, mut lon_off) = (100i64, 0i64, 0i64);
+    for f in Pb(block) {
+        match f? {
+            (1, Field::Bytes(st)) => for s in Pb(st) { if let (1, Field::Bytes(b)) = s? { strings.push(b) } },
+            (2, Field::Bytes(g)) => groups.push(g),
+            (17, Field::Varint(v)) => granularity = v as i64,
+            (19, Field::Varint(v)) => lat_off = v as i64,
+            (20, Field::Varint(v)) => lon_off = v as i64,
+            _ => {}
+        }
+    }
+    let deg = |off: i64, raw: i64| 1e-9 * (off + granularity * raw) as f64;
+    for g in groups {
+        for f in Pb(g) {
+            match f? {
+                (1, Field::Bytes(n)) => {
+                    let (mut id, mut lat, mut lon) = (0, 0, 0);
+                    for x in Pb(n) {
+                        match x? {
+                            (1, Field::Varint(v)) => id = zigzag(v),
+                            (8, Field::Varint(v)) => lat = zigzag(v),
+                            (9, Field::Varint(v)) => lon = zigzag(v),
+                            _ => {}
+                        }
+                    }
+                    nodes.push((id, deg(lon_off, lon), deg(lat_off, lat)));
+                }
+                (2, Field::Bytes(dense)) => {
+                    let (mut ids, mut lats, mut lons) = (Vec::new(), Vec::new(), Vec::new());
+                    for x in Pb(dense) {
+                        match x? {
+                            (1, Field::Bytes(b)) => ids = deltas(b)?,
+                            (8, Field::Bytes(b)) => lats = deltas(b)?,
+                            (9, Field::Bytes(b)) => lons = deltas(b)?,
+                            _ => {}
+                        }
+                    }
+                    if ids.len() != lats.len() || ids.len() != lons.len() { return Err(invalid("dense node arrays differ in length")); }
+                    nodes.extend(ids.iter().zip(&lats).zip(&lons).map(|((&id, &la), &lo)| (id, deg(lon_off, lo), deg(lat_off, la))));
+                }
+                (3, Field::Bytes(w)) => {
+                    let (mut id, mut keys, mut vals, mut refs) = (0, Vec::new(), Vec::new(), Vec::new());
+                    for x in Pb(w) {
+                        match x? {
+                            (1, Field::Varint(v)) => id = v as i64,
+                            (2, Field::Bytes(b)) => keys = varints(b)?,
+                            (3, Field::Bytes(b)) => vals = varints(b)?,
+                            (8, Field::Bytes(b)) => refs = deltas(b)?,
+                            _ => {}
+                        }
+                    }
+                    let tag = |k: &[u8]| keys.iter().zip(&vals).find(|(&ki, _)| strings.get(ki as usize) == Some(&k))
+                        .and_then(|(_, &vi)| strings.get(vi as usize)).and_then(|v| std::str::from_utf8(v).ok());
+                    if let Some(road) = road(id, tag(b"highway"), tag(b"oneway"), tag(b"junction"), tag(b"maxspeed"), refs) { ways.push(road); }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Drivable highway classes and their default speeds in km/h.
+fn default_kmh(highway: &str) -> Option<f64> {
+    Some(match highway {
+        "motorway" => 110.0,
+        "trunk" => 90.0,
+        "primary" => 70.0,
+        "secondary" => 60.0,
+        "tertiary" => 50.0,
+        "motorway_link" => 60.0,
+        "trunk_link" => 50.0,
+        "primary_link" | "secondary_link" | "tertiary_link" => 40.0,
+        "unclassified" | "road" => 40.0,
+        "residential" => 30.0,
+        "service" => 20.0,
+        "living_street" => 10.0,
+        _ => return None,
+    })
+}
+
+fn road(id: i64, highway: Option<&str>, oneway: Option<&str>, junction: Option<&str>, maxspeed: Option<&str>, refs: Vec<i64>) -> Option<Road> {
+    let highway = highway?;
+    let default = default_kmh(highway)?;
+    let (forward, backward) = match oneway {
+        Some("yes" | "true" | "1") => (true, false),
+        Some("-1" | "reverse") => (false, true),
+        Some("no" | "false" | "0") => (true, true),
+        _ if highway == "motorway" || junction == Some("roundabout") => (true, false),
+        _ => (true, true),
+    };
+    let kmh = maxspeed.and_then(|s| {
+        let s = s.trim();
+        let digits = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+        let v: f64 = s[..digits].parse().ok()?;
+        Some(if s.ends_with("mph") { v * 1.609_344 } else { v })
+    }).filter(|&v| v > 0.0).unwrap_or(default);
+    Some(Road{ id, refs, forward, backward, kmh })
+}
+
+fn haversine_m(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lat1, lat2) = (a.1.to_radians(), b.1.to_radians());
+    let (dlat, dlon) = (lat2 - lat1, (b.0 - a.0).to_radians());
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * 6_371_008.8 * h.sqrt().asin()
+}
+
+fn zigzag(v: u64) -> i64 { (v >> 1) as i64 ^ -((v & 1) as i64) }
+
+fn varints(mut b: &[u8]) -> io::Result<Vec<u64>> {
+    let mut out = Vec::new();
+    while !b.is_empty() { out.push(varint(&mut b)?); }
+    Ok(out)
+}
+
+/// Packed delta-coded sint64s, as used for ids, coordinates and way refs.
+fn deltas(b: &[u8]) -> io::Result<Vec<i64>> {
+    let mut acc = 0i64;
+    Ok(varints(b)?.into_iter().map(|v| { acc += zigzag(v); acc }).collect())
+}
+
+fn varint(b: &mut &[u8]) -> io::Result<u64> {
+    let mut v = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = b.split_first().ok_or_else(|| invalid("truncated varint"))?;
+        *b = rest;
+        v |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 { return Ok(v); }
+    }
+    Err(invalid("varint too long"))
+}
+
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Iterator over the `(field number, value)` pairs of one protobuf message.
+struct Pb<'a>(&'a [u8]);
+
+impl<'a> Iterator for Pb<'a> {
+    type Item = io::Result<(u32, Field<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_empty() { return None; }
+        let mut field = || -> io::Result<(u32, Field<'a>)> {
+            let key = varint(&mut self.0)?;
+            let value = match key & 7 {
+                0 => Field::Varint(varint(&mut self.0)?),
+                1 | 5 => {
+                    let n = if key & 7 == 1 { 8 } else { 4 };
+                    if self.0.len() < n { return Err(invalid("truncated fixed field")); }
+                    self.0 = &self.0[n..];
+                    Field::Fixed
+                }
+                2 => {
+                    let n = varint(&mut self.0)? as usize;
+                    if self.0.len() < n { return Err(invalid("truncated length-delimited field")); }
+                    let (b, rest) = self.0.split_at(n);
+                    self.0 = rest;
+                    Field::Bytes(b)
+                }
+                _ => return Err(invalid("unsupported wire type")),
+            };
+            Ok(((key >> 3) as u32, value))
+        };
+        let r = field();
+        if r.is_err() { self.0 = &[]; }
+        Some(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn put_varint(out: &mut Vec<u8>, mut v: u64) {
+        while v >= 0x80 { out.push(v as u8 | 0x80); v >>= 7; }
+        out.push(v as u8);
+    }
+    fn put_bytes(out: &mut Vec<u8>, field: u32, b: &[u8]) {
+        put_varint(out, u64::from(field) << 3 | 2);
+        put_varint(out, b.len() as u64);
+        out.extend_from_slice(b);
+    }
+    fn put_uint(out: &mut Vec<u8>, field: u32, v: u64) {
+        put_varint(out, u64::from(field) << 3);
+        put_varint(out, v);
+    }
+    fn packed(vals: impl IntoIterator<Item = u64>) -> Vec<u8> {
+        let mut out = Vec::new();
+        for v in vals { put_varint(&mut out, v); }
+        out
+    }
+    fn packed_deltas(vals: &[i64]) -> Vec<u8> {
+        let mut prev = 0;
+        packed(vals.iter().map(|&v| { let d = v - prev; prev = v; ((d << 1) ^ (d >> 63)) as u64 }))
+    }
+    fn frame(out: &mut Vec<u8>, kind: &str, payload: &[u8], zlib: bool) {
+        let mut blob = Vec::new();
+        if zlib {
+            let mut z = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            z.write_all(payload).unwrap();
+            put_uint(&mut blob, 2, payload.len() as u64);
+            put_bytes(&mut blob, 3, &z.finish().unwrap());
+        } else {
+            put_bytes(&mut blob, 1, payload);
+        }
+        let mut header = Vec::new();
+        put_bytes(&mut header, 1, kind.as_bytes());
+        put_uint(&mut header, 3, blob.len() as u64);
+        out.extend_from_slice(&(header.len() as u32).to_be_bytes());
+        out.extend_from_slice(&header);
+        out.extend_from_slice(&blob);
+    }
+    fn way(id: u64, tags: &[(u64, u64)], refs: &[i64]) -> Vec<u8> {
+        let mut w = Vec::new();
+        put_uint(&mut w, 1, id);
+        put_bytes(&mut w, 2, &packed(tags.iter().map(|t| t.0)));
+        put_bytes(&mut w, 3, &packed(tags.iter().map(|t| t.1)));
+        put_bytes(&mut w, 8, &packed_deltas(refs));
+        w
+    }
+
+    #[test]
+    fn reads_a_small_extract() {
+        // Three nodes ~111 m apart along a meridian; granularity 100 nanodegrees.
+        let strings = ["", "highway", "residential", "primary", "oneway", "yes", "footway", "maxspeed", "36"];
+        let mut st = Vec::new();
+        for s in strings { put_bytes(&mut st, 1, s.as_bytes()); }
+        let mut dense = Vec::new();
+        put_bytes(&mut dense, 1, &packed_deltas(&[10, 11, 12]));
+        put_bytes(&mut dense, 8, &packed_deltas(&[0, 10_000, 20_000]));
+        put_bytes(&mut dense, 9, &packed_deltas(&[0, 0, 0]));
+        let mut group = Vec::new();
+        put_bytes(&mut group, 2, &dense);
+        put_bytes(&mut group, 3, &way(1, &[(1, 2)], &[10, 11, 12]));
+        put_bytes(&mut group, 3, &way(2, &[(1, 3), (4, 5), (7, 8)], &[12, 10]));
+        put_bytes(&mut group, 3, &way(3, &[(1, 6)], &[10, 12]));
+        put_bytes(&mut group, 3, &way(4, &[(1, 2)], &[11, 99]));
+        let mut block = Vec::new();
+        put_bytes(&mut block, 1, &st);
+        put_bytes(&mut block, 2, &group);
+        let mut file = Vec::new();
+        frame(&mut file, "OSMHeader", b"", false);
+        frame(&mut file, "OSMData", &block, true);
+
+        let osm = read_pbf(&file[..]).unwrap();
+        assert_eq!(osm.osm_ids, [10, 11, 12]);
+        assert!((osm.coords[2].1 - 0.002).abs() < 1e-12 && osm.coords[2].0 == 0.0);
+        // ~111.2 m at 30 km/h is ~13.3 s; the primary is one-way 12 -> 10 at 36 km/h (10 m/s).
+        let w = osm.graph.adj[0][0].1;
+        assert!((13_300..13_400).contains(&w), "{w}");
+        assert_eq!(osm.graph.num_edges(), 5);
+        let back = osm.graph.adj[2].iter().find(|e| e.0 == 0).unwrap().1;
+        assert!((22_200..22_300).contains(&back), "{back}");
+        assert!(!osm.graph.adj[0].iter().any(|e| e.0 == 2));
+        assert!(osm.way_ids.is_aligned(&osm.graph));
+        assert_eq!((osm.way_ids.get(0, 0), osm.way_ids.get(2, 1)), (Some(&1), Some(&2)));
+        assert!(read_pbf(&file[..file.len() - 3]).is_err());
+    }
+}
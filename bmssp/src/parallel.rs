@@ -0,0 +1,272 @@
+//! Frontier-parallel bounded search with atomic distance relaxation.
+//!
+//! Delta-stepping style: labels live in `AtomicU64`s, nodes are grouped into buckets of width
+//! `delta`, and each bucket is drained in Bellman–Ford rounds where all threads relax the current
+//! frontier concurrently via `fetch_min`. Unlike `bmssp_sharded`, overlapping sources share work.
+use crate::numa::{alloc_dist_first_touch, owner_range, pin_current_thread, Topology};
+use crate::{settle_order_and_boundary, BmsspResult, Graph, Node, Weight};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Barrier, Mutex, RwLock};
+
+/// Frontiers smaller than this are relaxed on the calling thread; waking the workers isn't worth it.
+const PAR_MIN_FRONTIER: usize = 256;
+/// Most distance buckets a search keeps; `delta` is widened to stay under it.
+const MAX_BUCKETS: usize = 1 << 16;
+
+/// `bmssp_parallel_delta` with `delta = bound / 32` (at least 1).
+pub fn bmssp_parallel(
+    g: &Graph,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    threads: usize,
+) -> BmsspResult {
+    bmssp_parallel_delta(g, sources, bound, threads, (bound / 32).max(1))
+}
+
+/// Parallel bounded multi-source search. Distances, `explored` (sorted by `(dist, node)`) and
+/// `b_prime` match the sequential run; `edges_scanned` / `heap_pushes` count re-relaxations and
+/// bucket insertions, so they are >= the sequential counters. `delta` is raised as needed to
+/// keep `bound / delta` within 65536 buckets.
+pub fn bmssp_parallel_delta(
+    g: &Graph,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    threads: usize,
+    delta: Weight,
+) -> BmsspResult {
+    run(g, sources, bound, threads, delta, None)
+}
+
+/// NUMA-aware `bmssp_parallel`: the distance array is first-touched per owner range, and each
+/// round's frontier is split by owner so worker `i` (pinned via `topo` when the `numa` feature is
+/// on) relaxes the nodes whose labels live on its node. Pair with
+/// `numa::place_graph_first_touch` to place adjacency lists the same way.
+pub fn bmssp_parallel_numa(
+    g: &Graph,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    threads: usize,
+    topo: &Topology,
+) -> BmsspResult {
+    run(g, sources, bound, threads, (bound / 32).max(1), Some(topo))
+}
+
+fn run(
+    g: &Graph,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    threads: usize,
+    delta: Weight,
+    topo: Option<&Topology>,
+) -> BmsspResult {
+    let n = g.len();
+    let threads = threads.max(1);
+    // Widen buckets so labels below `bound` never index past `MAX_BUCKETS`.
+    let delta = delta.max(bound.div_ceil(MAX_BUCKETS as Weight)).max(1);
+    let dist: Vec<AtomicU64> = match topo {
+        Some(t) => alloc_dist_first_touch(n, threads, Some(t)),
+        None => (0..n).map(|_| AtomicU64::new(Weight::MAX)).collect(),
+    };
+    let mut buckets: Vec<Vec<Node>> = Vec::new();
+    let edges_scanned = AtomicUsize::new(0);
+
+    for &(s, d0) in sources {
+        if s < n && d0 < bound && !g.is_removed(s) && d0 < dist[s].load(Ordering::Relaxed) {
+            dist[s].store(d0, Ordering::Relaxed);
+            push_bucket(&mut buckets, (d0 / delta) as usize, s);
+        }
+    }
+
+    // Relax all out-edges of a frontier share; returns `(node, new label)` for every successful
+    // `fetch_min`.
+    let relax = |chunk: &[Node]| {
+        let mut out = Vec::new();
+        let mut scanned = 0usize;
+        for &u in chunk {
+            let d = dist[u].load(Ordering::Relaxed);
+            for &(to, w) in &g.adj[u] {
+                scanned += 1;
+                let nd = d.saturating_add(w);
+                if nd < bound && nd < dist[to].fetch_min(nd, Ordering::Relaxed) { out.push((to, nd)); }
+            }
+        }
+        edges_scanned.fetch_add(scanned, Ordering::Relaxed);
+        out
+    };
+    let heap_pushes = if threads == 1 {
+        drain(&mut buckets, &dist, delta, |f| relax(f))
+    } else {
+        let workers = Workers::new(threads);
+        std::thread::scope(|scope| {
+            for i in 0..threads {
+                let (workers, relax) = (&workers, &relax);
+                scope.spawn(move || workers.work(i, n, topo, relax));
+            }
+            let _stop = StopWorkers(&workers);
+            drain(&mut buckets, &dist, delta, |f| if f.len() < PAR_MIN_FRONTIER { relax(f) } else { workers.round(f) })
+        })
+    };
+
+    let dist: Vec<Weight> = dist.into_iter().map(AtomicU64::into_inner).collect();
+    // B' from final labels only: candidates seen mid-round may come from non-final labels.
+    let (explored, b_prime) = settle_order_and_boundary(g, &dist, bound);
+    BmsspResult{ dist, explored, b_prime, edges_scanned: edges_scanned.into_inner(), heap_pushes, ..Default::default() }
+}
+
+/// Drain `buckets` in order, each in rounds of `relax` over its sorted frontier; returns the
+/// number of bucket insertions.
+fn drain(
+    buckets: &mut Vec<Vec<Node>>,
+    dist: &[AtomicU64],
+    delta: Weight,
+    mut relax: impl FnMut(&mut Vec<Node>) -> Vec<(Node, Weight)>,
+) -> usize {
+    let bucket_of = |d: Weight| (d / delta) as usize;
+    let mut heap_pushes = 0;
+    let mut i = 0;
+    while i < buckets.len() {
+        let mut frontier = std::mem::take(&mut buckets[i]);
+        while !frontier.is_empty() {
+            frontier.sort_unstable();
+            frontier.dedup();
+            // Entries whose label later dropped into an earlier bucket were already processed there.
+            frontier.retain(|&v| bucket_of(dist[v].load(Ordering::Relaxed)) == i);
+            let improved = relax(&mut frontier);
+            heap_pushes += improved.len();
+            frontier.clear();
+            for (v, nd) in improved {
+                let j = bucket_of(nd);
+                if j == i { frontier.push(v); } else { push_bucket(buckets, j, v); }
+            }
+        }
+        i += 1;
+    }
+    heap_pushes
+}
+
+fn push_bucket(buckets: &mut Vec<Vec<Node>>, i: usize, v: Node) {
+    if i >= buckets.len() { buckets.resize_with(i + 1, Vec::new); }
+    buckets[i].push(v);
+}
+
+/// Relax threads kept for a whole search: each `round` publishes the frontier, releases the
+/// workers at `barrier`, worker `i` writes its share's improvements to `out[i]`, and all meet at
+/// `barrier` again. Spawning threads per round costs more than a small round is worth.
+struct Workers {
+    threads: usize,
+    frontier: RwLock<Vec<Node>>,
+    out: Vec<Mutex<Vec<(Node, Weight)>>>,
+    barrier: Barrier,
+    stop: AtomicBool,
+}
+
+impl Workers {
+    fn new(threads: usize) -> Self {
+        Self {
+            threads,
+            frontier: RwLock::new(Vec::new()),
+            out: (0..threads).map(|_| Mutex::new(Vec::new())).collect(),
+            barrier: Barrier::new(threads + 1),
+            stop: AtomicBool::new(false),
+        }
+    }
+
+    /// Body of worker `i` of a search over `n` nodes; pinned via `topo` when given.
+    fn work(&self, i: usize, n: usize, topo: Option<&Topology>, relax: impl Fn(&[Node]) -> Vec<(Node, Weight)>) {
+        if let Some(cpu) = topo.and_then(|t| t.cpu_for_worker(i)) { pin_current_thread(cpu); }
+        loop {
+            self.barrier.wait();
+            if self.stop.load(Ordering::Relaxed) { return; }
+            let frontier = self.frontier.read().unwrap();
+            let share = match topo {
+                // The frontier is sorted, so splitting at owner-range starts groups nodes by owner.
+                Some(_) => {
+                    let r = owner_range(n, self.threads, i);
+                    &frontier[frontier.partition_point(|&v| v < r.start)..frontier.partition_point(|&v| v < r.end)]
+                }
+                None => {
+                    let size = frontier.len().div_ceil(self.threads);
+                    &frontier[(i * size).min(frontier.len())..((i + 1) * size).min(frontier.len())]
+                }
+            };
+            *self.out[i].lock().unwrap() = relax(share);
+            drop(frontier);
+            self.barrier.wait();
+        }
+    }
+
+    /// Relax `frontier` on all workers; it is handed back unchanged.
+    fn round(&self, frontier: &mut Vec<Node>) -> Vec<(Node, Weight)> {
+        std::mem::swap(&mut *self.frontier.write().unwrap(), frontier);
+        self.barrier.wait();
+        self.barrier.wait();
+        std::mem::swap(&mut *self.frontier.write().unwrap(), frontier);
+        self.out.iter().flat_map(|o| std::mem::take(&mut *o.lock().unwrap())).collect()
+    }
+}
+
+/// Releases the workers from their wait at the start of a round, also when the search unwinds.
+struct StopWorkers<'a>(&'a Workers);
+
+impl Drop for StopWorkers<'_> {
+    fn drop(&mut self) {
+        self.0.stop.store(true, Ordering::Relaxed);
+        self.0.barrier.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use crate::generators::make_grid;
+
+    #[test]
+    fn parallel_matches_sequential() {
+        let g = make_grid(60, 60, 9, 1);
+        let sources = [(0, 0), (1830, 0), (1831, 4), (3599, 1)];
+        for (bound, delta) in [(40, 5), (200, 1000), (300, 1)] {
+            let a = bounded_multi_source_shortest_paths(&g, &sources, bound);
+            let b = bmssp_parallel_delta(&g, &sources, bound, 4, delta);
+            assert_eq!(a.dist, b.dist, "bound {bound} delta {delta}");
+            assert_eq!(a.explored, b.explored);
+            assert_eq!(a.b_prime, b.b_prime);
+            assert!(b.edges_scanned >= a.edges_scanned);
+        }
+    }
+
+    #[test]
+    fn wide_frontiers_and_huge_bounds() {
+        let g = make_grid(60, 60, 9, 1);
+        // Enough sources that the first rounds go to the workers.
+        let sources: Vec<_> = (0..g.len()).step_by(7).map(|v| (v, (v % 5) as Weight)).collect();
+        for (bound, delta) in [(30, 1000), (Weight::MAX, 1)] {
+            let a = bounded_multi_source_shortest_paths(&g, &sources, bound);
+            let b = bmssp_parallel_delta(&g, &sources, bound, 3, delta);
+            assert_eq!(a.dist, b.dist, "bound {bound} delta {delta}");
+            assert_eq!(a.explored, b.explored);
+            assert_eq!(a.b_prime, b.b_prime);
+        }
+    }
+
+    #[test]
+    fn numa_variant_matches_sequential() {
+        let g = make_grid(50, 50, 9, 1);
+        let topo = Topology { nodes: vec![vec![0], vec![0]] };
+        let placed = crate::numa::place_graph_first_touch(&g, 2, Some(&topo));
+        assert_eq!(placed.adj, g.adj);
+        let sources = [(0, 0), (1275, 2)];
+        let a = bounded_multi_source_shortest_paths(&g, &sources, 120);
+        let b = bmssp_parallel_numa(&placed, &sources, 120, 3, &topo);
+        assert_eq!(a.dist, b.dist);
+        assert_eq!(a.b_prime, b.b_prime);
+    }
+
+    #[test]
+    fn parallel_ignores_bad_sources() {
+        let g = make_grid(4, 4, 9, 1);
+        let r = bmssp_parallel(&g, &[(99, 0), (0, 50)], 10, 2);
+        assert!(r.explored.is_empty());
+        assert_eq!(r.b_prime, Weight::MAX);
+    }
+}
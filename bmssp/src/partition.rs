@@ -0,0 +1,147 @@
+//! Graph-partition sharding: each thread owns a block of nodes instead of a slice of sources.
+//!
+//! Blocks are contiguous runs of an undirected BFS order, so they are roughly connected and
+//! spatially compact. Each round, every owner runs a bounded Dijkstra seeded by its inbox,
+//! settling only its own nodes and emitting cross-block relaxations as messages; rounds repeat
+//! until no messages are in flight. Clustered sources then cost one region, not one per thread.
+use crate::{settle_order_and_boundary, BmsspResult, Entry, Graph, Node, Weight};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Split nodes into `parts` BFS blocks of near-equal size. Returns the block id of each node.
+pub fn partition_bfs(g: &Graph, parts: usize) -> Vec<usize> {
+    let parts = parts.max(1);
+    let order = crate::reorder::bfs_visit_order(g);
+    let block = order.len().div_ceil(parts).max(1);
+    let mut part = vec![0; g.len()];
+    for (i, &v) in order.iter().enumerate() { part[v] = i / block; }
+    part
+}
+
+/// Bounded multi-source search over `threads` BFS blocks with boundary-exchange rounds.
+/// Distances, `explored` (sorted by `(dist, node)`) and `b_prime` match the sequential run.
+pub fn bmssp_partitioned(
+    g: &Graph,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    threads: usize,
+) -> BmsspResult {
+    let t = threads.max(1);
+    bmssp_with_partition(g, sources, bound, &partition_bfs(g, t), t)
+}
+
+/// As [`bmssp_partitioned`] with a caller-supplied partition (`part[v] < parts`), so the
+/// partitioning cost can be paid once across many queries.
+pub fn bmssp_with_partition(
+    g: &Graph,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    part: &[usize],
+    parts: usize,
+) -> BmsspResult {
+    let n = g.len();
+    let dist: Vec<AtomicU64> = (0..n).map(|_| AtomicU64::new(Weight::MAX)).collect();
+    let mut inboxes: Vec<Vec<(Node, Weight)>> = vec![Vec::new(); parts];
+    for &(s, d0) in sources {
+        if s < n && d0 < bound && !g.is_removed(s) { inboxes[part[s]].push((s, d0)); }
+    }
+    let mut edges_scanned: usize = 0;
+    let mut heap_pushes: usize = 0;
+
+    while inboxes.iter().any(|b| !b.is_empty()) {
+        let outputs: Vec<BlockOutput> = std::thread::scope(|scope| {
+            let dist = &dist;
+            let handles: Vec<_> = inboxes
+                .iter_mut()
+                .enumerate()
+                .map(|(me, inbox)| {
+                    let seeds = std::mem::take(inbox);
+                    scope.spawn(move || settle_block(g, dist, part, me, seeds, bound))
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().expect("thread panicked")).collect()
+        });
+        for (msgs, scanned, pushes) in outputs {
+            edges_scanned += scanned;
+            heap_pushes += pushes;
+            for (v, d) in msgs { inboxes[part[v]].push((v, d)); }
+        }
+    }
+
+    let dist: Vec<Weight> = dist.into_iter().map(AtomicU64::into_inner).collect();
+    let (explored, b_prime) = settle_order_and_boundary(g, &dist, bound);
+    BmsspResult{ dist, explored, b_prime, edges_scanned, heap_pushes, ..Default::default() }
+}
+
+/// Messages for foreign nodes, edges scanned, heap pushes.
+type BlockOutput = (Vec<(Node, Weight)>, usize, usize);
+
+/// One owner's round: Dijkstra over block `me` from `seeds`. Only block-local labels are written;
+/// improvements for foreign nodes are returned as messages.
+fn settle_block(
+    g: &Graph,
+    dist: &[AtomicU64],
+    part: &[usize],
+    me: usize,
+    seeds: Vec<(Node, Weight)>,
+    bound: Weight,
+) -> BlockOutput {
+    let mut heap: BinaryHeap<Reverse<Entry>> = BinaryHeap::new();
+    let mut out = Vec::new();
+    let (mut scanned, mut pushes) = (0usize, 0usize);
+    for (v, d) in seeds {
+        if d < dist[v].load(Ordering::Relaxed) {
+            dist[v].store(d, Ordering::Relaxed);
+            heap.push(Reverse(Entry{ d, v }));
+        }
+    }
+    while let Some(Reverse(Entry{ d, v })) = heap.pop() {
+        if d != dist[v].load(Ordering::Relaxed) { continue; }
+        for &(to, w) in &g.adj[v] {
+            scanned += 1;
+            let nd = d.saturating_add(w);
+            if nd >= bound || nd >= dist[to].load(Ordering::Relaxed) { continue; }
+            if part[to] == me {
+                dist[to].store(nd, Ordering::Relaxed);
+                heap.push(Reverse(Entry{ d: nd, v: to }));
+                pushes += 1;
+            } else {
+                out.push((to, nd));
+            }
+        }
+    }
+    (out, scanned, pushes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use crate::generators::make_grid;
+
+    #[test]
+    fn partition_is_balanced() {
+        let g = make_grid(10, 10, 7, 1);
+        let part = partition_bfs(&g, 4);
+        let mut sizes = [0usize; 4];
+        for &p in &part { sizes[p] += 1; }
+        assert_eq!(sizes, [25, 25, 25, 25]);
+    }
+
+    #[test]
+    fn partitioned_matches_sequential() {
+        let g = make_grid(40, 40, 7, 1);
+        let clustered = [(820, 0), (821, 0), (860, 2), (861, 1)];
+        let spread = [(0, 0), (39, 0), (1560, 3), (1599, 0)];
+        for sources in [&clustered[..], &spread[..]] {
+            for bound in [15, 60, 400] {
+                let a = bounded_multi_source_shortest_paths(&g, sources, bound);
+                let b = bmssp_partitioned(&g, sources, bound, 4);
+                assert_eq!(a.dist, b.dist);
+                assert_eq!(a.explored, b.explored);
+                assert_eq!(a.b_prime, b.b_prime);
+            }
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Shortest-path structure recovered from a finished search.
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::options::{AvoidSet, BmsspOptions};
+use crate::{bmssp_with_options, BmsspResult, Graph, GraphLike, Node, Weight, NO_PRED};
+
+/// Edges `(u, v, w)` of the shortest-path DAG of `res`: `u` and `v` settled and
+/// `dist[u] + w == dist[v]`, so tentative labels left by an early stop stay out. Pass the
+/// options used for the search so filtered edges stay out of the DAG.
+pub fn shortest_path_dag(g: &Graph, res: &BmsspResult, opts: &BmsspOptions) -> Vec<(Node, Node, Weight)> {
+    let mut out = Vec::new();
+    let settled = res.settled_mask();
+    let mut order: Vec<Node> = res.explored.clone();
+    order.sort_unstable();
+    for u in order {
+        let du = res.dist[u];
+        for &(v, w) in &g.adj[u] {
+            if settled[v] && du.saturating_add(w) == res.dist[v] && opts.allows(u, v, w) {
+                out.push((u, v, w));
+            }
+        }
+    }
+    out
+}
+
+impl BmsspResult {
+    /// Node sequence from a root to `t` following `pred`. `None` if `t` wasn't settled or the
+    /// search didn't track predecessors.
+    pub fn path_to(&self, t: Node) -> Option<Vec<Node>> {
+        if self.pred.is_empty() || !self.is_settled(t) { return None; }
+        let mut path = vec![t];
+        while let Some(&p) = self.pred.get(*path.last().unwrap()).filter(|&&p| p != NO_PRED) { path.push(p); }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Tree edges from a root to `t` as `(tail, slot)`, where `slot` indexes the tail's
+    /// neighbor list (see `EdgeData`). `None` under the same conditions as `path_to`.
+    pub fn path_edges_to(&self, t: Node) -> Option<Vec<(Node, usize)>> {
+        if self.pred_slot.len() != self.pred.len() { return None; }
+        let path = self.path_to(t)?;
+        Some(path.windows(2).map(|e| (e[0], self.pred_slot[e[1]])).collect())
+    }
+
+    /// Nearest source of every node: the root of its tree path, `NO_PRED` if unsettled. `None`
+    /// if the search didn't track predecessors.
+    pub fn roots(&self) -> Option<Vec<Node>> {
+        if self.pred.is_empty() { return None; }
+        let mut root = vec![NO_PRED; self.dist.len()];
+        for &v in &self.explored {
+            let p = self.pred[v];
+            // Parents settle first except in the out-of-order variants; walk the chain there.
+            root[v] = if p == NO_PRED { v } else if root[p] != NO_PRED { root[p] } else { self.path_to(v).map_or(v, |path| path[0]) };
+        }
+        Some(root)
+    }
+
+    /// Graph on the settled nodes only, with every edge of `g` between two of them.
+    /// Returns the subgraph and `old_id[new]`; new ids follow settle order.
+    pub fn induced_subgraph(&self, g: &Graph) -> (Graph, Vec<Node>) {
+        let mut new_id = vec![NO_PRED; g.len()];
+        for (i, &v) in self.explored.iter().enumerate() { new_id[v] = i; }
+        let mut h = Graph::new(self.explored.len());
+        for (i, &u) in self.explored.iter().enumerate() {
+            for &(v, w) in &g.adj[u] {
+                if new_id[v] != NO_PRED { h.add_edge(i, new_id[v], w); }
+            }
+        }
+        (h, self.explored.clone())
+    }
+
+    /// Shortest-path forest as a `Graph` on the same node ids: one edge `parent -> v` with the
+    /// original weight per settled non-root `v`. Roots (the sources that won their own label) keep
+    /// no in-edge. Uses `pred` when tracked; otherwise each node's parent is the first settled
+    /// node with a tight edge into it.
+    pub fn shortest_path_tree(&self, g: &Graph) -> Graph {
+        let mut parent: Vec<(Node, Weight)> = vec![(NO_PRED, 0); g.len()];
+        let settled = self.settled_mask();
+        for &u in &self.explored {
+            for &(v, w) in &g.adj[u] {
+                let tight = settled[v] && self.dist[u].saturating_add(w) == self.dist[v];
+                let chosen = if self.pred.is_empty() { parent[v].0 == NO_PRED && v != u } else { self.pred[v] == u };
+                // With tracked predecessors keep the lightest parallel edge the search could have used.
+                if tight && chosen && (parent[v].0 != u || w < parent[v].1) { parent[v] = (u, w); }
+            }
+        }
+        let mut t = Graph::new(g.len());
+        for &v in &self.explored {
+            let (p, w) = parent[v];
+            if p != NO_PRED { t.add_edge(p, v, w); }
+        }
+        t
+    }
+
+    /// Graphviz rendering of the explored subgraph: settled nodes labelled with their distance,
+    /// sources as double circles, tree edges (from `pred`, or all tight edges when predecessors
+    /// weren't tracked) in bold, and boundary edges that reached `b_prime` dashed.
+    pub fn write_dot<W: Write>(&self, g: &Graph, mut w: W) -> io::Result<()> {
+        let mut tight_in = vec![false; g.len()];
+        let settled = self.settled_mask();
+        if self.pred.is_empty() {
+            for &u in &self.explored {
+                for &(v, wt) in &g.adj[u] {
+                    if v != u && settled[v] && self.dist[u].saturating_add(wt) == self.dist[v] { tight_in[v] = true; }
+                }
+            }
+        }
+        writeln!(w, "digraph bmssp {{")?;
+        writeln!(w, "  label=\"B'={}\";", fmt_weight(self.b_prime))?;
+        for &v in &self.explored {
+            let root = if self.pred.is_empty() { !tight_in[v] } else { self.pred[v] == NO_PRED };
+            let shape = if root { "doublecircle" } else { "circle" };
+            writeln!(w, "  {v} [shape={shape}, label=\"{v}\\nd={}\"];", self.dist[v])?;
+        }
+        for &u in &self.explored {
+            for &(v, wt) in &g.adj[u] {
+                let nd = self.dist[u].saturating_add(wt);
+                if settled[v] {
+                    let tree = if self.pred.is_empty() { nd == self.dist[v] } else { self.pred[v] == u };
+                    let style = if tree { "bold" } else { "dotted" };
+                    writeln!(w, "  {u} -> {v} [label=\"{wt}\", style={style}];")?;
+                } else if nd == self.b_prime {
+                    writeln!(w, "  {v} [shape=box, style=dashed, label=\"{v}\\n>={nd}\"];")?;
+                    writeln!(w, "  {u} -> {v} [label=\"{wt}\", style=dashed];")?;
+                }
+            }
+        }
+        writeln!(w, "}}")
+    }
+
+    /// `write_dot` to a file.
+    pub fn export_dot(&self, g: &Graph, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write_dot(g, &mut w)?;
+        w.flush()
+    }
+}
+
+/// Length and node sequence of a shortest `s -> t` path shorter than `bound`, or `None` if
+/// there is none. Tracks predecessors and stops as soon as `t` settles.
+pub fn bounded_shortest_path<G: GraphLike>(g: &G, s: Node, t: Node, bound: Weight) -> Option<(Weight, Vec<Node>)> {
+    let opts = BmsspOptions{ track_predecessors: true, stop_at: Some(t), ..Default::default() };
+    let r = bmssp_with_options(g, &[(s, 0)], bound, &opts);
+    Some((r.dist_of(t)?, r.path_to(t)?))
+}
+
+/// Up to `k` loopless `s -> t` paths shorter than `bound`, by increasing length (Yen's algorithm).
+/// Each spur search is a bounded search started at the root path's cost, with the root's nodes
+/// avoided and the next hops of already-found paths sharing that root filtered out. Blocking is
+/// per node pair, so parallel edges between the same nodes yield one path, not several.
+pub fn k_shortest_bounded(g: &Graph, s: Node, t: Node, k: usize, bound: Weight) -> Vec<(Weight, Vec<Node>)> {
+    if k == 0 { return Vec::new(); }
+    struct Found { nodes: Vec<Node>, pref: Vec<Weight> }
+    let search = |from: Node, offset: Weight, opts: &BmsspOptions| -> Option<Found> {
+        let r = bmssp_with_options(g, &[(from, offset)], bound, opts);
+        let nodes = r.path_to(t)?;
+        let pref = nodes.iter().map(|&v| r.dist[v]).collect();
+        Some(Found { nodes, pref })
+    };
+    let base = BmsspOptions{ track_predecessors: true, ..Default::default() };
+    let mut accepted: Vec<Found> = Vec::new();
+    let mut candidates: Vec<Found> = Vec::new();
+    match search(s, 0, &base) { Some(p) => accepted.push(p), None => return Vec::new() }
+    while accepted.len() < k {
+        let last = accepted.last().unwrap();
+        for i in 0..last.nodes.len() - 1 {
+            let root = &last.nodes[..=i];
+            let blocked: Vec<(Node, Node)> = accepted.iter()
+                .filter(|p| p.nodes.len() > i + 1 && &p.nodes[..=i] == root)
+                .map(|p| (p.nodes[i], p.nodes[i + 1]))
+                .collect();
+            let edge_filter = |u: Node, v: Node, _w: Weight| !blocked.contains(&(u, v));
+            let opts = BmsspOptions{ avoid: AvoidSet::new(&root[..i]), edge_filter: Some(&edge_filter), ..base.clone() };
+            let Some(spur) = search(root[i], last.pref[i], &opts) else { continue };
+            let nodes: Vec<Node> = root[..i].iter().chain(&spur.nodes).copied().collect();
+            if candidates.iter().chain(&accepted).any(|c| c.nodes == nodes) { continue; }
+            let pref = last.pref[..i].iter().chain(&spur.pref).copied().collect();
+            candidates.push(Found { nodes, pref });
+        }
+        let Some(best) = (0..candidates.len()).min_by(|&a, &b| {
+            let (a, b) = (&candidates[a], &candidates[b]);
+            (a.pref.last(), &a.nodes).cmp(&(b.pref.last(), &b.nodes))
+        }) else { break };
+        accepted.push(candidates.swap_remove(best));
+    }
+    accepted.into_iter().map(|p| (*p.pref.last().unwrap(), p.nodes)).collect()
+}
+
+fn fmt_weight(w: Weight) -> String {
+    if w == Weight::MAX { "inf".to_string() } else { w.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::make_grid;
+    use crate::{bmssp_with_options, Termination};
+
+    #[test]
+    fn grid_path_counts_are_binomial() {
+        let g = make_grid(4, 4, 1, 0);
+        let opts = BmsspOptions{ count_paths: true, ..Default::default() };
+        let r = bmssp_with_options(&g, &[(0, 0)], 100, &opts);
+        // C(i + j, i) monotone lattice paths to (i, j).
+        assert_eq!(r.path_counts[5], 2);
+        assert_eq!(r.path_counts[10], 6);
+        assert_eq!(r.path_counts[15], 20);
+        let dag = shortest_path_dag(&g, &r, &opts);
+        assert_eq!(dag.len(), 2 * 4 * 3);
+        for v in 1..16 {
+            let in_sum: u64 = dag.iter().filter(|e| e.1 == v).map(|e| r.path_counts[e.0]).sum();
+            assert_eq!(in_sum, r.path_counts[v]);
+        }
+        assert!(bmssp_with_options(&g, &[(0, 0)], 100, &BmsspOptions::default()).path_counts.is_empty());
+    }
+
+    #[test]
+    fn budgeted_dag_keeps_to_settled_nodes() {
+        let g = make_grid(4, 4, 1, 0);
+        let opts = BmsspOptions{ max_settled: Some(5), ..Default::default() };
+        let r = bmssp_with_options(&g, &[(0, 0)], 100, &opts);
+        assert_eq!(r.termination, Termination::SettledBudget);
+        let full = bmssp_with_options(&g, &[(0, 0)], 100, &BmsspOptions::default());
+        let want: Vec<_> = shortest_path_dag(&g, &full, &opts).into_iter().filter(|e| r.is_settled(e.0) && r.is_settled(e.1)).collect();
+        let dag = shortest_path_dag(&g, &r, &opts);
+        assert_eq!(dag, want);
+        assert!(dag.iter().all(|e| r.is_settled(e.0) && r.is_settled(e.1)));
+    }
+
+    #[test]
+    fn dot_and_induced_subgraph() {
+        let mut g = Graph::new(4);
+        g.add_edge(0, 1, 2); g.add_edge(1, 2, 2); g.add_edge(2, 3, 5); g.add_edge(1, 0, 1);
+        let opts = BmsspOptions{ track_predecessors: true, ..Default::default() };
+        let r = bmssp_with_options(&g, &[(0, 0)], 6, &opts);
+        assert_eq!(r.pred, vec![crate::NO_PRED, 0, 1, crate::NO_PRED]);
+        let (h, old) = r.induced_subgraph(&g);
+        assert_eq!(old, vec![0, 1, 2]);
+        assert_eq!(h.num_edges(), 3);
+        let mut buf = Vec::new();
+        r.write_dot(&g, &mut buf).unwrap();
+        let dot = String::from_utf8(buf).unwrap();
+        assert!(dot.starts_with("digraph bmssp {"));
+        assert!(dot.contains("0 [shape=doublecircle"));
+        assert!(dot.contains("0 -> 1 [label=\"2\", style=bold]"));
+        assert!(dot.contains("1 -> 0 [label=\"1\", style=dotted]"));
+        assert!(dot.contains("2 -> 3 [label=\"5\", style=dashed]"));
+    }
+
+    #[test]
+    fn tree_from_pred_and_from_dist_agree() {
+        let g = make_grid(5, 5, 1, 0);
+        let sources = [(0, 0), (24, 1)];
+        let with_pred = bmssp_with_options(&g, &sources, 5, &BmsspOptions{ track_predecessors: true, ..Default::default() });
+        let plain = bmssp_with_options(&g, &sources, 5, &BmsspOptions::default());
+        for r in [&with_pred, &plain] {
+            let t = r.shortest_path_tree(&g);
+            let roots: Vec<Node> = r.explored.iter().copied().filter(|&v| !t.adj.iter().flatten().any(|e| e.0 == v)).collect();
+            assert_eq!(roots, vec![0, 24]);
+            assert_eq!(t.num_edges(), r.explored.len() - 2);
+            // Distances along the tree reproduce the search labels.
+            let again = crate::bounded_multi_source_shortest_paths(&t, &sources, 5);
+            assert_eq!(again.dist, r.dist);
+        }
+    }
+
+    #[test]
+    fn yen_enumerates_alternatives_in_order() {
+        //   0 -1-> 1 -1-> 3
+        //   0 -2-> 2 -1-> 3,  1 -1-> 2,  0 -5-> 3
+        let mut g = Graph::new(4);
+        g.add_edge(0, 1, 1); g.add_edge(1, 3, 1); g.add_edge(0, 2, 2); g.add_edge(2, 3, 1);
+        g.add_edge(1, 2, 1); g.add_edge(0, 3, 5);
+        let paths = k_shortest_bounded(&g, 0, 3, 10, 100);
+        assert_eq!(paths, vec![
+            (2, vec![0, 1, 3]),
+            (3, vec![0, 1, 2, 3]),
+            (3, vec![0, 2, 3]),
+            (5, vec![0, 3]),
+        ]);
+        assert_eq!(k_shortest_bounded(&g, 0, 3, 2, 100).len(), 2);
+        assert_eq!(k_shortest_bounded(&g, 0, 3, 10, 4).len(), 3);
+        assert!(k_shortest_bounded(&g, 3, 0, 3, 100).is_empty());
+    }
+
+    #[test]
+    fn point_to_point_stops_at_the_target() {
+        let g = make_grid(20, 20, 1, 0);
+        let (d, path) = bounded_shortest_path(&g, 0, 42, 100).unwrap();
+        let full = bmssp_with_options(&g, &[(0, 0)], 100, &BmsspOptions{ track_predecessors: true, ..Default::default() });
+        assert_eq!((d, path.first(), path.last()), (full.dist[42], Some(&0), Some(&42)));
+        assert!(path.windows(2).all(|e| g.adj[e[0]].iter().any(|&(v, _)| v == e[1])));
+        let r = bmssp_with_options(&g, &[(0, 0)], 100, &BmsspOptions{ stop_at: Some(42), ..Default::default() });
+        assert_eq!((r.termination, r.explored.last()), (Termination::Target, Some(&42)));
+        assert!(r.settled_count() < full.settled_count());
+        assert_eq!(bounded_shortest_path(&g, 0, 0, 1), Some((0, vec![0])));
+        assert_eq!(bounded_shortest_path(&g, 0, 42, d), None);
+        assert_eq!(bounded_shortest_path(&g, 42, 0, 100).map(|p| p.0), Some(d));
+    }
+
+    #[test]
+    fn counts_saturate() {
+        // Chain of diamonds doubles the count per stage: 2^80 overflows u64.
+        let stages = 80;
+        let mut g = Graph::new(3 * stages + 1);
+        for s in 0..stages {
+            let (a, b, c, d) = (3 * s, 3 * s + 1, 3 * s + 2, 3 * s + 3);
+            g.add_edge(a, b, 1); g.add_edge(a, c, 1); g.add_edge(b, d, 1); g.add_edge(c, d, 1);
+        }
+        let opts = BmsspOptions{ count_paths: true, ..Default::default() };
+        let r = bmssp_with_options(&g, &[(0, 0)], Weight::MAX, &opts);
+        assert_eq!(r.path_counts[3 * 10], 1 << 10);
+        assert_eq!(r.path_counts[3 * stages], u64::MAX);
+    }
+}
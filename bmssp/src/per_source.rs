@@ -0,0 +1,121 @@
+//! Per-source bounds: each source only covers nodes strictly within its own radius.
+use crate::options::{BmsspOptions, Termination};
+use crate::{bmssp_with_options, BmsspResult, DistHistogram, Graph, Node, Weight, NO_PRED};
+
+/// Bounded multi-source search where source `i` only labels nodes with
+/// `offset_i + dist(source_i, v) < min(source_bounds[i], bound)`; `Weight::MAX` means
+/// "global bound only". `dist[v]` is the exact minimum over the sources that cover `v`.
+///
+/// Runs one bounded search per distinct effective bound and takes the pointwise min, so the
+/// cost scales with the number of distinct radii, not the number of sources. `explored` is in
+/// `(dist, node)` order and `b_prime` is the smallest boundary label over all groups, i.e. a lower
+/// bound on every pruned label. Filters, `avoid` and budgets in `opts` apply to each group; only
+/// the nodes a group settled are merged, so a group stopped early by a budget contributes no
+/// tentative labels. Predecessors come from the group that gave `v` its distance, path counts
+/// add up over groups that tie, and `overflows` and the histogram cover all groups.
+pub fn bmssp_per_source_bounds(
+    g: &Graph,
+    sources: &[(Node, Weight)],
+    source_bounds: &[Weight],
+    bound: Weight,
+    opts: &BmsspOptions,
+) -> BmsspResult {
+    assert_eq!(sources.len(), source_bounds.len(), "one bound per source");
+    let mut groups: Vec<(Weight, Vec<(Node, Weight)>)> = Vec::new();
+    for (&s, &sb) in sources.iter().zip(source_bounds) {
+        let b = sb.min(bound);
+        match groups.iter_mut().find(|(gb, _)| *gb == b) {
+            Some((_, v)) => v.push(s),
+            None => groups.push((b, vec![s])),
+        }
+    }
+    let n = g.len();
+    let mut out = BmsspResult{ dist: vec![Weight::MAX; n], b_prime: Weight::MAX, ..Default::default() };
+    if opts.track_predecessors { out.pred = vec![NO_PRED; n]; out.pred_slot = vec![0; n]; }
+    if opts.count_paths { out.path_counts = vec![0; n]; }
+    for (b, group) in groups {
+        let r = bmssp_with_options(g, &group, b, opts);
+        for &v in &r.explored {
+            let d = r.dist[v];
+            if d < out.dist[v] {
+                out.dist[v] = d;
+                if opts.track_predecessors { out.pred[v] = r.pred[v]; out.pred_slot[v] = r.pred_slot[v]; }
+                if opts.count_paths { out.path_counts[v] = r.path_counts[v]; }
+            } else if d == out.dist[v] && opts.count_paths {
+                out.path_counts[v] = out.path_counts[v].saturating_add(r.path_counts[v]);
+            }
+        }
+        out.b_prime = out.b_prime.min(r.b_prime);
+        out.edges_scanned += r.edges_scanned;
+        out.heap_pushes += r.heap_pushes;
+        out.overflows += r.overflows;
+        if out.termination == Termination::Bound { out.termination = r.termination; }
+    }
+    out.explored = (0..n).filter(|&v| out.dist[v] != Weight::MAX).collect();
+    out.explored.sort_unstable_by_key(|&v| (out.dist[v], v));
+    out.dist_histogram = opts.histogram_width.and_then(|w| DistHistogram::of(out.explored.iter().map(|&v| out.dist[v]), w));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+
+    fn path(n: usize) -> Graph {
+        let mut g = Graph::new(n);
+        for i in 0..n - 1 { g.add_undirected_edge(i, i + 1, 1); }
+        g
+    }
+
+    #[test]
+    fn radii_cover_independently() {
+        // Depot 0 and depot 9 on a unit path 0..=9.
+        let g = path(10);
+        let opts = BmsspOptions::default();
+        let r = bmssp_per_source_bounds(&g, &[(0, 0), (9, 0)], &[3, 6], 100, &opts);
+        let inf = Weight::MAX;
+        assert_eq!(r.dist, vec![0, 1, 2, inf, 5, 4, 3, 2, 1, 0]);
+        assert_eq!(r.b_prime, 3);
+        // A small-radius depot doesn't stop a wider one from passing through its area.
+        let r = bmssp_per_source_bounds(&g, &[(0, 0), (9, 0)], &[3, 9], 100, &opts);
+        assert_eq!(r.dist, vec![0, 1, 2, 6, 5, 4, 3, 2, 1, 0]);
+        assert_eq!(r.explored[..3], [0, 9, 1]);
+    }
+
+    #[test]
+    fn uniform_bounds_match_plain_search() {
+        let mut g = path(50);
+        g.add_edge(3, 40, 2);
+        let sources = [(0, 0), (20, 1), (45, 2)];
+        let plain = bounded_multi_source_shortest_paths(&g, &sources, 8);
+        let r = bmssp_per_source_bounds(&g, &sources, &[Weight::MAX; 3], 8, &BmsspOptions::default());
+        assert_eq!(r.dist, plain.dist);
+        assert_eq!(r.explored, plain.explored);
+        assert_eq!(r.b_prime, plain.b_prime);
+    }
+
+    #[test]
+    fn budgets_merge_only_settled_nodes() {
+        let g = path(30);
+        let (sources, bounds) = ([(0, 0), (29, 0), (15, 0)], [20, 20, 4]);
+        let opts = BmsspOptions{ max_settled: Some(6), track_predecessors: true, count_paths: true, ..Default::default() };
+        let r = bmssp_per_source_bounds(&g, &sources, &bounds, 100, &opts);
+        // Reference: each group on its own, keeping only what it settled.
+        let mut want = vec![Weight::MAX; 30];
+        for (group, b) in [(&sources[..2], 20), (&sources[2..], 4)] {
+            let q = bmssp_with_options(&g, group, b, &opts);
+            assert_eq!(q.termination, Termination::SettledBudget);
+            for &v in &q.explored { want[v] = want[v].min(q.dist[v]); }
+        }
+        let mut settled: Vec<Node> = (0..30).filter(|&v| want[v] != Weight::MAX).collect();
+        settled.sort_unstable_by_key(|&v| (want[v], v));
+        assert_eq!(r.explored, settled);
+        assert_eq!(r.dist, want);
+        assert_eq!(r.termination, Termination::SettledBudget);
+        for &v in &r.explored {
+            assert_eq!(r.path_counts[v], 1);
+            assert!(r.pred[v] == NO_PRED || r.dist[r.pred[v]] + 1 == r.dist[v]);
+        }
+    }
+}
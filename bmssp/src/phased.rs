@@ -0,0 +1,159 @@
+//! Incremental SSSP in bounded phases. `phased_sssp` returns an iterator whose every `next`
+//! settles the nodes in `[L, L + phase_budget)`, where `L` is the smallest unsettled label, and
+//! yields the phase with its boundary set; labels and the heap persist between phases, so the
+//! caller can interleave other work, inspect the partial labels or stop at any point.
+//!
+//! ```
+//! # use bmssp::{bounded_multi_source_shortest_paths, phased_sssp, generators::make_grid};
+//! let g = make_grid(50, 50, 9, 1);
+//! let mut run = phased_sssp(&g, &[(0, 0)], 100);
+//! for phase in run.by_ref() {
+//!     // ... other work between phases ...
+//!     if phase.settled.contains(&2499) { break; }
+//! }
+//! assert_eq!(run.dist()[2499], bounded_multi_source_shortest_paths(&g, &[(0, 0)], u64::MAX).dist[2499]);
+//! ```
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::{Entry, GraphLike, Node, Weight};
+
+/// What one phase did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Phase {
+    pub index: usize,
+    /// Smallest unsettled label when the phase started (the previous phase's `b_prime`).
+    pub lower: Weight,
+    /// `lower + phase_budget`; every node settled in the phase has a label below it.
+    pub bound: Weight,
+    /// Nodes settled in this phase, in settle order.
+    pub settled: Vec<Node>,
+    /// Labelled but unsettled nodes after the phase, by id, with their tentative labels (all
+    /// at least `bound`): the sources of the next phase.
+    pub boundary: Vec<(Node, Weight)>,
+    /// Smallest label in `boundary`, `Weight::MAX` once it is empty. At least the bounded
+    /// search's B', which also counts longer edges into settled nodes.
+    pub b_prime: Weight,
+    pub edges_scanned: usize,
+}
+
+/// Search state between phases; see `phased_sssp`.
+#[derive(Clone, Debug)]
+pub struct PhasedSssp<'g, G: GraphLike> {
+    g: &'g G,
+    budget: Weight,
+    dist: Vec<Weight>,
+    done: Vec<bool>,
+    heap: BinaryHeap<Reverse<Entry>>,
+    /// Labelled nodes not yet settled, possibly with stale (settled) entries.
+    frontier: Vec<Node>,
+    phases: usize,
+}
+
+/// Start a phased search from `sources`. Each phase widens the settled region by
+/// `phase_budget` (at least 1) of distance. Chaining every phase gives the full
+/// shortest-path distances; unlike one bounded call, sources past a phase's bound are kept
+/// for later phases.
+pub fn phased_sssp<'g, G: GraphLike>(g: &'g G, sources: &[(Node, Weight)], phase_budget: Weight) -> PhasedSssp<'g, G> {
+    let n = g.num_nodes();
+    let mut run = PhasedSssp{ g, budget: phase_budget.max(1), dist: vec![Weight::MAX; n], done: vec![false; n], heap: BinaryHeap::new(), frontier: Vec::new(), phases: 0 };
+    for &(s, d0) in sources {
+        if s < n && d0 < run.dist[s] && !g.is_removed(s) { run.label(s, d0); }
+    }
+    run
+}
+
+impl<G: GraphLike> PhasedSssp<'_, G> {
+    fn label(&mut self, v: Node, d: Weight) {
+        if self.dist[v] == Weight::MAX { self.frontier.push(v); }
+        self.dist[v] = d;
+        self.heap.push(Reverse(Entry{ d, v }));
+    }
+
+    /// Final distances for settled nodes, tentative labels on the boundary, `Weight::MAX`
+    /// elsewhere.
+    pub fn dist(&self) -> &[Weight] { &self.dist }
+
+    pub fn is_settled(&self, v: Node) -> bool { self.done.get(v).copied().unwrap_or(false) }
+
+    /// Smallest unsettled label, i.e. the next phase's `lower`; `None` when the search is done.
+    pub fn next_lower(&mut self) -> Option<Weight> {
+        while let Some(&Reverse(e)) = self.heap.peek() {
+            if !self.done[e.v] && e.d == self.dist[e.v] { return Some(e.d); }
+            self.heap.pop();
+        }
+        None
+    }
+
+    /// Run the remaining phases and return the distances.
+    pub fn into_dist(mut self) -> Vec<Weight> {
+        while self.next().is_some() {}
+        self.dist
+    }
+}
+
+impl<G: GraphLike> Iterator for PhasedSssp<'_, G> {
+    type Item = Phase;
+
+    fn next(&mut self) -> Option<Phase> {
+        let lower = self.next_lower()?;
+        let bound = lower.saturating_add(self.budget);
+        let (mut settled, mut edges_scanned) = (Vec::new(), 0);
+        while let Some(&Reverse(Entry{ d, v })) = self.heap.peek() {
+            if d >= bound { break; }
+            self.heap.pop();
+            if self.done[v] || d != self.dist[v] { continue; }
+            self.done[v] = true;
+            settled.push(v);
+            for (to, w) in self.g.neighbors(v) {
+                edges_scanned += 1;
+                let nd = d.saturating_add(w);
+                if nd < self.dist[to] && !self.done[to] { self.label(to, nd); }
+            }
+        }
+        let done = &self.done;
+        self.frontier.retain(|&v| !done[v]);
+        self.frontier.sort_unstable();
+        let boundary: Vec<(Node, Weight)> = self.frontier.iter().map(|&v| (v, self.dist[v])).collect();
+        let b_prime = boundary.iter().map(|&(_, d)| d).min().unwrap_or(Weight::MAX);
+        let index = self.phases;
+        self.phases += 1;
+        Some(Phase{ index, lower, bound, settled, boundary, b_prime, edges_scanned })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use crate::generators::{make_ba, make_grid};
+
+    #[test]
+    fn phases_chain_to_the_full_search() {
+        for g in [make_grid(30, 30, 9, 2), make_ba(600, 4, 3, 20, 5)] {
+            let sources = [(0, 0), (17, 3), (400, 8)];
+            let full = bounded_multi_source_shortest_paths(&g, &sources, Weight::MAX);
+            let mut run = phased_sssp(&g, &sources, 25);
+            let mut settled = 0;
+            let mut lower = 0;
+            while let Some(p) = run.next() {
+                assert_eq!(p.lower, lower);
+                // A phase is the bounded search at its bound, minus what earlier phases settled.
+                let r = bounded_multi_source_shortest_paths(&g, &sources, p.bound);
+                assert_eq!(settled + p.settled.len(), r.settled_count(), "phase {}", p.index);
+                assert!(r.b_prime <= p.b_prime && r.b_prime >= p.bound);
+                assert_eq!(Some(p.b_prime), (0..g.len()).map(|v| full.dist[v]).filter(|&d| d >= p.bound).min().or(Some(Weight::MAX)));
+                assert!(p.settled.iter().all(|&v| run.dist()[v] == full.dist[v] && run.dist()[v] < p.bound));
+                assert!(p.boundary.iter().all(|&(v, d)| d >= p.bound && !run.is_settled(v)));
+                settled += p.settled.len();
+                lower = p.b_prime;
+            }
+            assert_eq!(run.into_dist(), full.dist);
+        }
+        // A source past the first bound waits for its phase.
+        let g = make_grid(10, 10, 1, 1);
+        let phases: Vec<Phase> = phased_sssp(&g, &[(0, 0), (99, 100)], 5).collect();
+        assert_eq!(phases[0].boundary.iter().find(|b| b.0 == 99), Some(&(99, 100)));
+        assert!(phased_sssp(&g, &[], 5).next().is_none());
+    }
+}
@@ -0,0 +1,82 @@
+//! Query files for batch runs on one loaded graph. One query per line:
+//!
+//! ```text
+//! # bound  sources (node or node:d0)
+//! 500 0 17 42:3
+//! ```
+//!
+//! Blank lines and `#` comments are skipped. Shared with other language entries, so keep it
+//! whitespace-separated plain text.
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::{Node, Weight};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Query {
+    pub bound: Weight,
+    pub sources: Vec<(Node, Weight)>,
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.bound)?;
+        for &(s, d0) in &self.sources {
+            if d0 == 0 { write!(f, " {s}")?; } else { write!(f, " {s}:{d0}")?; }
+        }
+        Ok(())
+    }
+}
+
+fn parse_line(line: &str) -> Result<Option<Query>, String> {
+    let line = line.split('#').next().unwrap_or("").trim();
+    if line.is_empty() { return Ok(None); }
+    let mut parts = line.split_whitespace();
+    let bound = parts.next().unwrap().parse().map_err(|e| format!("bound: {e}"))?;
+    let sources = parts.map(|tok| {
+        let (s, d0) = tok.split_once(':').unwrap_or((tok, "0"));
+        Ok((s.parse().map_err(|e| format!("source {tok:?}: {e}"))?, d0.parse().map_err(|e| format!("source {tok:?}: {e}"))?))
+    }).collect::<Result<Vec<_>, String>>()?;
+    if sources.is_empty() { return Err("query has no sources".into()); }
+    Ok(Some(Query{ bound, sources }))
+}
+
+/// Parse a query file; errors name the offending line.
+pub fn read_queries<R: BufRead>(r: R) -> io::Result<Vec<Query>> {
+    let mut out = Vec::new();
+    for (i, line) in r.lines().enumerate() {
+        match parse_line(&line?) {
+            Ok(Some(q)) => out.push(q),
+            Ok(None) => {}
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {e}", i + 1))),
+        }
+    }
+    Ok(out)
+}
+
+pub fn write_queries<W: Write>(mut w: W, queries: &[Query]) -> io::Result<()> {
+    for q in queries { writeln!(w, "{q}")?; }
+    w.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_and_errors() {
+        let text = "# header\n500 0 17 42:3\n\n  7 1   # trailing comment\n";
+        let qs = read_queries(text.as_bytes()).unwrap();
+        assert_eq!(qs, vec![
+            Query{ bound: 500, sources: vec![(0, 0), (17, 0), (42, 3)] },
+            Query{ bound: 7, sources: vec![(1, 0)] },
+        ]);
+        let mut buf = Vec::new();
+        write_queries(&mut buf, &qs).unwrap();
+        assert_eq!(read_queries(&buf[..]).unwrap(), qs);
+        let err = read_queries("5 1\n9\n".as_bytes()).unwrap_err();
+        assert!(err.to_string().starts_with("line 2"), "{err}");
+        assert!(read_queries("5 x:1".as_bytes()).is_err());
+    }
+}
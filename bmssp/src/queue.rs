@@ -0,0 +1,323 @@
+//! Frontier queues for the sequential search behind one trait, so structures can be compared on
+//! the same loop (`bmssp_with_queue`, CLI `--queue NAME`). Every queue pops in `(dist, node)`
+//! order, so distances, settle order, B' and counters match `bounded_multi_source_shortest_paths`
+//! exactly; only the time differs.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::{BmsspResult, GraphLike, Node, Weight};
+
+pub trait FrontierQueue {
+    /// Queue `v` at `d`, or lower its key if it is already queued (the search only lowers keys).
+    fn push(&mut self, v: Node, d: Weight);
+    /// Remove the smallest `(d, v)`. Lazy queues may return an entry whose key was lowered
+    /// since; the search skips those.
+    fn pop(&mut self) -> Option<(Weight, Node)>;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum QueueKind {
+    /// `BinaryQueue`, the structure the other searches use.
+    #[default]
+    Binary,
+    Pairing,
+    /// `MultiLevelBuckets` sized from the bound.
+    Mlb,
+}
+
+impl QueueKind {
+    pub const ALL: &'static [QueueKind] = &[QueueKind::Binary, QueueKind::Pairing, QueueKind::Mlb];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QueueKind::Binary => "binary",
+            QueueKind::Pairing => "pairing",
+            QueueKind::Mlb => "mlb",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> { Self::ALL.iter().copied().find(|q| q.as_str() == s) }
+}
+
+/// `std::collections::BinaryHeap` with lazy deletion: lowering a key pushes a second entry.
+#[derive(Clone, Debug, Default)]
+pub struct BinaryQueue(BinaryHeap<Reverse<(Weight, Node)>>);
+
+impl FrontierQueue for BinaryQueue {
+    #[inline]
+    fn push(&mut self, v: Node, d: Weight) { self.0.push(Reverse((d, v))); }
+    #[inline]
+    fn pop(&mut self) -> Option<(Weight, Node)> { self.0.pop().map(|Reverse(e)| e) }
+}
+
+const NIL: Node = Node::MAX;
+
+/// Pairing heap over node ids with true decrease-key and two-pass merging on pop. Links live
+/// in per-node arrays, so there is no per-push allocation and no stale entries.
+#[derive(Clone, Debug)]
+pub struct PairingHeap {
+    key: Vec<Weight>,
+    child: Vec<Node>,
+    next: Vec<Node>,
+    /// Left sibling, or the parent for a leftmost child; `NIL` for the root.
+    prev: Vec<Node>,
+    queued: Vec<bool>,
+    root: Node,
+    /// Scratch for the first merge pass.
+    pairs: Vec<Node>,
+}
+
+impl PairingHeap {
+    /// Empty heap for node ids `0..n`.
+    pub fn new(n: usize) -> Self {
+        Self { key: vec![0; n], child: vec![NIL; n], next: vec![NIL; n], prev: vec![NIL; n], queued: vec![false; n], root: NIL, pairs: Vec::new() }
+    }
+
+    pub fn is_empty(&self) -> bool { self.root == NIL }
+
+    #[inline]
+    fn less(&self, a: Node, b: Node) -> bool { (self.key[a], a) < (self.key[b], b) }
+
+    /// Merge two detached roots; returns the new root.
+    #[inline]
+    fn link(&mut self, a: Node, b: Node) -> Node {
+        let (w, l) = if self.less(b, a) { (b, a) } else { (a, b) };
+        let c = self.child[w];
+        self.next[l] = c;
+        if c != NIL { self.prev[c] = l; }
+        self.prev[l] = w;
+        self.child[w] = l;
+        w
+    }
+}
+
+impl FrontierQueue for PairingHeap {
+    fn push(&mut self, v: Node, d: Weight) {
+        self.key[v] = d;
+        if !self.queued[v] {
+            self.queued[v] = true;
+            self.child[v] = NIL;
+        } else if v == self.root {
+            return;
+        } else {
+            // Cut `v`'s subtree out of its sibling list and re-link it at the top.
+            let p = self.prev[v];
+            if self.child[p] == v { self.child[p] = self.next[v]; } else { self.next[p] = self.next[v]; }
+            if self.next[v] != NIL { self.prev[self.next[v]] = p; }
+        }
+        self.next[v] = NIL;
+        self.prev[v] = NIL;
+        self.root = if self.root == NIL { v } else { self.link(self.root, v) };
+    }
+
+    fn pop(&mut self) -> Option<(Weight, Node)> {
+        let r = self.root;
+        if r == NIL { return None; }
+        self.queued[r] = false;
+        let mut pairs = std::mem::take(&mut self.pairs);
+        let mut a = self.child[r];
+        self.child[r] = NIL;
+        while a != NIL {
+            let b = self.next[a];
+            self.next[a] = NIL;
+            self.prev[a] = NIL;
+            if b == NIL { pairs.push(a); break; }
+            let rest = self.next[b];
+            self.next[b] = NIL;
+            self.prev[b] = NIL;
+            let m = self.link(a, b);
+            pairs.push(m);
+            a = rest;
+        }
+        self.root = pairs.pop().unwrap_or(NIL);
+        while let Some(x) = pairs.pop() { self.root = self.link(x, self.root); }
+        self.pairs = pairs;
+        Some((self.key[r], r))
+    }
+}
+
+const DIGIT_BITS: u32 = 6;
+const RADIX: usize = 1 << DIGIT_BITS;
+
+/// Multi-level bucket queue (Denardo-Fox) for monotone integer keys: `RADIX` buckets per
+/// level, one level per base-`RADIX` digit of `bound - 1`. An entry sits at the level of the
+/// highest digit where its key differs from the last popped key, in the bucket for that
+/// digit; popping past level 0 empties the lowest non-empty bucket into the levels below.
+/// Each entry moves down at most once per level, so pushes cost O(1), pops O(levels)
+/// amortized, and memory does not grow with the weight range the way Dial's buckets do.
+///
+/// Keys must lie in `last..bound`, where `last` is the last popped key; the search's
+/// non-negative weights guarantee it. Entries at the current key are kept sorted, so pops
+/// come out in `(key, node)` order. Lowered keys leave a stale entry behind, as in
+/// `BinaryQueue`.
+#[derive(Clone, Debug)]
+pub struct MultiLevelBuckets {
+    /// `levels * RADIX` buckets, level-major.
+    buckets: Vec<Vec<(Weight, Node)>>,
+    /// Per level, bit `j` set when bucket `j` is non-empty.
+    occupied: Vec<u64>,
+    last: Weight,
+    /// Nodes queued at `last`, in decreasing id order.
+    current: Vec<Node>,
+}
+
+impl MultiLevelBuckets {
+    /// Empty queue for keys below `bound`.
+    pub fn new(bound: Weight) -> Self {
+        let bits = Weight::BITS - bound.saturating_sub(1).leading_zeros();
+        let levels = bits.div_ceil(DIGIT_BITS).max(1) as usize;
+        Self { buckets: vec![Vec::new(); levels * RADIX], occupied: vec![0; levels], last: 0, current: Vec::new() }
+    }
+
+    pub fn levels(&self) -> usize { self.occupied.len() }
+
+    pub fn is_empty(&self) -> bool { self.current.is_empty() && self.occupied.iter().all(|&m| m == 0) }
+
+    #[inline]
+    fn digit(d: Weight, level: usize) -> usize { (d >> (level as u32 * DIGIT_BITS)) as usize & (RADIX - 1) }
+
+    #[inline]
+    fn place(&mut self, v: Node, d: Weight) {
+        let diff = d ^ self.last;
+        if diff == 0 {
+            let at = self.current.partition_point(|&u| u > v);
+            self.current.insert(at, v);
+            return;
+        }
+        let level = ((Weight::BITS - 1 - diff.leading_zeros()) / DIGIT_BITS) as usize;
+        let j = Self::digit(d, level);
+        self.buckets[level * RADIX + j].push((d, v));
+        self.occupied[level] |= 1 << j;
+    }
+}
+
+impl FrontierQueue for MultiLevelBuckets {
+    fn push(&mut self, v: Node, d: Weight) {
+        debug_assert!(d >= self.last, "MultiLevelBuckets keys must not go below the last pop");
+        debug_assert!(Weight::BITS - d.leading_zeros() <= (self.levels() as u32 * DIGIT_BITS).min(Weight::BITS), "key beyond the bound");
+        self.place(v, d);
+    }
+
+    fn pop(&mut self) -> Option<(Weight, Node)> {
+        if let Some(v) = self.current.pop() { return Some((self.last, v)); }
+        let level = self.occupied.iter().position(|&m| m != 0)?;
+        let j = self.occupied[level].trailing_zeros() as usize;
+        self.occupied[level] &= !(1 << j);
+        let mut bucket = std::mem::take(&mut self.buckets[level * RADIX + j]);
+        self.last = bucket.iter().map(|e| e.0).min().unwrap();
+        // Every entry now agrees with `last` above `level`, so it lands strictly lower (or in
+        // `current`); level-0 buckets hold one key each and go straight to `current`.
+        if level == 0 {
+            self.current.extend(bucket.iter().map(|e| e.1));
+            self.current.sort_unstable_by(|a, b| b.cmp(a));
+        } else {
+            for &(d, v) in &bucket { self.place(v, d); }
+        }
+        bucket.clear();
+        self.buckets[level * RADIX + j] = bucket;
+        self.pop()
+    }
+}
+
+/// The plain bounded search (no options) over any `FrontierQueue`. `heap_pushes` counts
+/// improving relaxations, as in `BmsspResult`, whether or not the queue stored a new entry.
+pub fn bmssp_with_queue<G: GraphLike, Q: FrontierQueue>(g: &G, sources: &[(Node, Weight)], bound: Weight, queue: &mut Q) -> BmsspResult {
+    let n = g.num_nodes();
+    let mut dist = vec![Weight::MAX; n];
+    for &(s, d0) in sources {
+        if s < n && d0 < bound && d0 < dist[s] && !g.is_removed(s) {
+            dist[s] = d0;
+            queue.push(s, d0);
+        }
+    }
+    let (mut explored, mut b_prime) = (Vec::new(), Weight::MAX);
+    let (mut edges_scanned, mut heap_pushes) = (0, 0);
+    while let Some((d, v)) = queue.pop() {
+        if d != dist[v] { continue; }
+        explored.push(v);
+        for (to, w) in g.neighbors(v) {
+            edges_scanned += 1;
+            let nd = d.saturating_add(w);
+            if nd < dist[to] && nd < bound {
+                dist[to] = nd;
+                queue.push(to, nd);
+                heap_pushes += 1;
+            } else if nd >= bound && nd < b_prime {
+                b_prime = nd;
+            }
+        }
+    }
+    BmsspResult{ dist, explored, b_prime, edges_scanned, heap_pushes, ..Default::default() }
+}
+
+/// `bmssp_with_queue` with a fresh queue of the given kind.
+pub fn bmssp_with_queue_kind<G: GraphLike>(g: &G, sources: &[(Node, Weight)], bound: Weight, kind: QueueKind) -> BmsspResult {
+    match kind {
+        QueueKind::Binary => bmssp_with_queue(g, sources, bound, &mut BinaryQueue::default()),
+        QueueKind::Pairing => bmssp_with_queue(g, sources, bound, &mut PairingHeap::new(g.num_nodes())),
+        QueueKind::Mlb => bmssp_with_queue(g, sources, bound, &mut MultiLevelBuckets::new(bound)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use crate::generators::{make_ba, make_grid, pick_sources};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn pairing_heap_pops_in_key_order_with_decrease_key() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut h = PairingHeap::new(200);
+        let mut key = vec![Weight::MAX; 200];
+        for _ in 0..2000 {
+            let v = rng.gen_range(0..200);
+            let d = rng.gen_range(0..1000);
+            if d < key[v] { key[v] = d; h.push(v, d); }
+            if rng.gen_bool(0.2) {
+                let (d, v) = h.pop().unwrap();
+                let min = (0..200).filter(|&u| key[u] != Weight::MAX).map(|u| (key[u], u)).min().unwrap();
+                assert_eq!((d, v), min);
+                key[v] = Weight::MAX;
+            }
+        }
+        let rest: Vec<_> = std::iter::from_fn(|| h.pop()).collect();
+        assert!(rest.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(rest.len(), key.iter().filter(|&&d| d != Weight::MAX).count());
+        assert!(h.is_empty());
+    }
+
+    #[test]
+    fn multi_level_buckets_match_on_wide_weights_and_ties() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut g = crate::Graph::new(500);
+        for _ in 0..4000 {
+            let (u, v) = (rng.gen_range(0..500), rng.gen_range(0..500));
+            let w = match rng.gen_range(0..4) { 0 => 0, 1 => rng.gen_range(1..4), _ => rng.gen_range(0..1u64 << 40) };
+            g.add_edge(u, v, w);
+        }
+        let sources = [(3, 0), (77, 5), (300, 1 << 39)];
+        for bound in [1, 6, 1 << 41, Weight::MAX] {
+            let expect = bounded_multi_source_shortest_paths(&g, &sources, bound);
+            let mut q = MultiLevelBuckets::new(bound);
+            let r = bmssp_with_queue(&g, &sources, bound, &mut q);
+            assert_eq!((r.dist, r.explored, r.b_prime), (expect.dist, expect.explored, expect.b_prime), "B={bound}");
+            assert!(q.is_empty());
+        }
+        assert_eq!((MultiLevelBuckets::new(0).levels(), MultiLevelBuckets::new(65).levels(), MultiLevelBuckets::new(Weight::MAX).levels()), (1, 2, 11));
+    }
+
+    #[test]
+    fn every_queue_matches_the_default_search() {
+        for g in [make_grid(30, 30, 20, 1), make_ba(600, 4, 3, 9, 2)] {
+            let sources = pick_sources(g.len(), 5, 3);
+            let expect = bounded_multi_source_shortest_paths(&g, &sources, 60);
+            for &q in QueueKind::ALL {
+                let r = bmssp_with_queue_kind(&g, &sources, 60, q);
+                assert_eq!((r.dist, r.explored, r.b_prime), (expect.dist.clone(), expect.explored.clone(), expect.b_prime), "{}", q.as_str());
+                assert_eq!((r.edges_scanned, r.heap_pushes), (expect.edges_scanned, expect.heap_pushes), "{}", q.as_str());
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Distance-limited reachability: which nodes lie within `bound`, without their distances. For
+//! coverage-style questions the dense `dist` array is pure overhead: `reachable_within` keeps
+//! one bit per node for the settled set and leaves tentative labels in the heap alone (a lazy
+//! Dijkstra without decrease-key), so the per-node state is n/8 bytes instead of 8n and the
+//! only random writes are bit sets.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::{Entry, GraphLike, Node, Weight};
+
+/// Nodes within the bound, as a bitset over node ids.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Reachability {
+    bits: Vec<u64>,
+    count: usize,
+    /// Smallest relaxation at or past the bound, as in `BmsspResult::b_prime`.
+    pub b_prime: Weight,
+    pub edges_scanned: usize,
+    pub heap_pushes: usize,
+}
+
+impl Reachability {
+    #[inline]
+    pub fn contains(&self, v: Node) -> bool { self.bits.get(v / 64).is_some_and(|&w| w & (1 << (v % 64)) != 0) }
+    /// Number of nodes within the bound.
+    pub fn len(&self) -> usize { self.count }
+    pub fn is_empty(&self) -> bool { self.count == 0 }
+    /// Members in increasing id order.
+    pub fn nodes(&self) -> Vec<Node> {
+        self.bits.iter().enumerate().flat_map(|(i, &w)| (0..64).filter(move |b| w & (1 << b) != 0).map(move |b| i * 64 + b)).collect()
+    }
+    /// The bitset, bit `v % 64` of word `v / 64` per node.
+    pub fn words(&self) -> &[u64] { &self.bits }
+}
+
+/// Nodes at distance below `bound` from `sources`: the settled set of
+/// `bounded_multi_source_shortest_paths` with the same B' and scan count, without distances.
+/// A node can sit in the heap once per incoming relaxation, so `heap_pushes` is higher.
+pub fn reachable_within<G: GraphLike>(g: &G, sources: &[(Node, Weight)], bound: Weight) -> Reachability {
+    let n = g.num_nodes();
+    let mut out = Reachability{ bits: vec![0; n.div_ceil(64)], b_prime: Weight::MAX, ..Default::default() };
+    let mut heap: BinaryHeap<Reverse<Entry>> = BinaryHeap::new();
+    for &(s, d0) in sources {
+        if s < n && d0 < bound && !g.is_removed(s) { heap.push(Reverse(Entry{ d: d0, v: s })); }
+    }
+    while let Some(Reverse(Entry{ d, v })) = heap.pop() {
+        let (w, b) = (v / 64, 1u64 << (v % 64));
+        if out.bits[w] & b != 0 { continue; }
+        out.bits[w] |= b;
+        out.count += 1;
+        for (to, wt) in g.neighbors(v) {
+            out.edges_scanned += 1;
+            let nd = d.saturating_add(wt);
+            if nd >= bound {
+                out.b_prime = out.b_prime.min(nd);
+            } else if !out.contains(to) {
+                heap.push(Reverse(Entry{ d: nd, v: to }));
+                out.heap_pushes += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use crate::generators::{make_ba, make_grid};
+
+    #[test]
+    fn matches_the_settled_set() {
+        for g in [make_grid(40, 40, 9, 2), make_ba(2000, 4, 3, 20, 5)] {
+            let sources = [(0, 0), (700, 5), (700, 2), (1599, 40)];
+            for bound in [0, 3, 40, 120, Weight::MAX] {
+                let (r, full) = (reachable_within(&g, &sources, bound), bounded_multi_source_shortest_paths(&g, &sources, bound));
+                let mut settled = full.explored.clone();
+                settled.sort_unstable();
+                assert_eq!(r.nodes(), settled, "B={bound}");
+                assert_eq!((r.len(), r.b_prime, r.edges_scanned), (full.settled_count(), full.b_prime, full.edges_scanned), "B={bound}");
+                assert!(settled.iter().all(|&v| r.contains(v)) && !r.contains(g.len()));
+            }
+        }
+        assert!(reachable_within(&make_grid(3, 3, 1, 1), &[], 10).is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+//! Node reordering for locality. Both orderings treat the graph as undirected (out + in edges)
+//! and return `(permuted graph, perm)` with `perm[old] = new`.
+use crate::{Graph, Node, Weight};
+use std::collections::VecDeque;
+
+/// Apply `perm` (`perm[old] = new`) to `g`. Edge order within each list is preserved.
+pub fn permute(g: &Graph, perm: &[Node]) -> Graph {
+    let mut out = Graph::new(g.len());
+    for (u, edges) in g.adj.iter().enumerate() {
+        out.adj[perm[u]] = edges.iter().map(|&(v, w)| (perm[v], w)).collect();
+    }
+    out
+}
+
+/// Map sources given in original ids into the permuted id space.
+pub fn permute_sources(perm: &[Node], sources: &[(Node, Weight)]) -> Vec<(Node, Weight)> {
+    sources.iter().filter(|&&(s, _)| s < perm.len()).map(|&(s, d0)| (perm[s], d0)).collect()
+}
+
+/// Map a distance array computed on the permuted graph back to original ids.
+pub fn restore_dist(perm: &[Node], dist: &[Weight]) -> Vec<Weight> {
+    perm.iter().map(|&p| dist[p]).collect()
+}
+
+fn symmetric_neighbors(g: &Graph) -> Vec<Vec<Node>> {
+    let mut nb: Vec<Vec<Node>> = g.adj.iter().map(|e| e.iter().map(|&(v, _)| v).collect()).collect();
+    for (u, edges) in g.adj.iter().enumerate() {
+        for &(v, _) in edges { nb[v].push(u); }
+    }
+    for l in &mut nb { l.sort_unstable(); l.dedup(); }
+    nb
+}
+
+/// Visit every component in BFS order, starting each from `roots` in order. `order_nb` may
+/// reorder a node's neighbors before they're enqueued. Returns the visit order.
+fn bfs_order(nb: &mut [Vec<Node>], roots: impl Iterator<Item = Node>, order_nb: impl Fn(&mut Vec<Node>)) -> Vec<Node> {
+    let n = nb.len();
+    let mut seen = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    let mut q = VecDeque::new();
+    for r in roots {
+        if seen[r] { continue; }
+        seen[r] = true;
+        q.push_back(r);
+        while let Some(u) = q.pop_front() {
+            order.push(u);
+            order_nb(&mut nb[u]);
+            for &v in &nb[u] {
+                if !seen[v] { seen[v] = true; q.push_back(v); }
+            }
+        }
+    }
+    order
+}
+
+fn order_to_perm(order: &[Node]) -> Vec<Node> {
+    let mut perm = vec![0; order.len()];
+    for (new, &old) in order.iter().enumerate() { perm[old] = new; }
+    perm
+}
+
+/// Undirected BFS visit order from node 0, then each unvisited node in id order.
+pub(crate) fn bfs_visit_order(g: &Graph) -> Vec<Node> {
+    let mut nb = symmetric_neighbors(g);
+    bfs_order(&mut nb, 0..g.len(), |_| {})
+}
+
+/// Plain BFS numbering from node 0 (then each unvisited node in id order).
+pub fn bfs(g: &Graph) -> (Graph, Vec<Node>) {
+    let perm = order_to_perm(&bfs_visit_order(g));
+    (permute(g, &perm), perm)
+}
+
+/// Reverse Cuthill–McKee: BFS from a minimum-degree node per component, enqueueing neighbors
+/// by increasing degree, then reverse the whole order.
+pub fn rcm(g: &Graph) -> (Graph, Vec<Node>) {
+    let mut nb = symmetric_neighbors(g);
+    let deg: Vec<usize> = nb.iter().map(|l| l.len()).collect();
+    let mut roots: Vec<Node> = (0..g.len()).collect();
+    roots.sort_by_key(|&v| (deg[v], v));
+    let mut order = bfs_order(&mut nb, roots.into_iter(), |l| l.sort_by_key(|&v| (deg[v], v)));
+    order.reverse();
+    let perm = order_to_perm(&order);
+    (permute(g, &perm), perm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+
+    fn scrambled_grid(side: usize) -> Graph {
+        // Grid with ids shuffled by a fixed multiplicative hash so reordering has work to do.
+        let n = side * side;
+        let id = |r: usize, c: usize| ((r * side + c) * 37) % n;
+        let mut g = Graph::new(n);
+        for r in 0..side {
+            for c in 0..side {
+                if r + 1 < side { g.add_undirected_edge(id(r, c), id(r + 1, c), 1 + (r % 3) as u64); }
+                if c + 1 < side { g.add_undirected_edge(id(r, c), id(r, c + 1), 1 + (c % 4) as u64); }
+            }
+        }
+        g
+    }
+
+    fn bandwidth(g: &Graph) -> usize {
+        g.adj.iter().enumerate().flat_map(|(u, e)| e.iter().map(move |&(v, _)| u.abs_diff(v))).max().unwrap_or(0)
+    }
+
+    #[test]
+    fn reorder_preserves_distances() {
+        let g = scrambled_grid(10);
+        let sources = [(3, 0), (51, 2)];
+        let base = bounded_multi_source_shortest_paths(&g, &sources, 12);
+        for (h, perm) in [rcm(&g), bfs(&g)] {
+            let mut sorted = perm.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, (0..g.len()).collect::<Vec<_>>());
+            let r = bounded_multi_source_shortest_paths(&h, &permute_sources(&perm, &sources), 12);
+            assert_eq!(restore_dist(&perm, &r.dist), base.dist);
+            assert_eq!(r.b_prime, base.b_prime);
+        }
+    }
+
+    #[test]
+    fn rcm_reduces_bandwidth() {
+        let g = scrambled_grid(12);
+        let (h, _) = rcm(&g);
+        assert!(bandwidth(&h) < bandwidth(&g));
+    }
+}
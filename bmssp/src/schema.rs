@@ -0,0 +1,67 @@
+//! Versioning of the JSON benchmark rows printed by `bmssp-cli --json`, the harness and the
+//! other language ports (fields are listed in `bench/schema.json`). `schema_version` is
+//! `"MAJOR.MINOR"`: a minor bump adds optional fields, a major bump renames or removes a field
+//! or changes what one means. Readers accept rows of their own major version, whatever the
+//! minor; rows without the field predate versioning and count as 1.0.
+use std::fmt;
+
+use serde_json::Value;
+
+/// Version the rows written by this crate carry.
+pub const ROW_SCHEMA_VERSION: &str = "1.0";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SchemaVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl SchemaVersion {
+    /// `ROW_SCHEMA_VERSION`.
+    pub const CURRENT: SchemaVersion = SchemaVersion{ major: 1, minor: 0 };
+
+    /// `"MAJOR.MINOR"`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (major, minor) = s.split_once('.')?;
+        Some(Self{ major: major.parse().ok()?, minor: minor.parse().ok()? })
+    }
+
+    /// Whether a reader built for `reader` understands rows of this version.
+    pub fn readable_by(self, reader: SchemaVersion) -> bool { self.major == reader.major }
+
+    /// Version of a JSON row: its `schema_version`, or 1.0 when it has none.
+    pub fn of_row(row: &Value) -> Result<Self, String> {
+        match row.get("schema_version") {
+            None => Ok(Self{ major: 1, minor: 0 }),
+            Some(Value::String(s)) => Self::parse(s).ok_or_else(|| format!("bad schema_version {s:?}")),
+            Some(v) => Err(format!("bad schema_version {v}")),
+        }
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}.{}", self.major, self.minor) }
+}
+
+/// `Err` with the reason when `row` can't be read as a `SchemaVersion::CURRENT` row.
+pub fn check_row_schema(row: &Value) -> Result<(), String> {
+    let v = SchemaVersion::of_row(row)?;
+    if v.readable_by(SchemaVersion::CURRENT) { Ok(()) } else { Err(format!("schema_version {v} is not readable as {}", SchemaVersion::CURRENT)) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn same_major_is_readable() {
+        assert_eq!(SchemaVersion::parse(ROW_SCHEMA_VERSION), Some(SchemaVersion::CURRENT));
+        assert_eq!(SchemaVersion::parse("2.13").map(|v| v.to_string()), Some("2.13".into()));
+        assert_eq!(SchemaVersion::parse("1"), None);
+        assert!(check_row_schema(&json!({"popped": 3})).is_ok());
+        assert!(check_row_schema(&json!({"schema_version": "1.7"})).is_ok());
+        assert!(check_row_schema(&json!({"schema_version": "2.0"})).unwrap_err().contains("2.0"));
+        assert!(check_row_schema(&json!({"schema_version": 1})).is_err());
+    }
+}
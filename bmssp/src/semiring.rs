@@ -0,0 +1,148 @@
+//! Cost models for the bounded search (combine along a path, pick the minimum by `Ord`). The
+//! sequential search is written once over `CostSemiring`; `bmssp_with_options` runs it with
+//! `MinPlus`, which monomorphizes to the plain `u64` loop, and `bmssp_semiring` with any other.
+use std::cmp::Ordering;
+
+use crate::{bmssp_core, BmsspOptions, BmsspResult, GraphLike, Node, NoopVisitor, Termination, Weight};
+
+/// Path-cost model. `extend` must never make a cost better (`extend(c, w) >= c`), which is what
+/// lets the label-setting search settle each node once.
+pub trait CostSemiring {
+    type Cost: Copy + Ord + std::fmt::Debug;
+    /// Cost of the empty path at a source.
+    fn identity() -> Self::Cost;
+    /// Cost of an unreached node; worse than every reachable cost.
+    fn infinity() -> Self::Cost;
+    /// Cost after appending an edge of weight `w`.
+    fn extend(c: Self::Cost, w: Weight) -> Self::Cost;
+    /// `extend` as the search calls it: `None` stops the search with `Termination::Overflow`.
+    /// Only `MinPlus` can overflow, and follows `opts.overflow` counting into `overflows`.
+    #[inline]
+    fn extend_with(c: Self::Cost, w: Weight, _opts: &BmsspOptions, _overflows: &mut usize) -> Option<Self::Cost> { Some(Self::extend(c, w)) }
+}
+
+/// Sum of weights (the default BMSSP metric).
+pub struct MinPlus;
+impl CostSemiring for MinPlus {
+    type Cost = Weight;
+    #[inline] fn identity() -> Weight { 0 }
+    #[inline] fn infinity() -> Weight { Weight::MAX }
+    #[inline] fn extend(c: Weight, w: Weight) -> Weight { c.saturating_add(w) }
+    #[inline]
+    fn extend_with(c: Weight, w: Weight, opts: &BmsspOptions, overflows: &mut usize) -> Option<Weight> { opts.extend(c, w, overflows) }
+}
+
+/// Heaviest edge on the path (bottleneck / widest-path by minimum capacity use).
+pub struct MinMax;
+impl CostSemiring for MinMax {
+    type Cost = Weight;
+    #[inline] fn identity() -> Weight { 0 }
+    #[inline] fn infinity() -> Weight { Weight::MAX }
+    #[inline] fn extend(c: Weight, w: Weight) -> Weight { c.max(w) }
+}
+
+/// Number of edges; weights are ignored.
+pub struct HopCount;
+impl CostSemiring for HopCount {
+    type Cost = Weight;
+    #[inline] fn identity() -> Weight { 0 }
+    #[inline] fn infinity() -> Weight { Weight::MAX }
+    #[inline] fn extend(c: Weight, _w: Weight) -> Weight { c.saturating_add(1) }
+}
+
+/// Probability that every edge on the path works; edge weight is its success probability in
+/// parts per million (values above 1e6 are clamped). Higher reliability compares as smaller.
+pub struct Reliability;
+
+/// Success probability ordered so that more reliable is `Less`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Prob(pub f64);
+impl Eq for Prob {}
+impl Ord for Prob {
+    fn cmp(&self, other: &Self) -> Ordering { other.0.total_cmp(&self.0) }
+}
+impl PartialOrd for Prob {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl CostSemiring for Reliability {
+    type Cost = Prob;
+    #[inline] fn identity() -> Prob { Prob(1.0) }
+    #[inline] fn infinity() -> Prob { Prob(0.0) }
+    #[inline] fn extend(c: Prob, w: Weight) -> Prob { Prob(c.0 * (w.min(1_000_000) as f64 / 1e6)) }
+}
+
+/// Result of `bmssp_semiring`; mirrors `BmsspResult` with semiring costs.
+#[derive(Debug, Clone)]
+pub struct SemiringResult<C> {
+    pub dist: Vec<C>,
+    pub explored: Vec<Node>,
+    /// Best cost among labels that reached the bound, `infinity()` if none did.
+    pub b_prime: C,
+    pub edges_scanned: usize,
+    pub heap_pushes: usize,
+    pub termination: Termination,
+    pub overflows: usize,
+    pub path_counts: Vec<u64>,
+    pub pred: Vec<Node>,
+    pub pred_slot: Vec<usize>,
+}
+
+impl SemiringResult<Weight> {
+    pub fn into_result(self) -> BmsspResult {
+        BmsspResult{
+            dist: self.dist,
+            explored: self.explored,
+            b_prime: self.b_prime,
+            edges_scanned: self.edges_scanned,
+            heap_pushes: self.heap_pushes,
+            termination: self.termination,
+            overflows: self.overflows,
+            path_counts: self.path_counts,
+            pred: self.pred,
+            pred_slot: self.pred_slot,
+            ..Default::default()
+        }
+    }
+}
+
+/// Bounded multi-source search under semiring `S`: settles nodes whose best cost is `< bound`,
+/// in `(cost, node)` order. Sources carry their starting cost.
+pub fn bmssp_semiring<S: CostSemiring>(g: &impl GraphLike, sources: &[(Node, S::Cost)], bound: S::Cost) -> SemiringResult<S::Cost> {
+    bmssp_core::<S, _, _>(g, sources, bound, &BmsspOptions::default(), &mut NoopVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bounded_multi_source_shortest_paths, Graph};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn min_plus_matches_core_search() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut g = Graph::new(200);
+        for _ in 0..800 { g.add_edge(rng.gen_range(0..200), rng.gen_range(0..200), rng.gen_range(1..30)); }
+        let sources = [(0, 0), (10, 5), (150, 2)];
+        let a = bounded_multi_source_shortest_paths(&g, &sources, 40);
+        let b = bmssp_semiring::<MinPlus>(&g, &sources, 40).into_result();
+        assert_eq!((a.dist, a.explored, a.b_prime, a.edges_scanned, a.heap_pushes), (b.dist, b.explored, b.b_prime, b.edges_scanned, b.heap_pushes));
+    }
+
+    #[test]
+    fn hops_and_reliability() {
+        let mut g = Graph::new(4);
+        // Two-hop route 0 -> 1 -> 3 at 0.9 * 0.9, direct 0 -> 3 at 0.5, 3 -> 2 at 0.99.
+        g.add_edge(0, 1, 900_000); g.add_edge(1, 3, 900_000); g.add_edge(0, 3, 500_000); g.add_edge(3, 2, 990_000);
+        let hops = bmssp_semiring::<HopCount>(&g, &[(0, 0)], 2);
+        assert_eq!(hops.dist, vec![0, 1, Weight::MAX, 1]);
+        assert_eq!(hops.b_prime, 2);
+        let rel = bmssp_semiring::<Reliability>(&g, &[(0, Prob(1.0))], Prob(0.75));
+        assert!((rel.dist[3].0 - 0.81).abs() < 1e-12);
+        assert!((rel.dist[2].0 - 0.81 * 0.99).abs() < 1e-12);
+        assert_eq!(rel.explored, vec![0, 1, 3, 2]);
+        let rel = bmssp_semiring::<Reliability>(&g, &[(0, Prob(1.0))], Prob(0.805));
+        assert_eq!(rel.explored, vec![0, 1, 3]);
+        assert!((rel.b_prime.0 - 0.81 * 0.99).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,174 @@
+//! Vectorized edge relaxation over the `CompactGraph` SoA arrays.
+//!
+//! The kernel handles 8 edges per step: it widens the u32 weights, computes `d + w` in two
+//! 4 x u64 AVX2 registers, and masks lanes against `bound`. Surviving lanes are then checked
+//! against `dist` and pushed in lane order, so results and counters are identical to the scalar
+//! `bounded_multi_source_shortest_paths_compact`. Non-x86_64 targets, or CPUs without AVX2,
+//! use the scalar 8-lane fallback.
+use crate::compact::CompactGraph;
+use crate::{BmsspResult, Node, Weight};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+const LANES: usize = 8;
+
+/// Bounded multi-source search on `g` using the vectorized relaxation kernel.
+pub fn bounded_multi_source_shortest_paths_simd(
+    g: &CompactGraph,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+) -> BmsspResult {
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        // SAFETY: AVX2 support was just checked at runtime.
+        return unsafe { search_avx2(g, sources, bound) };
+    }
+    search(g, sources, bound, below_bound_scalar)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn search_avx2(g: &CompactGraph, sources: &[(Node, Weight)], bound: Weight) -> BmsspResult {
+    search(g, sources, bound, |d, w, b| unsafe { below_bound_avx2(d, w, b) })
+}
+
+/// Shared driver; `below` computes `d + w[i]` for 8 lanes into `nd` and returns the bitmask of
+/// lanes with `nd < bound`.
+#[inline(always)]
+fn search(
+    g: &CompactGraph,
+    sources: &[(Node, Weight)],
+    bound: Weight,
+    below: impl Fn(Weight, &[u32; LANES], Weight) -> ([Weight; LANES], u32),
+) -> BmsspResult {
+    let n = g.len();
+    let mut dist = vec![Weight::MAX; n];
+    let mut heap: BinaryHeap<Reverse<(Weight, u32)>> = BinaryHeap::new();
+    let mut explored = Vec::<Node>::new();
+    for &(s, d0) in sources {
+        if s < n && d0 < bound && d0 < dist[s] {
+            dist[s] = d0;
+            heap.push(Reverse((d0, s as u32)));
+        }
+    }
+    let mut b_prime = Weight::MAX;
+    let mut edges_scanned: usize = 0;
+    let mut heap_pushes: usize = 0;
+
+    while let Some(Reverse((d, v))) = heap.pop() {
+        let v = v as usize;
+        if d != dist[v] { continue; }
+        if d >= bound { b_prime = d; break; }
+        explored.push(v);
+
+        let (a, b) = (g.offsets[v], g.offsets[v + 1]);
+        edges_scanned += b - a;
+        let (targets, weights) = (&g.targets[a..b], &g.weights[a..b]);
+        let mut t_chunks = targets.chunks_exact(LANES);
+        let mut w_chunks = weights.chunks_exact(LANES);
+        for (ts, ws) in (&mut t_chunks).zip(&mut w_chunks) {
+            let (nd, mask) = below(d, ws.try_into().unwrap(), bound);
+            for i in 0..LANES {
+                let to = ts[i] as usize;
+                if mask & (1 << i) != 0 {
+                    if nd[i] < dist[to] {
+                        dist[to] = nd[i];
+                        heap.push(Reverse((nd[i], ts[i])));
+                        heap_pushes += 1;
+                    }
+                } else if nd[i] < b_prime {
+                    b_prime = nd[i];
+                }
+            }
+        }
+        for (&to, &w) in t_chunks.remainder().iter().zip(w_chunks.remainder()) {
+            let nd = d.saturating_add(w as Weight);
+            let ti = to as usize;
+            if nd < dist[ti] && nd < bound {
+                dist[ti] = nd;
+                heap.push(Reverse((nd, to)));
+                heap_pushes += 1;
+            } else if nd >= bound && nd < b_prime {
+                b_prime = nd;
+            }
+        }
+    }
+    BmsspResult{ dist, explored, b_prime, edges_scanned, heap_pushes, ..Default::default() }
+}
+
+#[inline(always)]
+fn below_bound_scalar(d: Weight, w: &[u32; LANES], bound: Weight) -> ([Weight; LANES], u32) {
+    let mut nd = [0; LANES];
+    let mut mask = 0u32;
+    for i in 0..LANES {
+        nd[i] = d.saturating_add(w[i] as Weight);
+        mask |= ((nd[i] < bound) as u32) << i;
+    }
+    (nd, mask)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn below_bound_avx2(d: Weight, w: &[u32; LANES], bound: Weight) -> ([Weight; LANES], u32) {
+    use std::arch::x86_64::*;
+    // d < bound <= u64::MAX and w < 2^32, so d + w can only wrap when d > u64::MAX - 2^32;
+    // defer those (astronomically rare) labels to the saturating scalar path.
+    if d > Weight::MAX - u32::MAX as Weight { return below_bound_scalar(d, w, bound); }
+    let ws = _mm256_loadu_si256(w.as_ptr() as *const __m256i);
+    let lo = _mm256_cvtepu32_epi64(_mm256_castsi256_si128(ws));
+    let hi = _mm256_cvtepu32_epi64(_mm256_extracti128_si256::<1>(ws));
+    let dv = _mm256_set1_epi64x(d as i64);
+    let nd_lo = _mm256_add_epi64(dv, lo);
+    let nd_hi = _mm256_add_epi64(dv, hi);
+    // Unsigned `nd < bound` via signed compare after flipping the sign bit of both sides.
+    let flip = _mm256_set1_epi64x(i64::MIN);
+    let bv = _mm256_xor_si256(_mm256_set1_epi64x(bound as i64), flip);
+    let lt_lo = _mm256_cmpgt_epi64(bv, _mm256_xor_si256(nd_lo, flip));
+    let lt_hi = _mm256_cmpgt_epi64(bv, _mm256_xor_si256(nd_hi, flip));
+    let mask = (_mm256_movemask_pd(_mm256_castsi256_pd(lt_lo)) as u32)
+        | ((_mm256_movemask_pd(_mm256_castsi256_pd(lt_hi)) as u32) << 4);
+    let mut nd = [0 as Weight; LANES];
+    _mm256_storeu_si256(nd.as_mut_ptr() as *mut __m256i, nd_lo);
+    _mm256_storeu_si256(nd.as_mut_ptr().add(4) as *mut __m256i, nd_hi);
+    (nd, mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compact::bounded_multi_source_shortest_paths_compact;
+    use crate::Graph;
+
+    #[test]
+    fn simd_matches_scalar() {
+        let mut g = Graph::new(300);
+        for u in 0..300 {
+            // Mixed degrees so both full 8-lane chunks and remainders are exercised.
+            for j in 0..(u % 13) {
+                g.add_edge(u, (u * 31 + j * 7 + 1) % 300, 1 + ((u + j) % 11) as u64);
+            }
+        }
+        g.add_edge(0, 1, u32::MAX as u64);
+        let cg = CompactGraph::from_graph(&g).unwrap();
+        let sources = [(1, 0), (150, 3), (299, 0)];
+        for bound in [5, 20, 80, Weight::MAX] {
+            let a = bounded_multi_source_shortest_paths_compact(&cg, &sources, bound);
+            let b = bounded_multi_source_shortest_paths_simd(&cg, &sources, bound);
+            assert_eq!(a.dist, b.dist);
+            assert_eq!(a.explored, b.explored);
+            assert_eq!(a.b_prime, b.b_prime);
+            assert_eq!((a.edges_scanned, a.heap_pushes), (b.edges_scanned, b.heap_pushes));
+        }
+    }
+
+    #[test]
+    fn lane_kernels_agree() {
+        let w = [0, 1, 2, 3, u32::MAX, 5, 6, 7];
+        let scalar = below_bound_scalar(10, &w, 14);
+        assert_eq!(scalar.1, 0b0000_1111);
+        #[cfg(target_arch = "x86_64")]
+        if std::arch::is_x86_feature_detected!("avx2") {
+            assert_eq!(unsafe { below_bound_avx2(10, &w, 14) }, scalar);
+        }
+    }
+}
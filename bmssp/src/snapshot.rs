@@ -0,0 +1,116 @@
+//! Binary snapshots of finished results, so an oracle answer computed once can be diffed
+//! against other implementations many times. A snapshot keeps what the result reports, not the
+//! dense arrays: the settled `(node, dist)` pairs in settle order, B', the counters and the
+//! termination reason, plus the tree parents (and their edge slots) of the settled nodes when
+//! predecessors were tracked. Unsettled nodes load back with `Weight::MAX`.
+//!
+//! Layout, all little-endian: `SNAPSHOT_MAGIC`, a `u32` version and a `u32` of flags (bit 0:
+//! predecessors, bit 1: edge slots), then `n`, B', `edges_scanned`, `heap_pushes`, `overflows`,
+//! the `Termination::ALL` index and the settled count `k` as `u64`, then `k` node/dist pairs,
+//! then `k` parents (`u64::MAX` for roots) and `k` slots if flagged.
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use crate::io::{read_frame, save_atomically, write_frame_header};
+use crate::{BmsspResult, Termination, Weight, NO_PRED};
+
+/// Leading bytes of the format written by `BmsspResult::write_snapshot`.
+pub const SNAPSHOT_MAGIC: &[u8; 8] = b"BMSSPRES";
+const SNAPSHOT_VERSION: u32 = 1;
+const HAS_PRED: u32 = 1;
+const HAS_SLOTS: u32 = 2;
+/// Largest node count a snapshot may claim; anything above is a corrupt header, not a graph.
+const MAX_NODES: u64 = 1 << 32;
+
+impl BmsspResult {
+    pub fn write_snapshot<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let has_pred = !self.pred.is_empty();
+        let has_slots = has_pred && self.pred_slot.len() == self.pred.len();
+        let flags = if has_pred { HAS_PRED } else { 0 } | if has_slots { HAS_SLOTS } else { 0 };
+        write_frame_header(&mut w, SNAPSHOT_MAGIC, SNAPSHOT_VERSION, flags)?;
+        let term = Termination::ALL.iter().position(|&t| t == self.termination).unwrap_or(0);
+        let header = [self.dist.len() as u64, self.b_prime, self.edges_scanned as u64, self.heap_pushes as u64,
+            self.overflows as u64, term as u64, self.explored.len() as u64];
+        for x in header { w.write_all(&x.to_le_bytes())?; }
+        for &v in &self.explored { w.write_all(&(v as u64).to_le_bytes())?; w.write_all(&self.dist[v].to_le_bytes())?; }
+        if has_pred {
+            for &v in &self.explored {
+                let p = self.pred[v];
+                w.write_all(&if p == NO_PRED { u64::MAX } else { p as u64 }.to_le_bytes())?;
+            }
+        }
+        if has_slots {
+            for &v in &self.explored { w.write_all(&(self.pred_slot[v] as u64).to_le_bytes())?; }
+        }
+        w.flush()
+    }
+
+    pub fn read_snapshot<R: Read>(r: R) -> io::Result<Self> {
+        let bad = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let (flags, words) = read_frame(r, SNAPSHOT_MAGIC, SNAPSHOT_VERSION, 7)?;
+        let [n, b_prime, edges_scanned, heap_pushes, overflows, term, k] = words[..7].try_into().unwrap();
+        // The dense arrays below are sized by `n`, which nothing else in the file bounds.
+        if n > MAX_NODES { return Err(bad("BMSSPRES node count is implausibly large")); }
+        let per_node = 2 + u64::from(flags & HAS_PRED != 0) + u64::from(flags & HAS_SLOTS != 0);
+        if k.checked_mul(per_node) != Some(words.len() as u64 - 7) { return Err(bad("BMSSPRES length does not match header")); }
+        let termination = *Termination::ALL.get(term as usize).ok_or_else(|| bad("BMSSPRES termination out of range"))?;
+        let (n, k) = (n as usize, k as usize);
+        let body = &words[7..];
+        let explored: Vec<usize> = body[..2 * k].iter().step_by(2).map(|&v| v as usize).collect();
+        if explored.iter().any(|&v| v >= n) { return Err(bad("BMSSPRES node id out of range")); }
+        let mut dist = vec![Weight::MAX; n];
+        for (i, &v) in explored.iter().enumerate() { dist[v] = body[2 * i + 1]; }
+        let mut rest = body[2 * k..].chunks(k.max(1));
+        let (mut pred, mut pred_slot) = (Vec::new(), Vec::new());
+        if flags & HAS_PRED != 0 {
+            pred = vec![NO_PRED; n];
+            for (&v, &p) in explored.iter().zip(rest.next().unwrap_or_default()) {
+                if p != u64::MAX && p as usize >= n { return Err(bad("BMSSPRES parent out of range")); }
+                pred[v] = if p == u64::MAX { NO_PRED } else { p as usize };
+            }
+        }
+        if flags & HAS_SLOTS != 0 {
+            pred_slot = vec![0; n];
+            for (&v, &s) in explored.iter().zip(rest.next().unwrap_or_default()) { pred_slot[v] = s as usize; }
+        }
+        Ok(Self{ dist, explored, b_prime, edges_scanned: edges_scanned as usize, heap_pushes: heap_pushes as usize,
+            termination, overflows: overflows as usize, pred, pred_slot, ..Default::default() })
+    }
+
+    /// `write_snapshot` to `path` through a synced temporary file and a rename.
+    pub fn save(&self, path: &Path) -> io::Result<()> { save_atomically(path, |w| self.write_snapshot(w)) }
+
+    pub fn load(path: &Path) -> io::Result<Self> { Self::read_snapshot(File::open(path)?) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::make_grid;
+    use crate::{bmssp_with_options, BmsspOptions};
+
+    #[test]
+    fn snapshot_round_trip() {
+        let g = make_grid(20, 20, 9, 6);
+        let sources = [(0, 0), (211, 3)];
+        for opts in [BmsspOptions::default(), BmsspOptions{ track_predecessors: true, max_settled: Some(90), ..Default::default() }] {
+            let r = bmssp_with_options(&g, &sources, 40, &opts);
+            let mut buf = Vec::new();
+            r.write_snapshot(&mut buf).unwrap();
+            assert_eq!(buf.len(), 16 + 8 * (7 + r.settled_count() * if r.pred.is_empty() { 2 } else { 4 }));
+            let back = BmsspResult::read_snapshot(&buf[..]).unwrap();
+            let unsettled: Vec<Weight> = (0..400).filter(|&v| !r.is_settled(v)).map(|v| back.dist[v]).collect();
+            assert!(unsettled.iter().all(|&d| d == Weight::MAX));
+            assert_eq!((back.settled_pairs(), back.b_prime, back.edges_scanned, back.heap_pushes, back.termination),
+                (r.settled_pairs(), r.b_prime, r.edges_scanned, r.heap_pushes, r.termination));
+            for &v in &r.explored { assert_eq!(back.path_edges_to(v), r.path_edges_to(v)); }
+            assert!(BmsspResult::read_snapshot(&buf[..buf.len() - 8]).is_err());
+        }
+        assert!(BmsspResult::read_snapshot(&b"BMSSPCKP"[..]).is_err());
+        let mut huge = Vec::new();
+        BmsspResult::default().write_snapshot(&mut huge).unwrap();
+        huge[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(BmsspResult::read_snapshot(&huge[..]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,91 @@
+//! Reusable search state for running many bounded queries on one graph. Labels are reset
+//! by walking the previous query's settled set, so a query costs O(settled) rather than O(n).
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::{Entry, GraphLike, Node, Weight};
+
+#[derive(Debug, Clone, Default)]
+pub struct Solver {
+    dist: Vec<Weight>,
+    heap: BinaryHeap<Reverse<Entry>>,
+    settled: Vec<Node>,
+    b_prime: Weight,
+    edges_scanned: usize,
+    heap_pushes: usize,
+}
+
+impl Solver {
+    pub fn new(n: usize) -> Self { Self { dist: vec![Weight::MAX; n], b_prime: Weight::MAX, ..Default::default() } }
+
+    /// Same search as `bounded_multi_source_shortest_paths`, reusing this solver's buffers.
+    /// Returns B'; distances and the settle order stay readable until the next `run`.
+    pub fn run<G: GraphLike>(&mut self, g: &G, sources: &[(Node, Weight)], bound: Weight) -> Weight {
+        for &v in &self.settled { self.dist[v] = Weight::MAX; }
+        self.settled.clear();
+        self.heap.clear();
+        let n = g.num_nodes();
+        if self.dist.len() < n { self.dist.resize(n, Weight::MAX); }
+        let dist = &mut self.dist;
+        for &(s, d0) in sources {
+            if s < n && d0 < bound && d0 < dist[s] && !g.is_removed(s) {
+                dist[s] = d0;
+                self.heap.push(Reverse(Entry{ d: d0, v: s }));
+            }
+        }
+        let mut b_prime = Weight::MAX;
+        let (mut edges_scanned, mut heap_pushes) = (0, 0);
+        while let Some(Reverse(Entry{ d, v })) = self.heap.pop() {
+            if d != dist[v] { continue; }
+            self.settled.push(v);
+            for (to, w) in g.neighbors(v) {
+                edges_scanned += 1;
+                let nd = d.saturating_add(w);
+                if nd < dist[to] && nd < bound {
+                    dist[to] = nd;
+                    self.heap.push(Reverse(Entry{ d: nd, v: to }));
+                    heap_pushes += 1;
+                } else if nd >= bound && nd < b_prime {
+                    b_prime = nd;
+                }
+            }
+        }
+        self.b_prime = b_prime;
+        self.edges_scanned = edges_scanned;
+        self.heap_pushes = heap_pushes;
+        b_prime
+    }
+
+    /// Nodes settled by the last `run`, in settle order.
+    pub fn settled(&self) -> &[Node] { &self.settled }
+    /// Label of `v` from the last `run`; `Weight::MAX` if not settled.
+    pub fn dist(&self, v: Node) -> Weight { self.dist.get(v).copied().unwrap_or(Weight::MAX) }
+    /// B' of the last `run`.
+    pub fn b_prime(&self) -> Weight { self.b_prime }
+    /// Edges scanned by the last `run`.
+    pub fn edges_scanned(&self) -> usize { self.edges_scanned }
+    /// Heap pushes of the last `run`, not counting sources (as in `BmsspResult`).
+    pub fn heap_pushes(&self) -> usize { self.heap_pushes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bounded_multi_source_shortest_paths, Graph};
+
+    #[test]
+    fn reuse_matches_fresh_searches() {
+        let mut g = Graph::new(40);
+        for i in 0..39 { g.add_undirected_edge(i, i + 1, 1 + (i as Weight % 3)); }
+        g.add_edge(5, 30, 2);
+        let mut solver = Solver::new(g.len());
+        for (src, b) in [(0usize, 20), (35, 7), (10, 100), (0, 3)] {
+            let bp = solver.run(&g, &[(src, 0)], b);
+            let fresh = bounded_multi_source_shortest_paths(&g, &[(src, 0)], b);
+            assert_eq!(bp, fresh.b_prime);
+            assert_eq!(solver.settled(), &fresh.explored[..]);
+            assert_eq!((solver.edges_scanned(), solver.heap_pushes()), (fresh.edges_scanned, fresh.heap_pushes));
+            assert!((0..g.len()).all(|v| solver.dist(v) == fresh.dist[v]));
+        }
+    }
+}
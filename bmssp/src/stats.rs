@@ -0,0 +1,197 @@
+//! Structural summary of a graph, for sanity-checking generated or imported instances before
+//! benchmarking them (an ER graph whose giant component misses the sources settles a handful
+//! of nodes and reports a meaningless time).
+use serde_json::json;
+
+use crate::analytics::weak_components;
+use crate::deque::SmallWeightKernel;
+use crate::{Graph, Weight};
+
+/// Order statistics of a multiset of integers; all zero when it is empty.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Summary {
+    pub min: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+    pub mean: f64,
+}
+
+impl Summary {
+    /// Sorts `values` in place.
+    pub fn of(values: &mut [u64]) -> Self {
+        if values.is_empty() { return Self::default(); }
+        values.sort_unstable();
+        let at = |q: f64| values[((values.len() - 1) as f64 * q).round() as usize];
+        let mean = values.iter().map(|&x| x as f64).sum::<f64>() / values.len() as f64;
+        Summary{ min: values[0], p50: at(0.5), p90: at(0.9), p99: at(0.99), max: values[values.len() - 1], mean }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({ "min": self.min, "p50": self.p50, "p90": self.p90, "p99": self.p99, "max": self.max, "mean": self.mean })
+    }
+}
+
+/// Settled-distance counts in buckets `[i * width, (i + 1) * width)`, to see where a bound
+/// falls in a graph's distance distribution.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DistHistogram {
+    pub width: Weight,
+    pub counts: Vec<usize>,
+}
+
+impl DistHistogram {
+    /// Most buckets a histogram may have.
+    pub const MAX_BUCKETS: usize = 1 << 16;
+
+    /// Whether every distance below `bound` fits in `MAX_BUCKETS` buckets of `width`.
+    pub fn fits(bound: Weight, width: Weight) -> bool {
+        width > 0 && bound.div_ceil(width) <= Self::MAX_BUCKETS as Weight
+    }
+
+    /// `None` when `width` is 0 or a distance falls past the last of `MAX_BUCKETS` buckets.
+    pub fn of(dists: impl IntoIterator<Item = Weight>, width: Weight) -> Option<Self> {
+        if width == 0 { return None; }
+        let mut counts = Vec::new();
+        for d in dists {
+            let i = d / width;
+            if i >= Self::MAX_BUCKETS as Weight { return None; }
+            let i = i as usize;
+            if i >= counts.len() { counts.resize(i + 1, 0); }
+            counts[i] += 1;
+        }
+        Some(Self { width, counts })
+    }
+
+    pub fn total(&self) -> usize { self.counts.iter().sum() }
+
+    /// Upper edge of the bucket holding the `q`-quantile (`0.0..=1.0`); 0 when empty.
+    pub fn quantile_upper(&self, q: f64) -> Weight {
+        let want = ((self.total() as f64 * q).ceil() as usize).max(1);
+        let mut seen = 0;
+        for (i, &c) in self.counts.iter().enumerate() {
+            seen += c;
+            if seen >= want { return (i as Weight + 1).saturating_mul(self.width); }
+        }
+        0
+    }
+
+    /// One line per non-empty bucket: range, count and a bar of up to `bar` characters.
+    pub fn render(&self, bar: usize) -> String {
+        let max = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        let mut out = String::new();
+        for (i, &c) in self.counts.iter().enumerate().filter(|e| *e.1 > 0) {
+            let lo = i as Weight * self.width;
+            let hashes = "#".repeat((c * bar).div_ceil(max));
+            out += &format!("[{lo}, {}) {c} {hashes}\n", lo.saturating_add(self.width));
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> serde_json::Value { json!({ "width": self.width, "counts": self.counts }) }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphStats {
+    pub nodes: usize,
+    pub edges: usize,
+    pub out_degree: Summary,
+    pub in_degree: Summary,
+    pub weight: Summary,
+    /// Nodes with no incoming and no outgoing edges.
+    pub isolated: usize,
+    /// Weakly connected component sizes, largest first.
+    pub component_sizes: Vec<usize>,
+    /// Largest component size over `nodes` (0 for an empty graph).
+    pub largest_component_fraction: f64,
+}
+
+impl GraphStats {
+    pub fn components(&self) -> usize { self.component_sizes.len() }
+
+    /// Heap-free kernel for this graph's weight range (`bmssp_small_weights`); `None` when the
+    /// weights are too wide or there are no edges.
+    pub fn small_weight_kernel(&self) -> Option<SmallWeightKernel> {
+        if self.edges == 0 { return None; }
+        SmallWeightKernel::for_weights(self.weight.min, self.weight.max)
+    }
+
+    /// JSON object with every field; `component_sizes` is cut to the largest `top` entries.
+    pub fn to_json(&self, top: usize) -> serde_json::Value {
+        json!({
+            "nodes": self.nodes,
+            "edges": self.edges,
+            "out_degree": self.out_degree.to_json(),
+            "in_degree": self.in_degree.to_json(),
+            "weight": self.weight.to_json(),
+            "isolated": self.isolated,
+            "components": self.components(),
+            "component_sizes": &self.component_sizes[..top.min(self.component_sizes.len())],
+            "largest_component_fraction": self.largest_component_fraction,
+        })
+    }
+}
+
+impl Graph {
+    /// Degree and weight distributions, isolated nodes and weak components in one pass plus a
+    /// union-find.
+    pub fn stats(&self) -> GraphStats {
+        let n = self.len();
+        let mut out_deg: Vec<u64> = self.adj.iter().map(|e| e.len() as u64).collect();
+        let mut in_deg = vec![0u64; n];
+        let mut weights = Vec::with_capacity(self.num_edges());
+        for edges in &self.adj {
+            for &(v, w) in edges { in_deg[v] += 1; weights.push(w); }
+        }
+        let isolated = (0..n).filter(|&v| out_deg[v] == 0 && in_deg[v] == 0).count();
+        let (label, count) = weak_components(self);
+        let mut component_sizes = vec![0usize; count];
+        for &c in &label { component_sizes[c] += 1; }
+        component_sizes.sort_unstable_by(|a, b| b.cmp(a));
+        let largest_component_fraction = if n == 0 { 0.0 } else { component_sizes[0] as f64 / n as f64 };
+        GraphStats{
+            nodes: n,
+            edges: weights.len(),
+            out_degree: Summary::of(&mut out_deg),
+            in_degree: Summary::of(&mut in_deg),
+            weight: Summary::of(&mut weights),
+            isolated,
+            component_sizes,
+            largest_component_fraction,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_of_a_small_graph() {
+        // Path 0 -> 1 -> 2, edge 3 -> 4, isolated 5.
+        let mut g = Graph::new(6);
+        g.add_edge(0, 1, 2); g.add_edge(1, 2, 4); g.add_edge(3, 4, 9);
+        let s = g.stats();
+        assert_eq!((s.nodes, s.edges, s.isolated, s.components()), (6, 3, 1, 3));
+        assert_eq!(s.component_sizes, [3, 2, 1]);
+        assert_eq!(s.largest_component_fraction, 0.5);
+        assert_eq!((s.weight.min, s.weight.p50, s.weight.max), (2, 4, 9));
+        assert_eq!((s.out_degree.max, s.in_degree.max, s.out_degree.mean), (1, 1, 0.5));
+        assert_eq!(s.to_json(2)["component_sizes"], json!([3, 2]));
+        assert_eq!(Graph::new(0).stats().largest_component_fraction, 0.0);
+    }
+
+    #[test]
+    fn histogram_buckets_and_quantiles() {
+        let h = DistHistogram::of([0, 3, 4, 5, 9, 9, 10, 24], 5).unwrap();
+        assert_eq!(h.counts, [3, 3, 1, 0, 1]);
+        assert_eq!((h.total(), h.quantile_upper(0.5), h.quantile_upper(0.9), h.quantile_upper(0.0)), (8, 10, 25, 5));
+        assert_eq!(h.render(6), "[0, 5) 3 ######\n[5, 10) 3 ######\n[10, 15) 1 ##\n[20, 25) 1 ##\n");
+        assert_eq!(DistHistogram::of([], 3).unwrap().quantile_upper(0.5), 0);
+        assert_eq!(DistHistogram::of([1], 0), None);
+        assert_eq!(DistHistogram::of([Weight::MAX], 1), None);
+        assert!(DistHistogram::fits(100, 1) && !DistHistogram::fits(Weight::MAX, 1) && !DistHistogram::fits(100, 0));
+    }
+}
@@ -0,0 +1,115 @@
+//! Percentile-bounded search over stochastic edge weights ("reachable within 30 minutes with 95%
+//! confidence"). An edge's stored weight is its mean cost and `EdgeData` carries its variance;
+//! costs are treated as independent and normal, so a path's q-th percentile is
+//! `mean + z_q * sqrt(variance)`. That is not additive, so the search runs on the conservative
+//! scalarization `mean + ceil(z_q * sd)` per edge: since `sqrt` is subadditive its path sums
+//! never undershoot the path percentile, and every node it settles is reachable below `bound`
+//! at confidence `q`. Some nodes that would qualify on a tighter analysis may be missed.
+use crate::{bmssp_with_options, BmsspOptions, BmsspResult, EdgeData, Graph, Node, Weight, NO_PRED};
+
+/// Result of `percentile_bounded`.
+#[derive(Debug, Clone, Default)]
+pub struct PercentileResult {
+    /// Search on the scalarized weights: `dist[v]` is an upper bound on the `q`-th percentile
+    /// of the cost of the tree path to `v`; `pred`/`pred_slot` hold that tree.
+    pub result: BmsspResult,
+    /// Sum of edge means (plus the source offset) along the tree path, `Weight::MAX` if unsettled.
+    pub mean: Vec<Weight>,
+    /// Sum of edge variances along the tree path.
+    pub variance: Vec<Weight>,
+    /// Standard normal quantile used for the percentile (0 for `q <= 0.5`).
+    pub z: f64,
+}
+
+impl PercentileResult {
+    /// `q`-th percentile of the tree path's cost, `mean + z * sqrt(variance)`; never above
+    /// `result.dist[v]`. `None` if `v` wasn't settled.
+    pub fn percentile(&self, v: Node) -> Option<f64> {
+        let mean = *self.mean.get(v).filter(|&&m| m != Weight::MAX)?;
+        Some(mean as f64 + self.z * (self.variance[v] as f64).sqrt())
+    }
+}
+
+/// Standard normal quantile for `q` in `(0, 1)` (Acklam's rational approximation, relative
+/// error below 1.2e-9).
+pub fn normal_quantile(q: f64) -> f64 {
+    assert!(q > 0.0 && q < 1.0, "quantile needs 0 < q < 1, got {q}");
+    const A: [f64; 6] = [-3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2, 1.38357751867269e2, -3.066479806614716e1, 2.506628277459239];
+    const B: [f64; 5] = [-5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2, 6.680131188771972e1, -1.328068155288572e1];
+    const C: [f64; 6] = [-7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838, -2.549732539343734, 4.374664141464968, 2.938163982698783];
+    const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416];
+    let tail = |p: f64| {
+        let t = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * t + C[1]) * t + C[2]) * t + C[3]) * t + C[4]) * t + C[5]) / ((((D[0] * t + D[1]) * t + D[2]) * t + D[3]) * t + 1.0)
+    };
+    if q < 0.02425 { return tail(q); }
+    if q > 1.0 - 0.02425 { return -tail(1.0 - q); }
+    let r = q - 0.5;
+    let s = r * r;
+    (((((A[0] * s + A[1]) * s + A[2]) * s + A[3]) * s + A[4]) * s + A[5]) * r / (((((B[0] * s + B[1]) * s + B[2]) * s + B[3]) * s + B[4]) * s + 1.0)
+}
+
+/// Settle every node whose scalarized cost from `sources` is below `bound`, where `g`'s weights
+/// are edge means and `variance` is aligned with `g` (see the module docs). Source offsets
+/// are deterministic. For `q <= 0.5` the mean itself is bounded, which is already conservative.
+pub fn percentile_bounded(g: &Graph, variance: &EdgeData<Weight>, sources: &[(Node, Weight)], bound: Weight, q: f64) -> PercentileResult {
+    let z = normal_quantile(q).max(0.0);
+    let view = g.with_weight_fn(variance, |_, _, mean, &var| mean.saturating_add((z * (var as f64).sqrt()).ceil() as Weight));
+    let result = bmssp_with_options(&view, sources, bound, &BmsspOptions{ track_predecessors: true, ..Default::default() });
+    let (mut mean, mut var): (Vec<Weight>, Vec<Weight>) = (vec![Weight::MAX; g.len()], vec![0; g.len()]);
+    // Parents settle before their children, so one pass in settle order fills the sums.
+    for &v in &result.explored {
+        let p = result.pred[v];
+        if p == NO_PRED {
+            mean[v] = result.dist[v];
+        } else {
+            let slot = result.pred_slot[v];
+            mean[v] = mean[p].saturating_add(g.adj[p][slot].1);
+            var[v] = var[p].saturating_add(*variance.get(p, slot).unwrap());
+        }
+    }
+    PercentileResult{ result, mean, variance: var, z }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::make_grid;
+
+    #[test]
+    fn confident_route_prefers_low_variance() {
+        // 0 -> 3 via 1 (mean 10, variance 100) or via 2 (mean 14, variance 1).
+        let mut g = Graph::new(4);
+        let mut var = EdgeData::new(4);
+        for (u, v, m, s2) in [(0, 1, 5, 50), (1, 3, 5, 50), (0, 2, 7, 0), (2, 3, 7, 1)] {
+            g.add_edge(u, v, m);
+            var.push(u, s2);
+        }
+        let median = percentile_bounded(&g, &var, &[(0, 0)], 100, 0.5);
+        assert_eq!((median.result.dist[3], median.result.path_to(3)), (10, Some(vec![0, 1, 3])));
+        let safe = percentile_bounded(&g, &var, &[(0, 0)], 100, 0.95);
+        assert!((safe.z - 1.644854).abs() < 1e-5);
+        assert_eq!((safe.result.path_to(3), safe.mean[3], safe.variance[3]), (Some(vec![0, 2, 3]), 14, 1));
+        assert_eq!(safe.result.dist[3], 16);
+        assert!((safe.percentile(3).unwrap() - 15.644854).abs() < 1e-5);
+        // "Within 16 at 95%" leaves 3 out; the bound is on the conservative cost.
+        assert!(!percentile_bounded(&g, &var, &[(0, 0)], 16, 0.95).result.is_settled(3));
+        assert_eq!(normal_quantile(0.5), 0.0);
+        assert!((normal_quantile(0.01) + 2.326348).abs() < 1e-5);
+    }
+
+    #[test]
+    fn scalarization_never_undershoots() {
+        let g = make_grid(20, 20, 30, 6);
+        let var = EdgeData::from_fn(&g, |u, i, _, w| w * ((u + i) % 4) as Weight);
+        for q in [0.8, 0.95, 0.999] {
+            let r = percentile_bounded(&g, &var, &[(0, 0), (210, 7)], 150, q);
+            assert!(!r.result.explored.is_empty() && r.result.explored.len() < g.len());
+            for &v in &r.result.explored {
+                let p = r.percentile(v).unwrap();
+                assert!(p <= r.result.dist[v] as f64 + 1e-9 && (r.mean[v] as f64) <= p, "q={q} v={v}");
+            }
+            assert_eq!(r.percentile(r.mean.iter().position(|&m| m == Weight::MAX).unwrap()), None);
+        }
+    }
+}
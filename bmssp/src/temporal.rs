@@ -0,0 +1,139 @@
+//! Temporal graphs: edges usable only while they are active, for transit-style data. An edge
+//! `u -> v` with validity `[start, end)` and traversal time `duration` can be boarded at any
+//! time `t < end`, waiting at `u` until `start` if needed, and arrives at
+//! `max(t, start) + duration`. Arrival is non-decreasing in `t` (FIFO), so the earliest-arrival
+//! search is a Dijkstra over arrival times. A timetable with several departures lists one edge
+//! per departure window.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::{BmsspResult, Entry, Graph, Node, Weight};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemporalEdge {
+    pub to: Node,
+    pub duration: Weight,
+    /// Boarding is possible from `start` (inclusive) to `end` (exclusive).
+    pub start: Weight,
+    pub end: Weight,
+}
+
+impl TemporalEdge {
+    /// Arrival at `to` when reaching the tail at `t`, `None` if the edge has closed.
+    #[inline]
+    pub fn arrival(&self, t: Weight) -> Option<Weight> {
+        (t < self.end).then(|| t.max(self.start).saturating_add(self.duration))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemporalGraph {
+    pub adj: Vec<Vec<TemporalEdge>>,
+}
+
+impl TemporalGraph {
+    pub fn new(n: usize) -> Self { Self{ adj: vec![Vec::new(); n] } }
+    pub fn len(&self) -> usize { self.adj.len() }
+    pub fn is_empty(&self) -> bool { self.adj.is_empty() }
+    pub fn num_edges(&self) -> usize { self.adj.iter().map(|e| e.len()).sum() }
+
+    /// Edge `u -> v` taking `duration`, boardable during `[start, end)`.
+    pub fn add_edge(&mut self, u: Node, v: Node, duration: Weight, start: Weight, end: Weight) {
+        self.adj[u].push(TemporalEdge{ to: v, duration, start, end });
+    }
+
+    /// Every edge of `g`, active at all times.
+    pub fn from_static(g: &Graph) -> Self {
+        Self{ adj: g.adj.iter().map(|e| e.iter().map(|&(to, w)| TemporalEdge{ to, duration: w, start: 0, end: Weight::MAX }).collect()).collect() }
+    }
+
+    /// Static graph of the edges boardable at time `t`, weighted by their duration.
+    pub fn snapshot(&self, t: Weight) -> Graph {
+        let mut g = Graph::new(self.len());
+        for (u, edges) in self.adj.iter().enumerate() {
+            for e in edges.iter().filter(|e| e.start <= t && t < e.end) { g.add_edge(u, e.to, e.duration); }
+        }
+        g
+    }
+}
+
+/// Earliest arrival at every node from `sources` `(node, departure time)`, for arrivals before
+/// `bound` (an absolute time, like the offsets). `dist` holds arrival times; `b_prime` is the
+/// earliest arrival at or after `bound` over edges leaving reached stops, and `pred` the
+/// previous stop of each journey.
+pub fn earliest_arrival(g: &TemporalGraph, sources: &[(Node, Weight)], bound: Weight) -> BmsspResult {
+    let n = g.len();
+    let mut dist = vec![Weight::MAX; n];
+    let mut pred = vec![crate::NO_PRED; n];
+    let mut pred_slot = vec![0; n];
+    let mut heap: BinaryHeap<Reverse<Entry>> = BinaryHeap::new();
+    for &(s, t0) in sources {
+        if s < n && t0 < bound && t0 < dist[s] {
+            dist[s] = t0;
+            heap.push(Reverse(Entry{ d: t0, v: s }));
+        }
+    }
+    let (mut explored, mut b_prime) = (Vec::new(), Weight::MAX);
+    let (mut edges_scanned, mut heap_pushes) = (0, 0);
+    while let Some(Reverse(Entry{ d, v })) = heap.pop() {
+        if d != dist[v] { continue; }
+        explored.push(v);
+        for (i, e) in g.adj[v].iter().enumerate() {
+            edges_scanned += 1;
+            let Some(at) = e.arrival(d) else { continue };
+            if at < dist[e.to] && at < bound {
+                dist[e.to] = at;
+                pred[e.to] = v;
+                pred_slot[e.to] = i;
+                heap.push(Reverse(Entry{ d: at, v: e.to }));
+                heap_pushes += 1;
+            } else if at >= bound && at < b_prime {
+                b_prime = at;
+            }
+        }
+    }
+    BmsspResult{ dist, explored, b_prime, edges_scanned, heap_pushes, pred, pred_slot, ..Default::default() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use crate::generators::make_grid;
+
+    #[test]
+    fn waits_for_departures_and_misses_closed_edges() {
+        // Stops 0 -> 1 -> 2: buses leave 0 during [10, 11) and [30, 31), the 1 -> 2 leg runs
+        // [0, 25). A slow walk 0 -> 2 is always open.
+        let mut g = TemporalGraph::new(4);
+        g.add_edge(0, 1, 5, 10, 11);
+        g.add_edge(0, 1, 5, 30, 31);
+        g.add_edge(1, 2, 5, 0, 25);
+        g.add_edge(0, 2, 40, 0, Weight::MAX);
+        g.add_edge(2, 3, 1, 100, 101);
+        let r = earliest_arrival(&g, &[(0, 8)], 1000);
+        assert_eq!(&r.dist[..3], &[8, 15, 20]);
+        assert_eq!(r.path_to(2), Some(vec![0, 1, 2]));
+        assert_eq!(r.dist[3], 101);
+        // Missing the first bus: the second reaches 1 after its onward leg closed.
+        let late = earliest_arrival(&g, &[(0, 12)], 1000);
+        assert_eq!(&late.dist[..3], &[12, 35, 52]);
+        assert_eq!(late.path_to(2), Some(vec![0, 2]));
+        // Arrivals at 3 are past the bound.
+        let cut = earliest_arrival(&g, &[(0, 8)], 60);
+        assert_eq!((cut.settled_count(), cut.b_prime), (3, 101));
+        assert_eq!(g.snapshot(10).num_edges(), 3);
+    }
+
+    #[test]
+    fn always_active_edges_match_the_static_search() {
+        let s = make_grid(20, 20, 9, 3);
+        let t = TemporalGraph::from_static(&s);
+        for bound in [5, 40, Weight::MAX] {
+            let (a, b) = (earliest_arrival(&t, &[(0, 0), (150, 4)], bound), bounded_multi_source_shortest_paths(&s, &[(0, 0), (150, 4)], bound));
+            assert_eq!((a.dist, a.explored, a.b_prime, a.edges_scanned), (b.dist, b.explored, b.b_prime, b.edges_scanned));
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Step-level relaxation log for comparing implementations: every relaxation of the sequential
+//! search as `(u, v, old, new, accepted)`, in scan order. Two correct ports with the same
+//! tie-breaking produce identical logs, so `first_difference` points at the step where one
+//! goes wrong instead of at a wrong final distance.
+//!
+//! Format: `TRACE_MAGIC`, a `u32` version and a reserved `u32` (little-endian), then one record
+//! per relaxation as four LEB128 varints: `u << 1 | accepted`, `v`, `old` and `new`, where
+//! labels are stored `+ 1` and `Weight::MAX` (unreached, or saturated) as 0.
+use std::io::{self, Read, Write};
+
+use crate::options::BmsspOptions;
+use crate::visit::SearchVisitor;
+use crate::{bmssp_with_visitor, BmsspResult, GraphLike, Node, Weight};
+
+/// Leading bytes of a trace file.
+pub const TRACE_MAGIC: &[u8; 8] = b"BMSSPTRC";
+const TRACE_VERSION: u32 = 1;
+
+/// One relaxation of edge `u -> v`: `v` held `old` and was offered `new`. `accepted` means `new`
+/// became its label; otherwise it was not an improvement or not below the bound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Relaxation {
+    pub u: Node,
+    pub v: Node,
+    pub old: Weight,
+    pub new: Weight,
+    pub accepted: bool,
+}
+
+fn put_varint<W: Write>(w: &mut W, mut x: u64) -> io::Result<()> {
+    let mut buf = [0u8; 10];
+    let mut i = 0;
+    while x >= 0x80 {
+        buf[i] = x as u8 | 0x80;
+        x >>= 7;
+        i += 1;
+    }
+    buf[i] = x as u8;
+    w.write_all(&buf[..=i])
+}
+
+fn label_code(d: Weight) -> u64 { if d == Weight::MAX { 0 } else { d + 1 } }
+fn label_of(code: u64) -> Weight { if code == 0 { Weight::MAX } else { code - 1 } }
+
+/// `SearchVisitor` that appends every relaxation to `w`. Visitor callbacks can't fail, so the
+/// first write error is kept and returned by `finish`; later records are dropped.
+pub struct TraceWriter<W: Write> {
+    w: W,
+    records: u64,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> TraceWriter<W> {
+    /// Writes the header immediately.
+    pub fn new(mut w: W) -> io::Result<Self> {
+        w.write_all(TRACE_MAGIC)?;
+        w.write_all(&TRACE_VERSION.to_le_bytes())?;
+        w.write_all(&0u32.to_le_bytes())?;
+        Ok(Self { w, records: 0, error: None })
+    }
+
+    pub fn records(&self) -> u64 { self.records }
+
+    pub fn record(&mut self, r: Relaxation) {
+        if self.error.is_some() { return; }
+        let res = put_varint(&mut self.w, (r.u as u64) << 1 | r.accepted as u64)
+            .and_then(|_| put_varint(&mut self.w, r.v as u64))
+            .and_then(|_| put_varint(&mut self.w, label_code(r.old)))
+            .and_then(|_| put_varint(&mut self.w, label_code(r.new)));
+        match res {
+            Ok(()) => self.records += 1,
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// Flush and return the writer, or the first write error.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(e) = self.error { return Err(e); }
+        self.w.flush()?;
+        Ok(self.w)
+    }
+}
+
+impl<W: Write> SearchVisitor for TraceWriter<W> {
+    fn on_relax(&mut self, u: Node, v: Node, old: Weight, new: Weight) {
+        self.record(Relaxation{ u, v, old, new, accepted: true });
+    }
+    fn on_relax_rejected(&mut self, u: Node, v: Node, current: Weight, candidate: Weight) {
+        self.record(Relaxation{ u, v, old: current, new: candidate, accepted: false });
+    }
+}
+
+/// Iterator over the records of a trace file.
+pub struct TraceReader<R: Read> {
+    r: R,
+}
+
+impl<R: Read> TraceReader<R> {
+    /// Checks the header.
+    pub fn new(mut r: R) -> io::Result<Self> {
+        let mut head = [0u8; 16];
+        r.read_exact(&mut head)?;
+        let bad = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        if &head[..8] != TRACE_MAGIC { return Err(bad("not a BMSSPTRC file")); }
+        if u32::from_le_bytes(head[8..12].try_into().unwrap()) != TRACE_VERSION { return Err(bad("unsupported BMSSPTRC version")); }
+        Ok(Self { r })
+    }
+
+    /// `Ok(None)` at a clean end of input (before the first byte of a varint).
+    fn varint(&mut self, first: bool) -> io::Result<Option<u64>> {
+        let mut x = 0u64;
+        for shift in (0..64).step_by(7) {
+            let mut b = [0u8];
+            if self.r.read(&mut b)? == 0 {
+                if first && shift == 0 { return Ok(None); }
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            x |= ((b[0] & 0x7f) as u64) << shift;
+            if b[0] & 0x80 == 0 { return Ok(Some(x)); }
+        }
+        Err(io::Error::new(io::ErrorKind::InvalidData, "BMSSPTRC varint longer than 10 bytes"))
+    }
+
+    fn next_record(&mut self) -> io::Result<Option<Relaxation>> {
+        let Some(head) = self.varint(true)? else { return Ok(None) };
+        let mut field = || self.varint(false).map(|x| x.unwrap());
+        let (v, old, new) = (field()?, field()?, field()?);
+        Ok(Some(Relaxation{ u: (head >> 1) as Node, v: v as Node, old: label_of(old), new: label_of(new), accepted: head & 1 == 1 }))
+    }
+}
+
+impl<R: Read> Iterator for TraceReader<R> {
+    type Item = io::Result<Relaxation>;
+    fn next(&mut self) -> Option<Self::Item> { self.next_record().transpose() }
+}
+
+/// `bmssp_with_visitor` with a `TraceWriter` on `w`. The hop-limited search (`max_hops`) only
+/// reports accepted relaxations.
+pub fn bmssp_traced<G: GraphLike, W: Write>(g: &G, sources: &[(Node, Weight)], bound: Weight, opts: &BmsspOptions, w: W) -> io::Result<(BmsspResult, W)> {
+    let mut tw = TraceWriter::new(w)?;
+    let res = bmssp_with_visitor(g, sources, bound, opts, &mut tw);
+    Ok((res, tw.finish()?))
+}
+
+/// Step index and both records there; `None` for a trace that already ended.
+pub type Divergence = (usize, Option<Relaxation>, Option<Relaxation>);
+
+/// First step where two traces differ, or `Ok(None)` if they are identical.
+pub fn first_difference<A, B>(a: A, b: B) -> io::Result<Option<Divergence>>
+where
+    A: IntoIterator<Item = io::Result<Relaxation>>,
+    B: IntoIterator<Item = io::Result<Relaxation>>,
+{
+    let (mut a, mut b) = (a.into_iter(), b.into_iter());
+    for i in 0.. {
+        let (x, y) = (a.next().transpose()?, b.next().transpose()?);
+        if x != y { return Ok(Some((i, x, y))); }
+        if x.is_none() { break; }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Graph;
+
+    #[test]
+    fn round_trips_and_finds_first_difference() {
+        let mut g = Graph::new(4);
+        g.add_edge(0, 1, 5); g.add_edge(0, 2, 1); g.add_edge(2, 1, 1); g.add_edge(1, 3, 9); g.add_edge(1, 0, 1);
+        let (res, buf) = bmssp_traced(&g, &[(0, 0)], 10, &BmsspOptions::default(), Vec::new()).unwrap();
+        assert_eq!(res.explored, [0, 2, 1]);
+        let log: Vec<Relaxation> = TraceReader::new(&buf[..]).unwrap().collect::<io::Result<_>>().unwrap();
+        let r = |u, v, old, new, accepted| Relaxation{ u, v, old, new, accepted };
+        assert_eq!(log, [
+            r(0, 1, Weight::MAX, 5, true), r(0, 2, Weight::MAX, 1, true),
+            r(2, 1, 5, 2, true),
+            r(1, 3, Weight::MAX, 11, false), r(1, 0, 0, 3, false),
+        ]);
+        let mut other = log.clone();
+        other[2].new = 3;
+        let d = first_difference(TraceReader::new(&buf[..]).unwrap(), other.iter().copied().map(Ok)).unwrap();
+        assert_eq!(d, Some((2, Some(log[2]), Some(other[2]))));
+        assert_eq!(first_difference(log.iter().copied().map(Ok), log[..4].iter().copied().map(Ok)).unwrap(), Some((4, Some(log[4]), None)));
+        assert!(TraceReader::new(&buf[..buf.len() - 1]).unwrap().any(|x| x.is_err()));
+    }
+}
@@ -0,0 +1,110 @@
+//! Edge-based expansion for turn restrictions and turn costs. Each directed edge of the road
+//! graph becomes a node; an arc joins `u -> v` to `v -> x` unless the turn `(u, v, x)` is banned,
+//! weighted by the second edge plus the turn's penalty.
+use std::collections::{HashMap, HashSet};
+
+use crate::{BmsspResult, Graph, Node, Weight};
+
+/// Turn-expanded graph plus the mapping back to the original nodes.
+#[derive(Debug, Clone)]
+pub struct TurnGraph {
+    /// One node per original edge, numbered in `g.adj` order.
+    pub graph: Graph,
+    /// `tail[e]`, `head[e]`, `weight[e]` of original edge `e`.
+    pub tail: Vec<Node>,
+    pub head: Vec<Node>,
+    pub weight: Vec<Weight>,
+    /// Edges leaving original node `u` are `first_edge[u]..first_edge[u + 1]`.
+    pub first_edge: Vec<usize>,
+}
+
+impl Graph {
+    /// Expand to the edge-based graph. `restrictions` lists banned turns `(from, via, to)`;
+    /// `turn_costs` adds a penalty to the turn `(from, via, to)`. Unlisted turns, including U-turns,
+    /// are free.
+    pub fn expand_turns(&self, restrictions: &[(Node, Node, Node)], turn_costs: &[(Node, Node, Node, Weight)]) -> TurnGraph {
+        let banned: HashSet<(Node, Node, Node)> = restrictions.iter().copied().collect();
+        let mut penalty: HashMap<(Node, Node, Node), Weight> = HashMap::new();
+        for &(a, b, c, w) in turn_costs { *penalty.entry((a, b, c)).or_default() += w; }
+        let mut first_edge = Vec::with_capacity(self.len() + 1);
+        let (mut tail, mut head, mut weight) = (Vec::new(), Vec::new(), Vec::new());
+        for (u, adj) in self.adj.iter().enumerate() {
+            first_edge.push(tail.len());
+            if self.is_removed(u) { continue; }
+            for &(v, w) in adj {
+                if self.is_removed(v) { continue; }
+                tail.push(u); head.push(v); weight.push(w);
+            }
+        }
+        first_edge.push(tail.len());
+        let mut graph = Graph::new(tail.len());
+        for e in 0..tail.len() {
+            let (u, v) = (tail[e], head[e]);
+            for f in first_edge[v]..first_edge[v + 1] {
+                let x = head[f];
+                if banned.contains(&(u, v, x)) { continue; }
+                let turn = penalty.get(&(u, v, x)).copied().unwrap_or(0);
+                graph.add_edge(e, f, weight[f].saturating_add(turn));
+            }
+        }
+        TurnGraph { graph, tail, head, weight, first_edge }
+    }
+}
+
+impl TurnGraph {
+    /// Search sources for the expanded graph: leaving `s` along each of its edges, starting at
+    /// `d0` plus that edge's weight. No turn cost applies at the source.
+    pub fn sources(&self, sources: &[(Node, Weight)]) -> Vec<(Node, Weight)> {
+        sources.iter()
+            .flat_map(|&(s, d0)| (self.first_edge[s]..self.first_edge[s + 1]).map(move |e| (e, d0.saturating_add(self.weight[e]))))
+            .collect()
+    }
+
+    /// Collapse an expanded-graph result to original node distances: a node's distance is the best
+    /// label over its incoming edges, and sources keep their own offsets.
+    pub fn node_dist(&self, res: &BmsspResult, sources: &[(Node, Weight)]) -> Vec<Weight> {
+        let mut dist = vec![Weight::MAX; self.first_edge.len() - 1];
+        for &(s, d0) in sources { if s < dist.len() { dist[s] = dist[s].min(d0); } }
+        for (e, &d) in res.dist.iter().enumerate() {
+            let v = self.head[e];
+            if d < dist[v] { dist[v] = d; }
+        }
+        dist
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+
+    /// Plus-shaped junction 4 with arms 0 (west), 1 (north), 2 (east), 3 (south), plus a
+    /// long detour 0 -> 5 -> 1 avoiding the junction.
+    fn junction() -> Graph {
+        let mut g = Graph::new(6);
+        for arm in 0..4 { g.add_undirected_edge(arm, 4, 1); }
+        g.add_edge(0, 5, 3); g.add_edge(5, 1, 3);
+        g
+    }
+
+    #[test]
+    fn banned_turn_forces_detour() {
+        let g = junction();
+        let free = g.expand_turns(&[], &[]);
+        let srcs = free.sources(&[(0, 0)]);
+        let r = bounded_multi_source_shortest_paths(&free.graph, &srcs, 100);
+        let plain = bounded_multi_source_shortest_paths(&g, &[(0, 0)], 100);
+        assert_eq!(free.node_dist(&r, &[(0, 0)]), plain.dist);
+
+        // Ban west -> north at the junction and the U-turn at the east arm; north is now reached
+        // via the detour (6).
+        let tg = g.expand_turns(&[(0, 4, 1), (4, 2, 4)], &[(0, 4, 3, 5)]);
+        let srcs = tg.sources(&[(0, 0)]);
+        let r = bounded_multi_source_shortest_paths(&tg.graph, &srcs, 100);
+        let d = tg.node_dist(&r, &[(0, 0)]);
+        assert_eq!(d[1], 6);
+        assert_eq!(d[2], 2);
+        // The right turn south costs 5 extra but still beats the detour (3 + 3 + 1 + 1).
+        assert_eq!(d[3], 7);
+    }
+}
@@ -0,0 +1,148 @@
+//! Undirected CSR that stores each edge once. `Graph::add_undirected_edge` keeps two 16-byte
+//! copies of every edge; here edge `{u, v}` with `u <= v` lives only in `u`'s forward list
+//! (`u32` target, full `Weight`), and `v` finds it through a `u32` back-reference, so an edge
+//! costs 16 bytes instead of 32. Neighbor iteration yields both directions.
+use crate::{Graph, GraphLike, Node, Weight};
+
+#[derive(Clone, Debug, Default)]
+pub struct UndirectedCsr {
+    /// `offsets[u]..offsets[u+1]` indexes the edges stored at `u` (targets `>= u`, sorted).
+    offsets: Vec<usize>,
+    targets: Vec<u32>,
+    weights: Vec<Weight>,
+    /// `rev_offsets[v]..rev_offsets[v+1]` indexes the lower endpoints `u < v` of edges stored at
+    /// `u`, sorted, one entry per parallel edge.
+    rev_offsets: Vec<usize>,
+    rev_sources: Vec<u32>,
+}
+
+impl UndirectedCsr {
+    /// Build from undirected edges `{u, v}`. `None` if an endpoint is `>= n` or `n` doesn't fit
+    /// in `u32`.
+    pub fn from_edges(n: usize, edges: &[(Node, Node, Weight)]) -> Option<Self> {
+        if n > u32::MAX as usize || edges.iter().any(|&(u, v, _)| u >= n || v >= n) { return None; }
+        let mut fwd: Vec<(u32, u32, Weight)> = edges.iter().map(|&(u, v, w)| (u.min(v) as u32, u.max(v) as u32, w)).collect();
+        fwd.sort_unstable();
+        let mut offsets = vec![0; n + 1];
+        let mut rev_offsets = vec![0; n + 1];
+        for &(u, v, _) in &fwd {
+            offsets[u as usize + 1] += 1;
+            if u != v { rev_offsets[v as usize + 1] += 1; }
+        }
+        for i in 0..n { offsets[i + 1] += offsets[i]; rev_offsets[i + 1] += rev_offsets[i]; }
+        // `fwd` is sorted by (u, v), so each reverse list fills in increasing source order.
+        let mut rev_sources = vec![0; rev_offsets[n]];
+        let mut fill = rev_offsets.clone();
+        for &(u, v, _) in &fwd {
+            if u != v { rev_sources[fill[v as usize]] = u; fill[v as usize] += 1; }
+        }
+        Some(Self{ offsets, targets: fwd.iter().map(|e| e.1).collect(), weights: fwd.iter().map(|e| e.2).collect(), rev_offsets, rev_sources })
+    }
+
+    /// Convert a symmetric `Graph` (as built by `add_undirected_edge`). `None` if some
+    /// `u -> v` has no matching `v -> u` of the same weight, or ids don't fit in `u32`. A
+    /// self-loop added once with `add_undirected_edge` appears twice in `g` and is kept twice.
+    pub fn from_graph(g: &Graph) -> Option<Self> {
+        let (mut up, mut down, mut loops) = (Vec::new(), Vec::new(), Vec::new());
+        for (u, edges) in g.adj.iter().enumerate() {
+            for &(v, w) in edges {
+                match u.cmp(&v) {
+                    std::cmp::Ordering::Less => up.push((u, v, w)),
+                    std::cmp::Ordering::Greater => down.push((v, u, w)),
+                    std::cmp::Ordering::Equal => loops.push((u, u, w)),
+                }
+            }
+        }
+        up.sort_unstable();
+        down.sort_unstable();
+        if up != down { return None; }
+        up.extend(loops);
+        Self::from_edges(g.len(), &up)
+    }
+
+    pub fn len(&self) -> usize { self.offsets.len().saturating_sub(1) }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    /// Stored (undirected) edges; a search sees each non-loop edge from both ends.
+    pub fn num_edges(&self) -> usize { self.targets.len() }
+
+    #[inline]
+    pub fn neighbors(&self, u: Node) -> impl Iterator<Item = (Node, Weight)> + '_ {
+        let (a, b) = (self.offsets[u], self.offsets[u + 1]);
+        let up = self.targets[a..b].iter().zip(&self.weights[a..b]).map(|(&v, &w)| (v as Node, w));
+        // The k-th copy of `s` in the reverse list is the k-th `s -> u` edge in `s`'s list.
+        let mut last = (u32::MAX, 0);
+        let down = self.rev_sources[self.rev_offsets[u]..self.rev_offsets[u + 1]].iter().map(move |&s| {
+            let pos = if last.0 == s { last.1 + 1 } else {
+                let (a, b) = (self.offsets[s as usize], self.offsets[s as usize + 1]);
+                a + self.targets[a..b].partition_point(|&t| (t as Node) < u)
+            };
+            last = (s, pos);
+            (s as Node, self.weights[pos])
+        });
+        up.chain(down)
+    }
+
+    /// Same accounting as `Graph::memory_estimate_bytes`: graph arrays plus search working set.
+    pub fn memory_estimate_bytes(&self) -> usize {
+        let n = self.len();
+        let arrays = 2 * self.offsets.len() * std::mem::size_of::<usize>()
+            + self.num_edges() * (std::mem::size_of::<u32>() + std::mem::size_of::<Weight>())
+            + self.rev_sources.len() * std::mem::size_of::<u32>();
+        let headers = 5 * 3 * std::mem::size_of::<usize>();
+        arrays + headers + n * std::mem::size_of::<u64>() + n * std::mem::size_of::<u8>() * 2
+    }
+
+    /// `memory_estimate_bytes` with the arrays at their allocated capacities.
+    pub fn memory_actual_bytes(&self) -> usize {
+        let n = self.len();
+        std::mem::size_of::<Self>() + (self.offsets.capacity() + self.rev_offsets.capacity()) * std::mem::size_of::<usize>()
+            + (self.targets.capacity() + self.rev_sources.capacity()) * std::mem::size_of::<u32>()
+            + self.weights.capacity() * std::mem::size_of::<Weight>()
+            + n * std::mem::size_of::<u64>() + n * std::mem::size_of::<u8>() * 2
+    }
+}
+
+impl GraphLike for UndirectedCsr {
+    fn num_nodes(&self) -> usize { self.len() }
+    fn neighbors(&self, u: Node) -> impl Iterator<Item = (Node, Weight)> + '_ { UndirectedCsr::neighbors(self, u) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_multi_source_shortest_paths;
+    use crate::generators::{make_ba, make_grid};
+
+    #[test]
+    fn matches_the_doubled_graph_in_half_the_memory() {
+        let mut road = make_grid(40, 40, 9, 3);
+        road.add_undirected_edge(5, 900, 2);
+        road.add_undirected_edge(5, 900, 7);
+        road.add_undirected_edge(33, 33, 1);
+        // The BA generator is directed; its edges read as undirected ones.
+        let ba = make_ba(500, 4, 3, 50, 1);
+        assert!(UndirectedCsr::from_graph(&ba).is_none());
+        let mut social = Graph::new(ba.len());
+        for (u, edges) in ba.adj.iter().enumerate() { for &(v, w) in edges { social.add_undirected_edge(u, v, w); } }
+        for g in [road, social] {
+            let u = UndirectedCsr::from_graph(&g).unwrap();
+            let loops: usize = g.adj.iter().enumerate().map(|(v, e)| e.iter().filter(|x| x.0 == v).count()).sum();
+            assert_eq!((u.len(), 2 * u.num_edges() - loops), (g.len(), g.num_edges()));
+            for v in 0..g.len() {
+                let (mut a, mut b): (Vec<_>, Vec<_>) = (g.adj[v].clone(), u.neighbors(v).collect());
+                a.sort_unstable();
+                b.sort_unstable();
+                assert_eq!(a, b, "node {v}");
+            }
+            for bound in [0, 30, 200, Weight::MAX] {
+                let (a, b) = (bounded_multi_source_shortest_paths(&g, &[(0, 0), (99, 4)], bound), bounded_multi_source_shortest_paths(&u, &[(0, 0), (99, 4)], bound));
+                assert_eq!((a.dist, a.explored, a.b_prime, a.edges_scanned), (b.dist, b.explored, b.b_prime, b.edges_scanned));
+            }
+            assert!(u.memory_estimate_bytes() * 10 < g.memory_estimate_bytes() * 6);
+        }
+        let mut one_way = Graph::new(3);
+        one_way.add_edge(0, 1, 1);
+        assert!(UndirectedCsr::from_graph(&one_way).is_none());
+        assert!(UndirectedCsr::from_edges(2, &[(0, 2, 1)]).is_none());
+    }
+}
@@ -0,0 +1,40 @@
+//! Search event hooks. The core loop is generic over `SearchVisitor`, so `NoopVisitor`
+//! monomorphizes to the plain search with no per-edge cost. Labels are of type `C`, `Weight`
+//! unless the search runs over another `CostSemiring`.
+use crate::{Node, Weight};
+
+/// Callbacks fired from inside the sequential search. All methods default to no-ops;
+/// implement only the ones you need.
+pub trait SearchVisitor<C = Weight> {
+    /// `v` was popped with final distance `d` and is about to be expanded.
+    #[inline]
+    fn on_settle(&mut self, _v: Node, _d: C) {}
+    /// Edge `u -> v` improved `v` from `old` (`Weight::MAX` if unreached) to `new`.
+    #[inline]
+    fn on_relax(&mut self, _u: Node, _v: Node, _old: C, _new: C) {}
+    /// Edge `u -> v` offered `candidate` but `v` kept `current` (not an improvement, or at or
+    /// above the bound). Filtered edges and overflows under `OverflowPolicy::Error` are not reported.
+    #[inline]
+    fn on_relax_rejected(&mut self, _u: Node, _v: Node, _current: C, _candidate: C) {}
+    /// Edge into `v` produced a label `d >= bound`; B' is the minimum over these
+    /// (and over the first label popped at or above the bound).
+    #[inline]
+    fn on_boundary_candidate(&mut self, _v: Node, _d: C) {}
+}
+
+/// Visitor that does nothing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopVisitor;
+
+impl<C> SearchVisitor<C> for NoopVisitor {}
+
+impl<C, V: SearchVisitor<C> + ?Sized> SearchVisitor<C> for &mut V {
+    #[inline]
+    fn on_settle(&mut self, v: Node, d: C) { (**self).on_settle(v, d) }
+    #[inline]
+    fn on_relax(&mut self, u: Node, v: Node, old: C, new: C) { (**self).on_relax(u, v, old, new) }
+    #[inline]
+    fn on_relax_rejected(&mut self, u: Node, v: Node, current: C, candidate: C) { (**self).on_relax_rejected(u, v, current, candidate) }
+    #[inline]
+    fn on_boundary_candidate(&mut self, v: Node, d: C) { (**self).on_boundary_candidate(v, d) }
+}
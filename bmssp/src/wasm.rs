@@ -0,0 +1,110 @@
+//! wasm-bindgen wrappers for the browser demo (`docs/demo`). Graphs come in as typed arrays
+//! and results go back out as typed arrays.
+//!
+//! Build with `wasm-pack build bmssp --target web --no-default-features --features wasm`; the
+//! sharded and NUMA entry points compile on wasm32 but need threads, so only the sequential
+//! search is exposed here. Weights are `u32` because `Uint32Array` is what JS has at hand;
+//! distances come back as `f64` with `Infinity` for unreached nodes.
+use wasm_bindgen::prelude::*;
+
+use crate::{bounded_multi_source_shortest_paths, BmsspResult, Graph, Weight};
+
+#[wasm_bindgen]
+pub struct WasmGraph {
+    g: Graph,
+}
+
+#[wasm_bindgen]
+impl WasmGraph {
+    /// Directed graph on `n` nodes with edges `src[i] -> dst[i]` of weight `w[i]`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(n: u32, src: &[u32], dst: &[u32], w: &[u32]) -> Result<WasmGraph, JsError> {
+        Self::from_arrays(n, src, dst, w).map_err(|e| JsError::new(&e))
+    }
+
+    #[wasm_bindgen(getter, js_name = numNodes)]
+    pub fn num_nodes(&self) -> u32 { self.g.len() as u32 }
+
+    #[wasm_bindgen(getter, js_name = numEdges)]
+    pub fn num_edges(&self) -> u32 { self.g.num_edges() as u32 }
+
+    /// Bounded search from `sources` (all at distance 0). `Infinity` is unbounded, negative
+    /// bounds settle nothing and NaN is an error.
+    pub fn run(&self, sources: &[u32], bound: f64) -> Result<WasmResult, JsError> {
+        self.run_inner(sources, bound).map_err(|e| JsError::new(&e))
+    }
+}
+
+impl WasmGraph {
+    fn from_arrays(n: u32, src: &[u32], dst: &[u32], w: &[u32]) -> Result<Self, String> {
+        if src.len() != dst.len() || src.len() != w.len() {
+            return Err(format!("edge arrays differ in length: {} / {} / {}", src.len(), dst.len(), w.len()));
+        }
+        let mut g = Graph::new(n as usize);
+        for ((&u, &v), &w) in src.iter().zip(dst).zip(w) {
+            if u >= n || v >= n { return Err(format!("edge {u}->{v} out of range for {n} nodes")); }
+            g.add_edge(u as usize, v as usize, w as Weight);
+        }
+        Ok(Self { g })
+    }
+
+    fn run_inner(&self, sources: &[u32], bound: f64) -> Result<WasmResult, String> {
+        let n = self.g.len();
+        if let Some(&s) = sources.iter().find(|&&s| s as usize >= n) { return Err(format!("source {s} out of range for {n} nodes")); }
+        // `as` saturates: +Infinity and huge bounds mean "unbounded", negatives and -Infinity
+        // are 0. NaN would also cast to 0, but is more likely a bug on the JS side.
+        if bound.is_nan() { return Err("bound is NaN".into()); }
+        let bound = bound as Weight;
+        let srcs: Vec<_> = sources.iter().map(|&s| (s as usize, 0)).collect();
+        Ok(WasmResult { res: bounded_multi_source_shortest_paths(&self.g, &srcs, bound) })
+    }
+}
+
+#[wasm_bindgen]
+pub struct WasmResult {
+    res: BmsspResult,
+}
+
+fn to_js_number(d: Weight) -> f64 { if d == Weight::MAX { f64::INFINITY } else { d as f64 } }
+
+#[wasm_bindgen]
+impl WasmResult {
+    /// Per-node distance (`Float64Array`), `Infinity` where not settled.
+    pub fn dist(&self) -> Vec<f64> { self.res.dist.iter().map(|&d| to_js_number(d)).collect() }
+
+    /// Settled nodes in settle order (`Uint32Array`); the demo animates over this.
+    pub fn explored(&self) -> Vec<u32> { self.res.explored.iter().map(|&v| v as u32).collect() }
+
+    #[wasm_bindgen(getter, js_name = bPrime)]
+    pub fn b_prime(&self) -> f64 { to_js_number(self.res.b_prime) }
+
+    #[wasm_bindgen(getter, js_name = edgesScanned)]
+    pub fn edges_scanned(&self) -> f64 { self.res.edges_scanned as f64 }
+
+    #[wasm_bindgen(getter, js_name = heapPushes)]
+    pub fn heap_pushes(&self) -> f64 { self.res.heap_pushes as f64 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_array_round_trip() {
+        let g = WasmGraph::from_arrays(4, &[0, 1, 2], &[1, 2, 3], &[2, 3, 10]).unwrap();
+        assert_eq!((g.num_nodes(), g.num_edges()), (4, 3));
+        let r = g.run_inner(&[0], 8.0).unwrap();
+        assert_eq!(r.dist(), vec![0.0, 2.0, 5.0, f64::INFINITY]);
+        assert_eq!(r.explored(), vec![0, 1, 2]);
+        assert_eq!(r.b_prime(), 15.0);
+        assert_eq!(g.run_inner(&[0], f64::INFINITY).unwrap().explored(), vec![0, 1, 2, 3]);
+        assert_eq!(g.run_inner(&[0], 1e300).unwrap().explored(), vec![0, 1, 2, 3]);
+        for empty in [-1.0, f64::NEG_INFINITY, 0.0] {
+            assert_eq!(g.run_inner(&[0], empty).unwrap().explored(), Vec::<u32>::new());
+        }
+        assert!(g.run_inner(&[0], f64::NAN).is_err());
+        assert!(g.run_inner(&[4], 8.0).is_err());
+        assert!(WasmGraph::from_arrays(2, &[0], &[5], &[1]).is_err());
+        assert!(WasmGraph::from_arrays(2, &[0], &[1], &[]).is_err());
+    }
+}
@@ -0,0 +1,28 @@
+//! Bounded bottleneck (min-max) search: a path's cost is its heaviest edge.
+use crate::semiring::{bmssp_semiring, MinMax};
+use crate::{BmsspResult, Graph, Node, Weight};
+
+/// Like `bounded_multi_source_shortest_paths` but `dist[v]` is the smallest achievable maximum
+/// edge weight over paths from a source (a source's offset acts as an edge into it). Nodes whose
+/// bottleneck reaches `bound` are not settled; `b_prime` is the smallest such bottleneck.
+pub fn bounded_widest_paths(g: &Graph, sources: &[(Node, Weight)], bound: Weight) -> BmsspResult {
+    bmssp_semiring::<MinMax>(g, sources, bound).into_result()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_low_bottleneck_over_short_path() {
+        // 0 -> 3 directly costs 9; the detour 0 -> 1 -> 2 -> 3 never exceeds 4.
+        let mut g = Graph::new(5);
+        g.add_edge(0, 3, 9); g.add_edge(0, 1, 4); g.add_edge(1, 2, 3); g.add_edge(2, 3, 4); g.add_edge(3, 4, 7);
+        let r = bounded_widest_paths(&g, &[(0, 0)], 7);
+        assert_eq!(r.dist[..4], [0, 4, 4, 4]);
+        assert_eq!(r.dist[4], Weight::MAX);
+        assert_eq!(r.b_prime, 7);
+        assert_eq!(r.explored, vec![0, 1, 2, 3]);
+        assert_eq!(bounded_widest_paths(&g, &[(0, 0)], 8).dist[4], 7);
+    }
+}
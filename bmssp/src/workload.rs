@@ -0,0 +1,111 @@
+//! Seeded query-set generators writing the `queries` format, so every language entry can be
+//! timed on the same workload.
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{bmssp_with_options, BmsspOptions, GraphLike, Node, Query, Solver, Weight};
+
+/// `count` queries of `k` sources drawn uniformly from `0..n`, all at `d0 = 0`.
+pub fn uniform_queries(n: usize, count: usize, k: usize, bound: Weight, seed: u64) -> Vec<Query> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    if n == 0 { return Vec::new(); }
+    (0..count).map(|_| Query{ bound, sources: (0..k).map(|_| (rng.gen_range(0..n), 0)).collect() }).collect()
+}
+
+/// Sources clustered around a random center: `k` distinct nodes sampled from the first `4k`
+/// nodes a search from the center settles. Stands in for "spatially close" without coordinates.
+pub fn clustered_queries<G: GraphLike>(g: &G, count: usize, k: usize, bound: Weight, seed: u64) -> Vec<Query> {
+    let n = g.num_nodes();
+    let mut rng = StdRng::seed_from_u64(seed);
+    if n == 0 { return Vec::new(); }
+    let opts = BmsspOptions{ max_settled: Some(4 * k.max(1)), ..Default::default() };
+    (0..count).map(|_| {
+        let center = rng.gen_range(0..n);
+        let ball = bmssp_with_options(g, &[(center, 0)], Weight::MAX, &opts).explored;
+        let sources = ball.choose_multiple(&mut rng, k.min(ball.len())).map(|&v| (v, 0)).collect();
+        Query{ bound, sources }
+    }).collect()
+}
+
+/// A sampled `(s, t)` pair for rank-based evaluation: `t` is the `rank`-th node (1-based) a
+/// full search from `source` settles, at distance `dist`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RankPair {
+    pub source: Node,
+    pub target: Node,
+    pub rank: usize,
+    pub dist: Weight,
+}
+
+impl RankPair {
+    /// The smallest bound at which a search from `source` settles `target`.
+    pub fn bound(&self) -> Weight { self.dist.saturating_add(1) }
+
+    /// The single-source query that just reaches `target`.
+    pub fn query(&self) -> Query { Query{ bound: self.bound(), sources: vec![(self.source, 0)] } }
+}
+
+/// Dijkstra-rank pairs: for each of `count` random sources `s` and each `r` with `2^r` nodes
+/// reachable, the pair `(s, t)` where `t` is the `2^r`-th node `s` settles.
+pub fn rank_pairs<G: GraphLike>(g: &G, count: usize, seed: u64) -> Vec<RankPair> {
+    let n = g.num_nodes();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut solver = Solver::new(n);
+    let mut out = Vec::new();
+    if n == 0 { return out; }
+    for _ in 0..count {
+        let source = rng.gen_range(0..n);
+        solver.run(g, &[(source, 0)], Weight::MAX);
+        let order = solver.settled();
+        let mut rank = 1;
+        while rank <= order.len() {
+            let target = order[rank - 1];
+            out.push(RankPair{ source, target, rank, dist: solver.dist(target) });
+            rank *= 2;
+        }
+    }
+    out
+}
+
+/// Dijkstra-rank workload: the queries of `rank_pairs`, each with its bound just past the
+/// target's distance, so the search cost is bucketed by rank.
+pub fn rank_queries<G: GraphLike>(g: &G, count: usize, seed: u64) -> Vec<Query> {
+    rank_pairs(g, count, seed).iter().map(RankPair::query).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bounded_multi_source_shortest_paths, Graph};
+
+    fn path(n: usize) -> Graph {
+        let mut g = Graph::new(n);
+        for i in 0..n - 1 { g.add_undirected_edge(i, i + 1, 1); }
+        g
+    }
+
+    #[test]
+    fn generators_are_seeded_and_well_formed() {
+        let g = path(64);
+        assert_eq!(uniform_queries(64, 5, 3, 10, 1), uniform_queries(64, 5, 3, 10, 1));
+        assert!(uniform_queries(64, 5, 3, 10, 1).iter().all(|q| q.sources.len() == 3 && q.sources.iter().all(|&(s, _)| s < 64)));
+        for q in clustered_queries(&g, 4, 3, 10, 2) {
+            assert_eq!(q.sources.len(), 3);
+            let span = q.sources.iter().map(|s| s.0).max().unwrap() - q.sources.iter().map(|s| s.0).min().unwrap();
+            assert!(span < 12, "{q}");
+        }
+        // Ranks 1, 2, 4, ..., 64 on a connected 64-node graph; each query settles at least 2^r nodes.
+        let qs = rank_queries(&g, 2, 3);
+        assert_eq!(qs.len(), 2 * 7);
+        for (i, q) in qs.iter().enumerate() {
+            let r = bounded_multi_source_shortest_paths(&g, &q.sources, q.bound);
+            assert!(r.explored.len() >= 1 << (i % 7));
+        }
+        let pairs = rank_pairs(&g, 2, 3);
+        assert_eq!(pairs.iter().map(RankPair::query).collect::<Vec<_>>(), qs);
+        for p in &pairs {
+            let r = bounded_multi_source_shortest_paths(&g, &[(p.source, 0)], p.bound());
+            assert_eq!(r.dist[p.target], p.dist);
+            assert_eq!(r.explored.iter().position(|&v| v == p.target), Some(p.rank - 1));
+        }
+    }
+}
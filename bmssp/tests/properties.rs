@@ -0,0 +1,118 @@
+//! Property tests against a plain reference Dijkstra on small random instances, including
+//! disconnected graphs, zero weights and sources with non-zero (possibly out-of-bound) offsets.
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap};
+
+use bmssp::*;
+use proptest::prelude::*;
+
+#[derive(Debug, Clone)]
+struct Instance {
+    n: usize,
+    edges: Vec<(Node, Node, Weight)>,
+    sources: Vec<(Node, Weight)>,
+    bound: Weight,
+}
+
+impl Instance {
+    fn graph(&self) -> Graph {
+        let mut g = Graph::new(self.n);
+        for &(u, v, w) in &self.edges { g.add_edge(u, v, w); }
+        g
+    }
+}
+
+fn instance() -> impl Strategy<Value = Instance> {
+    (1usize..30).prop_flat_map(|n| {
+        let edges = prop::collection::vec((0..n, 0..n, 0u64..12), 0..4 * n);
+        let sources = prop::collection::vec((0..n, prop_oneof![3 => Just(0u64), 2 => 0u64..40]), 1..5);
+        (Just(n), edges, sources, 0u64..60)
+    }).prop_map(|(n, edges, sources, bound)| Instance{ n, edges, sources, bound })
+}
+
+/// Unbounded multi-source Dijkstra. Sources at or past the bound are dropped first, matching
+/// the library: they are neither settled nor counted towards B'.
+fn reference(inst: &Instance) -> Vec<Weight> {
+    let mut dist = vec![Weight::MAX; inst.n];
+    let mut adj = vec![Vec::new(); inst.n];
+    for &(u, v, w) in &inst.edges { adj[u].push((v, w)); }
+    let mut heap = BinaryHeap::new();
+    for &(s, d0) in &inst.sources {
+        if d0 < inst.bound && d0 < dist[s] { dist[s] = d0; heap.push(Reverse((d0, s))); }
+    }
+    while let Some(Reverse((d, u))) = heap.pop() {
+        if d != dist[u] { continue; }
+        for &(v, w) in &adj[u] {
+            let nd = d + w;
+            if nd < dist[v] { dist[v] = nd; heap.push(Reverse((nd, v))); }
+        }
+    }
+    dist
+}
+
+proptest! {
+    #[test]
+    fn settles_exactly_the_nodes_below_the_bound(inst in instance()) {
+        let res = bounded_multi_source_shortest_paths(&inst.graph(), &inst.sources, inst.bound);
+        let want = reference(&inst);
+        let settled: BTreeSet<Node> = res.explored.iter().copied().collect();
+        prop_assert_eq!(settled.len(), res.explored.len(), "a node was settled twice");
+        for (v, &d) in want.iter().enumerate() {
+            if d < inst.bound {
+                prop_assert!(settled.contains(&v), "node {} at {} not settled", v, d);
+                prop_assert_eq!(res.dist[v], d);
+            } else {
+                prop_assert!(!settled.contains(&v), "node {} at {} settled past the bound", v, d);
+            }
+        }
+        prop_assert!(res.explored.windows(2).all(|w| res.dist[w[0]] <= res.dist[w[1]]), "settle order not monotone");
+    }
+
+    #[test]
+    fn b_prime_is_min_boundary_relaxation(inst in instance()) {
+        // The cross-language definition: min of `dist[u] + w` at or above B over edges leaving
+        // settled nodes, whether or not the head was settled through another edge.
+        let res = bounded_multi_source_shortest_paths(&inst.graph(), &inst.sources, inst.bound);
+        let dist = reference(&inst);
+        let want = inst.edges.iter().filter(|&&(u, _, _)| dist[u] < inst.bound).map(|&(u, _, w)| dist[u] + w)
+            .filter(|&d| d >= inst.bound).min().unwrap_or(Weight::MAX);
+        prop_assert_eq!(res.b_prime, want);
+        // Never above the true next distance, which it bounds from below.
+        let next = dist.iter().copied().filter(|&d| d >= inst.bound).min().unwrap_or(Weight::MAX);
+        prop_assert!(res.b_prime <= next);
+    }
+
+    #[test]
+    fn monotone_in_the_bound(inst in instance(), extra in 0u64..30) {
+        let g = inst.graph();
+        let small = bounded_multi_source_shortest_paths(&g, &inst.sources, inst.bound);
+        let large = bounded_multi_source_shortest_paths(&g, &inst.sources, inst.bound + extra);
+        // Raising B can admit more sources, so compare only nodes reached from the common ones.
+        for &v in &small.explored {
+            prop_assert!(large.dist[v] <= small.dist[v]);
+        }
+        let admitted_same = inst.sources.iter().all(|&(_, d0)| d0 < inst.bound || d0 >= inst.bound + extra);
+        if admitted_same {
+            let a: BTreeSet<Node> = small.explored.iter().copied().collect();
+            let b: BTreeSet<Node> = large.explored.iter().copied().collect();
+            prop_assert!(a.is_subset(&b));
+            for &v in &small.explored { prop_assert_eq!(large.dist[v], small.dist[v]); }
+            prop_assert!(large.b_prime >= small.b_prime);
+        }
+    }
+
+    #[test]
+    fn sharded_matches_sequential(inst in instance(), threads in 1usize..5) {
+        let g = inst.graph();
+        let seq = bounded_multi_source_shortest_paths(&g, &inst.sources, inst.bound);
+        let par = bmssp_sharded(&g, &inst.sources, inst.bound, threads);
+        prop_assert_eq!(&par.dist, &seq.dist);
+        for r in [&seq, &par] { prop_assert!(r.explored.windows(2).all(|w| r.dist[w[0]] <= r.dist[w[1]])); }
+        let a: BTreeSet<Node> = seq.explored.iter().copied().collect();
+        let b: BTreeSet<Node> = par.explored.iter().copied().collect();
+        prop_assert_eq!(a, b);
+        // Zero-weight edges can settle equal labels out of id order; otherwise the orders agree.
+        if inst.edges.iter().all(|&(_, _, w)| w > 0) { prop_assert_eq!(&par.explored, &seq.explored); }
+        prop_assert_eq!(par.b_prime, seq.b_prime);
+    }
+}